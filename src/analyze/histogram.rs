@@ -0,0 +1,122 @@
+use image::{Pixel, RgbaImage};
+use std::fmt::Write;
+
+/* Characters for drawing the bars of the histogram chart */
+const BAR_CHARS: [char; 9] = [
+	' ', '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}',
+	'\u{2587}', '\u{2588}',
+];
+
+/* Output format of the histogram */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistogramFormat {
+	Chart,
+	Raw,
+}
+
+/* Histogram of the color channels and luminance */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram {
+	pub channels: Vec<(&'static str, Vec<u64>)>,
+}
+
+impl Histogram {
+	/**
+	 * Create a new Histogram object from the visible pixels of the image.
+	 *
+	 * @param  image
+	 * @param  bins
+	 * @return Histogram
+	 */
+	pub fn new(image: &RgbaImage, bins: usize) -> Self {
+		let bins = bins.clamp(1, 256);
+		let mut channels = ["Red", "Green", "Blue", "Luma"]
+			.map(|name| (name, vec![0; bins]))
+			.to_vec();
+		for pixel in image.pixels().filter(|pixel| pixel[3] != 0) {
+			let luma = pixel.to_luma()[0];
+			for (i, value) in pixel.0.iter().take(3).chain([&luma]).enumerate() {
+				channels[i].1[usize::from(*value) * bins / 256] += 1;
+			}
+		}
+		Self { channels }
+	}
+
+	/**
+	 * Get the bars of the histogram chart that are scaled to the highest bin.
+	 *
+	 * @param  values
+	 * @return String
+	 */
+	fn get_chart(values: &[u64]) -> String {
+		let max = values.iter().max().copied().unwrap_or_default().max(1);
+		values
+			.iter()
+			.map(|value| {
+				let level = (*value * 8).div_ceil(max) as usize;
+				BAR_CHARS[level.min(8)]
+			})
+			.collect()
+	}
+
+	/**
+	 * Format the histogram as a bar chart or raw bin values.
+	 *
+	 * @param  format
+	 * @return String
+	 */
+	pub fn format(&self, format: HistogramFormat) -> String {
+		let mut data = String::new();
+		for (name, values) in &self.channels {
+			let _ = writeln!(
+				data,
+				"  {:<6} {}",
+				format!("{name}:"),
+				match format {
+					HistogramFormat::Chart => Self::get_chart(values),
+					HistogramFormat::Raw => values
+						.iter()
+						.map(|value| value.to_string())
+						.collect::<Vec<String>>()
+						.join(","),
+				}
+			);
+		}
+		data
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_histogram() {
+		let mut image = RgbaImage::from_pixel(4, 1, Rgba([255, 0, 0, 255]));
+		image.put_pixel(1, 0, Rgba([0, 0, 0, 255]));
+		image.put_pixel(2, 0, Rgba([255, 255, 255, 255]));
+		image.put_pixel(3, 0, Rgba([128, 128, 128, 0]));
+		let histogram = Histogram::new(&image, 4);
+		assert_eq!(
+			vec![
+				("Red", vec![1, 0, 0, 2]),
+				("Green", vec![2, 0, 0, 1]),
+				("Blue", vec![2, 0, 0, 1]),
+				("Luma", vec![2, 0, 0, 1]),
+			],
+			histogram.channels
+		);
+		assert_eq!(
+			"  Red:   1,0,0,2\n  Green: 2,0,0,1\n  Blue:  2,0,0,1\n  \
+			Luma:  2,0,0,1\n",
+			histogram.format(HistogramFormat::Raw)
+		);
+		assert_eq!(
+			"\u{2584}  \u{2588}",
+			Histogram::get_chart(&histogram.channels[0].1)
+		);
+		assert_eq!("   ", Histogram::get_chart(&[0, 0, 0]));
+		assert_eq!(256, Histogram::new(&image, 1000).channels[0].1.len());
+	}
+}
@@ -1,5 +1,7 @@
+pub mod histogram;
 pub mod settings;
 
+use crate::analyze::histogram::Histogram;
 use crate::analyze::settings::AnalyzeSettings;
 use crate::app::AppResult;
 use bytesize::ByteSize;
@@ -7,10 +9,16 @@ use colored::{Color, Colorize};
 use exif::{Exif, Reader as ExifReader};
 use hex::ToHex;
 use image::io::Reader as ImageReader;
-use image::{DynamicImage, ImageFormat};
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use png::{Decoder as PngDecoder, DecodingError};
+use serde_json::{json, Map, Value};
 use std::fmt::Write;
 use std::fs::{self, File, Metadata};
 use std::io::BufReader;
+use std::path::Path;
+
+/* Number of bits per channel for grouping the similar colors */
+const COLOR_BUCKET_BITS: u8 = 3;
 
 /* Time information of a file */
 pub enum TimeInfo {
@@ -25,6 +33,7 @@ pub struct ImageAnalyzer<'a> {
 	image: DynamicImage,
 	metadata: Metadata,
 	exif: Option<Exif>,
+	text: Vec<(String, String)>,
 	settings: &'a AnalyzeSettings,
 }
 
@@ -38,8 +47,9 @@ impl<'a> ImageAnalyzer<'a> {
 	pub fn new(settings: &'a AnalyzeSettings) -> AppResult<Self> {
 		debug!("{:?}", settings);
 		let reader = ImageReader::open(&settings.file)?.with_guessed_format()?;
+		let format = reader.format();
 		Ok(Self {
-			format: reader.format(),
+			format,
 			image: reader.decode()?,
 			metadata: fs::metadata(&settings.file)?,
 			exif: ExifReader::new()
@@ -47,10 +57,57 @@ impl<'a> ImageAnalyzer<'a> {
 					&settings.file,
 				)?))
 				.ok(),
+			text: if format == Some(ImageFormat::Png) {
+				Self::read_text_chunks(&settings.file)
+			} else {
+				Vec::new()
+			},
 			settings,
 		})
 	}
 
+	/**
+	 * Read the keywords and values of the text chunks (tEXt, zTXt and iTXt)
+	 * from the PNG file.
+	 *
+	 * @param  file
+	 * @return Vector of keyword and text
+	 */
+	fn read_text_chunks(file: &Path) -> Vec<(String, String)> {
+		let reader = match File::open(file)
+			.map_err(DecodingError::from)
+			.and_then(|file| PngDecoder::new(BufReader::new(file)).read_info())
+		{
+			Ok(reader) => reader,
+			Err(e) => {
+				warn!("Failed to read the PNG text chunks: {}", e);
+				return Vec::new();
+			}
+		};
+		let info = reader.info();
+		info.uncompressed_latin1_text
+			.iter()
+			.map(|chunk| (chunk.keyword.to_string(), Ok(chunk.text.to_string())))
+			.chain(
+				info.compressed_latin1_text
+					.iter()
+					.map(|chunk| (chunk.keyword.to_string(), chunk.get_text())),
+			)
+			.chain(
+				info.utf8_text
+					.iter()
+					.map(|chunk| (chunk.keyword.to_string(), chunk.get_text())),
+			)
+			.filter_map(|(keyword, text)| match text {
+				Ok(text) => Some((keyword, text)),
+				Err(e) => {
+					warn!("Failed to decode the {:?} text chunk: {}", keyword, e);
+					None
+				}
+			})
+			.collect()
+	}
+
 	/**
 	 * Get the time information of the file.
 	 *
@@ -89,14 +146,78 @@ impl<'a> ImageAnalyzer<'a> {
 	}
 
 	/**
-	 * Get dominant colors of the image.
+	 * Get the dominant colors of the image with their percentages.
+	 *
+	 * Pixels are grouped into buckets of similar colors (weighted by their
+	 * opacity) and the average colors of the largest buckets are returned.
+	 *
+	 * @return Vector of hex color and percentage
+	 */
+	fn get_dominant_colors(&self) -> Vec<(String, f64)> {
+		let mut buckets = vec![[0.; 4]; 1 << (COLOR_BUCKET_BITS * 3)];
+		let shift = 8 - COLOR_BUCKET_BITS;
+		let mut total = 0.;
+		for pixel in self.image.to_rgba8().pixels() {
+			let alpha = f64::from(pixel[3]) / 255.;
+			let index = pixel.0[..3].iter().fold(0, |index, value| {
+				(index << COLOR_BUCKET_BITS) | (value >> shift)
+			}) as usize;
+			for i in 0..3 {
+				buckets[index][i] += f64::from(pixel[i]) * alpha;
+			}
+			buckets[index][3] += alpha;
+			total += alpha;
+		}
+		buckets.retain(|bucket| bucket[3] > 0.);
+		buckets.sort_by(|a, b| b[3].total_cmp(&a[3]));
+		buckets
+			.into_iter()
+			.take(self.settings.colors)
+			.map(|[red, green, blue, weight]| {
+				(
+					format!(
+						"#{}",
+						[red, green, blue]
+							.map(|value| (value / weight).round() as u8)
+							.encode_hex_upper::<String>()
+					),
+					weight / total * 100.,
+				)
+			})
+			.collect()
+	}
+
+	/**
+	 * Get the formatted dominant colors of the image.
 	 *
 	 * @return Vector of String
 	 */
-	fn get_dominant_colors(&self) -> Vec<String> {
-		dominant_color::get_colors(&self.image.clone().into_rgba8().into_vec(), true)
-			.chunks(4)
-			.map(|rgba| format!("#{}", rgba.encode_hex::<String>()).to_uppercase())
+	fn get_color_info(&self) -> Vec<String> {
+		self.get_dominant_colors()
+			.into_iter()
+			.map(|(color, percentage)| format!("{color} ({percentage:.2}%)"))
+			.collect()
+	}
+
+	/**
+	 * Get the EXIF fields of the image as tag, value and
+	 * whether the field belongs to the thumbnail.
+	 *
+	 * @return Vector of tuple
+	 */
+	fn get_exif_fields(&self) -> Vec<(String, String, bool)> {
+		let Some(exif) = &self.exif else {
+			return Vec::new();
+		};
+		exif.fields()
+			.map(|f| {
+				let tag = f.tag.to_string();
+				let mut value = f.display_value().with_unit(exif).to_string();
+				if value.len() > 64 && (tag == "MakerNote" || tag == "UserComment") {
+					value = format!("({} bytes binary data)", value.len());
+				}
+				(tag, value, f.ifd_num.index() == 1)
+			})
 			.collect()
 	}
 
@@ -107,28 +228,72 @@ impl<'a> ImageAnalyzer<'a> {
 	 */
 	fn get_exif_data(&self) -> String {
 		let mut data = String::new();
-		if let Some(exif) = &self.exif {
+		if self.exif.is_some() {
 			data += "\nEXIF Data\n";
-			for f in exif.fields() {
-				let mut value = f.display_value().with_unit(exif).to_string();
-				if value.len() > 64
-					&& (f.tag.to_string() == "MakerNote"
-						|| f.tag.to_string() == "UserComment")
-				{
-					value = format!("({} bytes binary data)", value.len());
-				}
+			for (tag, value, thumbnail) in self.get_exif_fields() {
 				let _ = writeln!(
 					data,
 					"  {}: {}{}",
-					f.tag,
+					tag,
 					value,
-					if f.ifd_num.index() == 1 { " (T)" } else { "" }
+					if thumbnail { " (T)" } else { "" }
 				);
 			}
 		}
 		data
 	}
 
+	/**
+	 * Get the text data from the PNG chunks.
+	 *
+	 * @return data
+	 */
+	fn get_text_data(&self) -> String {
+		let mut data = String::new();
+		if !self.text.is_empty() {
+			data += "\nPNG Text\n";
+			for (keyword, text) in &self.text {
+				let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+				let _ = writeln!(
+					data,
+					"  {}: {}",
+					keyword,
+					if text.len() > 256 {
+						format!("({} bytes text data)", text.len())
+					} else {
+						text
+					}
+				);
+			}
+		}
+		data
+	}
+
+	/**
+	 * Get the histogram of the image if it is requested.
+	 *
+	 * @return Histogram (Option)
+	 */
+	fn get_histogram(&self) -> Option<Histogram> {
+		self.settings
+			.histogram
+			.map(|_| Histogram::new(&self.image.to_rgba8(), self.settings.bins))
+	}
+
+	/**
+	 * Get the histogram data from the image.
+	 *
+	 * @return data
+	 */
+	fn get_histogram_data(&self) -> String {
+		match (self.get_histogram(), self.settings.histogram) {
+			(Some(histogram), Some(format)) => {
+				format!("\nHistogram\n{}", histogram.format(format))
+			}
+			_ => String::new(),
+		}
+	}
+
 	/**
 	 * Get the analysis report.
 	 *
@@ -148,7 +313,7 @@ impl<'a> ImageAnalyzer<'a> {
 			\n  Color Type: {}\
 			\n  Main Colors:\
 			\n   \u{2022} {}\
-			\n{}\n\
+			\n{}{}{}\n\
 			generated on {}\
 			",
 			env!("CARGO_PKG_NAME"),
@@ -171,12 +336,73 @@ impl<'a> ImageAnalyzer<'a> {
 			),
 			self.get_image_dimensions(),
 			format!("{:?}", self.image.color()).to_uppercase(),
-			self.get_dominant_colors().join("\n   \u{2022} "),
+			self.get_color_info().join("\n   \u{2022} "),
+			self.get_histogram_data(),
 			self.get_exif_data(),
+			self.get_text_data(),
 			self.settings.time.now(),
 		)
 	}
 
+	/**
+	 * Get the analysis report in JSON format.
+	 *
+	 * @return report
+	 */
+	pub fn get_json_report(self) -> String {
+		let (width, height) = self.image.dimensions();
+		json!({
+			"file": {
+				"path": self.settings.file,
+				"size": self.metadata.len(),
+				"readonly": self.metadata.permissions().readonly(),
+				"created": self.get_time_info(TimeInfo::Created),
+				"modified": self.get_time_info(TimeInfo::Modified),
+				"accessed": self.get_time_info(TimeInfo::Accessed),
+			},
+			"image": {
+				"format": self.format.map(|f| format!("{f:?}").to_uppercase()),
+				"width": width,
+				"height": height,
+				"color_type": format!("{:?}", self.image.color()).to_uppercase(),
+				"colors": self
+					.get_dominant_colors()
+					.into_iter()
+					.map(|(color, percentage)| json!({
+						"color": color,
+						"percentage": (percentage * 100.).round() / 100.,
+					}))
+					.collect::<Vec<Value>>(),
+			},
+			"histogram": self.get_histogram().map(|histogram| {
+				histogram
+					.channels
+					.into_iter()
+					.map(|(name, values)| (name.to_lowercase(), json!(values)))
+					.collect::<Map<String, Value>>()
+			}),
+			"exif": self
+				.get_exif_fields()
+				.into_iter()
+				.map(|(tag, value, thumbnail)| json!({
+					"tag": tag,
+					"value": value,
+					"thumbnail": thumbnail,
+				}))
+				.collect::<Vec<Value>>(),
+			"text": self
+				.text
+				.iter()
+				.map(|(keyword, text)| json!({
+					"keyword": keyword,
+					"text": text,
+				}))
+				.collect::<Vec<Value>>(),
+			"generated": self.settings.time.now(),
+		})
+		.to_string()
+	}
+
 	/**
 	 * Colorize the report by using the predefined format.
 	 *
@@ -190,18 +416,12 @@ impl<'a> ImageAnalyzer<'a> {
 			colored_report += &if !(line.starts_with("  ") || line.contains('-')) {
 				line.white().bold().to_string()
 			} else if line.starts_with("  ") && line.contains(':') {
-				let mut values = line.split(':');
-				format!(
-					"{}:{}",
-					values.next().unwrap_or_default().color(color),
-					values.collect::<String>()
-				)
+				let (key, value) = line.split_once(':').unwrap_or_default();
+				format!("{}:{}", key.color(color), value)
 			} else if line.starts_with("  ") && line.contains('\u{2022}') {
 				match hex::decode(
-					line.split('#')
-						.collect::<Vec<&str>>()
-						.get(1)
-						.cloned()
+					line.split_once('#')
+						.and_then(|(_, color)| color.get(..6))
 						.unwrap_or_default(),
 				) {
 					Ok(rgb) => {
@@ -234,11 +454,15 @@ impl<'a> ImageAnalyzer<'a> {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::analyze::histogram::HistogramFormat;
 	use crate::analyze::settings::TimeZone;
+	use crate::file::format::FileFormat;
+	use crate::image::metadata::Metadata;
 	use chrono::Utc;
 	use colored::Color;
 	use image::{ColorType, ImageBuffer, Rgba};
 	use pretty_assertions::assert_eq;
+	use std::io::Cursor;
 	use std::path::PathBuf;
 	#[test]
 	fn test_analyze() {
@@ -255,6 +479,10 @@ mod tests {
 			PathBuf::from(file_name),
 			Color::White,
 			TimeZone::Utc(false),
+			5,
+			None,
+			32,
+			false,
 		);
 		let analyzer = ImageAnalyzer::new(&settings).unwrap();
 		assert_eq!("72 B", analyzer.get_file_size());
@@ -271,11 +499,80 @@ mod tests {
 		assert_eq!(ColorType::Rgba8, analyzer.image.color());
 		assert_eq!("1x2", analyzer.get_image_dimensions());
 		assert_eq!(
-			"#000000FF-#FFFFFFFF",
-			analyzer.get_dominant_colors().join("-")
+			"#000000 (50.00%)-#FFFFFF (50.00%)",
+			analyzer.get_color_info().join("-")
 		);
 		assert!(analyzer.exif.is_none());
+		assert!(analyzer.text.is_empty());
 		assert_eq!(17, analyzer.get_colored_report().lines().count());
+		let mut data = Vec::new();
+		image::open(file_name)
+			.unwrap()
+			.write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+			.unwrap();
+		let metadata =
+			Metadata::new(Some(String::from("Test: 1")), Some(String::from("ü")));
+		fs::write(file_name, metadata.embed(&data, &FileFormat::Png).unwrap())
+			.unwrap();
+		let analyzer = ImageAnalyzer::new(&settings).unwrap();
+		assert_eq!(
+			vec![
+				(String::from("Software"), metadata.software.to_string()),
+				(
+					String::from("Creation Time"),
+					metadata.timestamp.to_rfc2822()
+				),
+				(String::from("Title"), String::from("Test: 1")),
+				(String::from("Comment"), String::from("ü")),
+			],
+			analyzer.text
+		);
+		let report = analyzer.get_report();
+		assert!(report.contains("\nPNG Text\n  Software: menyoki"));
+		assert!(report.contains("\n  Title: Test: 1\n"));
+		let report = serde_json::from_str::<Value>(
+			&ImageAnalyzer::new(&settings).unwrap().get_json_report(),
+		)
+		.unwrap();
+		assert_eq!(json!("PNG"), report["image"]["format"]);
+		assert_eq!(
+			json!([1, 2]),
+			json!([report["image"]["width"], report["image"]["height"]])
+		);
+		assert_eq!(
+			json!([
+				{"color": "#000000", "percentage": 50.0},
+				{"color": "#FFFFFF", "percentage": 50.0}
+			]),
+			report["image"]["colors"]
+		);
+		assert_eq!(Value::Null, report["histogram"]);
+		assert_eq!(json!([]), report["exif"]);
+		assert_eq!(
+			json!({"keyword": "Title", "text": "Test: 1"}),
+			report["text"][2]
+		);
+		let settings = AnalyzeSettings {
+			colors: 1,
+			histogram: Some(HistogramFormat::Raw),
+			bins: 2,
+			..settings
+		};
+		assert_eq!(
+			vec!["#000000 (50.00%)"],
+			ImageAnalyzer::new(&settings).unwrap().get_color_info()
+		);
+		assert!(ImageAnalyzer::new(&settings)
+			.unwrap()
+			.get_report()
+			.contains("\nHistogram\n  Red:   1,1\n"));
+		assert_eq!(
+			json!([1, 1]),
+			serde_json::from_str::<Value>(
+				&ImageAnalyzer::new(&settings).unwrap().get_json_report()
+			)
+			.unwrap()["histogram"]["luma"]
+		);
 		fs::remove_file(file_name).unwrap();
 	}
 }
@@ -1,3 +1,4 @@
+use crate::analyze::histogram::HistogramFormat;
 use crate::analyze::ImageAnalyzer;
 use crate::app::AppResult;
 use crate::args::matches::ArgMatches;
@@ -55,6 +56,10 @@ pub struct AnalyzeSettings {
 	pub file: PathBuf,
 	pub color: Color,
 	pub time: TimeZone,
+	pub colors: usize,
+	pub histogram: Option<HistogramFormat>,
+	pub bins: usize,
+	pub json: bool,
 }
 
 /* Default initialization values for AnalyzeSettings */
@@ -64,6 +69,10 @@ impl Default for AnalyzeSettings {
 			file: PathBuf::new(),
 			color: Color::White,
 			time: TimeZone::Utc(false),
+			colors: 5,
+			histogram: None,
+			bins: 32,
+			json: false,
 		}
 	}
 }
@@ -75,10 +84,30 @@ impl AnalyzeSettings {
 	 * @param  file
 	 * @param  color
 	 * @param  time
+	 * @param  colors
+	 * @param  histogram
+	 * @param  bins
+	 * @param  json
 	 * @return AnalyzeSettings
 	 */
-	pub fn new(file: PathBuf, color: Color, time: TimeZone) -> Self {
-		Self { file, color, time }
+	pub fn new(
+		file: PathBuf,
+		color: Color,
+		time: TimeZone,
+		colors: usize,
+		histogram: Option<HistogramFormat>,
+		bins: usize,
+		json: bool,
+	) -> Self {
+		Self {
+			file,
+			color,
+			time,
+			colors,
+			histogram,
+			bins,
+			json,
+		}
 	}
 
 	/**
@@ -101,7 +130,7 @@ impl AnalyzeSettings {
 	 */
 	fn from_parser(parser: ArgParser<'_>, color: Option<Color>) -> Self {
 		match parser.args {
-			Some(matches) => {
+			Some(ref matches) => {
 				let timestamp = matches.is_present("timestamp");
 				let file = matches.value_of("file").unwrap_or_default();
 				let file = shellexpand::full(file)
@@ -114,6 +143,14 @@ impl AnalyzeSettings {
 						Some("local") => TimeZone::Local(timestamp),
 						_ => TimeZone::Utc(timestamp),
 					},
+					parser.parse("colors", Self::default().colors).max(1),
+					match matches.value_of("histogram") {
+						Some("chart") => Some(HistogramFormat::Chart),
+						Some("raw") => Some(HistogramFormat::Raw),
+						_ => None,
+					},
+					parser.parse("bins", Self::default().bins).clamp(1, 256),
+					matches.is_present("json"),
 				)
 			}
 			None => Self::default(),
@@ -139,13 +176,38 @@ mod tests {
 	fn test_analyze_settings() {
 		let args = App::new("test")
 			.arg(Arg::with_name("file").required(true))
-			.get_matches_from(vec!["test", "test.png"]);
+			.arg(Arg::with_name("colors").long("colors").takes_value(true))
+			.arg(
+				Arg::with_name("histogram")
+					.long("histogram")
+					.takes_value(true),
+			)
+			.arg(Arg::with_name("bins").long("bins").takes_value(true))
+			.arg(Arg::with_name("json").long("json"))
+			.get_matches_from(vec![
+				"test",
+				"test.png",
+				"--colors",
+				"3",
+				"--histogram",
+				"raw",
+				"--bins",
+				"512",
+				"--json",
+			]);
 		let analyze_settings =
 			AnalyzeSettings::from_parser(ArgParser::from_args(&args), None);
 		assert_eq!(Some("test.png"), analyze_settings.file.to_str());
+		assert_eq!(3, analyze_settings.colors);
+		assert_eq!(Some(HistogramFormat::Raw), analyze_settings.histogram);
+		assert_eq!(256, analyze_settings.bins);
+		assert!(analyze_settings.json);
 		let analyze_settings = AnalyzeSettings::default();
 		assert_eq!(Some(""), analyze_settings.file.to_str());
 		assert_eq!(Color::White, analyze_settings.color);
+		assert_eq!(5, analyze_settings.colors);
+		assert_eq!(None, analyze_settings.histogram);
+		assert!(!analyze_settings.json);
 	}
 	#[test]
 	fn test_time_zone() {
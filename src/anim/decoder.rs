@@ -2,8 +2,11 @@ use crate::anim::settings::AnimSettings;
 use crate::anim::Frames;
 use crate::app::{AppError, AppResult};
 use crate::edit::ImageOps;
-use image::Frame;
+use image::{Frame, ImageFormat};
+use std::fs;
 use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
 
 /* Animation decoder and settings */
 pub struct AnimDecoder<'a> {
@@ -23,6 +26,56 @@ impl<'a> AnimDecoder<'a> {
 		Self { imageops, settings }
 	}
 
+	/**
+	 * Get the number of repetitions that is stored in the animation file.
+	 *
+	 * @param  path
+	 * @return i32 (Result Option)
+	 */
+	pub fn get_repeat(path: &Path) -> AppResult<Option<i32>> {
+		Ok(Self::parse_repeat(&fs::read(path)?))
+	}
+
+	/**
+	 * Parse the number of repetitions from the animation data.
+	 *
+	 * @param  data
+	 * @return i32 (Option)
+	 */
+	pub fn parse_repeat(data: &[u8]) -> Option<i32> {
+		let find = |chunk: &[u8], length: usize| {
+			data.windows(chunk.len() + length)
+				.find(|window| window.starts_with(chunk))
+				.map(|window| window[chunk.len()..].to_vec())
+		};
+		let (count, plays) = match image::guess_format(data) {
+			Ok(ImageFormat::Gif) => (
+				find(b"NETSCAPE2.0\x03\x01", 2).map(|count| {
+					u32::from(u16::from_le_bytes([count[0], count[1]]))
+				}),
+				false,
+			),
+			Ok(ImageFormat::Png) => (
+				find(b"acTL", 8).map(|control| {
+					u32::from_be_bytes([
+						control[4], control[5], control[6], control[7],
+					])
+				}),
+				true,
+			),
+			Ok(ImageFormat::WebP) => (
+				find(b"ANIM", 10)
+					.map(|anim| u32::from(u16::from_le_bytes([anim[8], anim[9]]))),
+				false,
+			),
+			_ => (None, false),
+		};
+		count.map(|count| match count {
+			0 => -1,
+			count => i32::try_from(count - u32::from(plays)).unwrap_or(-1),
+		})
+	}
+
 	/**
 	 * Update frames to cut the duration.
 	 *
@@ -63,7 +116,7 @@ impl<'a> AnimDecoder<'a> {
 			AppError::FrameError(String::from("No frames found to process"))
 		})?;
 		self.imageops
-			.init(first_frame.clone().into_buffer().dimensions());
+			.init(first_frame.clone().into_buffer().dimensions())?;
 		let fps = ((1e3 / first_frame.delay().numer_denom_ms().0 as f32)
 			* self.settings.speed) as u32;
 		debug!("FPS: {:?}", fps);
@@ -79,11 +132,22 @@ impl<'a> AnimDecoder<'a> {
 				frames.len()
 			);
 			io::stdout().flush()?;
-			images.push(
-				self.imageops
-					.process(frame.clone().into_buffer())
-					.get_image(),
-			);
+			let image = self
+				.imageops
+				.process(frame.clone().into_buffer())
+				.get_image();
+			let (numer, denom) = frame.delay().numer_denom_ms();
+			images.push(match self.settings.fps_override {
+				Some(_) => image,
+				None => image.with_delay(
+					Duration::try_from_secs_f64(
+						f64::from(numer)
+							/ f64::from(denom.max(1))
+							/ 1e3 / f64::from(self.settings.speed),
+					)
+					.unwrap_or_default(),
+				),
+			});
 		}
 		info!("\n");
 		Ok((images, fps))
@@ -125,5 +189,16 @@ mod tests {
 		assert_eq!(2, frames.1);
 		assert_eq!(1, frames.0.len());
 		assert_eq!(Geometry::new(0, 0, 2, 2), frames.0[0].geometry);
+		assert_eq!(Some(Duration::from_millis(5)), frames.0[0].delay);
+		let path = std::env::temp_dir().join("menyoki_repeat.gif");
+		fs::write(&path, b"GIF89a\x21\xFF\x0BNETSCAPE2.0\x03\x01\x05\x00\x00")
+			.unwrap();
+		assert_eq!(Some(5), AnimDecoder::get_repeat(&path).unwrap());
+		fs::write(&path, b"GIF89a\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")
+			.unwrap();
+		assert_eq!(Some(-1), AnimDecoder::get_repeat(&path).unwrap());
+		fs::write(&path, b"GIF89a").unwrap();
+		assert_eq!(None, AnimDecoder::get_repeat(&path).unwrap());
+		fs::remove_file(path).unwrap();
 	}
 }
@@ -0,0 +1,115 @@
+use crate::app::{AppError, AppResult};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/* Frame files and their delays that are listed in a manifest file */
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FrameManifest {
+	pub frames: Vec<(PathBuf, Duration)>,
+}
+
+impl FrameManifest {
+	/**
+	 * Create a new FrameManifest object from a manifest file.
+	 *
+	 * @param  path
+	 * @return FrameManifest (Result)
+	 */
+	pub fn from_file(path: &Path) -> AppResult<Self> {
+		let manifest = fs::read_to_string(path)?;
+		let frames = if manifest.trim_start().starts_with('[') {
+			Self::parse_json(&manifest)
+		} else {
+			Self::parse_csv(&manifest)
+		}
+		.filter(|frames| !frames.is_empty())
+		.ok_or_else(|| {
+			AppError::FrameError(format!("Invalid manifest file: {path:?}"))
+		})?;
+		let dir = path.parent().unwrap_or_else(|| Path::new(""));
+		debug!("Loaded {} frames from {:?}", frames.len(), path);
+		Ok(Self {
+			frames: frames
+				.into_iter()
+				.map(|(file, delay)| (dir.join(file), delay))
+				.collect(),
+		})
+	}
+
+	/**
+	 * Parse the lines of frame path and delay (ms) in CSV format.
+	 *
+	 * @param  manifest
+	 * @return Vector of PathBuf and Duration (Option)
+	 */
+	fn parse_csv(manifest: &str) -> Option<Vec<(PathBuf, Duration)>> {
+		let mut frames = Vec::new();
+		for (i, line) in manifest
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#'))
+			.enumerate()
+		{
+			let (file, delay) = line.rsplit_once(',')?;
+			match delay.trim().parse::<u64>() {
+				Ok(delay) => frames.push((
+					PathBuf::from(file.trim().trim_matches('"')),
+					Duration::from_millis(delay),
+				)),
+				Err(_) if i == 0 => continue,
+				Err(_) => return None,
+			}
+		}
+		Some(frames)
+	}
+
+	/**
+	 * Parse the array of objects with "path" and "delay" (ms) in JSON format.
+	 *
+	 * @param  manifest
+	 * @return Vector of PathBuf and Duration (Option)
+	 */
+	fn parse_json(manifest: &str) -> Option<Vec<(PathBuf, Duration)>> {
+		serde_json::from_str::<Value>(manifest)
+			.ok()?
+			.as_array()?
+			.iter()
+			.map(|frame| {
+				Some((
+					PathBuf::from(frame.get("path")?.as_str()?),
+					Duration::from_millis(frame.get("delay")?.as_u64()?),
+				))
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	use std::env;
+	#[test]
+	fn test_frame_manifest() -> AppResult<()> {
+		let frames = vec![
+			(PathBuf::from("1.png"), Duration::from_millis(500)),
+			(PathBuf::from("2 ,b.png"), Duration::from_millis(40)),
+		];
+		let csv = "path,delay\n# comment\n1.png, 500\n\"2 ,b.png\",40\n";
+		assert_eq!(Some(frames.clone()), FrameManifest::parse_csv(csv));
+		assert_eq!(None, FrameManifest::parse_csv("1.png,500\n2.png,x\n"));
+		let json = r#"[{"path": "1.png", "delay": 500},
+			{"path": "2 ,b.png", "delay": 40}]"#;
+		assert_eq!(Some(frames.clone()), FrameManifest::parse_json(json));
+		assert_eq!(None, FrameManifest::parse_json(r#"[{"path": "1.png"}]"#));
+		let path = env::temp_dir().join("menyoki_manifest.json");
+		fs::write(&path, json)?;
+		let manifest = FrameManifest::from_file(&path)?;
+		fs::remove_file(&path)?;
+		assert_eq!(env::temp_dir().join("1.png"), manifest.frames[0].0);
+		assert_eq!(frames[1].1, manifest.frames[1].1);
+		Ok(())
+	}
+}
@@ -1,17 +1,83 @@
 pub mod decoder;
+pub mod manifest;
 pub mod settings;
+pub mod stabilize;
+pub mod video;
 
+use crate::app::AppResult;
 use crate::image::Image;
 use std::fmt;
+use std::time::Duration;
 
 /* Images to encode and FPS value */
 pub type Frames = (Vec<Image>, u32);
 
+/**
+ * Get the frame delays of images or calculate them from the capture timestamps.
+ *
+ * @param  images
+ * @return Vector of Duration (Option)
+ */
+pub fn get_frame_delays(images: &[Image]) -> Option<Vec<Duration>> {
+	if let Some(delays) = images
+		.iter()
+		.map(|image| image.delay)
+		.collect::<Option<Vec<Duration>>>()
+		.filter(|delays| !delays.is_empty())
+	{
+		return Some(delays);
+	}
+	let timestamps = images
+		.iter()
+		.map(|image| image.timestamp)
+		.collect::<Option<Vec<Duration>>>()?;
+	if timestamps.len() < 2 {
+		return None;
+	}
+	let mut delays = timestamps
+		.windows(2)
+		.map(|t| t[1].saturating_sub(t[0]))
+		.collect::<Vec<Duration>>();
+	let average = delays.iter().sum::<Duration>() / delays.len() as u32;
+	delays.push(average);
+	Some(delays)
+}
+
+/**
+ * Drop the frames that are matched by the given function
+ * while adding their delays to the previous kept frame.
+ *
+ * @param  images
+ * @param  fps
+ * @param  should_drop (index, previous kept frame and frame)
+ * @return Vector of Image (Result)
+ */
+pub fn merge_frames(
+	images: Vec<Image>,
+	fps: u32,
+	mut should_drop: impl FnMut(usize, &Image, &Image) -> AppResult<bool>,
+) -> AppResult<Vec<Image>> {
+	let delays = get_frame_delays(&images).unwrap_or_else(|| {
+		vec![Duration::from_secs_f64(1. / f64::from(fps.max(1))); images.len()]
+	});
+	let mut frames: Vec<Image> = Vec::with_capacity(images.len());
+	for (i, (image, delay)) in images.into_iter().zip(delays).enumerate() {
+		match frames.last_mut() {
+			Some(previous) if should_drop(i, previous, &image)? => {
+				previous.delay = previous.delay.map(|previous| previous + delay);
+			}
+			_ => frames.push(image.with_delay(delay)),
+		}
+	}
+	Ok(frames)
+}
+
 /* Animation format */
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AnimFormat {
 	Gif,
 	Apng,
+	Y4m,
 }
 
 /* Display implementation for user-facing output */
@@ -59,6 +125,7 @@ impl AnimMode {
 			Self::Record(format) | Self::Edit(format) => match format {
 				AnimFormat::Gif => "Use the GIF encoder",
 				AnimFormat::Apng => "Use the APNG encoder",
+				AnimFormat::Y4m => "Use the YUV4MPEG2 encoder",
 			},
 			Self::Make => "Make an animation from frames",
 		}
@@ -104,4 +171,59 @@ mod tests {
 		assert_eq!("Make an animation from frames", anim_mode.get_description());
 		assert_eq!("make", anim_mode.to_string().as_str());
 	}
+	#[test]
+	fn test_frame_delays() {
+		let image = Image::new(Vec::new(), false, Default::default());
+		let mut images = vec![image.clone(), image.clone()];
+		assert_eq!(None, get_frame_delays(&images));
+		images = [0, 40, 140]
+			.iter()
+			.map(|ms| image.clone().with_timestamp(Duration::from_millis(*ms)))
+			.collect();
+		assert_eq!(
+			Some(vec![
+				Duration::from_millis(40),
+				Duration::from_millis(100),
+				Duration::from_millis(70)
+			]),
+			get_frame_delays(&images)
+		);
+		assert_eq!(None, get_frame_delays(&images[0..1]));
+		images = [500, 40]
+			.iter()
+			.map(|ms| image.clone().with_delay(Duration::from_millis(*ms)))
+			.collect();
+		assert_eq!(
+			Some(vec![Duration::from_millis(500), Duration::from_millis(40)]),
+			get_frame_delays(&images)
+		);
+	}
+	#[test]
+	fn test_merge_frames() -> AppResult<()> {
+		let images = (0..5)
+			.map(|i| {
+				Image::new(Vec::new(), false, Default::default())
+					.with_timestamp(Duration::from_millis(i * 100))
+			})
+			.collect::<Vec<Image>>();
+		assert_eq!(
+			vec![Some(200), Some(200), Some(100)],
+			merge_frames(images, 10, |i, _, _| Ok(i % 2 != 0))?
+				.iter()
+				.map(|image| image.delay.map(|delay| delay.as_millis()))
+				.collect::<Vec<Option<u128>>>()
+		);
+		assert_eq!(
+			vec![Some(100)],
+			merge_frames(
+				vec![Image::new(Vec::new(), false, Default::default()); 2],
+				20,
+				|_, previous, image| Ok(previous.get_diff_percentage(image)? == 0.)
+			)?
+			.iter()
+			.map(|image| image.delay.map(|delay| delay.as_millis()))
+			.collect::<Vec<Option<u128>>>()
+		);
+		Ok(())
+	}
 }
@@ -1,20 +1,57 @@
+use crate::anim::merge_frames;
+use crate::anim::stabilize;
+use crate::app::AppResult;
 use crate::args::matches::ArgMatches;
 use crate::args::parser::ArgParser;
 use crate::file::format::FileFormat;
 use crate::file::File;
+use crate::image::Image;
+use bytesize::ByteSize;
+use image::Rgb;
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/* Arguments of the gifski encoder settings */
+const GIFSKI_ARGS: [&str; 4] = [
+	"gifski-width",
+	"gifski-height",
+	"gifski-quality",
+	"gifski-motion-quality",
+];
 
 /* Animation and frame settings */
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AnimSettings {
 	pub fps: u32,
+	pub fps_override: Option<u32>,
 	pub repeat: i32,
+	pub repeat_override: bool,
 	pub quality: u8,
 	pub speed: f32,
+	pub speed_curve: Vec<(f64, f64)>,
 	pub cut: (f32, f32),
 	pub frames: Vec<PathBuf>,
+	pub manifest: Option<PathBuf>,
+	pub gap: u64,
+	pub transition: u64,
+	pub loop_crossfade: u64,
+	pub interpolate: Option<u32>,
 	pub gifski: (bool, bool),
+	#[cfg_attr(not(feature = "ski"), allow(dead_code))]
+	pub gifski_settings: GifskiSettings,
+	pub transparent: Option<Rgb<u8>>,
+	pub tolerance: u8,
+	pub global_palette: bool,
+	pub palette: Option<PathBuf>,
+	pub lossy: Option<u32>,
+	pub target_size: Option<u64>,
+	pub skip_frames: usize,
+	pub dedupe: bool,
+	pub stabilize: Option<u32>,
+	pub reverse: bool,
+	pub boomerang: bool,
+	pub resume: bool,
 }
 
 /* Default initialization values for AnimSettings */
@@ -22,12 +59,33 @@ impl Default for AnimSettings {
 	fn default() -> Self {
 		Self {
 			fps: 20,
+			fps_override: None,
 			repeat: -1,
+			repeat_override: false,
 			quality: 75,
 			speed: 1.,
+			speed_curve: Vec::new(),
 			cut: (0., 0.),
 			frames: Vec::new(),
+			manifest: None,
+			gap: 0,
+			transition: 0,
+			loop_crossfade: 0,
+			interpolate: None,
 			gifski: (false, false),
+			gifski_settings: GifskiSettings::default(),
+			transparent: None,
+			tolerance: 0,
+			global_palette: false,
+			palette: None,
+			lossy: None,
+			target_size: None,
+			skip_frames: 1,
+			dedupe: false,
+			stabilize: None,
+			reverse: false,
+			boomerang: false,
+			resume: false,
 		}
 	}
 }
@@ -56,12 +114,33 @@ impl AnimSettings {
 	) -> Self {
 		Self {
 			fps,
+			fps_override: None,
 			repeat,
+			repeat_override: false,
 			quality,
 			speed,
+			speed_curve: Vec::new(),
 			cut,
 			frames,
+			manifest: None,
+			gap: 0,
+			transition: 0,
+			loop_crossfade: 0,
+			interpolate: None,
 			gifski,
+			gifski_settings: GifskiSettings::default(),
+			transparent: None,
+			tolerance: 0,
+			global_palette: false,
+			palette: None,
+			lossy: None,
+			target_size: None,
+			skip_frames: 1,
+			dedupe: false,
+			stabilize: None,
+			reverse: false,
+			boomerang: false,
+			resume: false,
 		}
 	}
 
@@ -78,6 +157,15 @@ impl AnimSettings {
 			matches,
 			if matches.is_present("make") {
 				"make"
+			} else if matches!(
+				file_format,
+				FileFormat::Tiff
+					| FileFormat::Ansi
+					| FileFormat::Pdf
+					| FileFormat::Tar
+					| FileFormat::Zip
+			) {
+				"gif"
 			} else {
 				format
 			},
@@ -92,24 +180,110 @@ impl AnimSettings {
 	 */
 	fn from_parser(parser: ArgParser<'_>) -> Self {
 		match parser.args {
-			Some(ref matches) => Self::new(
-				match parser.parse("fps", Self::default().fps) {
-					fps if fps > 0 => fps,
-					_ => Self::default().fps,
+			Some(ref matches) => Self {
+				transparent: matches
+					.value_of("transparent")
+					.and_then(|color| {
+						hex::decode(color.trim_start_matches('#')).ok()
+					})
+					.filter(|rgb| rgb.len() == 3)
+					.map(|rgb| Rgb([rgb[0], rgb[1], rgb[2]])),
+				fps_override: if matches.args.occurrences_of("fps") > 0 {
+					Some(parser.parse("fps", Self::default().fps))
+						.filter(|fps| *fps > 0)
+				} else {
+					None
 				},
-				parser.parse("repeat", Self::default().repeat) - 1,
-				parser.parse("quality", Self::default().quality),
-				parser.parse("speed", Self::default().speed),
-				(
-					parser.parse("cut-beginning", Self::default().cut.0) * 1000.,
-					parser.parse("cut-end", Self::default().cut.1) * 1000.,
-				),
-				Self::get_frames(matches),
-				(
-					matches.is_present("gifski") || matches.is_present("fast"),
-					matches.is_present("fast"),
+				repeat_override: matches.args.occurrences_of("repeat") > 0,
+				tolerance: parser.parse("tolerance", Self::default().tolerance),
+				global_palette: matches.is_present("global-palette"),
+				speed_curve: matches
+					.value_of("speed-curve")
+					.and_then(|curve| {
+						let points = Self::parse_speed_curve(curve);
+						if points.is_none() {
+							warn!("Invalid speed curve: {:?}", curve);
+						}
+						points
+					})
+					.unwrap_or_default(),
+				gap: parser.parse("gap", Self::default().gap),
+				transition: parser.parse("transition", Self::default().transition),
+				loop_crossfade: parser
+					.parse("loop-crossfade", Self::default().loop_crossfade),
+				interpolate: match parser.parse("interpolate", 0) {
+					fps if fps > 0 => Some(fps),
+					_ => None,
+				},
+				gifski_settings: GifskiSettings::new(
+					matches
+						.value_of("gifski-width")
+						.and_then(|v| v.parse().ok()),
+					matches
+						.value_of("gifski-height")
+						.and_then(|v| v.parse().ok()),
+					matches
+						.value_of("gifski-quality")
+						.and_then(|v| v.parse().ok()),
+					matches
+						.value_of("gifski-motion-quality")
+						.and_then(|v| v.parse().ok()),
 				),
-			),
+				skip_frames: parser
+					.parse("skip-frames", Self::default().skip_frames)
+					.max(1),
+				dedupe: matches.is_present("dedupe"),
+				stabilize: match parser.parse("stabilize", 0) {
+					radius if radius > 0 => Some(radius),
+					_ => None,
+				},
+				reverse: matches.is_present("reverse"),
+				boomerang: matches.is_present("boomerang"),
+				resume: matches.is_present("resume"),
+				lossy: match parser.parse("lossy", 0) {
+					lossy if lossy > 0 => Some(lossy),
+					_ => None,
+				},
+				target_size: matches
+					.value_of("target-size")
+					.and_then(|size| size.parse::<ByteSize>().ok())
+					.map(|size| size.as_u64())
+					.filter(|size| *size > 0),
+				manifest: matches.value_of("manifest").map(|file| {
+					PathBuf::from(
+						shellexpand::full(file)
+							.map(|s| s.to_string())
+							.unwrap_or(file.to_string()),
+					)
+				}),
+				palette: matches.value_of("palette").map(|file| {
+					PathBuf::from(
+						shellexpand::full(file)
+							.map(|s| s.to_string())
+							.unwrap_or(file.to_string()),
+					)
+				}),
+				..Self::new(
+					match parser.parse("fps", Self::default().fps) {
+						fps if fps > 0 => fps,
+						_ => Self::default().fps,
+					},
+					parser.parse("repeat", Self::default().repeat) - 1,
+					parser.parse("quality", Self::default().quality),
+					parser.parse("speed", Self::default().speed),
+					(
+						parser.parse("cut-beginning", Self::default().cut.0) * 1000.,
+						parser.parse("cut-end", Self::default().cut.1) * 1000.,
+					),
+					Self::get_frames(matches),
+					(
+						matches.is_present("gifski")
+							|| matches.is_present("fast")
+							|| GIFSKI_ARGS.iter().any(|arg| matches.is_present(arg)),
+						matches.is_present("fast"),
+					),
+				)
+			},
 			None => Self::default(),
 		}
 	}
@@ -147,6 +321,254 @@ impl AnimSettings {
 		values.into_iter().map(PathBuf::from).collect()
 	}
 
+	/**
+	 * Keep every Nth frame and drop the identical consecutive frames
+	 * while extending the delays of the kept frames.
+	 *
+	 * @param  images
+	 * @param  fps
+	 * @return Vector of Image (Result)
+	 */
+	pub fn reduce_frames(
+		&self,
+		images: Vec<Image>,
+		fps: u32,
+	) -> AppResult<Vec<Image>> {
+		if self.skip_frames <= 1 && !self.dedupe {
+			return Ok(images);
+		}
+		let count = images.len();
+		let frames = merge_frames(images, fps, |i, previous, image| {
+			Ok((self.skip_frames > 1 && i % self.skip_frames != 0)
+				|| (self.dedupe && previous.get_diff_percentage(image)? == 0.))
+		})?;
+		info!("Reduced the frames from {} to {}.", count, frames.len());
+		Ok(frames)
+	}
+
+	/**
+	 * Stabilize the frames by smoothing the motion between them.
+	 *
+	 * @param  images
+	 * @return Vector of Image (Result)
+	 */
+	pub fn stabilize_frames(&self, images: Vec<Image>) -> AppResult<Vec<Image>> {
+		match self.stabilize {
+			Some(radius) => stabilize::stabilize_frames(images, radius),
+			None => Ok(images),
+		}
+	}
+
+	/**
+	 * Parse the speed curve from the comma-separated points of time:speed.
+	 *
+	 * @param  curve
+	 * @return Vector of f64 tuple (Option)
+	 */
+	pub fn parse_speed_curve(curve: &str) -> Option<Vec<(f64, f64)>> {
+		let mut points = curve
+			.split(',')
+			.map(|point| {
+				let (time, speed) = point.split_once(':')?;
+				Some((time.trim().parse().ok()?, speed.trim().parse().ok()?))
+			})
+			.map(|point| {
+				point.filter(|(time, speed): &(f64, f64)| *time >= 0. && *speed > 0.)
+			})
+			.collect::<Option<Vec<(f64, f64)>>>()?;
+		points.sort_by(|a, b| a.0.total_cmp(&b.0));
+		Some(points)
+	}
+
+	/**
+	 * Change the frame delays with the speed at the time of each frame.
+	 *
+	 * @param  images
+	 * @param  fps
+	 * @return Vector of Image
+	 */
+	pub fn apply_speed_curve(&self, mut images: Vec<Image>, fps: u32) -> Vec<Image> {
+		if self.speed_curve.is_empty() {
+			return images;
+		}
+		let frame_delay = Duration::from_secs_f64(1. / f64::from(fps.max(1)));
+		let mut elapsed = 0.;
+		for image in images.iter_mut() {
+			let delay = image.delay.unwrap_or(frame_delay);
+			let speed = self
+				.speed_curve
+				.iter()
+				.rev()
+				.find(|(time, _)| *time <= elapsed)
+				.map_or(1., |(_, speed)| *speed);
+			elapsed += delay.as_secs_f64();
+			image.delay = Some(delay.div_f64(speed));
+		}
+		images
+	}
+
+	/**
+	 * Concatenate the frames of the inputs by rescaling them to the first
+	 * frame and adding the pauses and transitions between them.
+	 *
+	 * @param  inputs
+	 * @return Vector of Image (Result)
+	 */
+	pub fn concat_frames(&self, inputs: Vec<Vec<Image>>) -> AppResult<Vec<Image>> {
+		let (width, height) = match inputs.iter().flatten().next() {
+			Some(image) => (image.geometry.width, image.geometry.height),
+			None => return Ok(Vec::new()),
+		};
+		let frame_delay = Duration::from_secs_f64(1. / f64::from(self.fps.max(1)));
+		let steps =
+			(self.transition as f64 / (frame_delay.as_secs_f64() * 1e3)) as u32;
+		let mut images = Vec::<Image>::new();
+		for input in inputs {
+			for (i, image) in input.into_iter().enumerate() {
+				let image = if (image.geometry.width, image.geometry.height)
+					== (width, height)
+				{
+					image
+				} else {
+					let mut fitted = image.fit(width, height, true)?;
+					fitted.delay = image.delay;
+					fitted
+				};
+				if let (0, Some(last)) = (i, images.last_mut()) {
+					if self.gap > 0 {
+						last.delay = Some(
+							last.delay.unwrap_or(frame_delay)
+								+ Duration::from_millis(self.gap),
+						);
+					}
+					let last = last.clone();
+					for step in 1..=steps {
+						images.push(
+							last.blend(
+								&image,
+								f64::from(step) / f64::from(steps + 1),
+							)?
+							.with_delay(frame_delay),
+						);
+					}
+				}
+				images.push(image);
+			}
+		}
+		if images.iter().any(|image| image.delay.is_some()) {
+			images
+				.iter_mut()
+				.filter(|image| image.delay.is_none())
+				.for_each(|image| image.delay = Some(frame_delay));
+		}
+		Ok(images)
+	}
+
+	/**
+	 * Synthesize the intermediate frames by blending the consecutive frames
+	 * for reaching the interpolation FPS.
+	 *
+	 * @param  images
+	 * @param  fps
+	 * @return Vector of Image and u32 (Result)
+	 */
+	pub fn interpolate_frames(
+		&self,
+		images: Vec<Image>,
+		fps: u32,
+	) -> AppResult<(Vec<Image>, u32)> {
+		let target_fps = match self.interpolate {
+			Some(target_fps) if target_fps > fps => target_fps,
+			_ => return Ok((images, fps)),
+		};
+		let frame_delay = Duration::from_secs_f64(1. / f64::from(fps.max(1)));
+		let mut frames = Vec::new();
+		for (i, image) in images.iter().enumerate() {
+			let delay = image.delay.unwrap_or(frame_delay);
+			let steps = ((delay.as_secs_f64() * f64::from(target_fps)).round()
+				as u32)
+				.max(1);
+			match images.get(i + 1) {
+				Some(next)
+					if next.geometry.width == image.geometry.width
+						&& next.geometry.height == image.geometry.height =>
+				{
+					frames.push(image.clone().with_delay(delay / steps));
+					for step in 1..steps {
+						frames.push(
+							image
+								.blend(next, f64::from(step) / f64::from(steps))?
+								.with_delay(delay / steps),
+						);
+					}
+				}
+				_ => frames.push(image.clone().with_delay(delay)),
+			}
+		}
+		debug!("Interpolated {} frames to {}", images.len(), frames.len());
+		Ok((frames, target_fps))
+	}
+
+	/**
+	 * Blend the last frames into the first frames for a seamless loop.
+	 *
+	 * @param  images
+	 * @param  fps
+	 * @return Vector of Image (Result)
+	 */
+	pub fn crossfade_loop(
+		&self,
+		mut images: Vec<Image>,
+		fps: u32,
+	) -> AppResult<Vec<Image>> {
+		let frame_delay = Duration::from_secs_f64(1. / f64::from(fps.max(1)));
+		let mut duration = 0;
+		let count = images
+			.iter()
+			.rev()
+			.take(images.len() / 2)
+			.take_while(|image| {
+				let elapsed = duration;
+				duration += image.delay.unwrap_or(frame_delay).as_millis();
+				elapsed < u128::from(self.loop_crossfade)
+			})
+			.count();
+		if count == 0 {
+			return Ok(images);
+		}
+		let head = images.drain(..count).collect::<Vec<Image>>();
+		let start = images.len() - count;
+		for (i, (image, first)) in
+			images[start..].iter_mut().zip(head.iter()).enumerate()
+		{
+			let delay = image.delay;
+			*image = image.blend(first, (i + 1) as f64 / (count + 1) as f64)?;
+			image.delay = delay;
+		}
+		Ok(images)
+	}
+
+	/**
+	 * Reorder the frames for reverse or boomerang playback.
+	 *
+	 * @param  images
+	 * @return Vector of Image
+	 */
+	pub fn reorder_frames(&self, mut images: Vec<Image>) -> Vec<Image> {
+		if self.reverse {
+			images.reverse();
+		}
+		if self.boomerang && images.len() > 2 {
+			let mirrored = images[1..images.len() - 1]
+				.iter()
+				.rev()
+				.cloned()
+				.collect::<Vec<Image>>();
+			images.extend(mirrored);
+		}
+		images
+	}
+
 	/**
 	 * Map the given number from a range to another range.
 	 *
@@ -172,6 +594,7 @@ impl AnimSettings {
 pub struct SplitSettings {
 	pub file: PathBuf,
 	pub dir: PathBuf,
+	pub archive: Option<PathBuf>,
 }
 
 /* Default initialization values for SplitSettings */
@@ -180,6 +603,7 @@ impl Default for SplitSettings {
 		Self {
 			file: PathBuf::new(),
 			dir: PathBuf::new(),
+			archive: None,
 		}
 	}
 }
@@ -190,10 +614,11 @@ impl SplitSettings {
 	 *
 	 * @param  file
 	 * @param  dir
+	 * @param  archive (Option)
 	 * @return SplitSettings
 	 */
-	pub fn new(file: PathBuf, dir: PathBuf) -> Self {
-		Self { file, dir }
+	pub fn new(file: PathBuf, dir: PathBuf, archive: Option<PathBuf>) -> Self {
+		Self { file, dir, archive }
 	}
 
 	/**
@@ -235,7 +660,129 @@ impl SplitSettings {
 							.unwrap_or_default(),
 					)),
 				};
-				Self::new(file, dir)
+				let archive = matches.value_of("archive").map(|archive| {
+					PathBuf::from(
+						shellexpand::full(archive)
+							.map(|s| s.to_string())
+							.unwrap_or(archive.to_string()),
+					)
+				});
+				Self::new(file, dir, archive)
+			}
+			None => Self::default(),
+		}
+	}
+}
+
+/* Output size and quality settings of the gifski encoder */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GifskiSettings {
+	pub width: Option<u32>,
+	pub height: Option<u32>,
+	pub quality: Option<u8>,
+	pub motion_quality: Option<u8>,
+}
+
+impl GifskiSettings {
+	/**
+	 * Create a new GifskiSettings object.
+	 *
+	 * @param  width (Option)
+	 * @param  height (Option)
+	 * @param  quality (Option)
+	 * @param  motion_quality (Option)
+	 * @return GifskiSettings
+	 */
+	pub fn new(
+		width: Option<u32>,
+		height: Option<u32>,
+		quality: Option<u8>,
+		motion_quality: Option<u8>,
+	) -> Self {
+		Self {
+			width: width.filter(|width| *width > 0),
+			height: height.filter(|height| *height > 0),
+			quality: quality.map(|quality| quality.clamp(1, 100)),
+			motion_quality: motion_quality.map(|quality| quality.clamp(1, 100)),
+		}
+	}
+
+	/**
+	 * Get the output size while keeping the aspect ratio
+	 * if only one of the dimensions is given.
+	 *
+	 * @param  width
+	 * @param  height
+	 * @return Tuple of u32
+	 */
+	#[cfg_attr(not(feature = "ski"), allow(dead_code))]
+	pub fn get_size(&self, width: u32, height: u32) -> (u32, u32) {
+		let scale = |value: u32, from: u32, to: u32| {
+			((f64::from(value) * f64::from(to) / f64::from(from.max(1))).round()
+				as u32)
+				.max(1)
+		};
+		match (self.width, self.height) {
+			(Some(w), Some(h)) => (w, h),
+			(Some(w), None) => (w, scale(height, width, w)),
+			(None, Some(h)) => (scale(width, height, h), h),
+			(None, None) => (width, height),
+		}
+	}
+}
+
+/* Animation trim settings */
+#[derive(Debug, Default)]
+pub struct TrimSettings {
+	pub file: PathBuf,
+	pub start: Duration,
+	pub end: Option<Duration>,
+}
+
+impl TrimSettings {
+	/**
+	 * Create a new TrimSettings object.
+	 *
+	 * @param  file
+	 * @param  start
+	 * @param  end (Option)
+	 * @return TrimSettings
+	 */
+	pub fn new(file: PathBuf, start: Duration, end: Option<Duration>) -> Self {
+		Self { file, start, end }
+	}
+
+	/**
+	 * Create a new TrimSettings object from arguments.
+	 *
+	 * @param  matches
+	 * @return TrimSettings
+	 */
+	pub fn from_args(matches: &ArgMatches<'_>) -> Self {
+		Self::from_parser(ArgParser::from_subcommand(matches, "trim"))
+	}
+
+	/**
+	 * Create a TrimSettings object from an argument parser.
+	 *
+	 * @param  parser
+	 * @return TrimSettings
+	 */
+	fn from_parser(parser: ArgParser<'_>) -> Self {
+		match parser.args {
+			Some(ref matches) => {
+				let file = matches.value_of("file").unwrap_or_default();
+				let file = shellexpand::full(file)
+					.map(|s| s.to_string())
+					.unwrap_or(file.to_string());
+				Self::new(
+					PathBuf::from(file),
+					Duration::from_secs_f64(parser.parse("start", 0_f64).max(0.)),
+					matches
+						.value_of("end")
+						.and_then(|end| end.parse::<f64>().ok())
+						.map(|end| Duration::from_secs_f64(end.max(0.))),
+				)
 			}
 			None => Self::default(),
 		}
@@ -245,7 +792,9 @@ impl SplitSettings {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::image::geometry::Geometry;
 	use clap::{App, Arg};
+	use image::{ExtendedColorType, Rgba};
 	use pretty_assertions::assert_eq;
 	use std::ffi::OsStr;
 	#[test]
@@ -263,6 +812,11 @@ mod tests {
 					.takes_value(true),
 			)
 			.arg(Arg::with_name("cut-end").long("cut-end").takes_value(true))
+			.arg(
+				Arg::with_name("transparent")
+					.long("transparent")
+					.takes_value(true),
+			)
 			.get_matches_from(vec![
 				"test",
 				"--fps",
@@ -279,22 +833,262 @@ mod tests {
 				"0.9",
 				"--cut-end",
 				"0.8",
+				"--transparent",
+				"#ff00ff",
 			]);
 		let anim_settings = AnimSettings::from_parser(ArgParser::from_args(&args));
 		assert_eq!(15, anim_settings.fps);
+		assert_eq!(Some(15), anim_settings.fps_override);
 		assert_eq!(4, anim_settings.repeat);
+		assert_eq!(true, anim_settings.repeat_override);
 		assert_eq!(10, anim_settings.quality);
 		assert_eq!(true, anim_settings.gifski.0);
 		assert_eq!(true, anim_settings.gifski.1);
 		assert_eq!(1.1, anim_settings.speed);
 		assert_eq!((900., 800.), anim_settings.cut);
+		assert_eq!(Some(Rgb([255, 0, 255])), anim_settings.transparent);
 		let anim_settings = AnimSettings::from_parser(ArgParser::new(None));
+		assert_eq!(None, anim_settings.fps_override);
 		assert_eq!(-1, anim_settings.repeat);
+		assert_eq!(false, anim_settings.repeat_override);
 		assert_eq!(75, anim_settings.quality);
 		assert_eq!(false, anim_settings.gifski.0);
 		assert_eq!(false, anim_settings.gifski.1);
 		assert_eq!(1.0, anim_settings.speed);
 		assert_eq!((0., 0.), anim_settings.cut);
+		assert_eq!(None, anim_settings.transparent);
+		assert_eq!(None, anim_settings.stabilize);
+	}
+	#[test]
+	fn test_gifski_settings() {
+		let settings = GifskiSettings::new(Some(0), None, Some(0), Some(150));
+		assert_eq!(
+			(None, Some(1), Some(100)),
+			(settings.width, settings.quality, settings.motion_quality)
+		);
+		assert_eq!((640, 480), settings.get_size(640, 480));
+		assert_eq!(
+			(320, 240),
+			GifskiSettings::new(Some(320), None, None, None).get_size(640, 480)
+		);
+		assert_eq!(
+			(200, 100),
+			GifskiSettings::new(None, Some(100), None, None).get_size(640, 320)
+		);
+		assert_eq!(
+			(10, 20),
+			GifskiSettings::new(Some(10), Some(20), None, None).get_size(640, 320)
+		);
+	}
+	#[test]
+	fn test_concat_frames() -> AppResult<()> {
+		let get_image = |width, height| {
+			Image::new(
+				vec![Rgba([0, 0, 0, 255]); (width * height) as usize],
+				false,
+				Geometry::new(0, 0, width, height),
+			)
+		};
+		let settings = AnimSettings::default();
+		assert!(settings.concat_frames(Vec::new())?.is_empty());
+		let images = settings.concat_frames(vec![
+			vec![get_image(2, 2), get_image(2, 2)],
+			vec![get_image(4, 2)],
+		])?;
+		assert_eq!(3, images.len());
+		assert!(images.iter().all(|image| (2, 2, None)
+			== (image.geometry.width, image.geometry.height, image.delay)));
+		let settings = AnimSettings {
+			fps: 10,
+			gap: 500,
+			transition: 200,
+			..AnimSettings::default()
+		};
+		let images = settings.concat_frames(vec![
+			vec![get_image(2, 2)],
+			vec![get_image(2, 2).with_delay(Duration::from_millis(300))],
+		])?;
+		assert_eq!(
+			vec![600, 100, 100, 300],
+			images
+				.iter()
+				.map(|image| image.delay.unwrap_or_default().as_millis())
+				.collect::<Vec<u128>>()
+		);
+		Ok(())
+	}
+	#[test]
+	fn test_speed_curve() {
+		assert_eq!(
+			Some(vec![(0., 1.), (1.5, 0.5), (5., 3.)]),
+			AnimSettings::parse_speed_curve("5:3.0, 0:1,1.5:0.5")
+		);
+		assert_eq!(None, AnimSettings::parse_speed_curve("garbage"));
+		assert_eq!(None, AnimSettings::parse_speed_curve("0:1,x:2"));
+		assert_eq!(None, AnimSettings::parse_speed_curve("0:1,1:0"));
+		assert_eq!(None, AnimSettings::parse_speed_curve("-1:2"));
+		let settings = AnimSettings {
+			speed_curve: AnimSettings::parse_speed_curve("1:2,1.5:0.5")
+				.unwrap_or_default(),
+			..AnimSettings::default()
+		};
+		let image = Image::new(Vec::new(), false, Geometry::default());
+		let images = settings.apply_speed_curve(
+			vec![
+				image.clone().with_delay(Duration::from_millis(1000)),
+				image.clone(),
+				image.with_delay(Duration::from_millis(1000)),
+			],
+			2,
+		);
+		assert_eq!(
+			vec![1000, 250, 2000],
+			images
+				.iter()
+				.map(|image| image.delay.unwrap_or_default().as_millis())
+				.collect::<Vec<u128>>()
+		);
+	}
+	#[test]
+	fn test_reduce_frames() -> AppResult<()> {
+		let images = [0, 0, 1, 2, 2, 2, 3]
+			.iter()
+			.map(|i| {
+				Image::new(
+					vec![Rgba([*i, 0, 0, 255])],
+					false,
+					Geometry::new(0, 0, 1, 1),
+				)
+			})
+			.collect::<Vec<Image>>();
+		let get_frames = |settings: AnimSettings| {
+			settings
+				.reduce_frames(images.clone(), 10)?
+				.iter()
+				.map(|image| {
+					Ok((
+						image.get_data(ExtendedColorType::Rgba8)?[0],
+						image.delay.unwrap_or_default().as_millis(),
+					))
+				})
+				.collect::<AppResult<Vec<(u8, u128)>>>()
+		};
+		assert_eq!(
+			vec![(0, 200), (1, 100), (2, 300), (3, 100)],
+			get_frames(AnimSettings {
+				dedupe: true,
+				..AnimSettings::default()
+			})?
+		);
+		assert_eq!(
+			vec![(0, 300), (2, 300), (3, 100)],
+			get_frames(AnimSettings {
+				skip_frames: 3,
+				..AnimSettings::default()
+			})?
+		);
+		assert_eq!(
+			None,
+			AnimSettings::default().reduce_frames(images, 10)?[0].delay
+		);
+		Ok(())
+	}
+	#[test]
+	fn test_interpolate_frames() -> AppResult<()> {
+		let images = (0..3)
+			.map(|i| {
+				Image::new(
+					vec![Rgba([i * 90, 0, 0, 255])],
+					false,
+					Geometry::new(0, 0, 1, 1),
+				)
+			})
+			.collect::<Vec<Image>>();
+		let settings = AnimSettings {
+			interpolate: Some(30),
+			..AnimSettings::default()
+		};
+		let (frames, fps) = settings.interpolate_frames(images.clone(), 10)?;
+		assert_eq!(30, fps);
+		assert_eq!(
+			vec![0, 30, 60, 90, 120, 150, 180],
+			frames
+				.iter()
+				.map(|image| Ok(image.get_data(ExtendedColorType::Rgba8)?[0]))
+				.collect::<AppResult<Vec<u8>>>()?
+		);
+		assert_eq!(
+			vec![33, 33, 33, 33, 33, 33, 100],
+			frames
+				.iter()
+				.map(|image| image.delay.unwrap_or_default().as_millis())
+				.collect::<Vec<u128>>()
+		);
+		assert_eq!(3, settings.interpolate_frames(images, 30)?.0.len());
+		Ok(())
+	}
+	#[test]
+	fn test_crossfade_loop() -> AppResult<()> {
+		let images = (0..6)
+			.map(|i| {
+				Image::new(
+					vec![Rgba([i * 40, 0, 0, 255])],
+					false,
+					Geometry::new(0, 0, 1, 1),
+				)
+			})
+			.collect::<Vec<Image>>();
+		let settings = AnimSettings {
+			loop_crossfade: 150,
+			..AnimSettings::default()
+		};
+		assert_eq!(
+			vec![80, 120, 107, 93],
+			settings
+				.crossfade_loop(images.clone(), 10)?
+				.iter()
+				.map(|image| Ok(image.get_data(ExtendedColorType::Rgba8)?[0]))
+				.collect::<AppResult<Vec<u8>>>()?
+		);
+		assert_eq!(1, settings.crossfade_loop(images[..1].to_vec(), 10)?.len());
+		assert_eq!(images.len(), settings.crossfade_loop(images, 5)?.len() + 1);
+		Ok(())
+	}
+	#[test]
+	fn test_reorder_frames() {
+		let images = (0..4)
+			.map(|i| Image::new(Vec::new(), false, Geometry::new(i, 0, 0, 0)))
+			.collect::<Vec<Image>>();
+		let get_order = |settings: AnimSettings| {
+			settings
+				.reorder_frames(images.clone())
+				.iter()
+				.map(|image| image.geometry.x)
+				.collect::<Vec<i32>>()
+		};
+		assert_eq!(vec![0, 1, 2, 3], get_order(AnimSettings::default()));
+		assert_eq!(
+			vec![3, 2, 1, 0],
+			get_order(AnimSettings {
+				reverse: true,
+				..AnimSettings::default()
+			})
+		);
+		assert_eq!(
+			vec![0, 1, 2, 3, 2, 1],
+			get_order(AnimSettings {
+				boomerang: true,
+				..AnimSettings::default()
+			})
+		);
+		assert_eq!(
+			vec![3, 2, 1, 0, 1, 2],
+			get_order(AnimSettings {
+				reverse: true,
+				boomerang: true,
+				..AnimSettings::default()
+			})
+		);
 	}
 	#[test]
 	fn test_split_settings() {
@@ -304,10 +1098,28 @@ mod tests {
 		let split_settings = SplitSettings::from_parser(ArgParser::from_args(&args));
 		assert_eq!(PathBuf::from("x"), split_settings.file);
 		assert_eq!(Some(OsStr::new("x_frames")), split_settings.dir.file_name());
+		assert_eq!(None, split_settings.archive);
 		let args = App::new("test")
 			.arg(Arg::with_name("dir").long("dir").takes_value(true))
-			.get_matches_from(vec!["test", "--dir", "~/"]);
+			.arg(Arg::with_name("archive").long("archive").takes_value(true))
+			.get_matches_from(vec!["test", "--dir", "~/", "--archive", "x.zip"]);
 		let split_settings = SplitSettings::from_parser(ArgParser::from_args(&args));
-		assert_eq!(dirs::home_dir().unwrap(), split_settings.dir)
+		assert_eq!(dirs::home_dir().unwrap(), split_settings.dir);
+		assert_eq!(Some(PathBuf::from("x.zip")), split_settings.archive)
+	}
+	#[test]
+	fn test_trim_settings() {
+		let args = App::new("test")
+			.arg(Arg::with_name("file").required(true))
+			.arg(Arg::with_name("start").long("start").takes_value(true))
+			.arg(Arg::with_name("end").long("end").takes_value(true))
+			.get_matches_from(vec!["test", "x.gif", "--start", "1.5", "--end", "3"]);
+		let trim_settings = TrimSettings::from_parser(ArgParser::from_args(&args));
+		assert_eq!(PathBuf::from("x.gif"), trim_settings.file);
+		assert_eq!(Duration::from_millis(1500), trim_settings.start);
+		assert_eq!(Some(Duration::from_secs(3)), trim_settings.end);
+		let trim_settings = TrimSettings::from_parser(ArgParser::new(None));
+		assert_eq!(Duration::ZERO, trim_settings.start);
+		assert_eq!(None, trim_settings.end);
 	}
 }
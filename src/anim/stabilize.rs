@@ -0,0 +1,223 @@
+use crate::app::AppResult;
+use crate::image::geometry::Geometry;
+use crate::image::Image;
+use image::ExtendedColorType;
+
+/* Maximum distance of the motion between consecutive frames */
+const SEARCH_RADIUS: i32 = 24;
+/* Maximum number of pixels that are sampled for comparing the frames */
+const MAX_SAMPLES: usize = 4096;
+/* Maximum ratio of the frame dimensions to crop on each side */
+const MAX_CROP_RATIO: f64 = 0.25;
+
+/* Grayscale frame for estimating the motion */
+#[derive(Clone, Debug)]
+struct LumaFrame {
+	width: i32,
+	height: i32,
+	data: Vec<u8>,
+}
+
+impl LumaFrame {
+	/**
+	 * Create a new LumaFrame object from the image.
+	 *
+	 * @param  image
+	 * @return LumaFrame (Result)
+	 */
+	fn from_image(image: &Image) -> AppResult<Self> {
+		Ok(Self {
+			width: image.geometry.width as i32,
+			height: image.geometry.height as i32,
+			data: image.get_data(ExtendedColorType::L8)?,
+		})
+	}
+
+	/**
+	 * Get the positions of the pixels to compare while leaving
+	 * a margin for the search radius around them.
+	 *
+	 * @param  radius
+	 * @return Vector of usize
+	 */
+	fn get_samples(&self, radius: (i32, i32)) -> Vec<usize> {
+		let (start_x, end_x) = (radius.0, self.width - radius.0);
+		let (start_y, end_y) = (radius.1, self.height - radius.1);
+		let area = (end_x - start_x).max(0) as f64 * (end_y - start_y).max(0) as f64;
+		let step = ((area / MAX_SAMPLES as f64).sqrt().ceil() as usize).max(1);
+		(start_y..end_y)
+			.step_by(step)
+			.flat_map(|y| {
+				(start_x..end_x)
+					.step_by(step)
+					.map(move |x| (y * self.width + x) as usize)
+			})
+			.collect()
+	}
+
+	/**
+	 * Estimate the motion of the content to the given frame by searching
+	 * the offset with the least difference at the sampled pixels.
+	 *
+	 * Comparing the same pixels for all offsets (instead of the overlapping
+	 * area) avoids favoring the offsets that leave out the changing parts.
+	 *
+	 * @param  frame
+	 * @return Tuple of i32
+	 */
+	fn get_motion(&self, frame: &Self) -> (i32, i32) {
+		let radius = (
+			SEARCH_RADIUS.min((self.width - 1) / 4),
+			SEARCH_RADIUS.min((self.height - 1) / 4),
+		);
+		let samples = self.get_samples(radius);
+		let mut offsets = (-radius.1..=radius.1)
+			.flat_map(|y| (-radius.0..=radius.0).map(move |x| (x, y)))
+			.collect::<Vec<(i32, i32)>>();
+		offsets.sort_by_key(|(x, y)| x * x + y * y);
+		let (mut motion, mut min_diff) = ((0, 0), u64::MAX);
+		for (x, y) in offsets {
+			let shift = y as isize * self.width as isize + x as isize;
+			let diff = samples
+				.iter()
+				.map(|i| {
+					u64::from(
+						self.data[*i]
+							.abs_diff(frame.data[(*i as isize + shift) as usize]),
+					)
+				})
+				.sum::<u64>();
+			if diff < min_diff {
+				(motion, min_diff) = ((x, y), diff);
+				if diff == 0 {
+					break;
+				}
+			}
+		}
+		motion
+	}
+}
+
+/**
+ * Stabilize the frames by smoothing the camera path over the given
+ * number of frames and cropping the borders of the shifted frames.
+ *
+ * @param  images
+ * @param  radius
+ * @return Vector of Image (Result)
+ */
+pub fn stabilize_frames(images: Vec<Image>, radius: u32) -> AppResult<Vec<Image>> {
+	let Some(first) = images.first() else {
+		return Ok(images);
+	};
+	let (width, height) = (first.geometry.width, first.geometry.height);
+	if images.len() < 3 || width < 2 || height < 2 {
+		return Ok(images);
+	} else if images.iter().any(|image| {
+		(image.geometry.width, image.geometry.height) != (width, height)
+	}) {
+		warn!("Skipping stabilization of the frames with different sizes");
+		return Ok(images);
+	}
+	let mut path = vec![(0, 0)];
+	let mut previous = LumaFrame::from_image(first)?;
+	for image in images.iter().skip(1) {
+		let current = LumaFrame::from_image(image)?;
+		let motion = previous.get_motion(&current);
+		let last = path[path.len() - 1];
+		path.push((last.0 + motion.0, last.1 + motion.1));
+		previous = current;
+	}
+	let radius = radius as usize;
+	let corrections = (0..path.len())
+		.map(|i| {
+			let window =
+				&path[i.saturating_sub(radius)..(i + radius + 1).min(path.len())];
+			let mean = |get: fn(&(i32, i32)) -> i32| {
+				window.iter().map(get).sum::<i32>() as f64 / window.len() as f64
+			};
+			(
+				(mean(|p| p.0) - f64::from(path[i].0)).round() as i32,
+				(mean(|p| p.1) - f64::from(path[i].1)).round() as i32,
+			)
+		})
+		.collect::<Vec<(i32, i32)>>();
+	let get_margin = |get: fn(&(i32, i32)) -> i32, size: u32| {
+		corrections
+			.iter()
+			.map(|c| get(c).abs())
+			.max()
+			.unwrap_or_default()
+			.min((f64::from(size) * MAX_CROP_RATIO) as i32)
+	};
+	let margin = (get_margin(|c| c.0, width), get_margin(|c| c.1, height));
+	if margin == (0, 0) {
+		return Ok(images);
+	}
+	let size = (width - margin.0 as u32 * 2, height - margin.1 as u32 * 2);
+	debug!(
+		"Stabilized {} frames with {}x{} crop",
+		images.len(),
+		size.0,
+		size.1
+	);
+	images
+		.into_iter()
+		.zip(corrections)
+		.map(|(image, correction)| {
+			let mut image = image.crop(Geometry::new(
+				margin.0 - correction.0.clamp(-margin.0, margin.0),
+				margin.1 - correction.1.clamp(-margin.1, margin.1),
+				size.0,
+				size.1,
+			))?;
+			image.geometry = Geometry::new(0, 0, size.0, size.1);
+			Ok(image)
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_stabilize() -> AppResult<()> {
+		let get_color = |x: i32, y: i32| {
+			let (x, y) = (x as f32, y as f32);
+			let value =
+				128. + 60. * (x * 0.21).sin() + 60. * (y * 0.17 + x * 0.05).cos();
+			Rgba([value as u8, value as u8, value as u8, 255])
+		};
+		let shifts = [(0, 0), (3, -2), (-2, 4), (5, 1), (0, 0), (-4, -3)];
+		let images = shifts
+			.iter()
+			.map(|(dx, dy)| {
+				Image::new(
+					(0..100 * 80)
+						.map(|i| get_color(i % 100 + dx + 10, i / 100 + dy + 10))
+						.collect(),
+					false,
+					Geometry::new(0, 0, 100, 80),
+				)
+			})
+			.collect::<Vec<Image>>();
+		let frames = stabilize_frames(images.clone(), 10)?;
+		assert_eq!(shifts.len(), frames.len());
+		assert_eq!(
+			(90, 72),
+			(frames[0].geometry.width, frames[0].geometry.height)
+		);
+		for frame in frames.iter().skip(1) {
+			assert_eq!(0., frames[0].get_diff_percentage(frame)?);
+		}
+		assert_eq!(
+			100,
+			stabilize_frames(images[..2].to_vec(), 10)?[0]
+				.geometry
+				.width
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,231 @@
+use crate::app::{AppError, AppResult};
+use crate::image::geometry::Geometry;
+use crate::image::Image;
+use image::Rgba;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/* File extensions of the supported video formats */
+const VIDEO_EXTENSIONS: [&str; 6] = ["mp4", "webm", "mkv", "mov", "avi", "m4v"];
+
+/* Video decoder that reads the frames via ffmpeg */
+#[derive(Debug)]
+pub struct VideoDecoder {
+	path: PathBuf,
+	fps: u32,
+}
+
+impl VideoDecoder {
+	/**
+	 * Create a new VideoDecoder object.
+	 *
+	 * @param  path
+	 * @param  fps
+	 * @return VideoDecoder
+	 */
+	pub fn new(path: &Path, fps: u32) -> Self {
+		Self {
+			path: path.to_path_buf(),
+			fps: fps.max(1),
+		}
+	}
+
+	/**
+	 * Check if the given file is a video from its extension.
+	 *
+	 * @param  path
+	 * @return bool
+	 */
+	pub fn is_video(path: &Path) -> bool {
+		path.extension()
+			.and_then(|extension| extension.to_str())
+			.map(|extension| {
+				VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+			})
+			.unwrap_or(false)
+	}
+
+	/**
+	 * Decode the video into frames with the FPS.
+	 *
+	 * @return Vector of Image (Result)
+	 */
+	pub fn decode(&self) -> AppResult<Vec<Image>> {
+		let (width, height) = self.get_size()?;
+		debug!("Video size: {}x{}", width, height);
+		let mut child = Command::new("ffmpeg")
+			.args(["-v", "error", "-i"])
+			.arg(&self.path)
+			.args(["-vf", &format!("fps={},scale={width}:{height}", self.fps)])
+			.args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+			.stdout(Stdio::piped())
+			.spawn()
+			.map_err(|e| Self::get_error("ffmpeg", e))?;
+		let frames = match child.stdout.take() {
+			Some(stdout) => Self::read_frames(
+				stdout,
+				width,
+				height,
+				Duration::from_secs_f64(1. / f64::from(self.fps)),
+			)?,
+			None => Vec::new(),
+		};
+		if !child.wait()?.success() || frames.is_empty() {
+			return Err(AppError::FrameError(format!(
+				"Failed to decode the video: {:?}",
+				self.path
+			)));
+		}
+		Ok(frames)
+	}
+
+	/**
+	 * Get the displayed size of the video stream via ffprobe.
+	 *
+	 * @return Tuple of u32 (Result)
+	 */
+	fn get_size(&self) -> AppResult<(u32, u32)> {
+		let output = Command::new("ffprobe")
+			.args(["-v", "error", "-select_streams", "v:0"])
+			.args([
+				"-show_entries",
+				"stream=width,height:stream_tags=rotate:stream_side_data=rotation",
+			])
+			.args(["-of", "default=noprint_wrappers=1"])
+			.arg(&self.path)
+			.output()
+			.map_err(|e| Self::get_error("ffprobe", e))?;
+		Self::parse_size(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+			AppError::FrameError(format!(
+				"Failed to get the video size: {:?}",
+				self.path
+			))
+		})
+	}
+
+	/**
+	 * Parse the video size from the "key=value" lines of ffprobe.
+	 * Width and height are swapped for the rotated videos (e.g. videos
+	 * recorded on phones) since ffmpeg rotates the frames while decoding.
+	 *
+	 * @param  size
+	 * @return Tuple of u32 (Option)
+	 */
+	fn parse_size(size: &str) -> Option<(u32, u32)> {
+		let (mut width, mut height, mut rotation) = (None, None, 0.);
+		for (key, value) in
+			size.lines().filter_map(|line| line.trim().split_once('='))
+		{
+			match key {
+				"width" => width = value.parse::<u32>().ok(),
+				"height" => height = value.parse::<u32>().ok(),
+				"rotation" | "TAG:rotate" => {
+					rotation = value.parse::<f64>().unwrap_or(rotation)
+				}
+				_ => {}
+			}
+		}
+		let (width, height) =
+			(width.filter(|v| *v > 0)?, height.filter(|v| *v > 0)?);
+		if (rotation.round() as i64).rem_euclid(180) == 90 {
+			Some((height, width))
+		} else {
+			Some((width, height))
+		}
+	}
+
+	/**
+	 * Read the raw RGBA frames with the given size.
+	 *
+	 * @param  input
+	 * @param  width
+	 * @param  height
+	 * @param  delay
+	 * @return Vector of Image (Result)
+	 */
+	fn read_frames<Input: Read>(
+		mut input: Input,
+		width: u32,
+		height: u32,
+		delay: Duration,
+	) -> AppResult<Vec<Image>> {
+		let mut buffer = vec![0; width as usize * height as usize * 4];
+		let mut frames = Vec::new();
+		loop {
+			match input.read_exact(&mut buffer) {
+				Ok(()) => frames.push(
+					Image::new(
+						buffer
+							.chunks_exact(4)
+							.map(|rgba| Rgba([rgba[0], rgba[1], rgba[2], rgba[3]]))
+							.collect(),
+						false,
+						Geometry::new(0, 0, width, height),
+					)
+					.with_delay(delay),
+				),
+				Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+				Err(e) => return Err(e.into()),
+			}
+		}
+		debug!("Decoded {} frames from the video", frames.len());
+		Ok(frames)
+	}
+
+	/**
+	 * Get the error for a failed command.
+	 *
+	 * @param  command
+	 * @param  error
+	 * @return AppError
+	 */
+	fn get_error(command: &str, error: std::io::Error) -> AppError {
+		AppError::CommandError(format!(
+			"Failed to run {command} (is it installed?): {error}"
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::ExtendedColorType;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_video_decoder() -> AppResult<()> {
+		assert!(VideoDecoder::is_video(Path::new("test.MP4")));
+		assert!(VideoDecoder::is_video(Path::new("dir/test.webm")));
+		assert!(!VideoDecoder::is_video(Path::new("test.gif")));
+		assert!(!VideoDecoder::is_video(Path::new("mkv")));
+		assert_eq!(
+			Some((640, 480)),
+			VideoDecoder::parse_size("width=640\nheight=480\n")
+		);
+		assert_eq!(
+			Some((1080, 1920)),
+			VideoDecoder::parse_size("width=1920\nheight=1080\nrotation=-90\n")
+		);
+		assert_eq!(
+			Some((480, 640)),
+			VideoDecoder::parse_size("width=640\nheight=480\nTAG:rotate=270\n")
+		);
+		assert_eq!(
+			Some((640, 480)),
+			VideoDecoder::parse_size("width=640\nheight=480\nrotation=180\n")
+		);
+		assert_eq!(None, VideoDecoder::parse_size("width=0\nheight=480"));
+		assert_eq!(None, VideoDecoder::parse_size(""));
+		let data = (0..20).collect::<Vec<u8>>();
+		let frames =
+			VideoDecoder::read_frames(&data[..], 2, 1, Duration::from_millis(50))?;
+		assert_eq!(2, frames.len());
+		assert_eq!(
+			vec![8, 9, 10, 12, 13, 14],
+			frames[1].get_data(ExtendedColorType::Rgb8)?
+		);
+		assert_eq!(Some(Duration::from_millis(50)), frames[0].delay);
+		Ok(())
+	}
+}
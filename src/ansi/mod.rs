@@ -0,0 +1,197 @@
+use crate::app::{AppError, AppResult};
+use crate::gif::encoder::CancelHandle;
+use crate::image::settings::AnsiSettings;
+use crate::image::Image;
+use image::imageops::{self, FilterType};
+use image::{ExtendedColorType, Rgba, RgbaImage};
+use std::io::{self, Write};
+
+/* Characters for representing the luminance in ASCII mode */
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/* Upper half block character for truecolor mode */
+const HALF_BLOCK: char = '\u{2580}';
+
+/* ANSI/ASCII art encoder and settings */
+pub struct AnsiEncoder<Output: Write> {
+	output: Output,
+	settings: AnsiSettings,
+}
+
+impl<Output: Write> AnsiEncoder<Output> {
+	/**
+	 * Create a new AnsiEncoder object.
+	 *
+	 * @param  output
+	 * @param  settings
+	 * @return AnsiEncoder
+	 */
+	pub fn new(output: Output, settings: AnsiSettings) -> Self {
+		Self { output, settings }
+	}
+
+	/**
+	 * Resize the image to fit the terminal cells.
+	 *
+	 * @param  image
+	 * @return RgbaImage (Result)
+	 */
+	fn get_buffer(&self, image: &Image) -> AppResult<RgbaImage> {
+		let (width, height) = (image.geometry.width, image.geometry.height);
+		let buffer = RgbaImage::from_raw(
+			width,
+			height,
+			image.get_data(ExtendedColorType::Rgba8)?,
+		)
+		.ok_or_else(|| AppError::FrameError(String::from("Invalid image data")))?;
+		let columns = match self.settings.width {
+			0 => width,
+			columns => columns.min(width),
+		}
+		.max(1);
+		let rows = (height as f32 * columns as f32
+			/ width.max(1) as f32
+			/ if self.settings.ascii { 2. } else { 1. })
+		.round()
+		.max(1.) as u32;
+		Ok(if (columns, rows) == (width, height) {
+			buffer
+		} else {
+			imageops::resize(&buffer, columns, rows, FilterType::Triangle)
+		})
+	}
+
+	/**
+	 * Get the luminance character of the given pixel.
+	 *
+	 * @param  pixel
+	 * @return char
+	 */
+	fn get_ascii_char(pixel: &Rgba<u8>) -> char {
+		let luma = 0.2126 * pixel[0] as f32
+			+ 0.7152 * pixel[1] as f32
+			+ 0.0722 * pixel[2] as f32;
+		ASCII_RAMP[((luma / 256.) * ASCII_RAMP.len() as f32) as usize] as char
+	}
+
+	/**
+	 * Convert the image to text.
+	 *
+	 * @param  image
+	 * @return String (Result)
+	 */
+	pub fn get_text(&self, image: &Image) -> AppResult<String> {
+		let buffer = self.get_buffer(image)?;
+		let mut text = String::new();
+		if self.settings.ascii {
+			for row in buffer.rows() {
+				text += &row.map(Self::get_ascii_char).collect::<String>();
+				text.push('\n');
+			}
+		} else {
+			for y in (0..buffer.height()).step_by(2) {
+				for x in 0..buffer.width() {
+					let top = buffer.get_pixel(x, y);
+					let bottom = if y + 1 < buffer.height() {
+						buffer.get_pixel(x, y + 1)
+					} else {
+						top
+					};
+					text += &format!(
+						"\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m{}",
+						top[0],
+						top[1],
+						top[2],
+						bottom[0],
+						bottom[1],
+						bottom[2],
+						HALF_BLOCK
+					);
+				}
+				text += "\x1b[0m\n";
+			}
+		}
+		Ok(text)
+	}
+
+	/**
+	 * Convert images to text and write to the output.
+	 *
+	 * @param  images
+	 * @param  cancel
+	 * @return Result
+	 */
+	pub fn save(
+		mut self,
+		images: Vec<Image>,
+		cancel: CancelHandle,
+	) -> AppResult<()> {
+		let animated = images.len() > 1;
+		if animated {
+			self.output.write_all(b"\x1b[2J")?;
+		}
+		for (i, image) in images.iter().enumerate() {
+			if animated {
+				let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
+				info!("Saving... ({:.1}%)\r", percentage);
+				debug!(
+					"Encoding... ({:.1}%) [{}/{}]\r",
+					percentage,
+					i + 1,
+					images.len()
+				);
+				io::stdout().flush()?;
+				cancel.check()?;
+				self.output.write_all(b"\x1b[H")?;
+			}
+			self.output.write_all(self.get_text(image)?.as_bytes())?;
+		}
+		if animated {
+			info!("\n");
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image::geometry::Geometry;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_ansi_encoder() -> AppResult<()> {
+		let geometry = Geometry::new(0, 0, 2, 2);
+		let data = vec![
+			Rgba::from([255, 0, 0, 255]),
+			Rgba::from([0, 0, 0, 255]),
+			Rgba::from([0, 0, 255, 255]),
+			Rgba::from([255, 255, 255, 255]),
+		];
+		let image = Image::new(data, false, geometry);
+		let encoder = AnsiEncoder::new(Vec::new(), AnsiSettings::new(0, false));
+		assert_eq!(
+			"\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}\
+			\x1b[38;2;0;0;0m\x1b[48;2;255;255;255m\u{2580}\x1b[0m\n",
+			encoder.get_text(&image)?
+		);
+		let encoder = AnsiEncoder::new(Vec::new(), AnsiSettings::new(0, true));
+		assert_eq!(
+			"@@\n",
+			encoder.get_text(&Image::new(
+				vec![Rgba::from([255, 255, 255, 255]); 4],
+				false,
+				geometry
+			))?
+		);
+		let cancel = CancelHandle::new(None);
+		cancel.cancel();
+		assert!(AnsiEncoder::new(Vec::new(), AnsiSettings::new(1, true))
+			.save(vec![image.clone(), image.clone()], cancel)
+			.is_err());
+		let mut output = Vec::new();
+		AnsiEncoder::new(&mut output, AnsiSettings::new(1, true))
+			.save(vec![image.clone(), image], CancelHandle::new(None))?;
+		assert!(output.starts_with(b"\x1b[2J\x1b[H"));
+		Ok(())
+	}
+}
@@ -1,3 +1,4 @@
+use crate::anim::get_frame_delays;
 use crate::anim::settings::AnimSettings;
 use crate::app::AppResult;
 use crate::image::geometry::Geometry;
@@ -32,7 +33,7 @@ impl<'a, Output: Write> ApngEncoder<'a, Output> {
 		let mut encoder = Encoder::new(output, geometry.width, geometry.height);
 		encoder.set_animated(
 			frame_count,
-			settings.repeat.try_into().unwrap_or_default(),
+			(settings.repeat + 1).try_into().unwrap_or_default(),
 		)?;
 		encoder.set_color(ColorType::Rgba);
 		encoder.set_depth(BitDepth::Eight);
@@ -54,6 +55,7 @@ impl<'a, Output: Write> ApngEncoder<'a, Output> {
 	) -> AppResult<()> {
 		let mut writer = self.encoder.write_header()?;
 		writer.set_frame_delay(1, self.settings.fps.try_into().unwrap_or(1))?;
+		let delays = get_frame_delays(&images);
 		for (i, image) in images.iter().enumerate() {
 			let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
 			info!("Saving... ({:.1}%)\r", percentage);
@@ -71,7 +73,13 @@ impl<'a, Output: Write> ApngEncoder<'a, Output> {
 					panic!("Failed to write the frames")
 				}
 			}
-			writer.write_image_data(&image.get_data(ExtendedColorType::Rgba8))?;
+			if let Some(delays) = &delays {
+				writer.set_frame_delay(
+					delays[i].as_millis().try_into().unwrap_or(u16::MAX),
+					1000,
+				)?;
+			}
+			writer.write_image_data(&image.get_data(ExtendedColorType::Rgba8)?)?;
 		}
 		info!("\n");
 		Ok(())
@@ -1,44 +1,73 @@
 use crate::anim::decoder::AnimDecoder;
-use crate::anim::Frames;
+use crate::anim::manifest::FrameManifest;
+use crate::anim::settings::AnimSettings;
+use crate::anim::video::VideoDecoder;
+use crate::anim::{merge_frames, Frames};
+use crate::ansi::AnsiEncoder;
 use crate::apng::ApngEncoder;
+use crate::archive::{ArchiveFormat, FrameArchive};
 use crate::args::Args;
+use crate::diff::ImageDiff;
 use crate::file::format::FileFormat;
 use crate::file::File as FileUtil;
-use crate::gif::encoder::{Encoder, EncoderConfig};
+use crate::gif::encoder::{CancelHandle, Encoder, EncoderConfig};
+use crate::gif::lossy::MAX_LOSSY_LEVEL;
+use crate::gif::progress::EncoderProgress;
 #[cfg(feature = "ski")]
 use crate::gif::ski::GifskiEncoder;
+use crate::gif::trim::GifTrimmer;
 use crate::gif::GifEncoder;
+#[cfg(feature = "heif")]
+use crate::heif::HeifEncoder;
+use crate::image::icc::{IccProfile, IccSource};
+use crate::image::metadata::Metadata;
 use crate::image::Image;
-use crate::record::Recorder;
+use crate::jxl::JxlEncoder;
+use crate::montage::ImageMontage;
+use crate::pdf::PdfEncoder;
+use crate::png::PngEncoder;
+use crate::qoi::{QoiDecoder, QoiEncoder};
+use crate::record::settings::RecordSegment;
+use crate::record::{Recorder, STREAM_BUFFER_SIZE};
 use crate::settings::AppSettings;
+use crate::sixel::SixelEncoder;
+use crate::tiff::MultiTiffEncoder;
+use crate::util::progress::ProgressBar;
+use crate::view::settings::ViewSettings;
 use crate::view::ImageViewer;
 use crate::window::Capture;
+use crate::y4m::Y4mEncoder;
 use bytesize::ByteSize;
 use image::codecs::bmp::BmpEncoder;
 use image::codecs::farbfeld::FarbfeldEncoder;
 use image::codecs::gif::GifDecoder;
-use image::codecs::ico::IcoEncoder;
+use image::codecs::ico::{IcoEncoder, IcoFrame};
 use image::codecs::jpeg::JpegEncoder;
 use image::codecs::openexr::OpenExrEncoder;
 use image::codecs::png::PngDecoder;
-use image::codecs::png::PngEncoder;
 use image::codecs::pnm::{PnmEncoder, PnmSubtype};
 use image::codecs::tga::TgaEncoder;
 use image::codecs::tiff::TiffEncoder;
-use image::codecs::webp::WebPEncoder;
+use image::codecs::webp::{WebPDecoder, WebPEncoder};
 use image::error::{
 	ImageError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind,
 };
+use image::imageops::{self, FilterType};
 use image::io::Reader;
 use image::{
-	AnimationDecoder, ColorType, ExtendedColorType, ImageEncoder, ImageFormat,
+	AnimationDecoder, ColorType, ExtendedColorType, Frame, ImageEncoder,
+	ImageFormat, RgbaImage,
 };
 use std::fmt::Debug;
-use std::fs::{self, File};
-use std::io::{self, Cursor, Read, Seek, Write};
-use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 use std::thread;
+use std::time::Duration;
 use thiserror::Error as ThisError;
+use viuer::{get_kitty_support, is_iterm_supported, KittySupport};
 
 /* Custom error implementation */
 #[derive(Debug, ThisError)]
@@ -51,8 +80,12 @@ pub enum AppError {
 	Image(#[from] image::error::ImageError),
 	#[error("GIF encoding error: `{0}`")]
 	GifEncoding(#[from] gif::EncodingError),
+	#[error("GIF decoding error: `{0}`")]
+	GifDecoding(#[from] gif::DecodingError),
 	#[error("PNG encoding error: `{0}`")]
 	PngEncoding(#[from] png::EncodingError),
+	#[error("TIFF error: `{0}`")]
+	Tiff(#[from] tiff::TiffError),
 	#[cfg(feature = "ski")]
 	#[error("gifski error: `{0}`")]
 	Gifski(#[from] gifski::Error),
@@ -70,6 +103,9 @@ pub enum AppError {
 pub type AppOutput = (Option<Image>, Option<Frames>);
 pub type AppResult<T> = std::result::Result<T, AppError>;
 
+/* Maximum number of encoding passes for fitting into the target size */
+const TARGET_SIZE_PASSES: usize = 5;
+
 /* Application and main functionalities */
 #[derive(Clone, Copy, Debug)]
 pub struct App<'a, Window> {
@@ -110,7 +146,11 @@ where
 			self.split_anim(File::open(&self.settings.split.file)?)?;
 			info!(
 				"Frames saved to {:?} in {} format.",
-				self.settings.split.dir,
+				self.settings
+					.split
+					.archive
+					.as_ref()
+					.unwrap_or(&self.settings.split.dir),
 				self.settings.save.file.format.as_extension().to_uppercase(),
 			);
 		} else if self.settings.args.is_present("analyze") {
@@ -121,30 +161,359 @@ where
 			self.view_image()?;
 		} else if self.settings.save.file.path.to_str() == Some("-") {
 			let mut buffer = Cursor::new(Vec::new());
-			self.save_output(self.get_app_output()?, &mut buffer)?;
+			self.save_app_output(&mut buffer)?;
+			self.save_stats(buffer.get_ref().len() as u64)?;
 			io::stdout().write_all(&buffer.into_inner())?;
+		} else if let Some(segment) = self
+			.settings
+			.record
+			.time
+			.segment
+			.filter(|_| self.settings.args.is_present("record"))
+		{
+			self.save_segments(segment)?;
 		} else {
-			self.save_output(
-				self.get_app_output()?,
-				File::create(&self.settings.save.file.path)?,
-			)?;
+			let saved = if self.is_resumable() {
+				self.save_resumable_gif()
+			} else {
+				if self.settings.anim.resume {
+					warn!("Cannot resume the encoding with these settings");
+				}
+				self.save_app_output(File::create(&self.settings.save.file.path)?)
+			};
+			if let Err(e) = saved {
+				if self.is_streamable() || self.settings.args.is_present("trim") {
+					if let Err(err) = fs::remove_file(&self.settings.save.file.path)
+					{
+						warn!("Failed to remove the incomplete output: {}", err);
+					}
+				}
+				return Err(e);
+			}
+			let file_size = fs::metadata(&self.settings.save.file.path)?.len();
 			info!(
 				"{} saved to: {:?} ({})",
 				self.settings.save.file.format.as_extension().to_uppercase(),
 				self.settings.save.file.path,
-				ByteSize(fs::metadata(&self.settings.save.file.path)?.len())
+				ByteSize(file_size)
+			);
+			self.save_stats(file_size)?;
+			if self.settings.args.is_present("preview") {
+				self.preview_output()?;
+			}
+		}
+		Ok(())
+	}
+
+	/**
+	 * Get the application output and save it.
+	 *
+	 * @param  output
+	 * @return Result
+	 */
+	fn save_app_output<Output: Write + Seek + Send>(
+		&self,
+		output: Output,
+	) -> AppResult<()> {
+		if self.settings.args.is_present("trim") {
+			info!("Trimming {:?}...", self.settings.trim.file);
+			let frames =
+				GifTrimmer::new(self.settings.trim.start, self.settings.trim.end)
+					.trim(File::open(&self.settings.trim.file)?, output)?;
+			debug!("Copied {} frames.", frames);
+		} else if self.is_streamable() {
+			let mut output = Some(output);
+			self.record_gif(
+				|_| Ok(output.take().expect("Output is already in use")),
+				None,
+			)?;
+		} else {
+			let app_output = self.get_app_output()?;
+			self.save_extra_outputs(&app_output)?;
+			self.save_output(app_output, output)?;
+		}
+		Ok(())
+	}
+
+	/**
+	 * Check if the frames can be encoded while recording.
+	 *
+	 * @return bool
+	 */
+	fn is_streamable(&self) -> bool {
+		self.settings.args.is_present("record")
+			&& self.settings.save.file.format == FileFormat::Gif
+			&& !self.settings.anim.gifski.0
+			&& !self.settings.anim.global_palette
+			&& self.settings.record.time.replay.is_none()
+			&& self.settings.anim.skip_frames <= 1
+			&& !self.settings.anim.dedupe
+			&& self.settings.anim.stabilize.is_none()
+			&& self.settings.save.extra.is_empty()
+			&& self.settings.save.background.is_none()
+	}
+
+	/**
+	 * Check if the encoding progress can be saved for resuming.
+	 *
+	 * @return bool
+	 */
+	fn is_resumable(&self) -> bool {
+		self.settings.args.is_present("make")
+			&& self.settings.save.file.format == FileFormat::Gif
+			&& !self.settings.anim.gifski.0
+			&& self.settings.anim.target_size.is_none()
+			&& self.settings.save.extra.is_empty()
+			&& self.settings.save.background.is_none()
+	}
+
+	/**
+	 * Encode the frames to a GIF file while saving the progress
+	 * and continue from the saved progress if requested.
+	 *
+	 * @return Result
+	 */
+	fn save_resumable_gif(&self) -> AppResult<()> {
+		let path = &self.settings.save.file.path;
+		let progress_path = EncoderProgress::get_path(path);
+		let (mut images, fps) = self.get_frames()?;
+		self.set_transparent(&mut images)?;
+		let geometry = images
+			.first()
+			.ok_or_else(|| {
+				AppError::FrameError(String::from("No frames found to save"))
+			})?
+			.geometry;
+		let progress = if self.settings.anim.resume {
+			EncoderProgress::from_file(&progress_path).filter(|progress| {
+				let resumable = progress.is_compatible(images.len(), geometry)
+					&& fs::metadata(path)
+						.is_ok_and(|metadata| metadata.len() >= progress.offset);
+				if !resumable {
+					warn!("Cannot resume from {:?}, restarting", progress_path);
+				}
+				resumable
+			})
+		} else {
+			None
+		};
+		let mut output = OpenOptions::new()
+			.write(true)
+			.create(true)
+			.truncate(false)
+			.open(path)?;
+		output.set_len(progress.as_ref().map_or(0, |progress| progress.offset))?;
+		output.seek(SeekFrom::End(0))?;
+		GifEncoder::new(EncoderConfig::new(
+			fps,
+			geometry,
+			output,
+			&self.settings.anim,
+		))?
+		.with_progress(progress_path.clone(), progress)
+		.save(
+			images.into_iter(),
+			CancelHandle::new(self.settings.input_state),
+		)?;
+		fs::remove_file(progress_path)?;
+		Ok(())
+	}
+
+	/**
+	 * Record the frames and save them to numbered files as segments.
+	 *
+	 * @param  segment
+	 * @return Result
+	 */
+	fn save_segments(&self, segment: RecordSegment) -> AppResult<()> {
+		let segment_frames = segment.get_frames(self.settings.anim.fps);
+		debug!("Frames per segment: {}", segment_frames);
+		let segments = if self.is_streamable() {
+			self.record_gif(
+				|index| Ok(File::create(self.get_segment_path(index))?),
+				Some(segment_frames),
+			)?
+		} else {
+			let app_output = self.get_app_output()?;
+			self.save_extra_outputs(&app_output)?;
+			let (images, fps) = app_output.1.ok_or_else(|| {
+				AppError::FrameError(String::from("No frames found to save"))
+			})?;
+			let mut images = images.into_iter();
+			let mut segments = 0;
+			loop {
+				let frames =
+					images.by_ref().take(segment_frames).collect::<Vec<_>>();
+				if frames.is_empty() {
+					break;
+				}
+				segments += 1;
+				self.save_output(
+					(None, Some((frames, fps))),
+					File::create(self.get_segment_path(segments))?,
+				)?;
+			}
+			segments
+		};
+		let mut file_size = 0;
+		for index in 1..=segments {
+			let path = self.get_segment_path(index);
+			let size = fs::metadata(&path)?.len();
+			info!(
+				"{} saved to: {:?} ({})",
+				self.settings.save.file.format.as_extension().to_uppercase(),
+				path,
+				ByteSize(size)
 			);
+			file_size += size;
+		}
+		self.save_stats(file_size)
+	}
+
+	/**
+	 * Get the path of the output file for the given segment.
+	 *
+	 * @param  index
+	 * @return PathBuf
+	 */
+	fn get_segment_path(&self, index: usize) -> PathBuf {
+		let path = &self.settings.save.file.path;
+		path.with_file_name(format!(
+			"{}_{}{}",
+			path.file_stem().unwrap_or_default().to_string_lossy(),
+			index,
+			path.extension().map_or(String::new(), |extension| {
+				format!(".{}", extension.to_string_lossy())
+			})
+		))
+	}
+
+	/**
+	 * Record and encode the frames to GIF files simultaneously.
+	 *
+	 * @param  get_output
+	 * @param  segment_frames (Option)
+	 * @return usize (Result)
+	 */
+	fn record_gif<Output: Write + Send>(
+		self,
+		mut get_output: impl FnMut(usize) -> AppResult<Output> + Send,
+		segment_frames: Option<usize>,
+	) -> AppResult<usize> {
+		let (sender, receiver) = mpsc::sync_channel::<Image>(STREAM_BUFFER_SIZE);
+		let (transparent, tolerance) =
+			(self.settings.anim.transparent, self.settings.anim.tolerance);
+		thread::scope(|scope| {
+			let encoder = scope.spawn(move || -> AppResult<(usize, usize)> {
+				let mut error = None;
+				let mut frames = receiver.into_iter().map_while(|mut image| {
+					if let Some(color) = transparent {
+						if let Err(e) = image.set_transparent(color, tolerance) {
+							error = Some(e);
+							return None;
+						}
+					}
+					Some(image)
+				});
+				let (mut encoded, mut segments) = (0, 0);
+				while let Some(first) = frames.next() {
+					segments += 1;
+					encoded += GifEncoder::new(EncoderConfig::new(
+						self.settings.anim.fps,
+						first.geometry,
+						get_output(segments)?,
+						&self.settings.anim,
+					))?
+					.save_stream(iter::once(first).chain(
+						frames.by_ref().take(
+							segment_frames.map_or(usize::MAX, |frames| frames - 1),
+						),
+					))?;
+				}
+				drop(frames);
+				match error {
+					Some(e) => Err(e),
+					None => Ok((encoded, segments)),
+				}
+			});
+			let recorded = self.record(Some(sender));
+			let (encoded, segments) = encoder
+				.join()
+				.expect("Failed to join the encoder thread.")?;
+			if let Some(window) = self.window {
+				window.release();
+			}
+			recorded?;
+			debug!("Encoded {} frames while recording.", encoded);
+			match self.settings.stats.get() {
+				Some(stats) if stats.frames == 0 => Err(AppError::FrameError(
+					String::from("No frames found to save"),
+				)),
+				_ => Ok(segments),
+			}
+		})
+	}
+
+	/**
+	 * Save the recording statistics as JSON if requested.
+	 *
+	 * @param  file_size
+	 * @return Result
+	 */
+	fn save_stats(&self, file_size: u64) -> AppResult<()> {
+		if let (Some(path), Some(stats)) = (
+			self.settings.record.flag.stats_file,
+			self.settings.stats.get(),
+		) {
+			fs::write(path, stats.to_json(Some(file_size)))?;
+			info!("Statistics saved to: {:?}", path);
 		}
 		Ok(())
 	}
 
+	/**
+	 * Preview the saved file in the terminal.
+	 *
+	 * @return Result
+	 */
+	fn preview_output(&self) -> AppResult<()> {
+		let path = &self.settings.save.file.path;
+		match Reader::open(path)?.with_guessed_format()?.decode() {
+			Ok(image) => {
+				let sixel = get_kitty_support() == KittySupport::None
+					&& !is_iterm_supported();
+				debug!("Previewing the output... (sixel: {})", sixel);
+				ImageViewer::new(
+					image,
+					&ViewSettings::new(path.to_path_buf(), false, sixel),
+				)
+				.view()
+				.map(|(w, h)| debug!("Preview dimensions: {}x{}", w, h))
+			}
+			Err(e) => {
+				warn!("Preview is not available: {}", e);
+				Ok(())
+			}
+		}
+	}
+
 	/**
 	 * Get the application output.
 	 *
 	 * @return AppOutput (Result)
 	 */
 	fn get_app_output(self) -> AppResult<AppOutput> {
-		let output = if self.settings.save.file.format.is_animation() {
+		let settings = self.settings;
+		if self.settings.save.metadata {
+			let _ = self.settings.metadata.set(Metadata::new(
+				self.window.and_then(|window| window.get_title()),
+				self.settings.save.comment.clone(),
+			));
+		}
+		let mut output = if self.settings.save.file.format.is_animation()
+			|| self.settings.args.is_present("record")
+			|| self.settings.args.is_present("make")
+		{
 			(None, Some(self.get_frames()?))
 		} else {
 			(Some(self.get_image()?), None)
@@ -152,6 +521,19 @@ where
 		if let Some(window) = self.window {
 			window.release();
 		}
+		if let Some(background) = &settings.save.background {
+			info!("Flattening the output onto the background...");
+			output
+				.0
+				.iter_mut()
+				.chain(
+					output
+						.1
+						.iter_mut()
+						.flat_map(|(images, _)| images.iter_mut()),
+				)
+				.try_for_each(|image| image.flatten(background))?;
+		}
 		Ok(output)
 	}
 
@@ -165,6 +547,12 @@ where
 			debug!("{:?}", self.settings.edit);
 			info!("Opening {:?}...", self.settings.edit.path);
 			self.edit_image(&self.settings.edit.path)
+		} else if self.settings.args.is_present("montage") {
+			debug!("{:?}", self.settings.montage);
+			self.montage_images()
+		} else if self.settings.args.is_present("diff") {
+			debug!("{:?}", self.settings.diff);
+			self.diff_images()
 		} else {
 			self.capture()
 		}
@@ -176,28 +564,80 @@ where
 	 * @return Frames (Result)
 	 */
 	fn get_frames(self) -> AppResult<Frames> {
-		if self.settings.args.is_present("edit") {
+		let (images, fps) = if self.settings.args.is_present("edit") {
 			info!("Reading frames from {:?}...", self.settings.edit.path);
-			self.edit_anim(
+			let (images, fps) = self.edit_anim(
 				File::open(&self.settings.edit.path)?,
 				&self.settings.edit.path,
-			)
+			)?;
+			(images, self.settings.anim.fps_override.unwrap_or(fps))
 		} else if self.settings.args.is_present("make") {
-			info!(
-				"Making an animation from {} frames...",
-				self.settings.anim.frames.len()
-			);
-			let mut images = Vec::new();
-			for path in &self.settings.anim.frames {
+			let frames = match &self.settings.anim.manifest {
+				Some(manifest) => FrameManifest::from_file(manifest)?
+					.frames
+					.into_iter()
+					.map(|(path, delay)| (path, Some(delay)))
+					.collect(),
+				None => self
+					.settings
+					.anim
+					.frames
+					.iter()
+					.map(|path| (path.clone(), None))
+					.collect::<Vec<(PathBuf, Option<Duration>)>>(),
+			};
+			info!("Making an animation from {} frames...", frames.len());
+			let mut inputs = Vec::new();
+			for (path, delay) in frames {
 				debug!("Reading a frame from {:?}   \r", path);
 				io::stdout().flush()?;
-				images.push(self.edit_image(path)?);
+				if VideoDecoder::is_video(&path) {
+					info!("Decoding the video {:?}...", path);
+					inputs.push(
+						VideoDecoder::new(&path, self.settings.anim.fps).decode()?,
+					);
+				} else if Self::is_anim(&path)? {
+					let (images, fps) = self.edit_anim(File::open(&path)?, &path)?;
+					let delay = Duration::from_secs_f64(1. / f64::from(fps.max(1)));
+					inputs.push(if images.len() > 1 {
+						images
+							.into_iter()
+							.map(|i| match i.delay {
+								Some(_) => i,
+								None => i.with_delay(delay),
+							})
+							.collect()
+					} else {
+						images
+					});
+				} else {
+					let image = self.edit_image(&path)?;
+					inputs.push(vec![match delay {
+						Some(delay) => image.with_delay(delay),
+						None => image,
+					}]);
+				}
 			}
 			debug!("\n");
-			Ok((images, self.settings.anim.fps))
+			(
+				self.settings.anim.concat_frames(inputs)?,
+				self.settings.anim.fps,
+			)
 		} else {
-			Ok((self.record()?, self.settings.anim.fps))
-		}
+			let images = self.settings.anim.stabilize_frames(self.record(None)?)?;
+			return Ok((
+				self.settings
+					.anim
+					.reduce_frames(images, self.settings.anim.fps)?,
+				self.settings.anim.fps,
+			));
+		};
+		let images = self.settings.anim.stabilize_frames(images)?;
+		let images = self.settings.anim.reduce_frames(images, fps)?;
+		let images = self.settings.anim.apply_speed_curve(images, fps);
+		let (images, fps) = self.settings.anim.interpolate_frames(images, fps)?;
+		let images = self.settings.anim.reorder_frames(images);
+		Ok((self.settings.anim.crossfade_loop(images, fps)?, fps))
 	}
 
 	/**
@@ -210,7 +650,9 @@ where
 			AppError::WsError(String::from("Failed to get the window"))
 		})?;
 		if self.settings.record.command.is_some() {
+			let time = self.settings.record.time;
 			let image_thread = thread::spawn(move || {
+				time.wait_for_start();
 				window.show_countdown();
 				info!("Capturing an image...");
 				window.get_image()
@@ -228,19 +670,28 @@ where
 				.join()
 				.expect("Failed to join the image thread.")
 		} else {
+			self.settings.record.time.wait_for_start();
 			window.show_countdown();
 			info!("Capturing an image...");
 			window.get_image()
 		}
 		.ok_or_else(|| AppError::WsError(String::from("Failed to get image")))
+		.and_then(|image| match self.settings.record.flag.scale {
+			Some(factor) => image.scale(factor),
+			None => Ok(image),
+		})
 	}
 
 	/**
 	 * Start recording the frames.
 	 *
+	 * @param  stream (Option)
 	 * @return Vector of Image (Result)
 	 */
-	fn record(self) -> AppResult<Vec<Image>> {
+	fn record(
+		self,
+		stream: Option<mpsc::SyncSender<Image>>,
+	) -> AppResult<Vec<Image>> {
 		let mut recorder = Recorder::new(
 			self.window.ok_or_else(|| {
 				AppError::WsError(String::from("Failed to get the window"))
@@ -249,6 +700,9 @@ where
 			self.settings.anim.gifski.0,
 			self.settings.record,
 		);
+		if let Some(sender) = stream {
+			recorder = recorder.with_stream(sender);
+		}
 		if self.settings.record.command.is_some() {
 			let record = recorder.record_async();
 			self.settings
@@ -260,18 +714,21 @@ where
 					))
 				})?
 				.execute()?;
-			Ok(match record.get() {
-				Some(frames) => frames.expect("Failed to retrieve the frames."),
-				None => Vec::new(),
-			})
+			let (frames, stats) = record.get().map_err(|_| {
+				AppError::FrameError(String::from("Failed to retrieve the frames"))
+			})??;
+			let _ = self.settings.stats.set(stats);
+			Ok(frames)
 		} else {
-			Ok(recorder.record_sync(
+			let frames = recorder.record_sync(
 				if self.settings.record.flag.action_keys.is_some() {
 					self.settings.input_state
 				} else {
 					None
 				},
-			)?)
+			)?;
+			let _ = self.settings.stats.set(recorder.get_stats());
+			Ok(frames)
 		}
 	}
 
@@ -282,19 +739,73 @@ where
 	 * @return Image (Result)
 	 */
 	fn edit_image(self, path: &Path) -> AppResult<Image> {
-		let image = Reader::open(path)?
-			.with_guessed_format()?
-			.decode()?
-			.to_rgba8();
+		let data = fs::read(path)?;
+		let image = match QoiDecoder::new(&data) {
+			Some(decoder) => decoder.decode()?,
+			None => {
+				let mut reader = Reader::new(Cursor::new(data));
+				if let Ok(format) = ImageFormat::from_path(path) {
+					reader.set_format(format);
+				}
+				reader.with_guessed_format()?.decode()?.to_rgba8()
+			}
+		};
 		Ok(self
 			.settings
 			.edit
 			.get_imageops()
-			.init(image.dimensions())
+			.init(image.dimensions())?
 			.process(image)
 			.get_image())
 	}
 
+	/**
+	 * Combine the images into a grid (or stitch them) and return it.
+	 *
+	 * @return Image (Result)
+	 */
+	fn montage_images(self) -> AppResult<Image> {
+		let images = self
+			.settings
+			.montage
+			.files
+			.iter()
+			.map(|path| {
+				info!("Opening {:?}...", path);
+				Ok(image::open(path)?.to_rgba8())
+			})
+			.collect::<AppResult<Vec<RgbaImage>>>()?;
+		if images.is_empty() {
+			return Err(AppError::FrameError(String::from(
+				"No images found to combine",
+			)));
+		}
+		let montage = ImageMontage::new(&self.settings.montage);
+		Ok(if self.settings.montage.stitch {
+			montage.stitch(&images)
+		} else {
+			montage.combine(&images)
+		})
+	}
+
+	/**
+	 * Compare the images and return the difference image.
+	 *
+	 * @return Image (Result)
+	 */
+	fn diff_images(self) -> AppResult<Image> {
+		info!(
+			"Comparing {:?} with {:?}...",
+			self.settings.diff.file, self.settings.diff.other
+		);
+		let (image, percentage) = ImageDiff::new(&self.settings.diff).compare(
+			&image::open(&self.settings.diff.file)?.to_rgba8(),
+			&image::open(&self.settings.diff.other)?.to_rgba8(),
+		);
+		info!("Changed pixels: {:.2}%", percentage);
+		Ok(image)
+	}
+
 	/**
 	 * Analyze the image and return/save the report.
 	 *
@@ -303,12 +814,22 @@ where
 	fn analyze_image(self) -> AppResult<()> {
 		let analyzer = self.settings.analyze.get_analyzer()?;
 		if self.settings.save.file.format == FileFormat::Txt {
-			fs::write(&self.settings.save.file.path, analyzer.get_report() + "\n")?;
+			fs::write(
+				&self.settings.save.file.path,
+				if self.settings.analyze.json {
+					analyzer.get_json_report()
+				} else {
+					analyzer.get_report()
+				} + "\n",
+			)?;
 			info!(
 				"Report saved to: {:?} ({})",
 				self.settings.save.file.path,
 				ByteSize(fs::metadata(&self.settings.save.file.path)?.len())
 			);
+		} else if self.settings.analyze.json {
+			io::stdout()
+				.write_all((analyzer.get_json_report() + "\n").as_bytes())?;
 		} else {
 			info!("{}#", analyzer.get_colored_report());
 		}
@@ -330,6 +851,35 @@ where
 			.map(|(w, h)| debug!("Image dimensions: {}x{}", w, h))
 	}
 
+	/**
+	 * Check if the given file is an animation (GIF/APNG/WebP).
+	 *
+	 * @param  path
+	 * @return bool (Result)
+	 */
+	fn is_anim(path: &Path) -> AppResult<bool> {
+		Ok(match Reader::open(path)?.with_guessed_format()?.format() {
+			Some(ImageFormat::Gif) => true,
+			Some(ImageFormat::Png) => PngDecoder::new(File::open(path)?)?.is_apng(),
+			Some(ImageFormat::WebP) => Self::is_animated_webp(File::open(path)?)?,
+			_ => false,
+		})
+	}
+
+	/**
+	 * Check if the animation flag is set in the extended WebP header.
+	 *
+	 * @param  input
+	 * @return bool (Result)
+	 */
+	fn is_animated_webp<Input: Read>(input: Input) -> AppResult<bool> {
+		let mut header = Vec::new();
+		input.take(21).read_to_end(&mut header)?;
+		Ok(header.len() == 21
+			&& &header[12..16] == b"VP8X"
+			&& header[20] & 0x02 != 0)
+	}
+
 	/**
 	 * Return the updated frames after decoding the animation.
 	 *
@@ -349,6 +899,18 @@ where
 						.apng()
 						.into_frames()
 						.collect_frames(),
+					Some(ImageFormat::WebP) => WebPDecoder::new(input)?
+						.into_frames()
+						.collect_frames()
+						.and_then(|frames| {
+							if frames.is_empty() {
+								Ok(vec![Frame::new(
+									Reader::open(path)?.decode()?.into_rgba8(),
+								)])
+							} else {
+								Ok(frames)
+							}
+						}),
 					_ => Err(ImageError::Unsupported(
 						UnsupportedError::from_format_and_kind(
 							ImageFormatHint::Unknown,
@@ -368,6 +930,28 @@ where
 	fn split_anim<Input: Read>(self, input: Input) -> AppResult<()> {
 		let (frames, fps) = self.edit_anim(input, &self.settings.split.file)?;
 		debug!("FPS: {}", fps);
+		let mut progress_bar = ProgressBar::new("Splitting", frames.len());
+		if let Some(path) = &self.settings.split.archive {
+			let format = ArchiveFormat::from_path(path).ok_or_else(|| {
+				AppError::FrameError(format!("Unsupported archive format: {path:?}"))
+			})?;
+			let mut archive = FrameArchive::new(File::create(path)?, format);
+			for (i, frame) in frames.into_iter().enumerate() {
+				let name = format!(
+					"frame_{i}.{}",
+					self.settings.save.file.format.as_extension()
+				);
+				debug!("Adding {:?} to the archive\r", name);
+				io::stdout().flush()?;
+				let mut buffer = Cursor::new(Vec::new());
+				self.save_output((Some(frame), None), &mut buffer)?;
+				archive.append(&name, buffer.get_ref())?;
+				progress_bar.inc();
+			}
+			debug!("\n");
+			progress_bar.finish();
+			return archive.finish();
+		}
 		fs::create_dir_all(&self.settings.split.dir)?;
 		for i in 0..frames.len() {
 			let path = FileUtil::get_path_with_extension(
@@ -377,8 +961,47 @@ where
 			debug!("Saving to {:?}\r", path);
 			io::stdout().flush()?;
 			self.save_output((frames.get(i).cloned(), None), File::create(path)?)?;
+			progress_bar.inc();
 		}
 		debug!("\n");
+		progress_bar.finish();
+		Ok(())
+	}
+
+	/**
+	 * Save the application output to the additional output files.
+	 * (cloned frames share the captured pixel buffers)
+	 *
+	 * @param   app_output
+	 * @return  Result
+	 */
+	fn save_extra_outputs(&self, app_output: &AppOutput) -> AppResult<()> {
+		let (image, frames) = app_output;
+		for file in &self.settings.save.extra {
+			info!("Saving an additional output to {:?}...", file.path);
+			self.save_output_as(
+				(
+					image.clone().or_else(|| {
+						frames
+							.as_ref()
+							.and_then(|(images, _)| images.first().cloned())
+					}),
+					frames.clone().or_else(|| {
+						image
+							.clone()
+							.map(|image| (vec![image], self.settings.anim.fps))
+					}),
+				),
+				File::create(&file.path)?,
+				&file.format,
+			)?;
+			info!(
+				"{} saved to: {:?} ({})",
+				file.format.as_extension().to_uppercase(),
+				file.path,
+				ByteSize(fs::metadata(&file.path)?.len())
+			);
+		}
 		Ok(())
 	}
 
@@ -390,12 +1013,92 @@ where
 	 * @return  Result
 	 */
 	fn save_output<Output: Write + Seek>(
+		&self,
+		app_output: AppOutput,
+		output: Output,
+	) -> AppResult<()> {
+		self.save_output_as(app_output, output, &self.settings.save.file.format)
+	}
+
+	/**
+	 * Save the application output in the given format
+	 * with the ICC profile and metadata embedded if there are any.
+	 *
+	 * @param   app_output
+	 * @param   output
+	 * @param   format
+	 * @return  Result
+	 */
+	fn save_output_as<Output: Write + Seek>(
+		&self,
+		app_output: AppOutput,
+		mut output: Output,
+		format: &FileFormat,
+	) -> AppResult<()> {
+		let profile = if IccProfile::is_supported(format) {
+			self.get_icc_profile()?
+		} else {
+			None
+		};
+		let metadata = self
+			.settings
+			.metadata
+			.get()
+			.filter(|_| Metadata::is_supported(format));
+		if profile.is_none() && metadata.is_none() {
+			return self.write_output_as(app_output, output, format);
+		}
+		let mut buffer = Cursor::new(Vec::new());
+		self.write_output_as(app_output, &mut buffer, format)?;
+		let mut data = buffer.into_inner();
+		if let Some(profile) = profile {
+			match profile.embed(&data, format) {
+				Ok(embedded) => data = embedded,
+				Err(e) => warn!("Failed to embed the ICC profile: {}", e),
+			}
+		}
+		if let Some(metadata) = metadata {
+			match metadata.embed(&data, format) {
+				Ok(embedded) => data = embedded,
+				Err(e) => warn!("Failed to embed the metadata: {}", e),
+			}
+		}
+		output.write_all(&data)?;
+		Ok(())
+	}
+
+	/**
+	 * Get the ICC profile to embed from a file or the display.
+	 *
+	 * @return IccProfile (Option)
+	 */
+	fn get_icc_profile(&self) -> AppResult<Option<IccProfile>> {
+		Ok(match &self.settings.save.icc {
+			IccSource::File(path) => Some(IccProfile::from_file(path)?),
+			IccSource::Display => self
+				.window
+				.and_then(|window| window.get_icc_profile())
+				.and_then(IccProfile::new),
+			IccSource::None => None,
+		})
+	}
+
+	/**
+	 * Write the application output in the given format.
+	 *
+	 * @param   app_output
+	 * @param   output
+	 * @param   format
+	 * @return  Result
+	 */
+	fn write_output_as<Output: Write + Seek>(
 		&self,
 		app_output: AppOutput,
 		mut output: Output,
+		format: &FileFormat,
 	) -> AppResult<()> {
 		let (image, frames) = app_output;
-		match self.settings.save.file.format {
+		match format {
 			FileFormat::Gif => {
 				debug!("{:?}", self.settings.anim);
 				self.save_gif(frames, output)
@@ -404,13 +1107,13 @@ where
 				debug!("{:?}", self.settings.anim);
 				self.save_apng(frames, output)
 			}
+			FileFormat::Y4m => {
+				debug!("{:?}", self.settings.anim);
+				self.save_y4m(frames, output)
+			}
 			FileFormat::Png => self.save_image(
 				image,
-				PngEncoder::new_with_quality(
-					output,
-					self.settings.png.compression,
-					self.settings.png.filter,
-				),
+				PngEncoder::new(output, self.settings.png),
 				ExtendedColorType::Rgba8,
 			),
 			FileFormat::Jpg => self.save_image(
@@ -421,27 +1124,69 @@ where
 				),
 				ExtendedColorType::Rgb8,
 			),
-			FileFormat::WebP => self.save_image(
-				image,
-				WebPEncoder::new_with_quality(
-					&mut output,
-					self.settings.webp.get_quality(),
+			FileFormat::WebP => match self.settings.webp.transparent {
+				Some(color) => self.save_image(
+					image
+						.map(|mut image| -> AppResult<Image> {
+							image.set_transparent(
+								color,
+								self.settings.webp.tolerance,
+							)?;
+							Ok(image)
+						})
+						.transpose()?,
+					WebPEncoder::new_with_quality(
+						&mut output,
+						self.settings.webp.get_quality(),
+					),
+					ExtendedColorType::Rgba8,
 				),
-				ExtendedColorType::Rgb8,
+				None => self.save_image(
+					image,
+					WebPEncoder::new_with_quality(
+						&mut output,
+						self.settings.webp.get_quality(),
+					),
+					ExtendedColorType::Rgb8,
+				),
+			},
+			FileFormat::Jxl => self.save_image(
+				image,
+				JxlEncoder::new(output, self.settings.jxl),
+				ExtendedColorType::Rgba8,
+			),
+			#[cfg(feature = "heif")]
+			FileFormat::Heif => self.save_image(
+				image,
+				HeifEncoder::new(output, self.settings.heif),
+				ExtendedColorType::Rgba8,
+			),
+			#[cfg(not(feature = "heif"))]
+			FileFormat::Heif => Err(AppError::FrameError(String::from(
+				"HEIF support is not enabled (build with the heif feature)",
+			))),
+			FileFormat::Qoi => self.save_image(
+				image,
+				QoiEncoder::new(output),
+				ExtendedColorType::Rgba8,
 			),
 			FileFormat::Bmp => self.save_image(
 				image,
 				BmpEncoder::new(&mut output),
 				ExtendedColorType::Rgba8,
 			),
+			FileFormat::Ico if !self.settings.ico.sizes.is_empty() => {
+				self.save_ico(image, output)
+			}
 			FileFormat::Ico => self.save_image(
 				image,
 				IcoEncoder::new(output),
 				ExtendedColorType::Rgba8,
 			),
+			FileFormat::Tiff if frames.is_some() => self.save_tiff(frames, output),
 			FileFormat::Tiff => self.save_image(
 				image,
-				TiffEncoder::new(File::create(&self.settings.save.file.path)?),
+				TiffEncoder::new(output),
 				ExtendedColorType::Rgba8,
 			),
 			FileFormat::Tga => self.save_image(
@@ -469,6 +1214,24 @@ where
 				OpenExrEncoder::new(output),
 				ExtendedColorType::Rgba32F,
 			),
+			FileFormat::Pdf => self.save_pdf(frames, output),
+			FileFormat::Tar => self.save_archive(frames, output, ArchiveFormat::Tar),
+			FileFormat::Zip => self.save_archive(frames, output, ArchiveFormat::Zip),
+			FileFormat::Sixel => self.save_image(
+				image,
+				SixelEncoder::new(output),
+				ExtendedColorType::Rgba8,
+			),
+			FileFormat::Ansi => {
+				debug!("{:?}", self.settings.ansi);
+				AnsiEncoder::new(output, self.settings.ansi).save(
+					match frames {
+						Some((images, _)) => images,
+						None => image.into_iter().collect(),
+					},
+					CancelHandle::new(self.settings.input_state),
+				)
+			}
 			_ => Ok(()),
 		}
 	}
@@ -498,11 +1261,13 @@ where
 			debug!("{:?}", image);
 			debug!("{:?}", self.settings.png);
 			debug!("{:?}", self.settings.jpg);
+			debug!("{:?}", self.settings.jxl);
+			debug!("{:?}", self.settings.heif);
 			debug!("{:?}", self.settings.pnm);
 			debug!("Color type: {:?}", color_type);
 		}
 		encoder.write_image(
-			&image.get_data(color_type),
+			&image.get_data(color_type)?,
 			image.geometry.width,
 			image.geometry.height,
 			match color_type {
@@ -516,6 +1281,54 @@ where
 		Ok(())
 	}
 
+	/**
+	 * Save the image as an ICO file with multiple sizes.
+	 *
+	 * @param  image (Option)
+	 * @param  output
+	 * @return Result
+	 */
+	fn save_ico<Output: Write>(
+		self,
+		image: Option<Image>,
+		output: Output,
+	) -> AppResult<()> {
+		let image = image.ok_or_else(|| {
+			AppError::WsError(String::from("Failed to get the image"))
+		})?;
+		info!("Saving the image as ICO...");
+		debug!("{:?}", image);
+		debug!("{:?}", self.settings.ico);
+		let (width, height) = (image.geometry.width, image.geometry.height);
+		let size = width.max(height);
+		let mut icon = RgbaImage::new(size, size);
+		imageops::overlay(
+			&mut icon,
+			&RgbaImage::from_raw(
+				width,
+				height,
+				image.get_data(ExtendedColorType::Rgba8)?,
+			)
+			.ok_or_else(|| {
+				AppError::FrameError(String::from("Invalid image data"))
+			})?,
+			((size - width) / 2).into(),
+			((size - height) / 2).into(),
+		);
+		let mut frames = Vec::new();
+		for size in &self.settings.ico.sizes {
+			let frame = imageops::resize(&icon, *size, *size, FilterType::Lanczos3);
+			frames.push(IcoFrame::as_png(
+				frame.as_raw(),
+				*size,
+				*size,
+				ColorType::Rgba8,
+			)?);
+		}
+		IcoEncoder::new(output).encode_images(&frames)?;
+		Ok(())
+	}
+
 	/**
 	 * Save frames to a GIF file.
 	 *
@@ -523,59 +1336,148 @@ where
 	 * @param   output
 	 * @return  Result
 	 */
-	#[cfg(feature = "ski")]
 	fn save_gif<Output: Write>(
 		self,
 		frames: Option<Frames>,
-		output: Output,
+		mut output: Output,
 	) -> AppResult<()> {
-		let (images, fps) = frames.ok_or_else(|| {
+		let (mut images, fps) = frames.ok_or_else(|| {
 			AppError::FrameError(String::from("Failed to get the frames"))
 		})?;
+		self.set_transparent(&mut images)?;
+		match self.settings.anim.target_size {
+			Some(target_size) => {
+				let data = self.encode_gif_with_size(images, fps, target_size)?;
+				output.write_all(&data)?;
+				Ok(())
+			}
+			None => self.encode_gif(images, fps, &self.settings.anim, output),
+		}
+	}
+
+	/**
+	 * Make the pixels of the frames transparent if a color is given.
+	 *
+	 * @param  images
+	 * @return Result
+	 */
+	fn set_transparent(&self, images: &mut [Image]) -> AppResult<()> {
+		if let Some(color) = self.settings.anim.transparent {
+			for image in images.iter_mut() {
+				image.set_transparent(color, self.settings.anim.tolerance)?;
+			}
+		}
+		Ok(())
+	}
+
+	/**
+	 * Encode the frames to GIF repeatedly while lowering the quality,
+	 * increasing the lossy compression and dropping frames until the
+	 * output fits into the target size.
+	 *
+	 * @param  images
+	 * @param  fps
+	 * @param  target_size
+	 * @return Vector of u8 (Result)
+	 */
+	fn encode_gif_with_size(
+		&self,
+		mut images: Vec<Image>,
+		fps: u32,
+		target_size: u64,
+	) -> AppResult<Vec<u8>> {
+		let mut settings = self.settings.anim.clone();
+		let mut data = Vec::new();
+		for pass in 1..=TARGET_SIZE_PASSES {
+			data.clear();
+			self.encode_gif(images.clone(), fps, &settings, &mut data)?;
+			let size = data.len() as u64;
+			debug!("Encoding pass {}: {}", pass, ByteSize(size));
+			if size <= target_size {
+				return Ok(data);
+			}
+			let ratio = target_size as f64 / size as f64;
+			settings.quality =
+				((f64::from(settings.quality) * ratio.sqrt()) as u8).max(1);
+			if !settings.gifski.0 {
+				settings.lossy = Some(
+					(settings.lossy.unwrap_or_default()
+						+ ((1. - ratio) * 100.).ceil() as u32)
+						.min(MAX_LOSSY_LEVEL),
+				);
+			}
+			if ratio < 0.5 || pass == TARGET_SIZE_PASSES - 1 {
+				images = merge_frames(images, fps, |i, _, _| Ok(i % 2 != 0))?;
+			}
+		}
+		warn!(
+			"Failed to fit the GIF into {} ({})",
+			ByteSize(target_size),
+			ByteSize(data.len() as u64)
+		);
+		Ok(data)
+	}
+
+	/**
+	 * Encode frames to GIF with the given settings.
+	 *
+	 * @param   images
+	 * @param   fps
+	 * @param   settings
+	 * @param   output
+	 * @return  Result
+	 */
+	#[cfg(feature = "ski")]
+	fn encode_gif<Output: Write>(
+		&self,
+		images: Vec<Image>,
+		fps: u32,
+		settings: &AnimSettings,
+		output: Output,
+	) -> AppResult<()> {
 		let geometry = images
 			.first()
 			.ok_or_else(|| {
 				AppError::FrameError(String::from("No frames found to save"))
 			})?
 			.geometry;
-		let config = EncoderConfig::new(fps, geometry, output, &self.settings.anim);
-		if self.settings.anim.gifski.0 {
-			GifskiEncoder::new(config)?.save(images, self.settings.input_state)?;
+		let config = EncoderConfig::new(fps, geometry, output, settings);
+		let cancel = CancelHandle::new(self.settings.input_state);
+		if settings.gifski.0 {
+			GifskiEncoder::new(config)?.save(images.into_iter(), cancel)?;
 		} else {
-			GifEncoder::new(config)?.save(images, self.settings.input_state)?;
+			GifEncoder::new(config)?.save(images.into_iter(), cancel)?;
 		}
 		Ok(())
 	}
 
 	/**
-	 * Save frames to a GIF file.
+	 * Encode frames to GIF with the given settings.
 	 *
-	 * @param   frames (Option)
+	 * @param   images
+	 * @param   fps
+	 * @param   settings
 	 * @param   output
 	 * @return  Result
 	 */
 	#[cfg(not(feature = "ski"))]
-	fn save_gif<Output: Write>(
-		self,
-		frames: Option<Frames>,
+	fn encode_gif<Output: Write>(
+		&self,
+		images: Vec<Image>,
+		fps: u32,
+		settings: &AnimSettings,
 		output: Output,
 	) -> AppResult<()> {
-		let (images, fps) = frames.ok_or_else(|| {
-			AppError::FrameError(String::from("Failed to get the frames"))
-		})?;
 		let geometry = images
 			.first()
 			.ok_or_else(|| {
 				AppError::FrameError(String::from("No frames found to save"))
 			})?
 			.geometry;
-		GifEncoder::new(EncoderConfig::new(
-			fps,
-			geometry,
-			output,
-			&self.settings.anim,
-		))?
-		.save(images, self.settings.input_state)?;
+		GifEncoder::new(EncoderConfig::new(fps, geometry, output, settings))?.save(
+			images.into_iter(),
+			CancelHandle::new(self.settings.input_state),
+		)?;
 		Ok(())
 	}
 
@@ -591,11 +1493,12 @@ where
 		frames: Option<Frames>,
 		output: Output,
 	) -> AppResult<()> {
-		let images = frames
+		let mut images = frames
 			.ok_or_else(|| {
 				AppError::FrameError(String::from("Failed to get the frames"))
 			})?
 			.0;
+		self.set_transparent(&mut images)?;
 		let geometry = images
 			.first()
 			.ok_or_else(|| {
@@ -611,6 +1514,112 @@ where
 		.save(images, self.settings.input_state)?;
 		Ok(())
 	}
+
+	/**
+	 * Save frames to a y4m file.
+	 *
+	 * @param   frames (Option)
+	 * @param   output
+	 * @return  Result
+	 */
+	fn save_y4m<Output: Write>(
+		self,
+		frames: Option<Frames>,
+		output: Output,
+	) -> AppResult<()> {
+		let (images, fps) = frames.ok_or_else(|| {
+			AppError::FrameError(String::from("Failed to get the frames"))
+		})?;
+		let geometry = images
+			.first()
+			.ok_or_else(|| {
+				AppError::FrameError(String::from("No frames found to save"))
+			})?
+			.geometry;
+		Y4mEncoder::new(fps, geometry, output)
+			.save(images, CancelHandle::new(self.settings.input_state))?;
+		Ok(())
+	}
+
+	/**
+	 * Save frames to a multi-page TIFF file.
+	 *
+	 * @param   frames (Option)
+	 * @param   output
+	 * @return  Result
+	 */
+	fn save_tiff<Output: Write + Seek>(
+		self,
+		frames: Option<Frames>,
+		output: Output,
+	) -> AppResult<()> {
+		let images = frames
+			.ok_or_else(|| {
+				AppError::FrameError(String::from("Failed to get the frames"))
+			})?
+			.0;
+		MultiTiffEncoder::new(output)?
+			.save(images, CancelHandle::new(self.settings.input_state))?;
+		Ok(())
+	}
+
+	/**
+	 * Save frames to a PDF file as pages.
+	 *
+	 * @param   frames (Option)
+	 * @param   output
+	 * @return  Result
+	 */
+	fn save_pdf<Output: Write>(
+		self,
+		frames: Option<Frames>,
+		output: Output,
+	) -> AppResult<()> {
+		let images = frames
+			.ok_or_else(|| {
+				AppError::FrameError(String::from("Failed to get the frames"))
+			})?
+			.0;
+		PdfEncoder::new(output, self.settings.jpg.quality)
+			.save(images, CancelHandle::new(self.settings.input_state))
+	}
+
+	/**
+	 * Save frames into an archive as PNG files.
+	 *
+	 * @param   frames (Option)
+	 * @param   output
+	 * @param   format
+	 * @return  Result
+	 */
+	fn save_archive<Output: Write>(
+		&self,
+		frames: Option<Frames>,
+		output: Output,
+		format: ArchiveFormat,
+	) -> AppResult<()> {
+		let images = frames
+			.ok_or_else(|| {
+				AppError::FrameError(String::from("Failed to get the frames"))
+			})?
+			.0;
+		let mut archive = FrameArchive::new(output, format);
+		for (i, image) in images.iter().enumerate() {
+			let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
+			info!("Saving... ({:.1}%)\r", percentage);
+			io::stdout().flush()?;
+			let mut buffer = Vec::new();
+			PngEncoder::new(&mut buffer, self.settings.png).write_image(
+				&image.get_data(ExtendedColorType::Rgba8)?,
+				image.geometry.width,
+				image.geometry.height,
+				ColorType::Rgba8,
+			)?;
+			archive.append(&format!("frame_{i}.png"), &buffer)?;
+		}
+		info!("\n");
+		archive.finish()
+	}
 }
 
 #[cfg(test)]
@@ -649,6 +1658,12 @@ mod tests {
 			app.analyze_image()?;
 			fs::remove_file(path)?;
 		}
+		settings.save.file.format = FileFormat::Ico;
+		settings.ico.sizes = vec![16, 32];
+		let image = App::new(Some(window), &settings).get_image().ok();
+		let mut output = Cursor::new(Vec::new());
+		App::new(Some(window), &settings).save_ico(image, &mut output)?;
+		assert_eq!([0, 0, 1, 0, 2, 0], output.get_ref()[..6]);
 		settings.save.file.path = PathBuf::from("test");
 		App::new(Some(window), &settings).start()?;
 		fs::remove_file(settings.save.file.path)?;
@@ -662,11 +1677,19 @@ mod tests {
 		settings.save.file.format = FileFormat::Gif;
 		settings.record.command = Some("sleep 0.3");
 		settings.anim.cut = (0.1, 0.1);
+		settings.save.file.path = PathBuf::from("dir/rec.gif");
 		let window = TestWindow::default();
 		let app = App::new(Some(window), &settings);
 		let images = app.get_frames()?.0;
 		app.save_gif(Some((images.clone(), 10)), File::create("test.gif")?)?;
+		assert_eq!(PathBuf::from("dir/rec_2.gif"), app.get_segment_path(2));
 		app.edit_anim(File::open("test.gif")?, Path::new("test.gif"))?;
+		assert!(App::<TestWindow>::is_anim(Path::new("test.gif"))?);
+		let mut header = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\x02".to_vec();
+		assert!(App::<TestWindow>::is_animated_webp(&header[..])?);
+		header[20] = 0x10;
+		assert!(!App::<TestWindow>::is_animated_webp(&header[..])?);
+		assert!(!App::<TestWindow>::is_animated_webp(&b"RIFF"[..])?);
 		let dir = env::current_dir()?;
 		settings.split.dir = PathBuf::from(dir.to_str().unwrap_or_default());
 		settings.split.file = PathBuf::from("test.gif");
@@ -676,6 +1699,8 @@ mod tests {
 		fs::remove_file("test.gif")?;
 		app.save_apng(Some((images.clone(), 20)), File::create("test.apng")?)?;
 		fs::remove_file("test.apng")?;
+		app.save_y4m(Some((images.clone(), 20)), File::create("test.y4m")?)?;
+		fs::remove_file("test.y4m")?;
 		for i in 0..images.len() {
 			let path = PathBuf::from(format!("frame_{i}.png"));
 			if path.exists() {
@@ -0,0 +1,395 @@
+use crate::app::{AppError, AppResult};
+use chrono::{Datelike, Local, Timelike};
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::Path;
+
+/* Size of a tar block */
+const TAR_BLOCK_SIZE: usize = 512;
+
+/* Maximum size of a file in the tar archive (11 octal digits) */
+const TAR_MAX_SIZE: u64 = 0o77_777_777_777;
+
+/* Zip versions needed for extracting the entries */
+const ZIP_VERSION: u16 = 20;
+const ZIP64_VERSION: u16 = 45;
+
+/* Format of the archive */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+	Tar,
+	Zip,
+}
+
+impl ArchiveFormat {
+	/**
+	 * Get the archive format from the extension of a path.
+	 *
+	 * @param  path
+	 * @return ArchiveFormat (Option)
+	 */
+	pub fn from_path(path: &Path) -> Option<Self> {
+		match path
+			.extension()
+			.and_then(OsStr::to_str)?
+			.to_lowercase()
+			.as_str()
+		{
+			"tar" => Some(Self::Tar),
+			"zip" => Some(Self::Zip),
+			_ => None,
+		}
+	}
+}
+
+/* Entry in the zip central directory */
+struct ZipEntry {
+	name: String,
+	crc: u32,
+	size: u64,
+	offset: u64,
+}
+
+impl ZipEntry {
+	/**
+	 * Get the ZIP64 extra field of the entry if the size or offset
+	 * does not fit into the zip headers.
+	 *
+	 * @return Vector of u8
+	 */
+	fn get_zip64_extra(&self) -> Vec<u8> {
+		let mut values = Vec::new();
+		if self.size >= u32::MAX.into() {
+			values.extend_from_slice(&[self.size, self.size]);
+		}
+		if self.offset >= u32::MAX.into() {
+			values.push(self.offset);
+		}
+		let mut extra = Vec::new();
+		if !values.is_empty() {
+			extra.extend_from_slice(&1_u16.to_le_bytes());
+			extra.extend_from_slice(&((values.len() * 8) as u16).to_le_bytes());
+			for value in values {
+				extra.extend_from_slice(&value.to_le_bytes());
+			}
+		}
+		extra
+	}
+}
+
+/* Uncompressed archive writer for storing frames */
+pub struct FrameArchive<Output: Write> {
+	output: Output,
+	format: ArchiveFormat,
+	entries: Vec<ZipEntry>,
+	position: u64,
+}
+
+impl<Output: Write> FrameArchive<Output> {
+	/**
+	 * Create a new FrameArchive object.
+	 *
+	 * @param  output
+	 * @param  format
+	 * @return FrameArchive
+	 */
+	pub fn new(output: Output, format: ArchiveFormat) -> Self {
+		Self {
+			output,
+			format,
+			entries: Vec::new(),
+			position: 0,
+		}
+	}
+
+	/**
+	 * Write raw bytes to the output and keep track of the position.
+	 *
+	 * @param  data
+	 * @return Result
+	 */
+	fn write(&mut self, data: &[u8]) -> AppResult<()> {
+		self.output.write_all(data)?;
+		self.position += data.len() as u64;
+		Ok(())
+	}
+
+	/**
+	 * Get the current time in MS-DOS format.
+	 *
+	 * @return Tuple (time, date)
+	 */
+	fn get_dos_time() -> (u16, u16) {
+		let now = Local::now();
+		(
+			((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16,
+			(((now.year().max(1980) - 1980) << 9) as u32
+				| (now.month() << 5)
+				| now.day()) as u16,
+		)
+	}
+
+	/**
+	 * Get the value of a zip header field or the ZIP64 placeholder
+	 * if the value does not fit.
+	 *
+	 * @param  value
+	 * @return u32
+	 */
+	fn get_zip_value(value: u64) -> u32 {
+		u32::try_from(value).unwrap_or(u32::MAX)
+	}
+
+	/**
+	 * Split the name of a file into the ustar prefix and name fields.
+	 *
+	 * @param  name
+	 * @return Tuple (prefix, name) (Result)
+	 */
+	fn split_tar_name(name: &str) -> AppResult<(&str, &str)> {
+		if name.len() <= 100 {
+			return Ok(("", name));
+		}
+		name.match_indices('/')
+			.map(|(i, _)| (&name[..i], &name[i + 1..]))
+			.find(|(prefix, name)| {
+				prefix.len() <= 155 && !name.is_empty() && name.len() <= 100
+			})
+			.ok_or_else(|| {
+				AppError::FrameError(format!(
+					"File name is too long for the tar archive: {name}"
+				))
+			})
+	}
+
+	/**
+	 * Get the tar header of a file.
+	 *
+	 * @param  name
+	 * @param  size
+	 * @return Vector of u8 (Result)
+	 */
+	fn get_tar_header(name: &str, size: usize) -> AppResult<Vec<u8>> {
+		if size as u64 > TAR_MAX_SIZE {
+			return Err(AppError::FrameError(format!(
+				"File is too large for the tar archive: {name}"
+			)));
+		}
+		let (prefix, name) = Self::split_tar_name(name)?;
+		let mut header = vec![0; TAR_BLOCK_SIZE];
+		header[..name.len()].copy_from_slice(name.as_bytes());
+		header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+		for (offset, value) in [
+			(100, format!("{:07o}", 0o644)),
+			(108, format!("{:07o}", 0)),
+			(116, format!("{:07o}", 0)),
+			(124, format!("{size:011o}")),
+			(136, format!("{:011o}", Local::now().timestamp().max(0))),
+		] {
+			header[offset..offset + value.len()].copy_from_slice(value.as_bytes());
+		}
+		header[148..156].copy_from_slice(b"        ");
+		header[156] = b'0';
+		header[257..265].copy_from_slice(b"ustar\x0000");
+		let checksum = header.iter().map(|v| *v as u32).sum::<u32>();
+		header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+		Ok(header)
+	}
+
+	/**
+	 * Append a file to the archive.
+	 *
+	 * @param  name
+	 * @param  data
+	 * @return Result
+	 */
+	pub fn append(&mut self, name: &str, data: &[u8]) -> AppResult<()> {
+		match self.format {
+			ArchiveFormat::Tar => {
+				self.write(&Self::get_tar_header(name, data.len())?)?;
+				self.write(data)?;
+				let padding =
+					(TAR_BLOCK_SIZE - data.len() % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+				self.write(&vec![0; padding])?;
+			}
+			ArchiveFormat::Zip => {
+				let name_len = u16::try_from(name.len()).map_err(|_| {
+					AppError::FrameError(format!(
+						"File name is too long for the zip archive: {name}"
+					))
+				})?;
+				let entry = ZipEntry {
+					name: name.to_string(),
+					crc: crc32fast::hash(data),
+					size: data.len() as u64,
+					offset: self.position,
+				};
+				let zip64 = entry.size >= u32::MAX.into();
+				let size = Self::get_zip_value(entry.size);
+				let (time, date) = Self::get_dos_time();
+				let mut header = Vec::new();
+				header.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+				let version = if zip64 { ZIP64_VERSION } else { ZIP_VERSION };
+				for value in [version, 0, 0, time, date] {
+					header.extend_from_slice(&u16::to_le_bytes(value));
+				}
+				for value in [entry.crc, size, size] {
+					header.extend_from_slice(&value.to_le_bytes());
+				}
+				let mut extra = Vec::new();
+				if zip64 {
+					extra.extend_from_slice(&1_u16.to_le_bytes());
+					extra.extend_from_slice(&16_u16.to_le_bytes());
+					extra.extend_from_slice(&entry.size.to_le_bytes());
+					extra.extend_from_slice(&entry.size.to_le_bytes());
+				}
+				header.extend_from_slice(&name_len.to_le_bytes());
+				header.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+				header.extend_from_slice(name.as_bytes());
+				header.extend_from_slice(&extra);
+				self.write(&header)?;
+				self.write(data)?;
+				self.entries.push(entry);
+			}
+		}
+		Ok(())
+	}
+
+	/**
+	 * Write the end of the archive.
+	 *
+	 * @return Result
+	 */
+	pub fn finish(mut self) -> AppResult<()> {
+		match self.format {
+			ArchiveFormat::Tar => self.write(&[0; TAR_BLOCK_SIZE * 2])?,
+			ArchiveFormat::Zip => {
+				let offset = self.position;
+				let (time, date) = Self::get_dos_time();
+				let mut directory = Vec::new();
+				for entry in &self.entries {
+					let extra = entry.get_zip64_extra();
+					let version = if extra.is_empty() {
+						ZIP_VERSION
+					} else {
+						ZIP64_VERSION
+					};
+					let size = Self::get_zip_value(entry.size);
+					directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+					for value in [version, version, 0, 0, time, date] {
+						directory.extend_from_slice(&u16::to_le_bytes(value));
+					}
+					for value in [entry.crc, size, size] {
+						directory.extend_from_slice(&value.to_le_bytes());
+					}
+					for value in
+						[entry.name.len() as u16, extra.len() as u16, 0, 0, 0]
+					{
+						directory.extend_from_slice(&value.to_le_bytes());
+					}
+					directory.extend_from_slice(&0_u32.to_le_bytes());
+					directory.extend_from_slice(
+						&Self::get_zip_value(entry.offset).to_le_bytes(),
+					);
+					directory.extend_from_slice(entry.name.as_bytes());
+					directory.extend_from_slice(&extra);
+				}
+				let count = self.entries.len() as u64;
+				let size = directory.len() as u64;
+				if count >= u16::MAX.into()
+					|| size >= u32::MAX.into()
+					|| offset >= u32::MAX.into()
+				{
+					let end_offset = offset + size;
+					directory.extend_from_slice(&0x0606_4b50_u32.to_le_bytes());
+					directory.extend_from_slice(&44_u64.to_le_bytes());
+					for value in [ZIP64_VERSION, ZIP64_VERSION] {
+						directory.extend_from_slice(&value.to_le_bytes());
+					}
+					directory.extend_from_slice(&[0; 8]);
+					for value in [count, count, size, offset] {
+						directory.extend_from_slice(&value.to_le_bytes());
+					}
+					directory.extend_from_slice(&0x0706_4b50_u32.to_le_bytes());
+					directory.extend_from_slice(&0_u32.to_le_bytes());
+					directory.extend_from_slice(&end_offset.to_le_bytes());
+					directory.extend_from_slice(&1_u32.to_le_bytes());
+				}
+				let count = u16::try_from(count).unwrap_or(u16::MAX);
+				directory.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+				for value in [0, 0, count, count] {
+					directory.extend_from_slice(&u16::to_le_bytes(value));
+				}
+				for value in [size, offset] {
+					directory.extend_from_slice(
+						&Self::get_zip_value(value).to_le_bytes(),
+					);
+				}
+				directory.extend_from_slice(&0_u16.to_le_bytes());
+				self.write(&directory)?;
+			}
+		}
+		self.output.flush()?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_frame_archive() -> AppResult<()> {
+		assert_eq!(
+			Some(ArchiveFormat::Zip),
+			ArchiveFormat::from_path(Path::new("frames.ZIP"))
+		);
+		assert_eq!(None, ArchiveFormat::from_path(Path::new("frames")));
+		let mut output = Vec::new();
+		let mut archive = FrameArchive::new(&mut output, ArchiveFormat::Tar);
+		archive.append("frame_0.png", &[1, 2, 3])?;
+		archive.append("frame_1.png", &[4; 513])?;
+		archive.finish()?;
+		assert_eq!(TAR_BLOCK_SIZE * 7, output.len());
+		assert_eq!(b"ustar\x0000", &output[257..265]);
+		let mut output = Vec::new();
+		let mut archive = FrameArchive::new(&mut output, ArchiveFormat::Zip);
+		archive.append("frame_0.png", &[1, 2, 3])?;
+		archive.finish()?;
+		assert_eq!((30 + 11 + 3) + (46 + 11) + 22, output.len());
+		assert_eq!(&[0x50, 0x4b, 0x05, 0x06], &output[output.len() - 22..][..4]);
+		let mut output = Vec::new();
+		let mut archive = FrameArchive::new(&mut output, ArchiveFormat::Tar);
+		let name = format!("{}/frame_0.png", "x".repeat(120));
+		archive.append(&name, &[1, 2, 3])?;
+		assert_eq!(b"frame_0.png\0", &output[..12]);
+		assert_eq!("x".repeat(120).as_bytes(), &output[345..465]);
+		let mut archive = FrameArchive::new(Vec::new(), ArchiveFormat::Tar);
+		assert!(archive.append(&"x".repeat(101), &[1, 2, 3]).is_err());
+		let mut output = Vec::new();
+		let mut archive = FrameArchive::new(&mut output, ArchiveFormat::Zip);
+		archive.position = u64::from(u32::MAX) + 1;
+		archive.append("frame_0.png", &[1, 2, 3])?;
+		archive.finish()?;
+		let directory = &output[30 + 11 + 3..];
+		assert_eq!(&u32::MAX.to_le_bytes(), &directory[42..46]);
+		assert_eq!(
+			&(u64::from(u32::MAX) + 1).to_le_bytes(),
+			&directory[46 + 11 + 4..][..8]
+		);
+		assert_eq!(&[0x50, 0x4b, 0x06, 0x06], &directory[46 + 11 + 12..][..4]);
+		let mut output = Vec::new();
+		let mut archive = FrameArchive::new(&mut output, ArchiveFormat::Zip);
+		for i in 0..=u16::MAX {
+			archive.append(&format!("{i}"), &[])?;
+		}
+		archive.finish()?;
+		let end = &output[output.len() - 22..];
+		assert_eq!(&u16::MAX.to_le_bytes(), &end[10..12]);
+		let locator = &output[output.len() - 42..][..20];
+		assert_eq!(&[0x50, 0x4b, 0x06, 0x07], &locator[..4]);
+		let record = &output[output.len() - 98..][..56];
+		assert_eq!(&(u64::from(u16::MAX) + 1).to_le_bytes(), &record[32..40]);
+		Ok(())
+	}
+}
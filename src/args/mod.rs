@@ -1,7 +1,11 @@
 pub mod matches;
 pub mod parser;
+use crate::anim::settings::AnimSettings;
 use crate::anim::{AnimFormat, AnimMode};
 use crate::file::format::FileFormat;
+use crate::image::settings::IcoSettings;
+use crate::record::settings::{RecordSettings, RecordTime};
+use crate::util::keys::ActionKeys;
 use clap::{App, AppSettings, Arg, ArgMatches, Shell, SubCommand};
 use std::io::Write;
 use std::str::FromStr;
@@ -31,8 +35,11 @@ pub struct Args<'a, 'b> {
 	record: App<'a, 'b>,
 	split: App<'a, 'b>,
 	make: App<'a, 'b>,
+	trim: App<'a, 'b>,
 	capture: App<'a, 'b>,
 	edit: App<'a, 'b>,
+	montage: App<'a, 'b>,
+	diff: App<'a, 'b>,
 	analyze: App<'a, 'b>,
 	view: App<'a, 'b>,
 	misc: App<'a, 'b>,
@@ -77,8 +84,11 @@ where
 			record: Self::get_record_args(false),
 			split: Self::get_split_args(),
 			make: Self::get_anim_args(AnimMode::Make),
+			trim: Self::get_trim_args(),
 			capture: Self::get_record_args(true),
 			edit: Self::get_edit_args(),
+			montage: Self::get_montage_args(),
+			diff: Self::get_diff_args(),
 			analyze: Self::get_analyze_args(),
 			view: Self::get_view_args(),
 			misc: Self::get_misc_args(),
@@ -126,6 +136,13 @@ where
 					.help("Do not show output")
 					.display_order(1001),
 			)
+			.arg(
+				Arg::with_name("preview")
+					.short("p")
+					.long("preview")
+					.help("Preview the saved file in the terminal")
+					.display_order(1002),
+			)
 			.arg(
 				Arg::with_name("config")
 					.short("c")
@@ -152,10 +169,15 @@ where
 						Self::get_anim_args(AnimMode::Record(AnimFormat::Apng))
 							.subcommand(Self::get_save_args(FileFormat::Apng)),
 					)
+					.subcommand(
+						Self::get_anim_args(AnimMode::Record(AnimFormat::Y4m))
+							.subcommand(Self::get_save_args(FileFormat::Y4m)),
+					)
 					.subcommand(Self::get_save_args(FileFormat::Gif)),
 			)
 			.subcommand(Self::get_image_args(args.split, false))
 			.subcommand(args.make.subcommand(Self::get_save_args(FileFormat::Gif)))
+			.subcommand(args.trim.subcommand(Self::get_save_args(FileFormat::Gif)))
 			.subcommand(Self::get_image_args(args.capture, true))
 			.subcommand(Self::get_image_args(
 				args.edit
@@ -169,6 +191,8 @@ where
 					),
 				true,
 			))
+			.subcommand(Self::get_image_args(args.montage, true))
+			.subcommand(Self::get_image_args(args.diff, true))
 			.subcommand(
 				args.analyze
 					.subcommand(Self::get_save_args(FileFormat::Txt)),
@@ -228,6 +252,17 @@ where
 			} else {
 				"Record the parent of the window"
 			}))
+			.arg(
+				Arg::with_name("with-decorations")
+					.long("with-decorations")
+					.conflicts_with("no-decorations")
+					.help("Include the window decorations (title bar, borders)"),
+			)
+			.arg(
+				Arg::with_name("no-decorations")
+					.long("no-decorations")
+					.help("Trim the window to the area without decorations"),
+			)
 			.arg(
 				Arg::with_name("with-alpha")
 					.long("with-alpha")
@@ -249,6 +284,7 @@ where
 					.alias("keys")
 					.value_name("KEYS")
 					.default_value("LAlt-S,LAlt-Enter")
+					.validator(ActionKeys::validate)
 					.help("Set the action keys")
 					.takes_value(true),
 			)
@@ -257,15 +293,199 @@ where
 					.long("cancel-keys")
 					.value_name("KEYS")
 					.default_value("LControl-D,Escape")
+					.validator(ActionKeys::validate)
 					.help("Set the cancel keys")
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("pause-keys")
+					.long("pause-keys")
+					.value_name("KEYS")
+					.default_value("LAlt-P")
+					.validator(ActionKeys::validate)
+					.help("Set the keys for pausing/resuming the recording")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("reselect-keys")
+					.long("reselect-keys")
+					.value_name("KEYS")
+					.default_value("LAlt-W")
+					.validator(ActionKeys::validate)
+					.help("Set the keys for selecting another window to record")
+					.takes_value(true)
+					.hidden(capture),
+			)
 			.arg(
 				Arg::with_name("mouse")
 					.short("m")
 					.long("mouse")
 					.help("Select the window with mouse click"),
 			)
+			.arg(
+				Arg::with_name("region")
+					.long("region")
+					.conflicts_with_all(&["root", "focus", "size", "pos"])
+					.help("Select a region by dragging the mouse"),
+			)
+			.arg(
+				Arg::with_name("freeze")
+					.long("freeze")
+					.requires("region")
+					.help("Freeze the screen while selecting a region"),
+			)
+			.arg(
+				Arg::with_name("side-by-side")
+					.long("side-by-side")
+					.requires("windows")
+					.help("Composite the windows side by side"),
+			)
+			.arg(
+				Arg::with_name("follow")
+					.long("follow")
+					.help("Track the window geometry while recording")
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("letterbox")
+					.long("letterbox")
+					.requires("follow")
+					.help("Letterbox the frames when the window is resized")
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("drop-frames")
+					.long("drop-frames")
+					.help("Duplicate the previous frame when capture falls behind")
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("best-effort")
+					.long("best-effort")
+					.conflicts_with("drop-frames")
+					.help("Adjust the frame delays when capture falls behind")
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("highlight")
+					.long("highlight")
+					.help("Highlight the mouse clicks while recording")
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("highlight-color")
+					.long("highlight-color")
+					.value_name("HEX")
+					.default_value("FFFF00")
+					.help("Set the color of the click highlights")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("highlight-radius")
+					.long("highlight-radius")
+					.value_name("PX")
+					.default_value("20")
+					.help("Set the radius of the click highlights")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("stamp")
+					.long("stamp")
+					.value_name("TIME")
+					.possible_values(&["elapsed", "clock"])
+					.help("Stamp the elapsed or wall-clock time on each frame")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("stamp-format")
+					.long("stamp-format")
+					.value_name("FORMAT")
+					.default_value("%H:%M:%S%.3f")
+					.help("Set the strftime format of the timestamps")
+					.allow_hyphen_values(true)
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("stamp-position")
+					.long("stamp-position")
+					.value_name("POSITION")
+					.possible_values(&[
+						"top-left",
+						"top",
+						"top-right",
+						"center",
+						"bottom-left",
+						"bottom",
+						"bottom-right",
+					])
+					.default_value("top-left")
+					.help("Set the position of the timestamps")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("window-name")
+					.long("window-name")
+					.value_name("NAME")
+					.conflicts_with_all(&["region", "windows"])
+					.help(if capture {
+						"Capture the window with a name that contains NAME"
+					} else {
+						"Record the window with a name that contains NAME"
+					})
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("class")
+					.long("class")
+					.value_name("CLASS")
+					.conflicts_with_all(&["region", "windows"])
+					.help(if capture {
+						"Capture the window with the given class"
+					} else {
+						"Record the window with the given class"
+					})
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("pid")
+					.long("pid")
+					.value_name("PID")
+					.conflicts_with_all(&["region", "windows"])
+					.help(if capture {
+						"Capture the window of the given process"
+					} else {
+						"Record the window of the given process"
+					})
+					.validator(|pid| {
+						pid.parse::<u32>()
+							.map(|_| ())
+							.map_err(|_| format!("Invalid PID: {pid:?}"))
+					})
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("windows")
+					.long("windows")
+					.value_name("IDS")
+					.conflicts_with("region")
+					.help(if capture {
+						"Set the IDs of the windows to capture together"
+					} else {
+						"Set the IDs of the windows to record together"
+					})
+					.validator(|ids| {
+						RecordSettings::parse_window_ids(&ids)
+							.map(|_| ())
+							.ok_or_else(|| format!("Invalid window IDs: {ids:?}"))
+					})
+					.takes_value(true),
+			)
 			.arg(
 				Arg::with_name("border")
 					.short("b")
@@ -300,6 +520,33 @@ where
 					.empty_values(true)
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("pos")
+					.long("pos")
+					.value_name("X:Y")
+					.help(if capture {
+						"Set the capture area position on the screen"
+					} else {
+						"Set the record area position on the screen"
+					})
+					.validator(|pos| {
+						RecordSettings::parse_position(&pos)
+							.map(|_| ())
+							.ok_or_else(|| format!("Invalid position: {pos:?}"))
+					})
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("scale")
+					.long("scale")
+					.value_name("FACTOR")
+					.help(if capture {
+						"Scale the captured image by the given factor"
+					} else {
+						"Scale the recorded frames by the given factor"
+					})
+					.takes_value(true),
+			)
 			.arg(
 				Arg::with_name("duration")
 					.short("d")
@@ -310,6 +557,97 @@ where
 					.takes_value(true)
 					.hidden(capture),
 			)
+			.arg(
+				Arg::with_name("motion")
+					.long("motion")
+					.value_name("%")
+					.help("Keep the frames that differ from the previous one by %")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("stats")
+					.long("stats")
+					.value_name("FILE")
+					.help("Save the recording statistics to FILE as JSON")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("buffer")
+					.long("buffer")
+					.value_name("BUFFER")
+					.possible_values(&["memory", "disk"])
+					.default_value("memory")
+					.help("Set where to keep the frames while recording")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("timelapse")
+					.long("timelapse")
+					.value_name("S")
+					.help("Record a timelapse with a frame every S seconds")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("replay")
+					.long("replay")
+					.value_name("S")
+					.help("Keep only the last S seconds of the recording")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("max-frames")
+					.long("max-frames")
+					.value_name("N")
+					.help("Set the maximum number of frames to record")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("segment")
+					.long("segment")
+					.value_name("SECONDS|FRAMES")
+					.help("Split the recording into files (e.g. 30s, 300f)")
+					.takes_value(true)
+					.hidden(capture),
+			)
+			.arg(
+				Arg::with_name("at")
+					.long("at")
+					.value_name("HH:MM[:SS]")
+					.help(if capture {
+						"Set the time of day to start capturing"
+					} else {
+						"Set the time of day to start recording"
+					})
+					.validator(|time| {
+						RecordTime::parse_time(&time)
+							.map(|_| ())
+							.ok_or_else(|| format!("Invalid time: {time:?}"))
+					})
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("after")
+					.long("after")
+					.value_name("DURATION")
+					.conflicts_with("at")
+					.help(if capture {
+						"Set the delay before capturing (e.g. 90s, 5m, 1h)"
+					} else {
+						"Set the delay before recording (e.g. 90s, 5m, 1h)"
+					})
+					.validator(|delay| {
+						RecordTime::parse_delay(&delay)
+							.map(|_| ())
+							.ok_or_else(|| format!("Invalid duration: {delay:?}"))
+					})
+					.takes_value(true),
+			)
 			.arg(
 				Arg::with_name("countdown")
 					.short("c")
@@ -353,11 +691,11 @@ where
 			.arg(
 				Arg::with_name("monitor")
 					.long("monitor")
-					.value_name("NUM")
+					.value_name("MONITOR")
 					.help(if capture {
-						"Set the monitor to capture as root window"
+						"Set the monitor (number or name) to capture as root window"
 					} else {
-						"Set the monitor to record as root window"
+						"Set the monitor (number or name) to record as root window"
 					})
 					.takes_value(true),
 			)
@@ -374,7 +712,7 @@ where
 			.about(mode.get_description())
 			.help_message("Print help information")
 			.aliases(if mode == AnimMode::Make {
-				&["combine"]
+				&["combine", "concat", "append"]
 			} else {
 				&[]
 			})
@@ -384,8 +722,11 @@ where
 					.long("fps")
 					.value_name("FPS")
 					.default_value("20")
-					.help("Set the FPS")
-					.hidden(mode.is_edit())
+					.help(if mode.is_edit() {
+						"Override the FPS of the animation"
+					} else {
+						"Set the FPS"
+					})
 					.takes_value(true),
 			)
 			.arg(
@@ -396,7 +737,25 @@ where
 					.default_value("75")
 					.help("Set the frame quality (1-100)")
 					.takes_value(true)
-					.hidden(mode.has_format(AnimFormat::Apng)),
+					.hidden(
+						!mode.has_format(AnimFormat::Gif) && mode != AnimMode::Make,
+					),
+			)
+			.arg(
+				Arg::with_name("transparent")
+					.long("transparent")
+					.value_name("HEX")
+					.help("Set the color to make transparent")
+					.takes_value(true)
+					.hidden(mode.has_format(AnimFormat::Y4m)),
+			)
+			.arg(
+				Arg::with_name("tolerance")
+					.long("tolerance")
+					.value_name("N")
+					.help("Set the tolerance of the transparent color (0-255)")
+					.takes_value(true)
+					.hidden(mode.has_format(AnimFormat::Y4m)),
 			)
 			.arg(
 				Arg::with_name("repeat")
@@ -412,16 +771,121 @@ where
 					.long("gifski")
 					.help("Use the gifski encoder")
 					.hidden(
-						mode.has_format(AnimFormat::Apng)
+						(!mode.has_format(AnimFormat::Gif)
+							&& mode != AnimMode::Make)
 							|| cfg!(not(feature = "ski")),
 					),
 			)
+			.arg(
+				Arg::with_name("global-palette")
+					.long("global-palette")
+					.help("Use a single palette for all frames")
+					.hidden(
+						!mode.has_format(AnimFormat::Gif) && mode != AnimMode::Make,
+					),
+			)
+			.arg(
+				Arg::with_name("palette")
+					.long("palette")
+					.value_name("FILE")
+					.help("Set the palette file (.gpl/.hex) to use")
+					.conflicts_with_all(&[
+						"global-palette",
+						"gifski",
+						"fast",
+						"gifski-width",
+						"gifski-height",
+						"gifski-quality",
+						"gifski-motion-quality",
+					])
+					.takes_value(true)
+					.hidden(
+						!mode.has_format(AnimFormat::Gif) && mode != AnimMode::Make,
+					),
+			)
+			.arg(
+				Arg::with_name("lossy")
+					.long("lossy")
+					.value_name("N")
+					.help("Set the lossy compression level (e.g. 80)")
+					.conflicts_with_all(&[
+						"gifski",
+						"fast",
+						"gifski-width",
+						"gifski-height",
+						"gifski-quality",
+						"gifski-motion-quality",
+					])
+					.takes_value(true)
+					.hidden(
+						!mode.has_format(AnimFormat::Gif) && mode != AnimMode::Make,
+					),
+			)
+			.arg(
+				Arg::with_name("target-size")
+					.long("target-size")
+					.value_name("SIZE")
+					.help("Set the maximum file size (e.g. 5MB)")
+					.takes_value(true)
+					.hidden(
+						!mode.has_format(AnimFormat::Gif) && mode != AnimMode::Make,
+					),
+			)
 			.arg(
 				Arg::with_name("fast")
 					.long("fast")
 					.help("Encode 3 times faster (gifski)")
 					.hidden(
-						mode.has_format(AnimFormat::Apng)
+						(!mode.has_format(AnimFormat::Gif)
+							&& mode != AnimMode::Make)
+							|| cfg!(not(feature = "ski")),
+					),
+			)
+			.arg(
+				Arg::with_name("gifski-width")
+					.long("gifski-width")
+					.value_name("PX")
+					.help("Set the output width (gifski)")
+					.takes_value(true)
+					.hidden(
+						(!mode.has_format(AnimFormat::Gif)
+							&& mode != AnimMode::Make)
+							|| cfg!(not(feature = "ski")),
+					),
+			)
+			.arg(
+				Arg::with_name("gifski-height")
+					.long("gifski-height")
+					.value_name("PX")
+					.help("Set the output height (gifski)")
+					.takes_value(true)
+					.hidden(
+						(!mode.has_format(AnimFormat::Gif)
+							&& mode != AnimMode::Make)
+							|| cfg!(not(feature = "ski")),
+					),
+			)
+			.arg(
+				Arg::with_name("gifski-quality")
+					.long("gifski-quality")
+					.value_name("QUALITY")
+					.help("Set the color quality (1-100) (gifski)")
+					.takes_value(true)
+					.hidden(
+						(!mode.has_format(AnimFormat::Gif)
+							&& mode != AnimMode::Make)
+							|| cfg!(not(feature = "ski")),
+					),
+			)
+			.arg(
+				Arg::with_name("gifski-motion-quality")
+					.long("gifski-motion-quality")
+					.value_name("QUALITY")
+					.help("Set the motion quality (1-100) (gifski)")
+					.takes_value(true)
+					.hidden(
+						(!mode.has_format(AnimFormat::Gif)
+							&& mode != AnimMode::Make)
 							|| cfg!(not(feature = "ski")),
 					),
 			)
@@ -453,13 +917,79 @@ where
 					.hidden(!mode.is_edit())
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("speed-curve")
+					.long("speed-curve")
+					.value_name("CURVE")
+					.help("Set the speed from the given seconds (e.g. 0:1,5:3)")
+					.hidden(!mode.is_edit() && mode != AnimMode::Make)
+					.validator(|curve| {
+						AnimSettings::parse_speed_curve(&curve)
+							.map(|_| ())
+							.ok_or_else(|| format!("Invalid speed curve: {curve:?}"))
+					})
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("interpolate")
+					.long("interpolate")
+					.value_name("FPS")
+					.help("Blend the frames for reaching the given FPS")
+					.hidden(!mode.is_edit() && mode != AnimMode::Make)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("loop-crossfade")
+					.long("loop-crossfade")
+					.value_name("MS")
+					.help("Set the duration of crossfade between the end and start")
+					.hidden(!mode.is_edit() && mode != AnimMode::Make)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("reverse")
+					.long("reverse")
+					.help("Play the animation in reverse")
+					.hidden(!mode.is_edit() && mode != AnimMode::Make),
+			)
+			.arg(
+				Arg::with_name("boomerang")
+					.long("boomerang")
+					.help("Play the animation forwards and backwards")
+					.hidden(!mode.is_edit() && mode != AnimMode::Make),
+			)
+			.arg(
+				Arg::with_name("dedupe")
+					.long("dedupe")
+					.help("Drop the identical consecutive frames")
+					.hidden(mode.has_format(AnimFormat::Y4m)),
+			)
+			.arg(
+				Arg::with_name("skip-frames")
+					.long("skip-frames")
+					.value_name("N")
+					.help("Keep every Nth frame")
+					.takes_value(true)
+					.hidden(mode.has_format(AnimFormat::Y4m)),
+			)
+			.arg(
+				Arg::with_name("stabilize")
+					.long("stabilize")
+					.value_name("FRAMES")
+					.help("Stabilize the motion by smoothing it over N frames")
+					.takes_value(true)
+					.hidden(mode.has_format(AnimFormat::Y4m)),
+			)
 			.arg(
 				Arg::with_name("frames")
 					.value_name("FRAMES")
 					.help("Set the animation frames")
 					.min_values(1)
 					.hidden(mode != AnimMode::Make)
-					.default_value_if("dir", None, "-")
+					.default_value_ifs(&[
+						("dir", None, "-"),
+						("manifest", None, "-"),
+					])
 					.required(mode == AnimMode::Make)
 					.empty_values(false)
 					.takes_value(true),
@@ -471,6 +1001,12 @@ where
 					.help("Use frames in the order given")
 					.hidden(mode != AnimMode::Make),
 			)
+			.arg(
+				Arg::with_name("resume")
+					.long("resume")
+					.help("Resume the interrupted encoding of the GIF")
+					.hidden(mode != AnimMode::Make),
+			)
 			.arg(
 				Arg::with_name("dir")
 					.short("d")
@@ -481,12 +1017,37 @@ where
 					.hidden(mode != AnimMode::Make)
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("manifest")
+					.long("manifest")
+					.conflicts_with_all(&["frames", "dir"])
+					.value_name("FILE")
+					.help("Set the manifest file (.csv/.json) of frames and delays")
+					.hidden(mode != AnimMode::Make)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("gap")
+					.long("gap")
+					.value_name("MS")
+					.help("Set the pause between the inputs")
+					.hidden(mode != AnimMode::Make)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("transition")
+					.long("transition")
+					.value_name("MS")
+					.help("Set the duration of crossfade between the inputs")
+					.hidden(mode != AnimMode::Make)
+					.takes_value(true),
+			)
 			.arg(
 				Arg::with_name("format")
 					.long("format")
 					.value_name("FORMAT")
 					.help("Set the animation format")
-					.possible_values(&["gif", "apng"])
+					.possible_values(&["gif", "apng", "y4m", "pdf"])
 					.default_value("gif")
 					.hidden(mode != AnimMode::Make)
 					.takes_value(true),
@@ -503,78 +1064,326 @@ where
 			.help_message("Print help information")
 			.about("Edit an image")
 			.arg(
-				Arg::with_name("file")
-					.value_name("FILE")
-					.help("Set the input file")
-					.required(true),
+				Arg::with_name("file")
+					.value_name("FILE")
+					.help("Set the input file")
+					.required(true),
+			)
+			.arg(
+				Arg::with_name("convert")
+					.long("convert")
+					.help("Convert image using the given encoder"),
+			)
+			.arg(
+				Arg::with_name("grayscale")
+					.long("grayscale")
+					.help("Convert image to grayscale"),
+			)
+			.arg(
+				Arg::with_name("invert")
+					.long("invert")
+					.help("Invert the colors of the image"),
+			)
+			.arg(
+				Arg::with_name("auto-levels")
+					.long("auto-levels")
+					.help("Stretch the levels of the colors to the full range"),
+			)
+			.arg(
+				Arg::with_name("equalize")
+					.long("equalize")
+					.help("Spread the levels of the colors evenly"),
+			)
+			.arg(
+				Arg::with_name("trim")
+					.long("trim")
+					.help("Remove the uniform borders around the image"),
+			)
+			.arg(
+				Arg::with_name("crop")
+					.long("crop")
+					.value_name("CROP")
+					.help("Crop the image by padding (T:R:B:L) or area (WxH+X+Y)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("resize")
+					.long("resize")
+					.value_name("WxH")
+					.help("Resize the image (keeps the aspect ratio if W or H is 0)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("ratio")
+					.long("ratio")
+					.alias("scale")
+					.value_name("RATIO")
+					.default_value("1.0")
+					.help("Resize the image by ratio or crop it to W:H aspect ratio")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("rotate")
+					.long("rotate")
+					.value_name("ROTATE")
+					.help("Rotate the image (clockwise)")
+					.possible_values(&["90", "180", "270"])
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("flip")
+					.long("flip")
+					.value_name("FLIP")
+					.help("Flip the image")
+					.possible_values(&["horizontal", "vertical"])
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("shear")
+					.long("shear")
+					.value_name("X[:Y]")
+					.help("Shear the image horizontally and vertically (in degrees)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("perspective")
+					.long("perspective")
+					.value_name("X,Y:X,Y:X,Y:X,Y")
+					.help("Warp the corners of the image to the given points")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("blur")
+					.long("blur")
+					.value_name("SIGMA")
+					.default_value("0.0")
+					.help("Blur the image")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("sharpen")
+					.long("sharpen")
+					.value_name("SIGMA")
+					.default_value("0.0")
+					.help("Sharpen the image (unsharp mask)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("denoise")
+					.long("denoise")
+					.value_name("STRENGTH")
+					.default_value("0")
+					.help("Reduce the noise in the image and between frames (0-100)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("text")
+					.long("text")
+					.value_name("TEXT")
+					.help("Draw the text over the image")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("font")
+					.long("font")
+					.value_name("FILE")
+					.help("Set the font file for the text [default: sans-serif]")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("font-size")
+					.long("font-size")
+					.value_name("PX")
+					.default_value("24")
+					.help("Set the font size of the text")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("text-color")
+					.long("text-color")
+					.value_name("HEX")
+					.default_value("FFFFFF")
+					.help("Set the color of the text (RRGGBB or RRGGBBAA)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("text-position")
+					.long("text-position")
+					.value_name("POSITION")
+					.possible_values(&[
+						"top-left",
+						"top",
+						"top-right",
+						"center",
+						"bottom-left",
+						"bottom",
+						"bottom-right",
+					])
+					.default_value("bottom")
+					.help("Set the position of the text")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("text-margin")
+					.long("text-margin")
+					.value_name("PX")
+					.default_value("10")
+					.help("Set the margin of the text from the edges")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("watermark")
+					.long("watermark")
+					.value_name("FILE")
+					.help("Draw the image file over the image as a watermark")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("watermark-position")
+					.long("watermark-position")
+					.alias("position")
+					.value_name("POSITION")
+					.possible_values(&[
+						"top-left",
+						"top",
+						"top-right",
+						"center",
+						"bottom-left",
+						"bottom",
+						"bottom-right",
+					])
+					.default_value("bottom-right")
+					.help("Set the position of the watermark")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("watermark-opacity")
+					.long("watermark-opacity")
+					.alias("opacity")
+					.value_name("OPACITY")
+					.default_value("1.0")
+					.help("Set the opacity of the watermark (0.0 to 1.0)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("watermark-margin")
+					.long("watermark-margin")
+					.value_name("PX")
+					.default_value("10")
+					.help("Set the margin of the watermark from the edges")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("draw")
+					.long("draw")
+					.value_name("SHAPE")
+					.help("Draw a rect, ellipse, line or arrow over the image")
+					.multiple(true)
+					.number_of_values(1)
+					.allow_hyphen_values(true)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("border")
+					.long("border")
+					.value_name("PX")
+					.default_value("0")
+					.help("Draw a border around the image")
+					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("convert")
-					.long("convert")
-					.help("Convert image using the given encoder"),
+				Arg::with_name("border-color")
+					.long("border-color")
+					.value_name("HEX")
+					.default_value("000000")
+					.help("Set the color of the border")
+					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("grayscale")
-					.long("grayscale")
-					.help("Convert image to grayscale"),
+				Arg::with_name("pad")
+					.long("pad")
+					.value_name("T:R:B:L")
+					.help("Add transparent padding around the image")
+					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("invert")
-					.long("invert")
-					.help("Invert the colors of the image"),
+				Arg::with_name("beautify")
+					.long("beautify")
+					.help("Put the image on a background with a shadow"),
 			)
 			.arg(
-				Arg::with_name("crop")
-					.long("crop")
-					.value_name("T:R:B:L")
-					.help("Apply padding to crop the image")
+				Arg::with_name("beautify-margin")
+					.long("beautify-margin")
+					.value_name("PX")
+					.default_value("64")
+					.help("Set the margin of the background around the image")
 					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("resize")
-					.long("resize")
-					.value_name("WxH")
-					.help("Resize the image without keeping the aspect ratio")
+				Arg::with_name("corner-radius")
+					.long("corner-radius")
+					.value_name("PX")
+					.default_value("12")
+					.help("Set the radius of the rounded corners")
 					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("ratio")
-					.long("ratio")
-					.value_name("RATIO")
-					.default_value("1.0")
-					.help("Resize the image proportionally by aspect ratio")
+				Arg::with_name("shadow")
+					.long("shadow")
+					.value_name("SIGMA")
+					.default_value("16.0")
+					.help("Set the blur of the drop shadow (0 to disable)")
 					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("rotate")
-					.long("rotate")
-					.value_name("ROTATE")
-					.help("Rotate the image (clockwise)")
-					.possible_values(&["90", "180", "270"])
+				Arg::with_name("beautify-background")
+					.long("beautify-background")
+					.value_name("HEX[:HEX]")
+					.default_value("6A82FB:FC5C7D")
+					.help("Set the background color, gradient or image")
 					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("flip")
-					.long("flip")
-					.value_name("FLIP")
-					.help("Flip the image")
-					.possible_values(&["horizontal", "vertical"])
+				Arg::with_name("background")
+					.long("background")
+					.value_name("BACKGROUND")
+					.help("Flatten the image onto a color, gradient or image")
 					.takes_value(true),
 			)
 			.arg(
-				Arg::with_name("blur")
-					.long("blur")
-					.value_name("SIGMA")
-					.default_value("0.0")
-					.help("Blur the image")
+				Arg::with_name("pixelate")
+					.long("pixelate")
+					.value_name("X:Y:W:H[:SIZE]")
+					.help("Pixelate the region of the image to censor it")
+					.multiple(true)
+					.number_of_values(1)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("blur-region")
+					.long("blur-region")
+					.value_name("X:Y:W:H[:SIGMA]")
+					.help("Blur the region of the image to censor it")
+					.multiple(true)
+					.number_of_values(1)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("replace-color")
+					.long("replace-color")
+					.value_name("FROM:TO[:N]")
+					.help("Replace the colors within the tolerance N (0-255)")
+					.multiple(true)
+					.number_of_values(1)
 					.takes_value(true),
 			)
 			.arg(
 				Arg::with_name("hue")
 					.long("hue")
+					.alias("hue-rotate")
 					.value_name("HUE")
 					.default_value("\u{00B1}0")
-					.help("Adjust the hue of the image")
+					.help("Rotate the hue of the image (degrees)")
 					.allow_hyphen_values(true)
 					.takes_value(true),
 			)
@@ -596,6 +1405,61 @@ where
 					.allow_hyphen_values(true)
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("saturate")
+					.long("saturate")
+					.value_name("SATURATION")
+					.default_value("\u{00B1}0")
+					.help("Adjust the saturation of the image (-100 to 100)")
+					.allow_hyphen_values(true)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("gamma")
+					.long("gamma")
+					.value_name("GAMMA")
+					.default_value("1.0")
+					.help("Adjust the gamma of the image (0.1 to 10.0)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("posterize")
+					.long("posterize")
+					.value_name("LEVELS")
+					.help("Reduce the number of levels in each color channel")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("colors")
+					.long("colors")
+					.value_name("N")
+					.help("Reduce the number of colors in the image (2 to 256)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("lut")
+					.long("lut")
+					.value_name("FILE")
+					.help("Grade the colors with a LUT (Hald CLUT image or .cube)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("mask")
+					.long("mask")
+					.value_name("FILE")
+					.help(
+						"Use the brightness of the image file as the alpha channel",
+					)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("vignette")
+					.long("vignette")
+					.value_name("STRENGTH")
+					.default_value("0")
+					.help("Darken the corners of the image (0-100)")
+					.takes_value(true),
+			)
 			.arg(
 				Arg::with_name("filter")
 					.long("filter")
@@ -637,6 +1501,132 @@ where
 					.help("Set the output directory")
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("archive")
+					.short("a")
+					.long("archive")
+					.value_name("FILE")
+					.conflicts_with("dir")
+					.help("Write the frames into a .tar or .zip archive")
+					.takes_value(true),
+			)
+	}
+
+	/**
+	 * Get the GIF trim arguments.
+	 *
+	 * @return App
+	 */
+	fn get_trim_args() -> App<'a, 'b> {
+		SubCommand::with_name("trim")
+			.about("Cut a time range out of a GIF")
+			.help_message("Print help information")
+			.arg(
+				Arg::with_name("file")
+					.value_name("FILE")
+					.help("Set the GIF file")
+					.required(true),
+			)
+			.arg(
+				Arg::with_name("start")
+					.long("start")
+					.value_name("S")
+					.default_value("0.0")
+					.help("Set the start of the time range")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("end")
+					.long("end")
+					.value_name("S")
+					.help("Set the end of the time range")
+					.takes_value(true),
+			)
+	}
+
+	/**
+	 * Get the image montage arguments.
+	 *
+	 * @return App
+	 */
+	fn get_montage_args() -> App<'a, 'b> {
+		SubCommand::with_name("montage")
+			.about("Combine images into a grid")
+			.help_message("Print help information")
+			.arg(
+				Arg::with_name("files")
+					.value_name("FILES")
+					.help("Set the image files")
+					.min_values(1)
+					.required(true),
+			)
+			.arg(
+				Arg::with_name("stitch")
+					.long("stitch")
+					.help("Stitch the captures of a scrolling window vertically"),
+			)
+			.arg(
+				Arg::with_name("grid")
+					.long("grid")
+					.value_name("CxR")
+					.default_value("0x0")
+					.help("Set the number of columns and rows (0 for auto)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("gap")
+					.long("gap")
+					.value_name("PX")
+					.default_value("0")
+					.help("Set the gap between the images")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("background")
+					.long("background")
+					.value_name("BG")
+					.help("Set the background color (HEX), gradient (HEX:HEX) or image")
+					.takes_value(true),
+			)
+	}
+
+	/**
+	 * Get the image comparison arguments.
+	 *
+	 * @return App
+	 */
+	fn get_diff_args() -> App<'a, 'b> {
+		SubCommand::with_name("diff")
+			.about("Compare two images")
+			.help_message("Print help information")
+			.arg(
+				Arg::with_name("file")
+					.value_name("FILE")
+					.help("Set the base image file")
+					.required(true),
+			)
+			.arg(
+				Arg::with_name("other")
+					.value_name("OTHER")
+					.help("Set the image file to compare")
+					.required(true),
+			)
+			.arg(
+				Arg::with_name("tolerance")
+					.long("tolerance")
+					.value_name("T")
+					.default_value("0")
+					.help("Set the allowed difference of the color channels (0-255)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("highlight")
+					.long("highlight")
+					.value_name("HEX")
+					.default_value("FF0000")
+					.help("Set the color of the changed pixels")
+					.takes_value(true),
+			)
 	}
 
 	/**
@@ -665,11 +1655,42 @@ where
 					.help("Set the time zone of the report")
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("colors")
+					.short("c")
+					.long("colors")
+					.value_name("N")
+					.default_value("5")
+					.help("Set the number of dominant colors to report")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("histogram")
+					.long("histogram")
+					.value_name("FORMAT")
+					.possible_values(&["chart", "raw"])
+					.help("Add the histogram of the color channels to the report")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("bins")
+					.long("bins")
+					.value_name("N")
+					.default_value("32")
+					.help("Set the number of histogram bins (1-256)")
+					.takes_value(true),
+			)
 			.arg(
 				Arg::with_name("timestamp")
 					.long("timestamp")
 					.help("Use Unix timestamp for report dates"),
 			)
+			.arg(
+				Arg::with_name("json")
+					.short("j")
+					.long("json")
+					.help("Create the report in JSON format"),
+			)
 	}
 
 	/**
@@ -693,6 +1714,12 @@ where
 					.long("transparent")
 					.help("Display transparent image with transparent background"),
 			)
+			.arg(
+				Arg::with_name("sixel")
+					.short("s")
+					.long("sixel")
+					.help("Display the image using the sixel graphics protocol"),
+			)
 	}
 
 	/**
@@ -718,7 +1745,7 @@ where
 						.value_name("COMPRESSION")
 						.possible_values(&["default", "fast", "best"])
 						.default_value("fast")
-						.help("Set the compression level")
+						.help("Set the compression level (best uses zopfli)")
 						.takes_value(true),
 				)
 				.arg(
@@ -731,6 +1758,34 @@ where
 						.help("Set the filter algorithm")
 						.takes_value(true),
 				)
+				.arg(
+					Arg::with_name("color-type")
+						.short("t")
+						.long("color-type")
+						.alias("png-color-type")
+						.value_name("TYPE")
+						.possible_values(&[
+							"rgba",
+							"rgb",
+							"gray",
+							"gray-alpha",
+							"indexed",
+						])
+						.default_value("rgba")
+						.help("Set the color type")
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("depth")
+						.short("d")
+						.long("depth")
+						.alias("png-depth")
+						.value_name("BITS")
+						.possible_values(&["8", "16"])
+						.default_value("8")
+						.help("Set the bit depth (indexed is always 8-bit)")
+						.takes_value(true),
+				)
 				.subcommand(
 					Self::get_save_args(FileFormat::Png).settings(&save_settings),
 				),
@@ -771,10 +1826,77 @@ where
 						.long("lossless")
 						.help("Use lossless encoding"),
 				)
+				.arg(
+					Arg::with_name("transparent")
+						.long("transparent")
+						.value_name("HEX")
+						.help("Set the color to make transparent")
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("tolerance")
+						.long("tolerance")
+						.value_name("N")
+						.help("Set the tolerance of the transparent color (0-255)")
+						.takes_value(true),
+				)
 				.subcommand(
 					Self::get_save_args(FileFormat::WebP).settings(&save_settings),
 				),
 		)
+		.subcommand(
+			SubCommand::with_name("jxl")
+				.about("Use the JPEG XL encoder (lossless)")
+				.help_message("Print help information")
+				.arg(
+					Arg::with_name("effort")
+						.short("e")
+						.long("effort")
+						.value_name("EFFORT")
+						.default_value("4")
+						.help("Set the encoding effort (0-127)")
+						.takes_value(true),
+				)
+				.subcommand(
+					Self::get_save_args(FileFormat::Jxl).settings(&save_settings),
+				),
+		)
+		.subcommand(
+			SubCommand::with_name("heif")
+				.about("Use the HEIF encoder")
+				.help_message("Print help information")
+				.settings(if cfg!(feature = "heif") {
+					&[]
+				} else {
+					&[AppSettings::Hidden]
+				})
+				.arg(
+					Arg::with_name("quality")
+						.short("q")
+						.long("quality")
+						.value_name("QUALITY")
+						.default_value("80")
+						.help("Set the lossy encoding quality (1-100)")
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("lossless")
+						.short("l")
+						.long("lossless")
+						.help("Use lossless encoding"),
+				)
+				.subcommand(
+					Self::get_save_args(FileFormat::Heif).settings(&save_settings),
+				),
+		)
+		.subcommand(
+			SubCommand::with_name("qoi")
+				.about("Use the QOI encoder")
+				.help_message("Print help information")
+				.subcommand(
+					Self::get_save_args(FileFormat::Qoi).settings(&save_settings),
+				),
+		)
 		.subcommand(
 			SubCommand::with_name("bmp")
 				.about("Use the BMP encoder")
@@ -787,6 +1909,20 @@ where
 			SubCommand::with_name("ico")
 				.about("Use the ICO encoder")
 				.help_message("Print help information")
+				.arg(
+					Arg::with_name("sizes")
+						.short("s")
+						.long("sizes")
+						.value_name("SIZES")
+						.default_value("16,32,48,64")
+						.help("Set the icon sizes to generate (1-256)")
+						.validator(|sizes| {
+							IcoSettings::parse_sizes(&sizes).map(|_| ()).ok_or_else(
+								|| format!("Invalid icon sizes: {sizes:?}"),
+							)
+						})
+						.takes_value(true),
+				)
 				.subcommand(
 					Self::get_save_args(FileFormat::Ico).settings(&save_settings),
 				),
@@ -857,6 +1993,37 @@ where
 					Self::get_save_args(FileFormat::Exr).settings(&save_settings),
 				),
 		)
+		.subcommand(
+			SubCommand::with_name("ansi")
+				.about("Use the ANSI/ASCII art encoder")
+				.help_message("Print help information")
+				.arg(
+					Arg::with_name("width")
+						.short("w")
+						.long("width")
+						.value_name("COLUMNS")
+						.default_value("80")
+						.help("Set the width in terminal columns (0 for original)")
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("ascii")
+						.short("a")
+						.long("ascii")
+						.help("Use ASCII characters instead of colored blocks"),
+				)
+				.subcommand(
+					Self::get_save_args(FileFormat::Ansi).settings(&save_settings),
+				),
+		)
+		.subcommand(
+			SubCommand::with_name("sixel")
+				.about("Use the sixel encoder")
+				.help_message("Print help information")
+				.subcommand(
+					Self::get_save_args(FileFormat::Sixel).settings(&save_settings),
+				),
+		)
 		.subcommand(Self::get_save_args(FileFormat::Any).settings(&save_settings))
 	}
 
@@ -902,6 +2069,45 @@ where
 					.help("Add formatted date/time to the file name")
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("output")
+					.short("o")
+					.long("output")
+					.value_name("FILE")
+					.help("Also save to the given file (format from extension)")
+					.multiple(true)
+					.number_of_values(1)
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("icc")
+					.short("i")
+					.long("icc")
+					.value_name("PROFILE")
+					.help("Embed the ICC profile (display, none or a file)")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("comment")
+					.short("c")
+					.long("comment")
+					.value_name("TEXT")
+					.help("Add a comment to the metadata")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("no-metadata")
+					.short("n")
+					.long("no-metadata")
+					.help("Do not write metadata to the file"),
+			)
+			.arg(
+				Arg::with_name("background")
+					.long("background")
+					.value_name("BACKGROUND")
+					.help("Flatten the output onto a color, gradient or image")
+					.takes_value(true),
+			)
 	}
 
 	/**
@@ -944,4 +2150,25 @@ mod tests {
 		Args::gen_completions("test", &mut output);
 		assert!(output.is_empty());
 	}
+	#[test]
+	fn test_speed_curve() {
+		assert!(Args::get_app()
+			.get_matches_from_safe(vec![
+				"menyoki",
+				"make",
+				"a.png",
+				"--speed-curve",
+				"0:1,5:3"
+			])
+			.is_ok());
+		assert!(Args::get_app()
+			.get_matches_from_safe(vec![
+				"menyoki",
+				"make",
+				"a.png",
+				"--speed-curve",
+				"garbage"
+			])
+			.is_err());
+	}
 }
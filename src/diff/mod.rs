@@ -0,0 +1,119 @@
+pub mod settings;
+
+use crate::diff::settings::DiffSettings;
+use crate::image::geometry::Geometry;
+use crate::image::Image;
+use image::{Pixel, Rgba, RgbaImage};
+
+/* Comparison of two images */
+#[derive(Debug)]
+pub struct ImageDiff<'a> {
+	settings: &'a DiffSettings,
+}
+
+impl<'a> ImageDiff<'a> {
+	/**
+	 * Create a new ImageDiff object.
+	 *
+	 * @param  settings
+	 * @return ImageDiff
+	 */
+	pub fn new(settings: &'a DiffSettings) -> Self {
+		Self { settings }
+	}
+
+	/**
+	 * Check if the pixels differ more than the tolerance. Pixels that only
+	 * exist in one of the images (i.e. different sizes) are counted as changed.
+	 *
+	 * @param  pixel
+	 * @param  other
+	 * @return bool
+	 */
+	fn is_changed(
+		&self,
+		pixel: Option<&Rgba<u8>>,
+		other: Option<&Rgba<u8>>,
+	) -> bool {
+		match (pixel, other) {
+			(Some(pixel), Some(other)) => pixel
+				.0
+				.iter()
+				.zip(other.0)
+				.any(|(a, b)| a.abs_diff(b) > self.settings.tolerance),
+			(None, None) => false,
+			_ => true,
+		}
+	}
+
+	/**
+	 * Compare the images and create a difference image that highlights
+	 * the changed pixels over the faded base image.
+	 *
+	 * @param  base
+	 * @param  image
+	 * @return Tuple of Image and the percentage of changed pixels
+	 */
+	pub fn compare(&self, base: &RgbaImage, image: &RgbaImage) -> (Image, f64) {
+		let (width, height) = (
+			base.width().max(image.width()),
+			base.height().max(image.height()),
+		);
+		let mut changed = 0;
+		let diff = RgbaImage::from_fn(width, height, |x, y| {
+			let (pixel, other) =
+				(base.get_pixel_checked(x, y), image.get_pixel_checked(x, y));
+			if self.is_changed(pixel, other) {
+				changed += 1;
+				return self.settings.highlight;
+			}
+			pixel.or(other).map_or(Rgba([0, 0, 0, 0]), |pixel| {
+				let luma = pixel.to_luma()[0];
+				let value = ((u16::from(luma) + 255 * 2) / 3) as u8;
+				Rgba([value, value, value, pixel[3]])
+			})
+		});
+		let percentage =
+			f64::from(changed) / f64::from((width * height).max(1)) * 100.;
+		(
+			Image::new(
+				diff.pixels().copied().collect(),
+				true,
+				Geometry::new(0, 0, width, height),
+			),
+			percentage,
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::AppResult;
+	use image::ExtendedColorType;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_diff() -> AppResult<()> {
+		let mut settings = DiffSettings::default();
+		let base = RgbaImage::from_pixel(4, 2, Rgba([0, 0, 0, 255]));
+		let mut image = base.clone();
+		image.put_pixel(1, 0, Rgba([5, 0, 0, 255]));
+		image.put_pixel(2, 1, Rgba([0, 200, 0, 255]));
+		let (diff, percentage) = ImageDiff::new(&settings).compare(&base, &image);
+		assert_eq!(25., percentage);
+		assert_eq!(Geometry::new(0, 0, 4, 2), diff.geometry);
+		let data = diff.get_data(ExtendedColorType::Rgba8)?;
+		assert_eq!([170, 170, 170, 255], data[0..4]);
+		assert_eq!([255, 0, 0, 255], data[4..8]);
+		assert_eq!([255, 0, 0, 255], data[24..28]);
+		settings.tolerance = 10;
+		assert_eq!(12.5, ImageDiff::new(&settings).compare(&base, &image).1);
+		assert_eq!(
+			50.,
+			ImageDiff::new(&settings)
+				.compare(&base, &RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255])))
+				.1
+		);
+		Ok(())
+	}
+}
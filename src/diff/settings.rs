@@ -0,0 +1,132 @@
+use crate::args::matches::ArgMatches;
+use crate::args::parser::ArgParser;
+use crate::edit::text::parse_color;
+use image::Rgba;
+use std::path::PathBuf;
+
+/* Image comparison settings */
+#[derive(Debug)]
+pub struct DiffSettings {
+	pub file: PathBuf,
+	pub other: PathBuf,
+	pub tolerance: u8,
+	pub highlight: Rgba<u8>,
+}
+
+/* Default initialization values for DiffSettings */
+impl Default for DiffSettings {
+	fn default() -> Self {
+		Self {
+			file: PathBuf::new(),
+			other: PathBuf::new(),
+			tolerance: 0,
+			highlight: Rgba([255, 0, 0, 255]),
+		}
+	}
+}
+
+impl DiffSettings {
+	/**
+	 * Create a new DiffSettings object.
+	 *
+	 * @param  file
+	 * @param  other
+	 * @param  tolerance
+	 * @param  highlight
+	 * @return DiffSettings
+	 */
+	pub fn new(
+		file: PathBuf,
+		other: PathBuf,
+		tolerance: u8,
+		highlight: Rgba<u8>,
+	) -> Self {
+		Self {
+			file,
+			other,
+			tolerance,
+			highlight,
+		}
+	}
+
+	/**
+	 * Create a new DiffSettings object from arguments.
+	 *
+	 * @param  matches
+	 * @return DiffSettings
+	 */
+	pub fn from_args(matches: &ArgMatches<'_>) -> Self {
+		Self::from_parser(ArgParser::from_subcommand(matches, "diff"))
+	}
+
+	/**
+	 * Create a DiffSettings object from an argument parser.
+	 *
+	 * @param  parser
+	 * @return DiffSettings
+	 */
+	fn from_parser(parser: ArgParser<'_>) -> Self {
+		match parser.args {
+			Some(ref matches) => {
+				let expand = |arg: &str| {
+					let file = matches.value_of(arg).unwrap_or_default();
+					PathBuf::from(
+						shellexpand::full(file)
+							.map(|s| s.to_string())
+							.unwrap_or(file.to_string()),
+					)
+				};
+				Self::new(
+					expand("file"),
+					expand("other"),
+					parser.parse("tolerance", Self::default().tolerance),
+					matches
+						.value_of("highlight")
+						.and_then(parse_color)
+						.unwrap_or(Self::default().highlight),
+				)
+			}
+			None => Self::default(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::{App, Arg};
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_diff_settings() {
+		let args = App::new("test")
+			.arg(Arg::with_name("file").required(true))
+			.arg(Arg::with_name("other").required(true))
+			.arg(
+				Arg::with_name("tolerance")
+					.long("tolerance")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("highlight")
+					.long("highlight")
+					.takes_value(true),
+			)
+			.get_matches_from(vec![
+				"test",
+				"a.png",
+				"b.png",
+				"--tolerance",
+				"8",
+				"--highlight",
+				"00FF00",
+			]);
+		let settings = DiffSettings::from_parser(ArgParser::from_args(&args));
+		assert_eq!(PathBuf::from("a.png"), settings.file);
+		assert_eq!(PathBuf::from("b.png"), settings.other);
+		assert_eq!(8, settings.tolerance);
+		assert_eq!(Rgba([0, 255, 0, 255]), settings.highlight);
+		let settings = DiffSettings::from_parser(ArgParser::new(None));
+		assert_eq!(0, settings.tolerance);
+		assert_eq!(Rgba([255, 0, 0, 255]), settings.highlight);
+	}
+}
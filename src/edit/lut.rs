@@ -0,0 +1,180 @@
+use crate::app::{AppError, AppResult};
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::Path;
+
+/* Maximum size of the LUT cube */
+const MAX_LUT_SIZE: usize = 256;
+
+/* 3D color lookup table */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lut {
+	size: usize,
+	table: Vec<[f32; 3]>,
+}
+
+impl Lut {
+	/**
+	 * Create a new Lut object from a Hald CLUT image or a .cube file.
+	 *
+	 * @param  path
+	 * @return Lut (Result)
+	 */
+	pub fn from_file(path: &Path) -> AppResult<Self> {
+		let lut = match path.extension().and_then(|v| v.to_str()) {
+			Some(extension) if extension.eq_ignore_ascii_case("cube") => {
+				Self::parse_cube(&fs::read_to_string(path)?)
+			}
+			_ => Self::from_hald(&image::open(path)?.to_rgba8()),
+		}
+		.ok_or_else(|| {
+			AppError::FrameError(format!("Invalid LUT file: {path:?}"))
+		})?;
+		debug!("Loaded {0}x{0}x{0} LUT from {1:?}", lut.size, path);
+		Ok(lut)
+	}
+
+	/**
+	 * Create a new Lut object from a Hald CLUT image.
+	 * Level 1 is not accepted since the interpolation needs at least 2 entries.
+	 *
+	 * @param  image
+	 * @return Lut (Option)
+	 */
+	fn from_hald(image: &RgbaImage) -> Option<Self> {
+		let (width, height) = image.dimensions();
+		let level = (2..=16).find(|level| level * level * level == width)?;
+		if width != height {
+			return None;
+		}
+		Some(Self {
+			size: (level * level) as usize,
+			table: image
+				.pixels()
+				.map(|pixel| std::array::from_fn(|i| f32::from(pixel[i]) / 255.))
+				.collect(),
+		})
+	}
+
+	/**
+	 * Parse the LUT from the contents of a .cube file.
+	 *
+	 * @param  cube
+	 * @return Lut (Option)
+	 */
+	fn parse_cube(cube: &str) -> Option<Self> {
+		let (mut size, mut table) = (0, Vec::new());
+		let (mut min, mut max) = ([0.; 3], [1.; 3]);
+		for line in cube.lines().map(str::trim) {
+			let mut values = line.split_whitespace();
+			match values.next() {
+				None => continue,
+				Some(key) if key.starts_with('#') || key == "TITLE" => continue,
+				Some("LUT_3D_SIZE") => size = values.next()?.parse().ok()?,
+				Some("DOMAIN_MIN") => min = Self::parse_rgb(values)?,
+				Some("DOMAIN_MAX") => max = Self::parse_rgb(values)?,
+				Some(key) if key.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+					return None;
+				}
+				Some(value) => table
+					.push(Self::parse_rgb(std::iter::once(value).chain(values))?),
+			}
+		}
+		if !(2..=MAX_LUT_SIZE).contains(&size) || table.len() != size.pow(3) {
+			return None;
+		}
+		for color in table.iter_mut() {
+			for i in 0..3 {
+				color[i] = (color[i] - min[i]) / (max[i] - min[i]);
+			}
+		}
+		Some(Self { size, table })
+	}
+
+	/**
+	 * Parse the RGB values from the given values.
+	 *
+	 * @param  values
+	 * @return Array of f32 (Option)
+	 */
+	fn parse_rgb<'a>(mut values: impl Iterator<Item = &'a str>) -> Option<[f32; 3]> {
+		let mut rgb = [0.; 3];
+		for value in rgb.iter_mut() {
+			*value = values.next()?.parse().ok()?;
+		}
+		values.next().is_none().then_some(rgb)
+	}
+
+	/**
+	 * Map the color of the pixel using trilinear interpolation.
+	 *
+	 * @param pixel
+	 */
+	pub fn apply(&self, pixel: &mut Rgba<u8>) {
+		let max = (self.size - 1) as f32;
+		let position: [f32; 3] =
+			std::array::from_fn(|i| f32::from(pixel[i]) / 255. * max);
+		let start =
+			position.map(|value| (value.floor() as usize).min(self.size - 2));
+		let ratio: [f32; 3] = std::array::from_fn(|i| position[i] - start[i] as f32);
+		let mut color = [0.; 3];
+		for corner in 0..8 {
+			let offset: [usize; 3] = std::array::from_fn(|i| (corner >> i) & 1);
+			let weight = (0..3)
+				.map(|i| {
+					if offset[i] == 1 {
+						ratio[i]
+					} else {
+						1. - ratio[i]
+					}
+				})
+				.product::<f32>();
+			let index = (start[0] + offset[0])
+				+ (start[1] + offset[1]) * self.size
+				+ (start[2] + offset[2]) * self.size * self.size;
+			for (value, entry) in color.iter_mut().zip(self.table[index]) {
+				*value += entry * weight;
+			}
+		}
+		for (i, value) in color.iter().enumerate() {
+			pixel[i] = (value * 255.).round().clamp(0., 255.) as u8;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_lut() {
+		let hald = RgbaImage::from_fn(8, 8, |x, y| {
+			let index = y * 8 + x;
+			Rgba([
+				(index % 4 * 85) as u8,
+				(index / 4 % 4 * 85) as u8,
+				(index / 16 * 85) as u8,
+				255,
+			])
+		});
+		let lut = Lut::from_hald(&hald).unwrap();
+		assert_eq!(4, lut.size);
+		for color in [[0, 0, 0, 255], [12, 200, 255, 100], [100, 50, 170, 0]] {
+			let mut pixel = Rgba(color);
+			lut.apply(&mut pixel);
+			assert_eq!(Rgba(color), pixel);
+		}
+		assert_eq!(None, Lut::from_hald(&RgbaImage::new(8, 4)));
+		assert_eq!(None, Lut::from_hald(&RgbaImage::new(1, 1)));
+		let cube = "TITLE \"Invert\"\n# Comment\nLUT_3D_SIZE 2\n\
+			DOMAIN_MIN 0 0 0\nDOMAIN_MAX 2 2 2\n\n\
+			2 2 2\n0 2 2\n2 0 2\n0 0 2\n2 2 0\n0 2 0\n2 0 0\n0 0 0\n";
+		let lut = Lut::parse_cube(cube).unwrap();
+		let mut pixel = Rgba([255, 0, 51, 255]);
+		lut.apply(&mut pixel);
+		assert_eq!(Rgba([0, 255, 204, 255]), pixel);
+		assert_eq!(None, Lut::parse_cube("LUT_3D_SIZE 2\n0 0 0\n"));
+		assert_eq!(None, Lut::parse_cube("LUT_3D_SIZE 1\n0 0 0\n"));
+		assert_eq!(None, Lut::parse_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n"));
+	}
+}
@@ -1,17 +1,45 @@
+pub mod lut;
 pub mod settings;
+pub mod shape;
+pub mod text;
+pub mod transform;
 
+use crate::app::{AppError, AppResult};
+use crate::edit::lut::Lut;
 use crate::edit::settings::ColorSettings;
-use crate::edit::settings::{EditSettings, Flip};
+use crate::edit::settings::{EditOp, EditSettings, Flip, Redaction};
+use crate::edit::text::TextRenderer;
+use crate::edit::transform::Transform;
 use crate::image::geometry::Geometry;
 use crate::image::Image;
+use color_quant::NeuQuant;
 use image::imageops::{self, colorops};
-use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
+use image::{DynamicImage, GrayImage, ImageBuffer, Luma, Rgba, RgbaImage};
+use std::collections::HashMap;
+
+/* Minimum brightness difference for sharpening the pixels */
+const SHARPEN_THRESHOLD: i32 = 1;
+/* Opacity of the drop shadow */
+const SHADOW_OPACITY: f32 = 0.5;
+/* Maximum difference of the pixel values to treat as noise */
+const MAX_NOISE_THRESHOLD: u32 = 48;
+/* Ratio of the darkest and brightest values to ignore while stretching */
+const AUTO_LEVELS_CLIP: f64 = 0.005;
+/* Maximum difference of the border pixels from the corner color */
+const TRIM_TOLERANCE: u8 = 8;
 
 /* Image processor */
 #[derive(Debug)]
 pub struct ImageOps<'a> {
 	pub image: RgbaImage,
 	pub geometry: Geometry,
+	text: Option<TextRenderer>,
+	watermark: Option<RgbaImage>,
+	lut: Option<Lut>,
+	mask: Option<GrayImage>,
+	palette: Option<Vec<Rgba<u8>>>,
+	previous: Option<RgbaImage>,
+	trim: Option<Geometry>,
 	settings: &'a EditSettings,
 }
 
@@ -26,40 +54,167 @@ impl<'a> ImageOps<'a> {
 		Self {
 			image: ImageBuffer::new(0, 0),
 			geometry: Geometry::default(),
+			text: None,
+			watermark: None,
+			lut: None,
+			mask: None,
+			palette: None,
+			previous: None,
+			trim: None,
 			settings,
 		}
 	}
 
 	/**
-	 * Set the geometry to use while processing.
+	 * Set the geometry to use while processing and
+	 * load the font and watermark if there are any.
 	 *
-	 * @param size
+	 * @param  size
+	 * @return ImageOps (Result)
 	 */
-	pub fn init(&mut self, size: (u32, u32)) -> &mut Self {
-		let (mut width, mut height) = if !self.settings.image.resize.is_zero() {
-			(
-				self.settings.image.resize.width,
-				self.settings.image.resize.height,
-			)
-		} else {
-			size
-		};
-		if self.settings.image.ratio > 0.
-			&& (self.settings.image.ratio - 1.).abs() > f32::EPSILON
+	pub fn init(&mut self, size: (u32, u32)) -> AppResult<&mut Self> {
+		if self.settings.text.is_enabled() && self.text.is_none() {
+			self.text = Some(TextRenderer::new(self.settings.text.clone())?);
+		}
+		if let (Some(path), None) = (&self.settings.watermark.path, &self.watermark)
 		{
-			let (w, h) = (width, height);
-			width = (w as f32 * self.settings.image.ratio) as u32;
-			height = (h as f32 * self.settings.image.ratio) as u32;
+			debug!("Loading the watermark from {:?}", path);
+			let mut watermark = image::open(path)?.to_rgba8();
+			for pixel in watermark.pixels_mut() {
+				pixel[3] =
+					(f32::from(pixel[3]) * self.settings.watermark.opacity) as u8;
+			}
+			self.watermark = Some(watermark);
+		}
+		if let (Some(path), None) = (&self.settings.lut, &self.lut) {
+			self.lut = Some(Lut::from_file(path)?);
 		}
-		if self.settings.image.rotate == 90 || self.settings.image.rotate == 270 {
-			let (w, h) = (width, height);
-			width = h;
-			height = w;
+		if let (Some(path), None) = (&self.settings.mask, &self.mask) {
+			debug!("Loading the mask from {:?}", path);
+			let mask = image::open(path)?.to_luma_alpha8();
+			self.mask =
+				Some(GrayImage::from_fn(mask.width(), mask.height(), |x, y| {
+					let [luma, alpha] = mask.get_pixel(x, y).0;
+					Luma([((u16::from(luma) * u16::from(alpha)) / 255) as u8])
+				}));
+		}
+		let mut size = size;
+		for op in &self.settings.ops {
+			if matches!(op, EditOp::Crop) {
+				if let Some(area) = self.get_crop_area(size) {
+					if area.width == 0 || area.height == 0 {
+						return Err(AppError::FrameError(format!(
+							"Crop area is outside of the image ({}x{})",
+							size.0, size.1
+						)));
+					}
+				}
+			}
+			size = self.get_size(*op, size);
 		}
-		self.geometry = Geometry::new(0, 0, width, height)
-			.with_padding(self.settings.image.crop);
+		let (width, height) = size;
+		let padding = self.settings.image.crop.padding;
+		self.geometry = Geometry::new(
+			i32::try_from(padding.left).unwrap_or_default(),
+			i32::try_from(padding.top).unwrap_or_default(),
+			width,
+			height,
+		);
 		debug!("{:?} -> {:?}", size, self.geometry);
-		self
+		Ok(self)
+	}
+
+	/**
+	 * Get the size of the image after applying the operation.
+	 *
+	 * @param  op
+	 * @param  size
+	 * @return Tuple of u32
+	 */
+	fn get_size(&self, op: EditOp, size: (u32, u32)) -> (u32, u32) {
+		let settings = self.settings;
+		match op {
+			EditOp::Crop => self
+				.get_crop_area(size)
+				.map_or(size, |area| (area.width, area.height)),
+			EditOp::Resize => {
+				let (mut width, mut height) = if !settings.image.resize.is_zero() {
+					Self::get_resize_dimensions(size, settings.image.resize)
+				} else {
+					size
+				};
+				if settings.image.ratio > 0.
+					&& (settings.image.ratio - 1.).abs() > f32::EPSILON
+				{
+					width = (width as f32 * settings.image.ratio) as u32;
+					height = (height as f32 * settings.image.ratio) as u32;
+				}
+				(width, height)
+			}
+			EditOp::Rotate
+				if settings.image.rotate == 90 || settings.image.rotate == 270 =>
+			{
+				(size.1, size.0)
+			}
+			EditOp::Transform => {
+				let size = if settings.image.shear != (0., 0.) {
+					Transform::shear(settings.image.shear).get_size(size)
+				} else {
+					size
+				};
+				settings
+					.image
+					.perspective
+					.and_then(|points| Transform::perspective(size, points))
+					.map_or(size, |transform| transform.get_size(size))
+			}
+			EditOp::Border if settings.border.is_enabled() => {
+				settings.border.get_size(size)
+			}
+			EditOp::Beautify if settings.beautify.enabled => (
+				size.0 + settings.beautify.margin * 2,
+				size.1 + settings.beautify.margin * 2,
+			),
+			_ => size,
+		}
+	}
+
+	/**
+	 * Get the area to crop with the padding applied.
+	 *
+	 * @param  size
+	 * @return Geometry (Option)
+	 */
+	fn get_crop_area(&self, size: (u32, u32)) -> Option<Geometry> {
+		let crop = self.settings.image.crop;
+		let area = crop.get_area(size);
+		if crop.padding.is_zero() {
+			area
+		} else {
+			Some(
+				area.unwrap_or_else(|| Geometry::new(0, 0, size.0, size.1))
+					.with_padding(crop.padding),
+			)
+		}
+	}
+
+	/**
+	 * Get the dimensions to resize to while keeping the
+	 * aspect ratio if one of the given dimensions is zero.
+	 *
+	 * @param  size
+	 * @param  resize
+	 * @return Tuple of u32
+	 */
+	fn get_resize_dimensions(size: (u32, u32), resize: Geometry) -> (u32, u32) {
+		let scale = |value: u32, from: u32, to: u32| {
+			(u64::from(value) * u64::from(to) / u64::from(from.max(1))).max(1) as u32
+		};
+		match (resize.width, resize.height) {
+			(0, height) => (scale(size.0, size.1, height), height),
+			(width, 0) => (width, scale(size.1, size.0, width)),
+			(width, height) => (width, height),
+		}
 	}
 
 	/**
@@ -69,7 +224,38 @@ impl<'a> ImageOps<'a> {
 	 */
 	pub fn process(&mut self, image: RgbaImage) -> &mut Self {
 		self.image = image;
-		self.crop().flip().rotate().resize().blur().update_colors();
+		let settings = self.settings;
+		for op in &settings.ops {
+			match op {
+				EditOp::Redact => self.redact(),
+				EditOp::Trim => self.trim(),
+				EditOp::Crop => self.crop(),
+				EditOp::Flip => self.flip(),
+				EditOp::Resize => self.resize(),
+				EditOp::Rotate => self.rotate(),
+				EditOp::Transform => self.transform(),
+				EditOp::Denoise => self.denoise(),
+				EditOp::Blur => self.blur(),
+				EditOp::Sharpen => self.sharpen(),
+				EditOp::ReplaceColors => self.replace_colors(),
+				EditOp::UpdateColors => self.update_colors(),
+				EditOp::Lut => self.apply_lut(),
+				EditOp::Vignette => self.vignette(),
+				EditOp::Watermark => self.draw_watermark(),
+				EditOp::Shapes => self.draw_shapes(),
+				EditOp::Text => self.draw_text(),
+				EditOp::Mask => self.apply_mask(),
+				EditOp::Border => self.draw_border(),
+				EditOp::Beautify => self.beautify(),
+				EditOp::Flatten => self.flatten(),
+				EditOp::Quantize => self.quantize(),
+			};
+		}
+		if self.trim.is_some() {
+			let (width, height) = self.image.dimensions();
+			self.geometry =
+				Geometry::new(self.geometry.x, self.geometry.y, width, height);
+		}
 		self
 	}
 
@@ -91,20 +277,68 @@ impl<'a> ImageOps<'a> {
 		)
 	}
 
+	/* Pixelate or blur the regions to censor */
+	fn redact(&mut self) -> &mut Self {
+		for redaction in &self.settings.redact {
+			let Some(area) = redaction.get_area(self.image.dimensions()) else {
+				continue;
+			};
+			info!("Redacting the region... ({}+{}+{})", area, area.x, area.y);
+			let (x, y) = (area.x as u32, area.y as u32);
+			let mut region =
+				imageops::crop_imm(&self.image, x, y, area.width, area.height)
+					.to_image();
+			match *redaction {
+				Redaction::Pixelate(_, size) => Self::pixelate(&mut region, size),
+				Redaction::Blur(_, sigma) => region = imageops::blur(&region, sigma),
+			}
+			imageops::replace(&mut self.image, &region, i64::from(x), i64::from(y));
+		}
+		self
+	}
+
+	/**
+	 * Fill the blocks of the image with their average color.
+	 *
+	 * @param image
+	 * @param size
+	 */
+	fn pixelate(image: &mut RgbaImage, size: u32) {
+		let (width, height) = image.dimensions();
+		for block_y in (0..height).step_by(size as usize) {
+			for block_x in (0..width).step_by(size as usize) {
+				let (block_width, block_height) =
+					(size.min(width - block_x), size.min(height - block_y));
+				let mut sum = [0u64; 4];
+				for y in block_y..block_y + block_height {
+					for x in block_x..block_x + block_width {
+						let pixel = image.get_pixel(x, y);
+						for i in 0..4 {
+							sum[i] += u64::from(pixel[i]);
+						}
+					}
+				}
+				let count = u64::from(block_width * block_height);
+				let color = Rgba(sum.map(|value| (value / count) as u8));
+				for y in block_y..block_y + block_height {
+					for x in block_x..block_x + block_width {
+						image.put_pixel(x, y, color);
+					}
+				}
+			}
+		}
+	}
+
 	/* Resize the image */
 	fn resize(&mut self) -> &mut Self {
-		if !self.settings.image.resize.is_zero()
-			|| (self.settings.image.ratio > 0.
-				&& (self.settings.image.ratio - 1.).abs() > f32::EPSILON)
-		{
-			info!(
-				"Resizing image... ({}x{})",
-				self.geometry.width, self.geometry.height
-			);
+		let size = self.image.dimensions();
+		let (width, height) = self.get_size(EditOp::Resize, size);
+		if (width, height) != size {
+			info!("Resizing image... ({}x{})", width, height);
 			self.image = imageops::resize(
 				&self.image,
-				self.geometry.width,
-				self.geometry.height,
+				width,
+				height,
 				self.settings.image.filter,
 			);
 		}
@@ -113,23 +347,87 @@ impl<'a> ImageOps<'a> {
 
 	/* Crop the image */
 	fn crop(&mut self) -> &mut Self {
-		if !self.settings.image.crop.is_zero() {
-			info!(
-				"Cropping the image... ({}x{})",
-				self.geometry.width, self.geometry.height
-			);
+		if let Some(area) = self.get_crop_area(self.image.dimensions()) {
+			info!("Cropping the image... ({}+{}+{})", area, area.x, area.y);
 			self.image = imageops::crop(
 				&mut self.image,
-				self.geometry.x.try_into().unwrap_or_default(),
-				self.geometry.y.try_into().unwrap_or_default(),
-				self.geometry.width,
-				self.geometry.height,
+				area.x.try_into().unwrap_or_default(),
+				area.y.try_into().unwrap_or_default(),
+				area.width,
+				area.height,
 			)
 			.to_image();
 		}
 		self
 	}
 
+	/* Remove the uniform borders around the image */
+	fn trim(&mut self) -> &mut Self {
+		if !self.settings.image.trim {
+			return self;
+		}
+		let area = match self.trim {
+			Some(area) => area,
+			None => {
+				let area = Self::get_trim_area(&self.image);
+				info!("Trimming the image... ({}+{}+{})", area, area.x, area.y);
+				*self.trim.insert(area)
+			}
+		};
+		if (area.width, area.height) != self.image.dimensions() {
+			self.image = imageops::crop(
+				&mut self.image,
+				area.x.try_into().unwrap_or_default(),
+				area.y.try_into().unwrap_or_default(),
+				area.width,
+				area.height,
+			)
+			.to_image();
+		}
+		self
+	}
+
+	/**
+	 * Get the area of the image without the borders that have the same
+	 * color as the top-left corner (or that are fully transparent).
+	 *
+	 * @param  image
+	 * @return Geometry
+	 */
+	fn get_trim_area(image: &RgbaImage) -> Geometry {
+		let (width, height) = image.dimensions();
+		let Some(corner) = image.get_pixel_checked(0, 0).copied() else {
+			return Geometry::new(0, 0, width, height);
+		};
+		let is_border = |pixel: &Rgba<u8>| {
+			if corner[3] == 0 {
+				pixel[3] == 0
+			} else {
+				pixel
+					.0
+					.iter()
+					.zip(corner.0)
+					.all(|(a, b)| a.abs_diff(b) <= TRIM_TOLERANCE)
+			}
+		};
+		let (mut start, mut end) = ((width, height), (0, 0));
+		for (x, y, pixel) in image.enumerate_pixels() {
+			if !is_border(pixel) {
+				start = (start.0.min(x), start.1.min(y));
+				end = (end.0.max(x), end.1.max(y));
+			}
+		}
+		if start.0 > end.0 {
+			return Geometry::new(0, 0, width, height);
+		}
+		Geometry::new(
+			start.0 as i32,
+			start.1 as i32,
+			end.0 - start.0 + 1,
+			end.1 - start.1 + 1,
+		)
+	}
+
 	/* Flip the image */
 	fn flip(&mut self) -> &mut Self {
 		match self.settings.image.flip {
@@ -161,6 +459,30 @@ impl<'a> ImageOps<'a> {
 		self
 	}
 
+	/* Shear the image and warp its perspective */
+	fn transform(&mut self) -> &mut Self {
+		let shear = self.settings.image.shear;
+		if shear != (0., 0.) {
+			info!(
+				"Shearing the image... ({}\u{00B0}, {}\u{00B0})",
+				shear.0, shear.1
+			);
+			if let Some(image) = Transform::shear(shear).warp(&self.image) {
+				self.image = image;
+			}
+		}
+		if let Some(points) = self.settings.image.perspective {
+			info!("Warping the perspective of the image... ({:?})", points);
+			match Transform::perspective(self.image.dimensions(), points)
+				.and_then(|transform| transform.warp(&self.image))
+			{
+				Some(image) => self.image = image,
+				None => warn!("Invalid perspective points: {:?}", points),
+			}
+		}
+		self
+	}
+
 	/* Blur the image */
 	fn blur(&mut self) -> &mut Self {
 		if self.settings.image.blur > 0. {
@@ -173,6 +495,71 @@ impl<'a> ImageOps<'a> {
 		self
 	}
 
+	/* Reduce the noise in the image and between the consecutive frames */
+	fn denoise(&mut self) -> &mut Self {
+		if self.settings.image.denoise == 0 {
+			return self;
+		}
+		let threshold =
+			u32::from(self.settings.image.denoise) * MAX_NOISE_THRESHOLD / 100;
+		debug!("Reducing the noise... (threshold: {})", threshold);
+		let (width, height) = self.image.dimensions();
+		let image = &self.image;
+		let mut denoised = RgbaImage::from_fn(width, height, |x, y| {
+			let center = image.get_pixel(x, y);
+			let (mut sum, mut count) = ([0_u32; 3], 0);
+			for ny in y.saturating_sub(1)..(y + 2).min(height) {
+				for nx in x.saturating_sub(1)..(x + 2).min(width) {
+					let pixel = image.get_pixel(nx, ny);
+					if (0..3).all(|i| {
+						u32::from(pixel[i].abs_diff(center[i])) <= threshold
+					}) {
+						(0..3).for_each(|i| sum[i] += u32::from(pixel[i]));
+						count += 1;
+					}
+				}
+			}
+			Rgba([
+				((sum[0] + count / 2) / count) as u8,
+				((sum[1] + count / 2) / count) as u8,
+				((sum[2] + count / 2) / count) as u8,
+				center[3],
+			])
+		});
+		if let Some(previous) = self
+			.previous
+			.as_ref()
+			.filter(|previous| previous.dimensions() == (width, height))
+		{
+			for (pixel, previous) in denoised.pixels_mut().zip(previous.pixels()) {
+				if (0..4).all(|i| {
+					u32::from(pixel[i].abs_diff(previous[i])) <= threshold / 2
+				}) {
+					*pixel = *previous;
+				}
+			}
+		}
+		self.previous = Some(denoised.clone());
+		self.image = denoised;
+		self
+	}
+
+	/* Sharpen the image with unsharp masking */
+	fn sharpen(&mut self) -> &mut Self {
+		if self.settings.image.sharpen > 0. {
+			info!(
+				"Sharpening the image... (\u{03C3}={})",
+				self.settings.image.sharpen
+			);
+			self.image = imageops::unsharpen(
+				&self.image,
+				self.settings.image.sharpen,
+				SHARPEN_THRESHOLD,
+			);
+		}
+		self
+	}
+
 	/* Update the colors of the image */
 	fn update_colors(&mut self) -> &mut Self {
 		if format!("{:?}", self.settings.color)
@@ -181,12 +568,22 @@ impl<'a> ImageOps<'a> {
 			info!("Updating the colors...");
 		}
 		if self.settings.color.grayscale {
-			self.image = DynamicImage::ImageLuma8(colorops::grayscale(&self.image))
-				.to_rgba8();
+			self.image =
+				DynamicImage::ImageLumaA8(colorops::grayscale_alpha(&self.image))
+					.to_rgba8();
 		}
 		if self.settings.color.invert {
 			colorops::invert(&mut self.image);
 		}
+		if self.settings.color.auto_levels {
+			self.auto_levels();
+		}
+		if self.settings.color.equalize {
+			self.equalize();
+		}
+		if self.settings.color.gamma != 1. {
+			self.adjust_gamma();
+		}
 		if self.settings.color.brightness != 0 {
 			self.image =
 				colorops::brighten(&self.image, self.settings.color.brightness);
@@ -198,17 +595,377 @@ impl<'a> ImageOps<'a> {
 			self.image =
 				colorops::contrast(&self.image, self.settings.color.contrast);
 		}
+		if self.settings.color.saturation != 0 {
+			self.saturate();
+		}
+		if self.settings.color.posterize >= 2 {
+			self.posterize();
+		}
+		self
+	}
+
+	/* Grade the colors using the lookup table */
+	fn apply_lut(&mut self) -> &mut Self {
+		if let Some(lut) = &self.lut {
+			debug!("Applying the LUT: {:?}", self.settings.lut);
+			self.image.pixels_mut().for_each(|pixel| lut.apply(pixel));
+		}
+		self
+	}
+
+	/* Use the brightness of the mask as the alpha channel */
+	fn apply_mask(&mut self) -> &mut Self {
+		let Some(mask) = &mut self.mask else {
+			return self;
+		};
+		let (width, height) = self.image.dimensions();
+		if mask.dimensions() != (width, height) {
+			info!("Resizing the mask... ({}x{})", width, height);
+			*mask =
+				imageops::resize(mask, width, height, self.settings.image.filter);
+		}
+		for (pixel, value) in self.image.pixels_mut().zip(mask.pixels()) {
+			pixel[3] = ((u16::from(pixel[3]) * u16::from(value[0])) / 255) as u8;
+		}
+		self
+	}
+
+	/* Darken the corners of the image */
+	fn vignette(&mut self) -> &mut Self {
+		if self.settings.image.vignette == 0 {
+			return self;
+		}
+		info!(
+			"Applying the vignette... ({}%)",
+			self.settings.image.vignette
+		);
+		let strength = f32::from(self.settings.image.vignette) / 100.;
+		let (width, height) = self.image.dimensions();
+		let (center_x, center_y) =
+			((width as f32 - 1.) / 2., (height as f32 - 1.) / 2.);
+		for (x, y, pixel) in self.image.enumerate_pixels_mut() {
+			let distance = ((x as f32 - center_x) / center_x.max(1.))
+				.hypot((y as f32 - center_y) / center_y.max(1.))
+				/ std::f32::consts::SQRT_2;
+			let factor = (1. - strength * distance.powi(2)).max(0.);
+			for value in pixel.0.iter_mut().take(3) {
+				*value = (f32::from(*value) * factor).round() as u8;
+			}
+		}
+		self
+	}
+
+	/* Replace the colors that match within the tolerance */
+	fn replace_colors(&mut self) -> &mut Self {
+		if !self.settings.replace.is_empty() {
+			info!("Replacing the colors... ({})", self.settings.replace.len());
+			for pixel in self.image.pixels_mut() {
+				if let Some(replacement) = self
+					.settings
+					.replace
+					.iter()
+					.find(|replacement| replacement.matches(pixel))
+				{
+					replacement.apply(pixel);
+				}
+			}
+		}
+		self
+	}
+
+	/* Draw the watermark over the image */
+	fn draw_watermark(&mut self) -> &mut Self {
+		if let Some(watermark) = &self.watermark {
+			let (x, y) = self.settings.watermark.position.get_offset(
+				self.image.dimensions(),
+				watermark.dimensions(),
+				self.settings.watermark.margin,
+			);
+			imageops::overlay(&mut self.image, watermark, x, y);
+		}
+		self
+	}
+
+	/* Draw the shape annotations over the image */
+	fn draw_shapes(&mut self) -> &mut Self {
+		if !self.settings.shapes.is_empty() {
+			info!("Drawing the shapes... ({})", self.settings.shapes.len());
+			for shape in &self.settings.shapes {
+				shape.draw(&mut self.image);
+			}
+		}
+		self
+	}
+
+	/* Draw the text over the image */
+	fn draw_text(&mut self) -> &mut Self {
+		if let Some(text) = &self.text {
+			debug!("Drawing the text: {:?}", self.settings.text.text);
+			text.draw(&mut self.image);
+		}
+		self
+	}
+
+	/* Add the padding and draw the border around the image */
+	fn draw_border(&mut self) -> &mut Self {
+		let border = self.settings.border;
+		if border.is_enabled() {
+			info!(
+				"Adding the border... ({}px, {})",
+				border.width, border.padding
+			);
+			let (width, height) = border.get_size(self.image.dimensions());
+			let mut image = RgbaImage::from_pixel(width, height, border.color);
+			imageops::replace(
+				&mut image,
+				&RgbaImage::new(width - border.width * 2, height - border.width * 2),
+				i64::from(border.width),
+				i64::from(border.width),
+			);
+			imageops::replace(
+				&mut image,
+				&self.image,
+				i64::from(border.width + border.padding.left),
+				i64::from(border.width + border.padding.top),
+			);
+			self.image = image;
+		}
+		self
+	}
+
+	/* Put the image on a background with rounded corners and a shadow */
+	fn beautify(&mut self) -> &mut Self {
+		let beautify = &self.settings.beautify;
+		if beautify.enabled {
+			info!(
+				"Beautifying the image... (r={}, \u{03C3}={})",
+				beautify.radius, beautify.shadow
+			);
+			Self::round_corners(&mut self.image, beautify.radius);
+			let (width, height) = self.image.dimensions();
+			let size = (width + beautify.margin * 2, height + beautify.margin * 2);
+			let margin = i64::from(beautify.margin);
+			let mut canvas = beautify.background.get_image(size);
+			if beautify.shadow > 0. {
+				let mut shadow = RgbaImage::new(size.0, size.1);
+				imageops::replace(
+					&mut shadow,
+					&RgbaImage::from_fn(width, height, |x, y| {
+						let alpha = f32::from(self.image.get_pixel(x, y)[3]);
+						Rgba([0, 0, 0, (alpha * SHADOW_OPACITY) as u8])
+					}),
+					margin,
+					margin + (beautify.shadow / 2.) as i64,
+				);
+				imageops::overlay(
+					&mut canvas,
+					&imageops::blur(&shadow, beautify.shadow),
+					0,
+					0,
+				);
+			}
+			imageops::overlay(&mut canvas, &self.image, margin, margin);
+			self.image = canvas;
+		}
+		self
+	}
+
+	/* Flatten the image onto the background */
+	fn flatten(&mut self) -> &mut Self {
+		if let Some(background) = &self.settings.background {
+			info!("Flattening the image onto the background...");
+			let mut canvas = background.get_image(self.image.dimensions());
+			imageops::overlay(&mut canvas, &self.image, 0, 0);
+			self.image = canvas;
+		}
+		self
+	}
+
+	/* Reduce the colors with a palette that is shared between the frames */
+	fn quantize(&mut self) -> &mut Self {
+		let colors = self.settings.color.colors;
+		if colors >= 2 {
+			let palette = self.palette.get_or_insert_with(|| {
+				info!("Quantizing the colors... ({})", colors);
+				NeuQuant::new(10, colors, &self.image)
+					.color_map_rgba()
+					.chunks_exact(4)
+					.map(|color| Rgba([color[0], color[1], color[2], color[3]]))
+					.collect()
+			});
+			let mut indices = HashMap::new();
+			for pixel in self.image.pixels_mut() {
+				*pixel = *indices.entry(*pixel).or_insert_with(|| {
+					palette
+						.iter()
+						.min_by_key(|color| {
+							color
+								.0
+								.iter()
+								.zip(pixel.0)
+								.map(|(c, p)| (i32::from(*c) - i32::from(p)).pow(2))
+								.sum::<i32>()
+						})
+						.copied()
+						.unwrap_or(*pixel)
+				});
+			}
+		}
 		self
 	}
+
+	/**
+	 * Make the corners of the image transparent with the given radius.
+	 *
+	 * @param image
+	 * @param radius
+	 */
+	fn round_corners(image: &mut RgbaImage, radius: u32) {
+		let (width, height) = image.dimensions();
+		let radius = radius.min(width / 2).min(height / 2) as f32;
+		let distance = |value: u32, size: u32| {
+			(radius - value as f32 - 0.5)
+				.max(value as f32 + 0.5 - (size as f32 - radius))
+				.max(0.)
+		};
+		for (x, y, pixel) in image.enumerate_pixels_mut() {
+			let (dx, dy) = (distance(x, width), distance(y, height));
+			if dx > 0. && dy > 0. {
+				let coverage = (radius - dx.hypot(dy) + 0.5).clamp(0., 1.);
+				pixel[3] = (f32::from(pixel[3]) * coverage) as u8;
+			}
+		}
+	}
+
+	/* Adjust the gamma of the color channels in linear light */
+	fn adjust_gamma(&mut self) {
+		let exponent = 1. / self.settings.color.gamma;
+		let table: [u8; 256] = std::array::from_fn(|value| {
+			let value = value as f32 / 255.;
+			let linear = if value <= 0.04045 {
+				value / 12.92
+			} else {
+				((value + 0.055) / 1.055).powf(2.4)
+			}
+			.powf(exponent);
+			let value = if linear <= 0.003_130_8 {
+				linear * 12.92
+			} else {
+				1.055 * linear.powf(1. / 2.4) - 0.055
+			};
+			(value * 255.).round().clamp(0., 255.) as u8
+		});
+		self.map_levels(&table);
+	}
+
+	/* Get the histogram of the color channels of the visible pixels */
+	fn get_histogram(&self) -> [u64; 256] {
+		let mut histogram = [0; 256];
+		for pixel in self.image.pixels().filter(|pixel| pixel[3] != 0) {
+			for value in pixel.0.iter().take(3) {
+				histogram[usize::from(*value)] += 1;
+			}
+		}
+		histogram
+	}
+
+	/* Map the values of the color channels using the lookup table */
+	fn map_levels(&mut self, table: &[u8; 256]) {
+		for pixel in self.image.pixels_mut() {
+			for value in pixel.0.iter_mut().take(3) {
+				*value = table[usize::from(*value)];
+			}
+		}
+	}
+
+	/* Stretch the levels of the colors to the full range */
+	fn auto_levels(&mut self) {
+		let histogram = self.get_histogram();
+		let clip = (histogram.iter().sum::<u64>() as f64 * AUTO_LEVELS_CLIP) as u64;
+		let get_level = |mut levels: Box<dyn Iterator<Item = usize>>| {
+			let mut count = 0;
+			levels
+				.find(|level| {
+					count += histogram[*level];
+					count > clip
+				})
+				.unwrap_or_default() as f32
+		};
+		let (low, high) = (
+			get_level(Box::new(0..256)),
+			get_level(Box::new((0..256).rev())),
+		);
+		if high <= low {
+			return;
+		}
+		debug!("Stretching the levels from {}-{}", low, high);
+		self.map_levels(&std::array::from_fn(|value| {
+			((value as f32 - low) / (high - low) * 255.)
+				.round()
+				.clamp(0., 255.) as u8
+		}));
+	}
+
+	/* Spread the levels of the colors evenly by equalizing the histogram */
+	fn equalize(&mut self) {
+		let mut histogram = self.get_histogram();
+		for i in 1..histogram.len() {
+			histogram[i] += histogram[i - 1];
+		}
+		let total = histogram[255];
+		let min = histogram.iter().copied().find(|count| *count != 0);
+		let Some(min) = min.filter(|min| *min != total) else {
+			return;
+		};
+		self.map_levels(&std::array::from_fn(|value| {
+			((histogram[value].saturating_sub(min)) as f64 / (total - min) as f64
+				* 255.)
+				.round() as u8
+		}));
+	}
+
+	/* Reduce the number of levels in each color channel */
+	fn posterize(&mut self) {
+		let levels = f32::from(self.settings.color.posterize - 1);
+		for pixel in self.image.pixels_mut() {
+			for value in pixel.0.iter_mut().take(3) {
+				*value = ((f32::from(*value) / 255. * levels).round() / levels
+					* 255.)
+					.round() as u8;
+			}
+		}
+	}
+
+	/* Adjust the saturation by mixing the colors with their luminance */
+	fn saturate(&mut self) {
+		let factor = 1. + self.settings.color.saturation as f32 / 100.;
+		for pixel in self.image.pixels_mut() {
+			let [red, green, blue, _] = &mut pixel.0;
+			let luma = 0.2126 * f32::from(*red)
+				+ 0.7152 * f32::from(*green)
+				+ 0.0722 * f32::from(*blue);
+			for value in [red, green, blue] {
+				*value = (luma + (f32::from(*value) - luma) * factor)
+					.round()
+					.clamp(0., 255.) as u8;
+			}
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::edit::settings::{
+		BeautifySettings, BorderSettings, ColorReplacement, CropSettings,
+		OverlayPosition, WatermarkSettings,
+	};
+	use crate::image::background::Background;
+	use crate::image::padding::Padding;
 	use image::{ExtendedColorType, Rgba, RgbaImage};
 	use pretty_assertions::assert_eq;
 	#[test]
-	fn test_edit() {
+	fn test_edit() -> AppResult<()> {
 		let mut image = RgbaImage::new(32, 32);
 		for x in 15..=17 {
 			for y in 8..24 {
@@ -217,28 +974,418 @@ mod tests {
 			}
 		}
 		let mut settings = EditSettings::default();
-		settings.image.crop.top = 10;
+		settings.image.crop.padding.top = 10;
 		settings.image.ratio = 2.;
 		settings.image.resize = Geometry::new(0, 0, 32, 42);
 		settings.image.flip = Some(Flip::Vertical);
 		settings.image.rotate = 270;
 		settings.image.blur = 1.5;
+		settings.image.sharpen = 0.5;
 		settings.color.grayscale = true;
 		settings.color.invert = true;
 		settings.color.brightness = -2;
 		settings.color.hue = 15;
 		settings.color.contrast = -5.;
 		let mut imageops = ImageOps::new(&settings);
-		let image = imageops.init(image.dimensions()).process(image).get_image();
+		let image = imageops
+			.init(image.dimensions())?
+			.process(image)
+			.get_image();
 		let (width, height) = imageops.image.dimensions();
 		assert_eq!(
 			format!("{image:?}"),
-			"Image { data_len: 4536, alpha_channel: true, \
-			geometry: Geometry { x: 0, y: 10, width: 84, height: 54 } }"
+			"Image { data_len: 5376, alpha_channel: true, \
+			geometry: Geometry { x: 0, y: 10, width: 84, height: 64 }, \
+			timestamp: None, delay: None }"
 		);
 		assert_eq!(
 			width * height * 4,
-			image.get_data(ExtendedColorType::Rgba8).len() as u32
+			image.get_data(ExtendedColorType::Rgba8)?.len() as u32
+		);
+		let mut settings = EditSettings::default();
+		settings.image.crop = CropSettings::parse("20x20+4+8", "16:9");
+		let mut imageops = ImageOps::new(&settings);
+		let image = imageops
+			.init((32, 32))?
+			.process(RgbaImage::from_fn(32, 32, |x, y| {
+				Rgba([x as u8, y as u8, 0, 255])
+			}))
+			.get_image();
+		assert_eq!(Geometry::new(0, 0, 20, 11), image.geometry);
+		assert_eq!(
+			Some(&Rgba([4, 12, 0, 255])),
+			imageops.image.get_pixel_checked(0, 0)
+		);
+		let mut settings = EditSettings {
+			ops: vec![EditOp::Resize, EditOp::Crop],
+			..EditSettings::default()
+		};
+		settings.image.resize = Geometry::new(0, 0, 16, 16);
+		settings.image.crop = CropSettings::parse("8x8+4+4", "");
+		let mut imageops = ImageOps::new(&settings);
+		let image = imageops
+			.init((32, 32))?
+			.process(RgbaImage::from_fn(32, 32, |x, y| {
+				Rgba([x as u8, y as u8, 0, 255])
+			}))
+			.get_image();
+		assert_eq!(Geometry::new(0, 0, 8, 8), image.geometry);
+		assert_eq!((8, 8), imageops.image.dimensions());
+		assert_eq!(
+			Some(&Rgba([9, 8, 0, 255])),
+			imageops.image.get_pixel_checked(0, 0)
+		);
+		let mut settings = EditSettings::default();
+		settings.image.crop = CropSettings::parse("100x100+50+50", "");
+		assert!(ImageOps::new(&settings).init((40, 30)).is_err());
+		settings.image.crop = CropSettings::parse("100x100+20+10", "");
+		let mut imageops = ImageOps::new(&settings);
+		let image = imageops
+			.init((40, 30))?
+			.process(RgbaImage::new(40, 30))
+			.get_image();
+		assert_eq!(Geometry::new(0, 0, 20, 20), image.geometry);
+		let mut settings = EditSettings::default();
+		settings.color.saturation = -100;
+		let mut imageops = ImageOps::new(&settings);
+		imageops.init((1, 1))?.process(RgbaImage::from_pixel(
+			1,
+			1,
+			Rgba([255, 0, 0, 255]),
+		));
+		assert_eq!(
+			Some(&Rgba([54, 54, 54, 255])),
+			imageops.image.get_pixel_checked(0, 0)
+		);
+		settings.color.saturation = 50;
+		let mut imageops = ImageOps::new(&settings);
+		imageops.init((1, 1))?.process(RgbaImage::from_pixel(
+			1,
+			1,
+			Rgba([200, 100, 100, 255]),
+		));
+		assert_eq!(
+			Some(&Rgba([239, 89, 89, 255])),
+			imageops.image.get_pixel_checked(0, 0)
+		);
+		settings.color.saturation = 0;
+		settings.color.gamma = 2.2;
+		let mut imageops = ImageOps::new(&settings);
+		imageops
+			.init((3, 1))?
+			.process(RgbaImage::from_fn(3, 1, |x, _| {
+				Rgba([(x * 64) as u8, 255, 0, 100])
+			}));
+		assert_eq!(
+			vec![
+				&Rgba([0, 255, 0, 100]),
+				&Rgba([139, 255, 0, 100]),
+				&Rgba([187, 255, 0, 100])
+			],
+			imageops.image.pixels().collect::<Vec<&Rgba<u8>>>()
+		);
+		settings.color.gamma = 1.;
+		settings.color.posterize = 3;
+		let mut imageops = ImageOps::new(&settings);
+		imageops.init((1, 1))?.process(RgbaImage::from_pixel(
+			1,
+			1,
+			Rgba([50, 100, 200, 10]),
+		));
+		assert_eq!(Rgba([0, 128, 255, 10]), *imageops.image.get_pixel(0, 0));
+		settings.color.posterize = 0;
+		settings.color.auto_levels = true;
+		let mut imageops = ImageOps::new(&settings);
+		imageops
+			.init((3, 1))?
+			.process(RgbaImage::from_fn(3, 1, |x, _| {
+				let value = 100 + x as u8 * 25;
+				Rgba([value, value, value, 255])
+			}));
+		assert_eq!(
+			vec![0, 128, 255],
+			imageops.image.pixels().map(|p| p[0]).collect::<Vec<u8>>()
+		);
+		settings.color.auto_levels = false;
+		settings.color.equalize = true;
+		let mut imageops = ImageOps::new(&settings);
+		imageops
+			.init((4, 1))?
+			.process(RgbaImage::from_fn(4, 1, |x, _| {
+				let value = [10, 10, 20, 200][x as usize];
+				Rgba([value, value, value, 255])
+			}));
+		assert_eq!(
+			vec![0, 0, 128, 255],
+			imageops.image.pixels().map(|p| p[0]).collect::<Vec<u8>>()
+		);
+		settings.color.equalize = false;
+		settings.color.colors = 2;
+		let mut imageops = ImageOps::new(&settings);
+		imageops
+			.init((4, 1))?
+			.process(RgbaImage::from_fn(4, 1, |x, _| {
+				Rgba([if x < 2 { 0 } else { 255 }, 0, x as u8, 255])
+			}));
+		let palette = imageops.palette.clone();
+		assert_eq!(Some(2), palette.as_ref().map(Vec::len));
+		assert_eq!(
+			imageops.image.get_pixel(0, 0),
+			imageops.image.get_pixel(1, 0)
+		);
+		assert_eq!(
+			imageops.image.get_pixel(2, 0),
+			imageops.image.get_pixel(3, 0)
+		);
+		imageops.process(RgbaImage::from_pixel(4, 1, Rgba([250, 10, 10, 255])));
+		assert_eq!(palette, imageops.palette);
+		assert!(palette
+			.unwrap_or_default()
+			.contains(imageops.image.get_pixel(0, 0)));
+		let mut settings = EditSettings::default();
+		settings.image.vignette = 50;
+		let mut imageops = ImageOps::new(&settings);
+		imageops.init((5, 5))?.process(RgbaImage::from_pixel(
+			5,
+			5,
+			Rgba([200, 100, 50, 255]),
+		));
+		assert_eq!(Rgba([200, 100, 50, 255]), *imageops.image.get_pixel(2, 2));
+		assert_eq!(Rgba([100, 50, 25, 255]), *imageops.image.get_pixel(4, 0));
+		assert_eq!(Rgba([150, 75, 38, 255]), *imageops.image.get_pixel(2, 4));
+		settings.image.vignette = 0;
+		settings.image.denoise = 50;
+		let noise = [100, 110, 90, 104, 100, 96, 92, 108, 250];
+		let mut imageops = ImageOps::new(&settings);
+		imageops
+			.init((3, 3))?
+			.process(RgbaImage::from_fn(3, 3, |x, y| {
+				let value = noise[(y * 3 + x) as usize];
+				Rgba([value, value, value, 255])
+			}));
+		let denoised = imageops.image.clone();
+		assert_eq!(Rgba([100, 100, 100, 255]), *denoised.get_pixel(1, 1));
+		assert_eq!(Rgba([250, 250, 250, 255]), *denoised.get_pixel(2, 2));
+		imageops.process(RgbaImage::from_fn(3, 3, |x, y| {
+			let value = noise[(y * 3 + x) as usize] + 5;
+			Rgba([value, value, value, 255])
+		}));
+		assert_eq!(denoised, imageops.image);
+		let path = std::env::temp_dir().join("menyoki_watermark.png");
+		RgbaImage::from_pixel(2, 2, Rgba([0, 0, 255, 255])).save(&path)?;
+		let settings = EditSettings {
+			watermark: WatermarkSettings::new(
+				Some(path.clone()),
+				OverlayPosition::BottomRight,
+				0.5,
+				1,
+			),
+			..EditSettings::default()
+		};
+		let mut imageops = ImageOps::new(&settings);
+		imageops.init((4, 4))?.process(RgbaImage::from_pixel(
+			4,
+			4,
+			Rgba([255, 0, 0, 255]),
+		));
+		std::fs::remove_file(path)?;
+		assert_eq!(
+			Some(&Rgba([255, 0, 0, 255])),
+			imageops.image.get_pixel_checked(0, 0)
+		);
+		assert_eq!(
+			Some(&Rgba([127, 0, 127, 255])),
+			imageops.image.get_pixel_checked(2, 2)
+		);
+		assert_eq!(
+			Some(&Rgba([255, 0, 0, 255])),
+			imageops.image.get_pixel_checked(3, 3)
+		);
+		let settings = EditSettings {
+			border: BorderSettings::new(
+				1,
+				Rgba([0, 255, 0, 255]),
+				Padding::parse("2:0:0:1"),
+			),
+			..EditSettings::default()
+		};
+		let mut imageops = ImageOps::new(&settings);
+		let image = imageops
+			.init((2, 2))?
+			.process(RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255])))
+			.get_image();
+		assert_eq!(Geometry::new(0, 0, 5, 6), image.geometry);
+		assert_eq!(
+			Some(&Rgba([0, 255, 0, 255])),
+			imageops.image.get_pixel_checked(4, 5)
+		);
+		assert_eq!(
+			Some(&Rgba([0, 0, 0, 0])),
+			imageops.image.get_pixel_checked(1, 1)
+		);
+		assert_eq!(
+			Some(&Rgba([255, 0, 0, 255])),
+			imageops.image.get_pixel_checked(2, 3)
+		);
+		let settings = EditSettings {
+			beautify: BeautifySettings::new(
+				true,
+				4,
+				2,
+				0.,
+				Background::Solid(Rgba([0, 0, 255, 255])),
+			),
+			..EditSettings::default()
+		};
+		let mut imageops = ImageOps::new(&settings);
+		let image = imageops
+			.init((4, 4))?
+			.process(RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255])))
+			.get_image();
+		assert_eq!(Geometry::new(0, 0, 12, 12), image.geometry);
+		assert_eq!(
+			Some(&Rgba([0, 0, 255, 255])),
+			imageops.image.get_pixel_checked(3, 3)
+		);
+		assert_eq!(
+			Some(&Rgba([96, 0, 159, 255])),
+			imageops.image.get_pixel_checked(4, 4)
+		);
+		assert_eq!(
+			Some(&Rgba([255, 0, 0, 255])),
+			imageops.image.get_pixel_checked(5, 5)
+		);
+		let settings = EditSettings {
+			replace: vec![
+				ColorReplacement::parse("FF0000:00FF00").unwrap(),
+				ColorReplacement::parse("00FF00:0000FF").unwrap(),
+			],
+			..EditSettings::default()
+		};
+		let mut imageops = ImageOps::new(&settings);
+		imageops
+			.init((2, 1))?
+			.process(RgbaImage::from_fn(2, 1, |x, _| {
+				Rgba([255 * (1 - x as u8), 255 * x as u8, 0, 255])
+			}));
+		assert_eq!(
+			vec![&Rgba([0, 255, 0, 255]), &Rgba([0, 0, 255, 255])],
+			imageops.image.pixels().collect::<Vec<&Rgba<u8>>>()
+		);
+		let settings = EditSettings {
+			redact: vec![
+				Redaction::parse_pixelate("1:0:3:2:2").unwrap(),
+				Redaction::parse_blur("0:3:4:1").unwrap(),
+			],
+			..EditSettings::default()
+		};
+		let mut imageops = ImageOps::new(&settings);
+		imageops
+			.init((4, 4))?
+			.process(RgbaImage::from_fn(4, 4, |x, y| {
+				Rgba([(x * 10) as u8, (y * 10) as u8, 0, 255])
+			}));
+		assert_eq!(
+			vec![0, 15, 15, 30],
+			imageops
+				.image
+				.rows()
+				.next()
+				.unwrap()
+				.map(|p| p[0])
+				.collect::<Vec<u8>>()
+		);
+		assert_eq!(Rgba([15, 5, 0, 255]), *imageops.image.get_pixel(1, 1));
+		assert_eq!(Rgba([30, 20, 0, 255]), *imageops.image.get_pixel(3, 2));
+		assert_eq!(30, imageops.image.get_pixel(0, 3)[1]);
+		let settings = EditSettings {
+			background: Some(Background::Solid(Rgba([0, 255, 0, 255]))),
+			..EditSettings::default()
+		};
+		let mut imageops = ImageOps::new(&settings);
+		imageops.init((1, 1))?.process(RgbaImage::from_pixel(
+			1,
+			1,
+			Rgba([255, 0, 0, 0]),
+		));
+		assert_eq!(
+			Some(&Rgba([0, 255, 0, 255])),
+			imageops.image.get_pixel_checked(0, 0)
+		);
+		let mut settings = EditSettings::default();
+		settings.color.grayscale = true;
+		settings.color.invert = true;
+		let mut imageops = ImageOps::new(&settings);
+		imageops.init((1, 1))?.process(RgbaImage::from_pixel(
+			1,
+			1,
+			Rgba([255, 0, 0, 128]),
+		));
+		assert_eq!(
+			Some(&Rgba([201, 201, 201, 128])),
+			imageops.image.get_pixel_checked(0, 0)
+		);
+		assert_eq!(
+			(800, 450),
+			ImageOps::get_resize_dimensions((1920, 1080), Geometry::parse("800x"))
+		);
+		assert_eq!(
+			(200, 100),
+			ImageOps::get_resize_dimensions((400, 200), Geometry::new(0, 0, 0, 100))
+		);
+		assert_eq!(
+			(30, 40),
+			ImageOps::get_resize_dimensions((400, 200), Geometry::parse("30x40"))
+		);
+		let mut settings = EditSettings::default();
+		settings.image.trim = true;
+		let mut imageops = ImageOps::new(&settings);
+		let mut image = RgbaImage::from_pixel(6, 5, Rgba([255, 255, 255, 255]));
+		image.put_pixel(2, 1, Rgba([0, 0, 0, 255]));
+		image.put_pixel(3, 3, Rgba([250, 250, 250, 255]));
+		image.put_pixel(4, 2, Rgba([255, 0, 0, 255]));
+		let image = imageops.init((6, 5))?.process(image.clone()).get_image();
+		assert_eq!(Geometry::new(0, 0, 3, 2), image.geometry);
+		assert_eq!(Rgba([0, 0, 0, 255]), *imageops.image.get_pixel(0, 0));
+		assert_eq!(Rgba([255, 0, 0, 255]), *imageops.image.get_pixel(2, 1));
+		imageops.process(RgbaImage::new(6, 5));
+		assert_eq!((3, 2), imageops.image.dimensions());
+		let mut image = RgbaImage::new(4, 4);
+		image.put_pixel(1, 2, Rgba([10, 20, 30, 40]));
+		assert_eq!(Geometry::new(1, 2, 1, 1), ImageOps::get_trim_area(&image));
+		assert_eq!(
+			Geometry::new(0, 0, 4, 4),
+			ImageOps::get_trim_area(&RgbaImage::new(4, 4))
+		);
+		let mut settings = EditSettings::default();
+		settings.image.shear = (45., 0.);
+		settings.image.perspective = Some([(0., 0.), (7., 0.), (7., 6.), (0., 6.)]);
+		let mut imageops = ImageOps::new(&settings);
+		let image = imageops
+			.init((4, 3))?
+			.process(RgbaImage::from_pixel(4, 3, Rgba([255, 0, 0, 255])))
+			.get_image();
+		assert_eq!(Geometry::new(0, 0, 7, 6), imageops.geometry);
+		assert_eq!((7, 6), imageops.image.dimensions());
+		assert_eq!(image.get_data(ExtendedColorType::Rgba8)?.len(), 7 * 6 * 4);
+		let settings = EditSettings::default();
+		let mut imageops = ImageOps::new(&settings);
+		imageops.mask = Some(GrayImage::from_fn(3, 1, |x, _| {
+			Luma([[0, 128, 255][x as usize]])
+		}));
+		imageops.init((3, 1))?.process(RgbaImage::from_pixel(
+			3,
+			1,
+			Rgba([10, 20, 30, 200]),
+		));
+		assert_eq!(
+			vec![0, 100, 200],
+			imageops.image.pixels().map(|p| p[3]).collect::<Vec<u8>>()
 		);
+		imageops.mask = Some(GrayImage::from_pixel(2, 2, Luma([51])));
+		imageops.process(RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255])));
+		assert_eq!(Some((4, 4)), imageops.mask.as_ref().map(|m| m.dimensions()));
+		assert!(imageops.image.pixels().all(|p| p[3] == 51));
+		Ok(())
 	}
 }
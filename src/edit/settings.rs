@@ -1,20 +1,134 @@
 use crate::args::matches::ArgMatches;
 use crate::args::parser::ArgParser;
+use crate::edit::shape::Shape;
+use crate::edit::text::parse_color;
+use crate::edit::transform::Transform;
 use crate::edit::ImageOps;
+use crate::image::background::Background;
 use crate::image::geometry::Geometry;
 use crate::image::padding::Padding;
 use image::imageops::FilterType;
+use image::Rgba;
 use std::path::PathBuf;
 
+/* Maximum number of colors to quantize the image to */
+pub const MAX_COLORS: usize = 256;
+
+/**
+ * Parse the argument value and warn if it is invalid.
+ *
+ * @param  value
+ * @param  parse
+ * @param  name
+ * @return T (Option)
+ */
+fn parse_or_warn<T>(
+	value: &str,
+	parse: impl Fn(&str) -> Option<T>,
+	name: &str,
+) -> Option<T> {
+	let parsed = parse(value);
+	if parsed.is_none() {
+		warn!("Invalid {}: {:?}", name, value);
+	}
+	parsed
+}
+
+/* Crop settings */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CropSettings {
+	pub padding: Padding,
+	pub area: Option<Geometry>,
+	pub aspect: Option<(u32, u32)>,
+}
+
+impl CropSettings {
+	/**
+	 * Create a new CropSettings object.
+	 *
+	 * @param  padding
+	 * @param  area (Option)
+	 * @param  aspect (Option)
+	 * @return CropSettings
+	 */
+	pub fn new(
+		padding: Padding,
+		area: Option<Geometry>,
+		aspect: Option<(u32, u32)>,
+	) -> Self {
+		Self {
+			padding,
+			area,
+			aspect,
+		}
+	}
+
+	/**
+	 * Parse the crop settings from padding/area and aspect ratio values.
+	 *
+	 * @param  crop
+	 * @param  ratio
+	 * @return CropSettings
+	 */
+	pub fn parse(crop: &str, ratio: &str) -> Self {
+		let area = Geometry::parse_area(crop);
+		Self::new(
+			if area.is_some() {
+				Padding::default()
+			} else {
+				Padding::parse(crop)
+			},
+			area,
+			ratio.split_once(':').and_then(|(width, height)| {
+				Some((width.parse().ok()?, height.parse().ok()?))
+					.filter(|(width, height)| *width > 0 && *height > 0)
+			}),
+		)
+	}
+
+	/**
+	 * Get the area to crop before applying the padding.
+	 *
+	 * @param  size
+	 * @return Geometry (Option)
+	 */
+	pub fn get_area(&self, size: (u32, u32)) -> Option<Geometry> {
+		if self.area.is_none() && self.aspect.is_none() {
+			return None;
+		}
+		let mut area = Geometry::new(0, 0, size.0, size.1);
+		if let Some(crop_area) = self.area {
+			let x = u32::try_from(crop_area.x).unwrap_or_default().min(size.0);
+			let y = u32::try_from(crop_area.y).unwrap_or_default().min(size.1);
+			area = Geometry::new(
+				x as i32,
+				y as i32,
+				crop_area.width.min(size.0 - x),
+				crop_area.height.min(size.1 - y),
+			);
+		}
+		if let Some(aspect) = self.aspect {
+			area = area.with_aspect_ratio(aspect);
+		}
+		Some(area)
+	}
+}
+
 /* Image settings */
 #[derive(Clone, Copy, Debug)]
 pub struct ImageSettings {
-	pub crop: Padding,
+	pub crop: CropSettings,
 	pub resize: Geometry,
 	pub ratio: f32,
 	pub flip: Option<Flip>,
 	pub rotate: u32,
+	pub shear: (f64, f64),
+	pub perspective: Option<[(f64, f64); 4]>,
 	pub blur: f32,
+	pub sharpen: f32,
+	pub denoise: u8,
+	pub vignette: u8,
+	pub trim: bool,
 	pub filter: FilterType,
 }
 
@@ -22,12 +136,18 @@ pub struct ImageSettings {
 impl Default for ImageSettings {
 	fn default() -> Self {
 		Self {
-			crop: Padding::default(),
+			crop: CropSettings::default(),
 			resize: Geometry::default(),
 			ratio: 1.,
 			flip: None,
 			rotate: 0,
+			shear: (0., 0.),
+			perspective: None,
 			blur: 0.,
+			sharpen: 0.,
+			denoise: 0,
+			vignette: 0,
+			trim: false,
 			filter: FilterType::Lanczos3,
 		}
 	}
@@ -35,33 +155,47 @@ impl Default for ImageSettings {
 
 impl ImageSettings {
 	/**
-	 * Create a new ImageSettings object.
+	 * Create an ImageSettings object from an argument parser.
 	 *
-	 * @param  crop
-	 * @param  resize
-	 * @param  ratio
-	 * @param  flip (Option)
-	 * @param  rotate
-	 * @param  blur
-	 * @param  filter
+	 * @param  parser
+	 * @return ImageSettings
 	 */
-	pub fn new(
-		crop: Padding,
-		resize: Geometry,
-		ratio: f32,
-		flip: Option<Flip>,
-		rotate: u32,
-		blur: f32,
-		filter: FilterType,
-	) -> Self {
+	fn from_parser(parser: &ArgParser<'_>) -> Self {
+		let matches = parser.args.as_ref().expect("Invalid arguments");
 		Self {
-			crop,
-			resize,
-			ratio,
-			flip,
-			rotate,
-			blur,
-			filter,
+			crop: CropSettings::parse(
+				matches.value_of("crop").unwrap_or_default(),
+				matches.value_of("ratio").unwrap_or_default(),
+			),
+			resize: Geometry::parse(matches.value_of("resize").unwrap_or_default()),
+			ratio: parser.parse("ratio", Self::default().ratio),
+			flip: match matches.value_of("flip") {
+				Some("horizontal") => Some(Flip::Horizontal),
+				Some("vertical") => Some(Flip::Vertical),
+				_ => None,
+			},
+			rotate: parser.parse("rotate", Self::default().rotate),
+			shear: matches
+				.value_of("shear")
+				.and_then(|value| {
+					parse_or_warn(value, Transform::parse_shear, "shear")
+				})
+				.unwrap_or_default(),
+			perspective: matches.value_of("perspective").and_then(|value| {
+				parse_or_warn(value, Transform::parse_points, "perspective")
+			}),
+			blur: parser.parse("blur", Self::default().blur),
+			sharpen: parser.parse("sharpen", Self::default().sharpen),
+			denoise: parser.parse("denoise", Self::default().denoise).min(100),
+			vignette: parser.parse("vignette", Self::default().vignette).min(100),
+			trim: matches.is_present("trim"),
+			filter: match matches.value_of("filter") {
+				Some("nearest") => FilterType::Nearest,
+				Some("triangle") => FilterType::Triangle,
+				Some("catmull-rom") => FilterType::CatmullRom,
+				Some("gaussian") => FilterType::Gaussian,
+				_ => FilterType::Lanczos3,
+			},
 		}
 	}
 }
@@ -71,9 +205,15 @@ impl ImageSettings {
 pub struct ColorSettings {
 	pub grayscale: bool,
 	pub invert: bool,
+	pub auto_levels: bool,
+	pub equalize: bool,
 	pub hue: i32,
 	pub contrast: f32,
 	pub brightness: i32,
+	pub saturation: i32,
+	pub gamma: f32,
+	pub posterize: u8,
+	pub colors: usize,
 }
 
 /* Default initialization values for ColorSettings */
@@ -82,37 +222,487 @@ impl Default for ColorSettings {
 		Self {
 			grayscale: false,
 			invert: false,
+			auto_levels: false,
+			equalize: false,
 			hue: 0,
 			contrast: 0.,
 			brightness: 0,
+			saturation: 0,
+			gamma: 1.,
+			posterize: 0,
+			colors: 0,
 		}
 	}
 }
 
 impl ColorSettings {
 	/**
-	 * Create a new ColorSettings object.
+	 * Create a ColorSettings object from an argument parser.
 	 *
-	 * @param  grayscale
-	 * @param  invert
-	 * @param  hue
-	 * @param  contrast
-	 * @param  brightness
+	 * @param  parser
 	 * @return ColorSettings
 	 */
+	fn from_parser(parser: &ArgParser<'_>) -> Self {
+		let matches = parser.args.as_ref().expect("Invalid arguments");
+		Self {
+			grayscale: matches.is_present("grayscale"),
+			invert: matches.is_present("invert"),
+			auto_levels: matches.is_present("auto-levels"),
+			equalize: matches.is_present("equalize"),
+			hue: parser.parse("hue", Self::default().hue) % 360,
+			contrast: parser
+				.parse("contrast", Self::default().contrast)
+				.clamp(-100., 100.),
+			brightness: parser
+				.parse("brightness", Self::default().brightness)
+				.clamp(-255, 255),
+			saturation: parser
+				.parse("saturate", Self::default().saturation)
+				.clamp(-100, 100),
+			gamma: parser.parse("gamma", Self::default().gamma).clamp(0.1, 10.),
+			posterize: parser.parse("posterize", Self::default().posterize),
+			colors: parser
+				.parse("colors", Self::default().colors)
+				.min(MAX_COLORS),
+		}
+	}
+}
+
+/* Position of the overlays on the image */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverlayPosition {
+	TopLeft,
+	Top,
+	TopRight,
+	Center,
+	BottomLeft,
+	#[default]
+	Bottom,
+	BottomRight,
+}
+
+impl OverlayPosition {
+	/**
+	 * Parse the text position from the given value.
+	 *
+	 * @param  position
+	 * @return OverlayPosition
+	 */
+	pub fn parse(position: &str) -> Self {
+		match position {
+			"top-left" => Self::TopLeft,
+			"top" => Self::Top,
+			"top-right" => Self::TopRight,
+			"center" => Self::Center,
+			"bottom-left" => Self::BottomLeft,
+			"bottom-right" => Self::BottomRight,
+			_ => Self::Bottom,
+		}
+	}
+
+	/**
+	 * Get the offset of the overlay on the image.
+	 *
+	 * @param  size
+	 * @param  overlay_size
+	 * @param  margin
+	 * @return Tuple of i64
+	 */
+	pub fn get_offset(
+		&self,
+		size: (u32, u32),
+		overlay_size: (u32, u32),
+		margin: u32,
+	) -> (i64, i64) {
+		let margin = i64::from(margin);
+		let (width, height) = (i64::from(size.0), i64::from(size.1));
+		let (overlay_width, overlay_height) =
+			(i64::from(overlay_size.0), i64::from(overlay_size.1));
+		let (left, center, right) = (
+			margin,
+			(width - overlay_width) / 2,
+			width - overlay_width - margin,
+		);
+		let (top, middle, bottom) = (
+			margin,
+			(height - overlay_height) / 2,
+			height - overlay_height - margin,
+		);
+		match self {
+			Self::TopLeft => (left, top),
+			Self::Top => (center, top),
+			Self::TopRight => (right, top),
+			Self::Center => (center, middle),
+			Self::BottomLeft => (left, bottom),
+			Self::Bottom => (center, bottom),
+			Self::BottomRight => (right, bottom),
+		}
+	}
+}
+
+/* Text overlay settings */
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextSettings {
+	pub text: String,
+	pub font: Option<PathBuf>,
+	pub size: f32,
+	pub color: Rgba<u8>,
+	pub position: OverlayPosition,
+	pub margin: u32,
+}
+
+/* Default initialization values for TextSettings */
+impl Default for TextSettings {
+	fn default() -> Self {
+		Self {
+			text: String::new(),
+			font: None,
+			size: 24.,
+			color: Rgba([255, 255, 255, 255]),
+			position: OverlayPosition::default(),
+			margin: 10,
+		}
+	}
+}
+
+impl TextSettings {
+	/**
+	 * Create a new TextSettings object.
+	 *
+	 * @param  text
+	 * @param  font (Option)
+	 * @param  size
+	 * @param  color
+	 * @param  position
+	 * @param  margin
+	 * @return TextSettings
+	 */
+	pub fn new(
+		text: String,
+		font: Option<PathBuf>,
+		size: f32,
+		color: Rgba<u8>,
+		position: OverlayPosition,
+		margin: u32,
+	) -> Self {
+		Self {
+			text,
+			font,
+			size: size.max(1.),
+			color,
+			position,
+			margin,
+		}
+	}
+
+	/**
+	 * Check if there is any text to draw.
+	 *
+	 * @return bool
+	 */
+	pub fn is_enabled(&self) -> bool {
+		!self.text.trim().is_empty()
+	}
+}
+
+/* Watermark overlay settings */
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatermarkSettings {
+	pub path: Option<PathBuf>,
+	pub position: OverlayPosition,
+	pub opacity: f32,
+	pub margin: u32,
+}
+
+/* Default initialization values for WatermarkSettings */
+impl Default for WatermarkSettings {
+	fn default() -> Self {
+		Self {
+			path: None,
+			position: OverlayPosition::BottomRight,
+			opacity: 1.,
+			margin: 10,
+		}
+	}
+}
+
+impl WatermarkSettings {
+	/**
+	 * Create a new WatermarkSettings object.
+	 *
+	 * @param  path (Option)
+	 * @param  position
+	 * @param  opacity
+	 * @param  margin
+	 * @return WatermarkSettings
+	 */
+	pub fn new(
+		path: Option<PathBuf>,
+		position: OverlayPosition,
+		opacity: f32,
+		margin: u32,
+	) -> Self {
+		Self {
+			path,
+			position,
+			opacity: opacity.clamp(0., 1.),
+			margin,
+		}
+	}
+}
+
+/* Color replacement with a tolerance */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorReplacement {
+	pub from: Rgba<u8>,
+	pub to: Rgba<u8>,
+	pub tolerance: u8,
+}
+
+impl ColorReplacement {
+	/**
+	 * Create a new ColorReplacement object.
+	 *
+	 * @param  from
+	 * @param  to
+	 * @param  tolerance
+	 * @return ColorReplacement
+	 */
+	pub fn new(from: Rgba<u8>, to: Rgba<u8>, tolerance: u8) -> Self {
+		Self {
+			from,
+			to,
+			tolerance,
+		}
+	}
+
+	/**
+	 * Parse the color replacement from FROM:TO[:TOLERANCE].
+	 *
+	 * @param  replacement
+	 * @return ColorReplacement (Option)
+	 */
+	pub fn parse(replacement: &str) -> Option<Self> {
+		let mut values = replacement.split(':');
+		let replacement = Self::new(
+			parse_color(values.next()?)?,
+			parse_color(values.next()?)?,
+			match values.next() {
+				Some(tolerance) => tolerance.parse().ok()?,
+				None => 0,
+			},
+		);
+		values.next().is_none().then_some(replacement)
+	}
+
+	/**
+	 * Check if the pixel matches the color within the tolerance.
+	 *
+	 * @param  pixel
+	 * @return bool
+	 */
+	pub fn matches(&self, pixel: &Rgba<u8>) -> bool {
+		pixel.0[0..3]
+			.iter()
+			.zip(self.from.0)
+			.all(|(value, key)| value.abs_diff(key) <= self.tolerance)
+	}
+
+	/**
+	 * Replace the color of the pixel while keeping its transparency.
+	 *
+	 * @param pixel
+	 */
+	pub fn apply(&self, pixel: &mut Rgba<u8>) {
+		*pixel =
+			Rgba([self.to[0], self.to[1], self.to[2], pixel[3].min(self.to[3])]);
+	}
+}
+
+/* Region of the image to censor */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Redaction {
+	Pixelate(Geometry, u32),
+	Blur(Geometry, f32),
+}
+
+impl Redaction {
+	/**
+	 * Parse the region to pixelate from X:Y:W:H[:SIZE].
+	 *
+	 * @param  region
+	 * @return Redaction (Option)
+	 */
+	pub fn parse_pixelate(region: &str) -> Option<Self> {
+		let (area, size) = Self::parse_region(region)?;
+		Some(Self::Pixelate(
+			area,
+			size.map_or(Some(10), |size| size.parse().ok())?.max(1),
+		))
+	}
+
+	/**
+	 * Parse the region to blur from X:Y:W:H[:SIGMA].
+	 *
+	 * @param  region
+	 * @return Redaction (Option)
+	 */
+	pub fn parse_blur(region: &str) -> Option<Self> {
+		let (area, sigma) = Self::parse_region(region)?;
+		Some(Self::Blur(
+			area,
+			sigma
+				.map_or(Some(8_f32), |sigma| sigma.parse().ok())?
+				.max(0.1),
+		))
+	}
+
+	/**
+	 * Parse the area and the optional value of the region.
+	 *
+	 * @param  region
+	 * @return Tuple of Geometry and str (Option)
+	 */
+	fn parse_region(region: &str) -> Option<(Geometry, Option<&str>)> {
+		let values = region.split(':').collect::<Vec<&str>>();
+		if !(4..=5).contains(&values.len()) {
+			return None;
+		}
+		let area = Geometry::new(
+			values[0].parse().ok()?,
+			values[1].parse().ok()?,
+			values[2].parse().ok()?,
+			values[3].parse().ok()?,
+		);
+		Some((area, values.get(4).copied()))
+	}
+
+	/**
+	 * Get the area of the region that is inside the image.
+	 *
+	 * @param  size
+	 * @return Geometry (Option)
+	 */
+	pub fn get_area(&self, size: (u32, u32)) -> Option<Geometry> {
+		let (Self::Pixelate(area, _) | Self::Blur(area, _)) = self;
+		let x = u32::try_from(area.x).ok()?.min(size.0);
+		let y = u32::try_from(area.y).ok()?.min(size.1);
+		Some(Geometry::new(
+			x as i32,
+			y as i32,
+			area.width.min(size.0 - x),
+			area.height.min(size.1 - y),
+		))
+		.filter(|area| area.width > 0 && area.height > 0)
+	}
+}
+
+/* Border and padding settings */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BorderSettings {
+	pub width: u32,
+	pub color: Rgba<u8>,
+	pub padding: Padding,
+}
+
+/* Default initialization values for BorderSettings */
+impl Default for BorderSettings {
+	fn default() -> Self {
+		Self {
+			width: 0,
+			color: Rgba([0, 0, 0, 255]),
+			padding: Padding::default(),
+		}
+	}
+}
+
+impl BorderSettings {
+	/**
+	 * Create a new BorderSettings object.
+	 *
+	 * @param  width
+	 * @param  color
+	 * @param  padding
+	 * @return BorderSettings
+	 */
+	pub fn new(width: u32, color: Rgba<u8>, padding: Padding) -> Self {
+		Self {
+			width,
+			color,
+			padding,
+		}
+	}
+
+	/**
+	 * Check if there is any border or padding to add.
+	 *
+	 * @return bool
+	 */
+	pub fn is_enabled(&self) -> bool {
+		self.width > 0 || !self.padding.is_zero()
+	}
+
+	/**
+	 * Get the size of the image after adding the border and padding.
+	 *
+	 * @param  size
+	 * @return Tuple of u32
+	 */
+	pub fn get_size(&self, size: (u32, u32)) -> (u32, u32) {
+		(
+			size.0 + self.padding.left + self.padding.right + self.width * 2,
+			size.1 + self.padding.top + self.padding.bottom + self.width * 2,
+		)
+	}
+}
+
+/* Beautify (rounded corners, shadow and background) settings */
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeautifySettings {
+	pub enabled: bool,
+	pub margin: u32,
+	pub radius: u32,
+	pub shadow: f32,
+	pub background: Background,
+}
+
+/* Default initialization values for BeautifySettings */
+impl Default for BeautifySettings {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			margin: 64,
+			radius: 12,
+			shadow: 16.,
+			background: Background::default(),
+		}
+	}
+}
+
+impl BeautifySettings {
+	/**
+	 * Create a new BeautifySettings object.
+	 *
+	 * @param  enabled
+	 * @param  margin
+	 * @param  radius
+	 * @param  shadow
+	 * @param  background
+	 * @return BeautifySettings
+	 */
 	pub fn new(
-		grayscale: bool,
-		invert: bool,
-		hue: i32,
-		contrast: f32,
-		brightness: i32,
+		enabled: bool,
+		margin: u32,
+		radius: u32,
+		shadow: f32,
+		background: Background,
 	) -> Self {
 		Self {
-			grayscale,
-			invert,
-			hue,
-			contrast,
-			brightness,
+			enabled,
+			margin,
+			radius,
+			shadow: shadow.max(0.),
+			background,
 		}
 	}
 }
@@ -124,6 +714,135 @@ pub enum Flip {
 	Vertical,
 }
 
+/* Operation that is applied while editing the image */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditOp {
+	Redact,
+	Trim,
+	Crop,
+	Flip,
+	Resize,
+	Rotate,
+	Transform,
+	Denoise,
+	Blur,
+	Sharpen,
+	ReplaceColors,
+	UpdateColors,
+	Lut,
+	Vignette,
+	Watermark,
+	Shapes,
+	Text,
+	Mask,
+	Border,
+	Beautify,
+	Flatten,
+	Quantize,
+}
+
+impl EditOp {
+	/* Default order of the operations */
+	pub const ALL: [Self; 22] = [
+		Self::Redact,
+		Self::Trim,
+		Self::Crop,
+		Self::Flip,
+		Self::Resize,
+		Self::Rotate,
+		Self::Transform,
+		Self::Denoise,
+		Self::Blur,
+		Self::Sharpen,
+		Self::ReplaceColors,
+		Self::UpdateColors,
+		Self::Lut,
+		Self::Vignette,
+		Self::Watermark,
+		Self::Shapes,
+		Self::Text,
+		Self::Mask,
+		Self::Border,
+		Self::Beautify,
+		Self::Flatten,
+		Self::Quantize,
+	];
+
+	/**
+	 * Get the names of the arguments that enable the operation.
+	 *
+	 * @return Array of str
+	 */
+	fn get_args(&self) -> &'static [&'static str] {
+		match self {
+			Self::Redact => &["pixelate", "blur-region"],
+			Self::Trim => &["trim"],
+			Self::Crop => &["crop"],
+			Self::Flip => &["flip"],
+			Self::Resize => &["resize", "ratio"],
+			Self::Rotate => &["rotate"],
+			Self::Transform => &["shear", "perspective"],
+			Self::Denoise => &["denoise"],
+			Self::Blur => &["blur"],
+			Self::Sharpen => &["sharpen"],
+			Self::ReplaceColors => &["replace-color"],
+			Self::UpdateColors => &[
+				"grayscale",
+				"invert",
+				"auto-levels",
+				"equalize",
+				"hue",
+				"contrast",
+				"brightness",
+				"saturate",
+				"gamma",
+				"posterize",
+			],
+			Self::Lut => &["lut"],
+			Self::Vignette => &["vignette"],
+			Self::Watermark => &["watermark"],
+			Self::Shapes => &["draw"],
+			Self::Text => &["text"],
+			Self::Mask => &["mask"],
+			Self::Border => &["border", "pad"],
+			Self::Beautify => &["beautify"],
+			Self::Flatten => &["background"],
+			Self::Quantize => &["colors"],
+		}
+	}
+
+	/**
+	 * Get the operations in the order of their arguments on the command line.
+	 * Other operations keep their place in the default order.
+	 *
+	 * @param  matches
+	 * @return Vector of EditOp
+	 */
+	pub fn get_order(matches: &ArgMatches<'_>) -> Vec<Self> {
+		let indices = Self::ALL.map(|op| {
+			op.get_args()
+				.iter()
+				.filter_map(|arg| matches.index_of(arg))
+				.min()
+		});
+		let mut given = Self::ALL
+			.into_iter()
+			.zip(indices)
+			.filter_map(|(op, index)| index.map(|index| (index, op)))
+			.collect::<Vec<(usize, Self)>>();
+		given.sort_unstable_by_key(|(index, _)| *index);
+		let mut given = given.into_iter().map(|(_, op)| op);
+		Self::ALL
+			.into_iter()
+			.zip(indices)
+			.map(|(op, index)| match index {
+				Some(_) => given.next().unwrap_or(op),
+				None => op,
+			})
+			.collect()
+	}
+}
+
 /* Image editing settings */
 #[derive(Debug)]
 pub struct EditSettings {
@@ -131,6 +850,17 @@ pub struct EditSettings {
 	pub convert: bool,
 	pub image: ImageSettings,
 	pub color: ColorSettings,
+	pub text: TextSettings,
+	pub watermark: WatermarkSettings,
+	pub border: BorderSettings,
+	pub beautify: BeautifySettings,
+	pub background: Option<Background>,
+	pub replace: Vec<ColorReplacement>,
+	pub redact: Vec<Redaction>,
+	pub shapes: Vec<Shape>,
+	pub lut: Option<PathBuf>,
+	pub mask: Option<PathBuf>,
+	pub ops: Vec<EditOp>,
 }
 
 /* Default initialization values for EditSettings */
@@ -141,34 +871,22 @@ impl Default for EditSettings {
 			convert: false,
 			image: ImageSettings::default(),
 			color: ColorSettings::default(),
+			text: TextSettings::default(),
+			watermark: WatermarkSettings::default(),
+			border: BorderSettings::default(),
+			beautify: BeautifySettings::default(),
+			background: None,
+			replace: Vec::new(),
+			redact: Vec::new(),
+			shapes: Vec::new(),
+			lut: None,
+			mask: None,
+			ops: EditOp::ALL.to_vec(),
 		}
 	}
 }
 
 impl EditSettings {
-	/**
-	 * Create a new EditSettings object.
-	 *
-	 * @param  path
-	 * @param  convert
-	 * @param  image
-	 * @param  color
-	 * @return EditSettings
-	 */
-	pub fn new(
-		path: PathBuf,
-		convert: bool,
-		image: ImageSettings,
-		color: ColorSettings,
-	) -> Self {
-		Self {
-			path,
-			convert,
-			image,
-			color,
-		}
-	}
-
 	/**
 	 * Create a new EditSettings object from arguments.
 	 *
@@ -188,45 +906,109 @@ impl EditSettings {
 	fn from_parser(parser: ArgParser<'_>) -> Self {
 		match parser.args {
 			Some(ref matches) => {
-				let file = matches.value_of("file").unwrap_or_default();
-				let file = shellexpand::full(file)
-					.map(|s| s.to_string())
-					.unwrap_or(file.to_string());
-				Self::new(
-					PathBuf::from(file),
-					matches.is_present("convert"),
-					ImageSettings::new(
-						Padding::parse(matches.value_of("crop").unwrap_or_default()),
-						Geometry::parse(
-							matches.value_of("resize").unwrap_or_default(),
+				let expand = |path: &str| {
+					PathBuf::from(
+						shellexpand::full(path)
+							.map(|s| s.to_string())
+							.unwrap_or(path.to_string()),
+					)
+				};
+				Self {
+					path: expand(matches.value_of("file").unwrap_or_default()),
+					convert: matches.is_present("convert"),
+					image: ImageSettings::from_parser(&parser),
+					color: ColorSettings::from_parser(&parser),
+					text: TextSettings::new(
+						matches.value_of("text").unwrap_or_default().to_string(),
+						matches.value_of("font").map(expand),
+						parser.parse("font-size", TextSettings::default().size),
+						matches
+							.value_of("text-color")
+							.and_then(parse_color)
+							.unwrap_or(TextSettings::default().color),
+						OverlayPosition::parse(
+							matches.value_of("text-position").unwrap_or_default(),
 						),
-						parser.parse("ratio", ImageSettings::default().ratio),
-						match matches.value_of("flip") {
-							Some("horizontal") => Some(Flip::Horizontal),
-							Some("vertical") => Some(Flip::Vertical),
-							_ => None,
-						},
-						parser.parse("rotate", ImageSettings::default().rotate),
-						parser.parse("blur", ImageSettings::default().blur),
-						match matches.value_of("filter") {
-							Some("nearest") => FilterType::Nearest,
-							Some("triangle") => FilterType::Triangle,
-							Some("catmull-rom") => FilterType::CatmullRom,
-							Some("gaussian") => FilterType::Gaussian,
-							_ => FilterType::Lanczos3,
-						},
+						parser.parse("text-margin", TextSettings::default().margin),
 					),
-					ColorSettings::new(
-						matches.is_present("grayscale"),
-						matches.is_present("invert"),
-						parser.parse("hue", ColorSettings::default().hue),
-						parser.parse("contrast", ColorSettings::default().contrast),
+					watermark: WatermarkSettings::new(
+						matches.value_of("watermark").map(expand),
+						OverlayPosition::parse(
+							matches
+								.value_of("watermark-position")
+								.unwrap_or("bottom-right"),
+						),
+						parser.parse(
+							"watermark-opacity",
+							WatermarkSettings::default().opacity,
+						),
 						parser.parse(
-							"brightness",
-							ColorSettings::default().brightness,
+							"watermark-margin",
+							WatermarkSettings::default().margin,
 						),
 					),
-				)
+					border: BorderSettings::new(
+						parser.parse("border", BorderSettings::default().width),
+						matches
+							.value_of("border-color")
+							.and_then(parse_color)
+							.unwrap_or(BorderSettings::default().color),
+						Padding::parse(matches.value_of("pad").unwrap_or_default()),
+					),
+					beautify: BeautifySettings::new(
+						matches.is_present("beautify"),
+						parser.parse(
+							"beautify-margin",
+							BeautifySettings::default().margin,
+						),
+						parser.parse(
+							"corner-radius",
+							BeautifySettings::default().radius,
+						),
+						parser.parse("shadow", BeautifySettings::default().shadow),
+						matches
+							.value_of("beautify-background")
+							.and_then(Background::parse)
+							.unwrap_or_default(),
+					),
+					background: matches
+						.value_of("background")
+						.and_then(Background::parse),
+					replace: matches
+						.values_of("replace-color")
+						.into_iter()
+						.flatten()
+						.filter_map(|value| {
+							parse_or_warn(
+								value,
+								ColorReplacement::parse,
+								"color replacement",
+							)
+						})
+						.collect(),
+					redact: [
+						("pixelate", Redaction::parse_pixelate as fn(&str) -> _),
+						("blur-region", Redaction::parse_blur),
+					]
+					.iter()
+					.flat_map(|(name, parse)| {
+						matches.values_of(name).into_iter().flatten().filter_map(
+							move |value| parse_or_warn(value, parse, "region"),
+						)
+					})
+					.collect(),
+					shapes: matches
+						.values_of("draw")
+						.into_iter()
+						.flatten()
+						.filter_map(|value| {
+							parse_or_warn(value, Shape::parse, "shape")
+						})
+						.collect(),
+					lut: matches.value_of("lut").map(expand),
+					mask: matches.value_of("mask").map(expand),
+					ops: EditOp::get_order(matches),
+				}
 			}
 			None => Self::default(),
 		}
@@ -254,12 +1036,28 @@ mod tests {
 			.arg(Arg::with_name("convert").long("convert"))
 			.arg(Arg::with_name("grayscale").long("grayscale"))
 			.arg(Arg::with_name("invert").long("invert"))
+			.arg(Arg::with_name("trim").long("trim"))
+			.arg(Arg::with_name("auto-levels").long("auto-levels"))
+			.arg(Arg::with_name("equalize").long("equalize"))
 			.arg(Arg::with_name("crop").long("crop").takes_value(true))
 			.arg(Arg::with_name("resize").long("resize").takes_value(true))
 			.arg(Arg::with_name("ratio").long("ratio").takes_value(true))
 			.arg(Arg::with_name("flip").long("flip").takes_value(true))
 			.arg(Arg::with_name("rotate").long("rotate").takes_value(true))
+			.arg(Arg::with_name("shear").long("shear").takes_value(true))
+			.arg(
+				Arg::with_name("perspective")
+					.long("perspective")
+					.takes_value(true),
+			)
 			.arg(Arg::with_name("blur").long("blur").takes_value(true))
+			.arg(Arg::with_name("sharpen").long("sharpen").takes_value(true))
+			.arg(Arg::with_name("denoise").long("denoise").takes_value(true))
+			.arg(
+				Arg::with_name("vignette")
+					.long("vignette")
+					.takes_value(true),
+			)
 			.arg(Arg::with_name("hue").long("hue").takes_value(true))
 			.arg(
 				Arg::with_name("contrast")
@@ -272,6 +1070,19 @@ mod tests {
 					.long("brightness")
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("saturate")
+					.long("saturate")
+					.allow_hyphen_values(true)
+					.takes_value(true),
+			)
+			.arg(Arg::with_name("gamma").long("gamma").takes_value(true))
+			.arg(
+				Arg::with_name("posterize")
+					.long("posterize")
+					.takes_value(true),
+			)
+			.arg(Arg::with_name("colors").long("colors").takes_value(true))
 			.arg(Arg::with_name("filter").long("filter").takes_value(true))
 			.get_matches_from(vec![
 				"test",
@@ -287,32 +1098,143 @@ mod tests {
 				"horizontal",
 				"--rotate",
 				"90",
+				"--shear",
+				"10",
+				"--perspective",
+				"0,0:1,0:1,1",
 				"--blur",
 				"1.5",
+				"--sharpen",
+				"0.8",
+				"--denoise",
+				"150",
+				"--vignette",
+				"40",
 				"--grayscale",
 				"--invert",
+				"--trim",
+				"--auto-levels",
 				"--hue",
 				"3",
 				"--contrast",
 				"-5",
 				"--brightness",
 				"2",
+				"--saturate",
+				"-150",
+				"--gamma",
+				"20",
+				"--posterize",
+				"4",
+				"--colors",
+				"1000",
 				"--filter",
 				"triangle",
 			]);
 		let edit_settings = EditSettings::from_parser(ArgParser::from_args(&args));
 		assert_eq!(PathBuf::from("x"), edit_settings.path);
 		assert_eq!(true, edit_settings.convert);
-		assert_eq!(10, edit_settings.image.crop.top);
+		assert_eq!(10, edit_settings.image.crop.padding.top);
 		assert_eq!(0.5, edit_settings.image.ratio);
 		assert_eq!(Some(Flip::Horizontal), edit_settings.image.flip);
 		assert_eq!(90, edit_settings.image.rotate);
+		assert_eq!((10., 0.), edit_settings.image.shear);
+		assert_eq!(None, edit_settings.image.perspective);
 		assert_eq!(1.5, edit_settings.image.blur);
+		assert_eq!(0.8, edit_settings.image.sharpen);
+		assert_eq!(100, edit_settings.image.denoise);
+		assert_eq!(40, edit_settings.image.vignette);
 		assert_eq!(true, edit_settings.color.grayscale);
 		assert_eq!(true, edit_settings.color.invert);
+		assert_eq!(true, edit_settings.color.auto_levels);
+		assert_eq!(true, edit_settings.image.trim);
+		assert_eq!(false, edit_settings.color.equalize);
 		assert_eq!(2, edit_settings.color.brightness);
 		assert_eq!(3, edit_settings.color.hue);
 		assert_eq!(-5., edit_settings.color.contrast);
+		assert_eq!(-100, edit_settings.color.saturation);
+		assert_eq!(10., edit_settings.color.gamma);
+		assert_eq!(4, edit_settings.color.posterize);
+		assert_eq!(MAX_COLORS, edit_settings.color.colors);
+		assert_eq!(
+			vec![EditOp::Redact, EditOp::Crop, EditOp::Resize, EditOp::Flip],
+			edit_settings.ops[..4]
+		);
+		assert_eq!(EditOp::ALL.len(), edit_settings.ops.len());
+		let args = App::new("test")
+			.arg(Arg::with_name("hue").long("hue").takes_value(true))
+			.get_matches_from(vec!["test", "--hue", "450"]);
+		assert_eq!(
+			90,
+			ColorSettings::from_parser(&ArgParser::from_args(&args)).hue
+		);
 		assert_eq!("Triangle", format!("{:?}", edit_settings.image.filter));
+		let crop = CropSettings::parse("100x50+10+20", "1:1");
+		assert!(crop.padding.is_zero());
+		assert_eq!(
+			Some(Geometry::new(35, 20, 50, 50)),
+			crop.get_area((200, 200))
+		);
+		assert_eq!(
+			Some(Geometry::new(150, 0, 50, 100)),
+			CropSettings::parse("500x500+150+0", "").get_area((200, 100))
+		);
+		assert_eq!(
+			Some(Geometry::new(0, 90, 100, 50)),
+			CropSettings::parse("", "2:1").get_area((100, 230))
+		);
+		assert_eq!(None, CropSettings::parse("10:10", "1.5").get_area((10, 10)));
+		let replacement = ColorReplacement::parse("#FF0000:0000FF80:10").unwrap();
+		assert_eq!(
+			ColorReplacement::new(
+				Rgba([255, 0, 0, 255]),
+				Rgba([0, 0, 255, 128]),
+				10
+			),
+			replacement
+		);
+		assert!(replacement.matches(&Rgba([250, 5, 0, 0])));
+		assert!(!replacement.matches(&Rgba([240, 0, 0, 255])));
+		let mut pixel = Rgba([250, 5, 0, 64]);
+		replacement.apply(&mut pixel);
+		assert_eq!(Rgba([0, 0, 255, 64]), pixel);
+		assert_eq!(
+			0,
+			ColorReplacement::parse("000000:FFFFFF").unwrap().tolerance
+		);
+		assert_eq!(None, ColorReplacement::parse("000000:FFFFFF:300"));
+		assert_eq!(None, ColorReplacement::parse("000000"));
+		assert_eq!(
+			Some(Redaction::Pixelate(Geometry::new(1, 2, 30, 40), 10)),
+			Redaction::parse_pixelate("1:2:30:40")
+		);
+		assert_eq!(
+			Some(Redaction::Blur(Geometry::new(0, 0, 5, 5), 2.5)),
+			Redaction::parse_blur("0:0:5:5:2.5")
+		);
+		assert_eq!(None, Redaction::parse_pixelate("0:0:5"));
+		assert_eq!(
+			Some(Geometry::new(90, 0, 10, 5)),
+			Redaction::parse_pixelate("90:0:20:5:0")
+				.and_then(|redaction| redaction.get_area((100, 100)))
+		);
+		assert_eq!(
+			None,
+			Redaction::parse_blur("100:0:20:5")
+				.unwrap()
+				.get_area((100, 100))
+		);
+		assert_eq!(
+			(10, 10),
+			OverlayPosition::parse("top-left").get_offset((100, 100), (20, 20), 10)
+		);
+		assert_eq!(
+			(40, 70),
+			OverlayPosition::parse("bottom").get_offset((100, 100), (20, 20), 10)
+		);
+		assert_eq!(
+			(40, 40),
+			OverlayPosition::parse("center").get_offset((100, 100), (20, 20), 0)
+		);
 	}
 }
@@ -0,0 +1,325 @@
+use crate::edit::text::parse_color;
+use image::{Pixel, Rgba, RgbaImage};
+
+/* Default line width of the shapes */
+const DEFAULT_WIDTH: f32 = 3.;
+/* Angle between the arrow line and its head in radians */
+const ARROW_ANGLE: f32 = std::f32::consts::PI / 6.;
+
+/* Kind and geometry of the shape */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShapeKind {
+	Rect((f32, f32), (f32, f32)),
+	Ellipse((f32, f32), (f32, f32)),
+	Line((f32, f32), (f32, f32)),
+	Arrow((f32, f32), (f32, f32)),
+}
+
+/* Shape annotation to draw on the image */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shape {
+	pub kind: ShapeKind,
+	pub color: Rgba<u8>,
+	pub width: f32,
+	pub fill: bool,
+}
+
+impl Shape {
+	/**
+	 * Create a new Shape object.
+	 *
+	 * @param  kind
+	 * @param  color
+	 * @param  width
+	 * @param  fill
+	 * @return Shape
+	 */
+	pub fn new(kind: ShapeKind, color: Rgba<u8>, width: f32, fill: bool) -> Self {
+		Self {
+			kind,
+			color,
+			width: width.max(1.),
+			fill,
+		}
+	}
+
+	/**
+	 * Parse the shape from a description such as "rect X,Y WxH [COLOR] [WIDTH]",
+	 * "ellipse X,Y WxH ...", "line X1,Y1 X2,Y2 ..." or "arrow X1,Y1 X2,Y2 ...".
+	 *
+	 * @param  shape
+	 * @return Shape (Option)
+	 */
+	pub fn parse(shape: &str) -> Option<Self> {
+		let mut values = shape.split_whitespace();
+		let name = values.next()?.to_lowercase();
+		let position = Self::parse_pair(values.next()?, ',')?;
+		let kind = match name.as_str() {
+			"rect" | "ellipse" => {
+				let size = Self::parse_pair(values.next()?, 'x')?;
+				if name == "rect" {
+					ShapeKind::Rect(position, size)
+				} else {
+					ShapeKind::Ellipse(position, size)
+				}
+			}
+			"line" => {
+				ShapeKind::Line(position, Self::parse_pair(values.next()?, ',')?)
+			}
+			"arrow" => {
+				ShapeKind::Arrow(position, Self::parse_pair(values.next()?, ',')?)
+			}
+			_ => return None,
+		};
+		let (mut color, mut width, mut fill) =
+			(Rgba([255, 0, 0, 255]), DEFAULT_WIDTH, false);
+		for value in values {
+			if value == "fill" {
+				fill = true;
+			} else if let Ok(value) = value.parse::<f32>() {
+				width = value;
+			} else {
+				color = parse_color_name(value)?;
+			}
+		}
+		Some(Self::new(kind, color, width, fill))
+	}
+
+	/**
+	 * Parse a pair of numbers that are separated by the given character.
+	 *
+	 * @param  pair
+	 * @param  separator
+	 * @return Tuple of f32 (Option)
+	 */
+	fn parse_pair(pair: &str, separator: char) -> Option<(f32, f32)> {
+		let (first, second) = pair.split_once(separator)?;
+		Some((first.parse().ok()?, second.parse().ok()?))
+	}
+
+	/**
+	 * Draw the shape on the image.
+	 *
+	 * Coordinates refer to the pixel centers, so the outline of the
+	 * rectangles and ellipses is drawn over their first and last pixels.
+	 *
+	 * @param image
+	 */
+	pub fn draw(&self, image: &mut RgbaImage) {
+		let half_width = self.width / 2.;
+		let inner = |(width, height): (f32, f32)| {
+			((width - 1.).max(0.), (height - 1.).max(0.))
+		};
+		match self.kind {
+			ShapeKind::Rect((x, y), size) => {
+				let (width, height) = inner(size);
+				let (center_x, center_y) = (x + width / 2., y + height / 2.);
+				self.fill_area(
+					image,
+					(x - half_width, y - half_width),
+					(x + width + half_width, y + height + half_width),
+					|px, py| {
+						let (dx, dy) = (
+							(px - center_x).abs() - width / 2.,
+							(py - center_y).abs() - height / 2.,
+						);
+						let outside =
+							dx.max(0.).hypot(dy.max(0.)) + dx.max(dy).min(0.);
+						self.get_coverage(outside)
+					},
+				);
+			}
+			ShapeKind::Ellipse((x, y), size) => {
+				let (width, height) = inner(size);
+				let (radius_x, radius_y) =
+					((width / 2.).max(0.5), (height / 2.).max(0.5));
+				let (center_x, center_y) = (x + radius_x, y + radius_y);
+				self.fill_area(
+					image,
+					(x - half_width, y - half_width),
+					(x + width + half_width, y + height + half_width),
+					|px, py| {
+						let (dx, dy) = (px - center_x, py - center_y);
+						let value = (dx / radius_x).hypot(dy / radius_y);
+						let gradient = (dx / radius_x.powi(2))
+							.hypot(dy / radius_y.powi(2))
+							/ value.max(f32::EPSILON);
+						self.get_coverage((value - 1.) / gradient.max(f32::EPSILON))
+					},
+				);
+			}
+			ShapeKind::Line(start, end) => self.draw_line(image, start, end),
+			ShapeKind::Arrow(start, end) => {
+				self.draw_line(image, start, end);
+				let length = (self.width * 4.).max(12.);
+				let angle = (start.1 - end.1).atan2(start.0 - end.0);
+				for side in [-ARROW_ANGLE, ARROW_ANGLE] {
+					self.draw_line(
+						image,
+						end,
+						(
+							end.0 + length * (angle + side).cos(),
+							end.1 + length * (angle + side).sin(),
+						),
+					);
+				}
+			}
+		}
+	}
+
+	/**
+	 * Get the coverage of a pixel from its signed distance to the shape edge.
+	 *
+	 * @param  distance
+	 * @return f32
+	 */
+	fn get_coverage(&self, distance: f32) -> f32 {
+		if self.fill {
+			(0.5 - distance).clamp(0., 1.)
+		} else {
+			(self.width / 2. - distance.abs() + 0.5).clamp(0., 1.)
+		}
+	}
+
+	/**
+	 * Draw a line with round caps between the given points.
+	 *
+	 * @param image
+	 * @param start
+	 * @param end
+	 */
+	fn draw_line(&self, image: &mut RgbaImage, start: (f32, f32), end: (f32, f32)) {
+		let half_width = self.width / 2. + 1.;
+		let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+		let length = dx * dx + dy * dy;
+		self.fill_area(
+			image,
+			(
+				start.0.min(end.0) - half_width,
+				start.1.min(end.1) - half_width,
+			),
+			(
+				start.0.max(end.0) + half_width,
+				start.1.max(end.1) + half_width,
+			),
+			|px, py| {
+				let ratio = if length > 0. {
+					(((px - start.0) * dx + (py - start.1) * dy) / length)
+						.clamp(0., 1.)
+				} else {
+					0.
+				};
+				let distance =
+					(px - start.0 - ratio * dx).hypot(py - start.1 - ratio * dy);
+				(self.width / 2. - distance + 0.5).clamp(0., 1.)
+			},
+		);
+	}
+
+	/**
+	 * Blend the shape color into the pixels of the area by their coverage.
+	 *
+	 * @param image
+	 * @param start
+	 * @param end
+	 * @param get_coverage
+	 */
+	fn fill_area(
+		&self,
+		image: &mut RgbaImage,
+		start: (f32, f32),
+		end: (f32, f32),
+		get_coverage: impl Fn(f32, f32) -> f32,
+	) {
+		let (width, height) = image.dimensions();
+		let clamp = |value: f32, max: u32| value.max(0.).min(max as f32) as u32;
+		for y in clamp(start.1.floor(), height)..clamp(end.1.ceil() + 1., height) {
+			for x in clamp(start.0.floor(), width)..clamp(end.0.ceil() + 1., width) {
+				let coverage = get_coverage(x as f32, y as f32);
+				if coverage > 0. {
+					let mut color = self.color;
+					color[3] = (f32::from(color[3]) * coverage) as u8;
+					image.get_pixel_mut(x, y).blend(&color);
+				}
+			}
+		}
+	}
+}
+
+/**
+ * Create a Rgba color from the given name or hex value.
+ *
+ * @param  color
+ * @return Rgba (Option)
+ */
+pub fn parse_color_name(color: &str) -> Option<Rgba<u8>> {
+	let rgb = match color.to_lowercase().as_str() {
+		"black" => [0, 0, 0],
+		"white" => [255, 255, 255],
+		"red" => [255, 0, 0],
+		"green" => [0, 255, 0],
+		"blue" => [0, 0, 255],
+		"yellow" => [255, 255, 0],
+		"cyan" => [0, 255, 255],
+		"magenta" => [255, 0, 255],
+		"orange" => [255, 165, 0],
+		"gray" | "grey" => [128, 128, 128],
+		_ => return parse_color(color),
+	};
+	Some(Rgba([rgb[0], rgb[1], rgb[2], u8::MAX]))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_shape() {
+		assert_eq!(
+			Some(Shape::new(
+				ShapeKind::Rect((10., 10.), (200., 80.)),
+				Rgba([255, 0, 0, 255]),
+				3.,
+				false
+			)),
+			Shape::parse("rect 10,10 200x80 red 3")
+		);
+		assert_eq!(
+			Some(Shape::new(
+				ShapeKind::Arrow((300., 300.), (400., 200.)),
+				Rgba([255, 255, 0, 255]),
+				DEFAULT_WIDTH,
+				false
+			)),
+			Shape::parse("arrow 300,300 400,200 yellow")
+		);
+		assert_eq!(
+			Some(Rgba([0, 0, 255, 128])),
+			Shape::parse("ellipse 0,0 4x4 0000FF80 fill").map(|shape| shape.color)
+		);
+		assert_eq!(None, Shape::parse("circle 0,0 10x10"));
+		assert_eq!(None, Shape::parse("rect 0,0 10x10 purple"));
+		assert_eq!(None, Shape::parse("line 0,0"));
+		let black = Rgba([0, 0, 0, 255]);
+		let mut image = RgbaImage::from_pixel(20, 20, black);
+		Shape::parse("rect 5,5 10x10 white 1")
+			.unwrap()
+			.draw(&mut image);
+		assert_eq!(Rgba([255, 255, 255, 255]), *image.get_pixel(5, 10));
+		assert_eq!(black, *image.get_pixel(10, 10));
+		assert_eq!(black, *image.get_pixel(2, 10));
+		let mut image = RgbaImage::from_pixel(20, 20, black);
+		Shape::parse("ellipse 2,2 16x16 white fill")
+			.unwrap()
+			.draw(&mut image);
+		assert_eq!(Rgba([255, 255, 255, 255]), *image.get_pixel(10, 10));
+		assert_eq!(black, *image.get_pixel(3, 3));
+		let mut image = RgbaImage::from_pixel(20, 20, black);
+		Shape::parse("arrow 0,10 19,10 white 1")
+			.unwrap()
+			.draw(&mut image);
+		assert_eq!(Rgba([255, 255, 255, 255]), *image.get_pixel(10, 10));
+		assert!(image.get_pixel(15, 8)[0] > 128);
+		assert_eq!(black, *image.get_pixel(5, 5));
+	}
+}
@@ -0,0 +1,178 @@
+use crate::app::{AppError, AppResult};
+use crate::edit::settings::TextSettings;
+use fontdue::layout::{CoordinateSystem, Layout, LayoutSettings, TextStyle};
+use fontdue::{Font, FontSettings};
+use image::{Pixel, Rgba, RgbaImage};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/* Text renderer for burning captions into the images */
+pub struct TextRenderer {
+	font: Font,
+	settings: TextSettings,
+}
+
+impl TextRenderer {
+	/**
+	 * Create a new TextRenderer object by loading the font.
+	 *
+	 * @param  settings
+	 * @return TextRenderer (Result)
+	 */
+	pub fn new(settings: TextSettings) -> AppResult<Self> {
+		let path = match &settings.font {
+			Some(font) => font.to_path_buf(),
+			None => Self::find_font()?,
+		};
+		debug!("Loading the font from {:?}", path);
+		let font = Font::from_bytes(
+			fs::read(&path)?,
+			FontSettings {
+				scale: settings.size,
+				..FontSettings::default()
+			},
+		)
+		.map_err(|e| AppError::FrameError(format!("Invalid font {path:?}: {e}")))?;
+		Ok(Self { font, settings })
+	}
+
+	/**
+	 * Find the default sans-serif font via fontconfig.
+	 *
+	 * @return PathBuf (Result)
+	 */
+	fn find_font() -> AppResult<PathBuf> {
+		let output = Command::new("fc-match")
+			.args(["-f", "%{file}", "sans-serif"])
+			.output()
+			.map_err(|e| {
+				AppError::CommandError(format!(
+					"Failed to find a font (use --font to set it): {e}"
+				))
+			})?;
+		Some(PathBuf::from(
+			String::from_utf8_lossy(&output.stdout).trim(),
+		))
+		.filter(|path| output.status.success() && path.is_file())
+		.ok_or_else(|| {
+			AppError::FrameError(String::from(
+				"No font is found, use --font to set it",
+			))
+		})
+	}
+
+	/**
+	 * Draw the text on the image.
+	 *
+	 * @param  image
+	 */
+	pub fn draw(&self, image: &mut RgbaImage) {
+		self.render(&self.settings.text, image.dimensions(), |x, y, color| {
+			image.get_pixel_mut(x, y).blend(&color)
+		});
+	}
+
+	/**
+	 * Render the given text and pass the colored pixels to the callback.
+	 *
+	 * @param  text
+	 * @param  size
+	 * @param  blend
+	 */
+	pub fn render(
+		&self,
+		text: &str,
+		size: (u32, u32),
+		mut blend: impl FnMut(u32, u32, Rgba<u8>),
+	) {
+		let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+		layout.reset(&LayoutSettings::default());
+		layout.append(&[&self.font], &TextStyle::new(text, self.settings.size, 0));
+		let glyphs = layout.glyphs();
+		let width = glyphs
+			.iter()
+			.map(|glyph| glyph.x + glyph.width as f32)
+			.fold(0., f32::max);
+		let (x, y) = self.settings.position.get_offset(
+			size,
+			(width.ceil() as u32, layout.height().ceil() as u32),
+			self.settings.margin,
+		);
+		for glyph in glyphs {
+			if glyph.width == 0 || glyph.height == 0 {
+				continue;
+			}
+			let (_, bitmap) = self.font.rasterize_config(glyph.key);
+			for (i, coverage) in bitmap.into_iter().enumerate() {
+				let (pixel_x, pixel_y) = (
+					x + glyph.x.round() as i64 + (i % glyph.width) as i64,
+					y + glyph.y.round() as i64 + (i / glyph.width) as i64,
+				);
+				if coverage == 0
+					|| pixel_x < 0 || pixel_y < 0
+					|| pixel_x >= i64::from(size.0)
+					|| pixel_y >= i64::from(size.1)
+				{
+					continue;
+				}
+				let mut color = self.settings.color;
+				color[3] = (u16::from(color[3]) * u16::from(coverage) / 255) as u8;
+				blend(pixel_x as u32, pixel_y as u32, color);
+			}
+		}
+	}
+}
+
+/* Debug implementation for skipping the font data */
+impl std::fmt::Debug for TextRenderer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("TextRenderer")
+			.field("settings", &self.settings)
+			.finish()
+	}
+}
+
+/**
+ * Create a Rgba color from the given hex value.
+ *
+ * @param  color
+ * @return Rgba (Option)
+ */
+pub fn parse_color(color: &str) -> Option<Rgba<u8>> {
+	match hex::decode(color.trim_start_matches('#')).ok()?[..] {
+		[red, green, blue] => Some(Rgba([red, green, blue, u8::MAX])),
+		[red, green, blue, alpha] => Some(Rgba([red, green, blue, alpha])),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::edit::settings::OverlayPosition;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_text_renderer() -> AppResult<()> {
+		assert_eq!(Some(Rgba([255, 0, 16, 255])), parse_color("#FF0010"));
+		assert_eq!(Some(Rgba([0, 0, 0, 128])), parse_color("00000080"));
+		assert_eq!(None, parse_color("red"));
+		let Ok(font) = TextRenderer::find_font() else {
+			return Ok(());
+		};
+		let settings = TextSettings::new(
+			String::from("menyoki"),
+			Some(font),
+			24.,
+			Rgba([255, 255, 255, 255]),
+			OverlayPosition::Bottom,
+			10,
+		);
+		let renderer = TextRenderer::new(settings)?;
+		let mut image = RgbaImage::from_pixel(200, 80, Rgba([0, 0, 0, 255]));
+		renderer.draw(&mut image);
+		assert!(image.pixels().any(|pixel| pixel[0] > 128));
+		assert!(image.rows().take(20).flatten().all(|pixel| pixel[0] == 0));
+		Ok(())
+	}
+}
@@ -0,0 +1,292 @@
+use image::{Rgba, RgbaImage};
+
+/* Maximum angle of the shear in degrees */
+const MAX_SHEAR_ANGLE: f64 = 80.;
+
+/* Projective transformation of the image */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+	matrix: [f64; 9],
+}
+
+impl Transform {
+	/**
+	 * Create a new Transform object that shears the image.
+	 *
+	 * @param  angles
+	 * @return Transform
+	 */
+	pub fn shear(angles: (f64, f64)) -> Self {
+		let (x, y) = (
+			angles.0.clamp(-MAX_SHEAR_ANGLE, MAX_SHEAR_ANGLE),
+			angles.1.clamp(-MAX_SHEAR_ANGLE, MAX_SHEAR_ANGLE),
+		);
+		Self {
+			matrix: [
+				1.,
+				x.to_radians().tan(),
+				0.,
+				y.to_radians().tan(),
+				1.,
+				0.,
+				0.,
+				0.,
+				1.,
+			],
+		}
+	}
+
+	/**
+	 * Create a new Transform object that maps the corners of the image
+	 * (top-left, top-right, bottom-right, bottom-left) to the given points.
+	 *
+	 * @param  size
+	 * @param  points
+	 * @return Transform (Option)
+	 */
+	pub fn perspective(size: (u32, u32), points: [(f64, f64); 4]) -> Option<Self> {
+		let (width, height) = (f64::from(size.0), f64::from(size.1));
+		let corners = [(0., 0.), (width, 0.), (width, height), (0., height)];
+		let mut equations = [[0.; 9]; 8];
+		for (i, ((x, y), (u, v))) in corners.into_iter().zip(points).enumerate() {
+			equations[i * 2] = [x, y, 1., 0., 0., 0., -u * x, -u * y, u];
+			equations[i * 2 + 1] = [0., 0., 0., x, y, 1., -v * x, -v * y, v];
+		}
+		for column in 0..8 {
+			let pivot = (column..8).max_by(|a, b| {
+				equations[*a][column]
+					.abs()
+					.total_cmp(&equations[*b][column].abs())
+			})?;
+			if equations[pivot][column].abs() < f64::EPSILON {
+				return None;
+			}
+			equations.swap(column, pivot);
+			let pivot = equations[column];
+			for (row, equation) in equations.iter_mut().enumerate() {
+				if row != column {
+					let factor = equation[column] / pivot[column];
+					for (value, pivot) in equation.iter_mut().zip(pivot).skip(column)
+					{
+						*value -= factor * pivot;
+					}
+				}
+			}
+		}
+		let mut matrix = [1.; 9];
+		for (i, value) in matrix.iter_mut().take(8).enumerate() {
+			*value = equations[i][8] / equations[i][i];
+		}
+		Some(Self { matrix })
+	}
+
+	/**
+	 * Parse the shear angles from a "X[:Y]" value in degrees.
+	 *
+	 * @param  shear
+	 * @return Tuple of f64 (Option)
+	 */
+	pub fn parse_shear(shear: &str) -> Option<(f64, f64)> {
+		let (x, y) = shear.split_once(':').unwrap_or((shear, "0"));
+		Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+	}
+
+	/**
+	 * Parse the corner points from a "X,Y:X,Y:X,Y:X,Y" value.
+	 *
+	 * @param  points
+	 * @return Array of points (Option)
+	 */
+	pub fn parse_points(points: &str) -> Option<[(f64, f64); 4]> {
+		let points = points
+			.split(':')
+			.map(|point| {
+				let (x, y) = point.split_once(',')?;
+				Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+			})
+			.collect::<Option<Vec<(f64, f64)>>>()?;
+		points.try_into().ok()
+	}
+
+	/**
+	 * Get the inverse of the transformation.
+	 *
+	 * @return Transform (Option)
+	 */
+	fn invert(&self) -> Option<Self> {
+		let [a, b, c, d, e, f, g, h, i] = self.matrix;
+		let determinant =
+			a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g);
+		if determinant.abs() < f64::EPSILON {
+			return None;
+		}
+		Some(Self {
+			matrix: [
+				e * i - f * h,
+				c * h - b * i,
+				b * f - c * e,
+				f * g - d * i,
+				a * i - c * g,
+				c * d - a * f,
+				d * h - e * g,
+				b * g - a * h,
+				a * e - b * d,
+			]
+			.map(|value| value / determinant),
+		})
+	}
+
+	/**
+	 * Map the point using the transformation.
+	 *
+	 * @param  point
+	 * @return Tuple of f64 (Option)
+	 */
+	fn apply(&self, (x, y): (f64, f64)) -> Option<(f64, f64)> {
+		let m = self.matrix;
+		let w = m[6] * x + m[7] * y + m[8];
+		if w <= f64::EPSILON {
+			return None;
+		}
+		Some((
+			(m[0] * x + m[1] * y + m[2]) / w,
+			(m[3] * x + m[4] * y + m[5]) / w,
+		))
+	}
+
+	/**
+	 * Get the bounding box of the transformed image.
+	 *
+	 * @param  size
+	 * @return Tuple of the position and size (Option)
+	 */
+	fn get_bounds(&self, size: (u32, u32)) -> Option<((f64, f64), (u32, u32))> {
+		let (width, height) = (f64::from(size.0), f64::from(size.1));
+		let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+		for corner in [(0., 0.), (width, 0.), (width, height), (0., height)] {
+			let (x, y) = self.apply(corner)?;
+			min = (min.0.min(x), min.1.min(y));
+			max = (max.0.max(x), max.1.max(y));
+		}
+		Some((
+			min,
+			(
+				((max.0 - min.0).round() as u32).max(1),
+				((max.1 - min.1).round() as u32).max(1),
+			),
+		))
+	}
+
+	/**
+	 * Get the size of the transformed image.
+	 *
+	 * @param  size
+	 * @return Tuple of u32
+	 */
+	pub fn get_size(&self, size: (u32, u32)) -> (u32, u32) {
+		self.get_bounds(size).map_or(size, |(_, size)| size)
+	}
+
+	/**
+	 * Transform the image by sampling the source pixel of each
+	 * pixel in the bounding box of the transformed image.
+	 *
+	 * @param  image
+	 * @return RgbaImage (Option)
+	 */
+	pub fn warp(&self, image: &RgbaImage) -> Option<RgbaImage> {
+		let (min, (width, height)) = self.get_bounds(image.dimensions())?;
+		let inverse = self.invert()?;
+		Some(RgbaImage::from_fn(width, height, |x, y| {
+			inverse
+				.apply((f64::from(x) + 0.5 + min.0, f64::from(y) + 0.5 + min.1))
+				.map_or(Rgba([0, 0, 0, 0]), |(x, y)| {
+					Self::sample(image, x - 0.5, y - 0.5)
+				})
+		}))
+	}
+
+	/**
+	 * Get the color at the given position using bilinear interpolation.
+	 * Pixels outside of the image are treated as transparent.
+	 *
+	 * @param  image
+	 * @param  x
+	 * @param  y
+	 * @return Rgba
+	 */
+	fn sample(image: &RgbaImage, x: f64, y: f64) -> Rgba<u8> {
+		let (left, top) = (x.floor(), y.floor());
+		let (ratio_x, ratio_y) = (x - left, y - top);
+		let mut color = [0.; 4];
+		for (dx, dy, weight) in [
+			(0., 0., (1. - ratio_x) * (1. - ratio_y)),
+			(1., 0., ratio_x * (1. - ratio_y)),
+			(0., 1., (1. - ratio_x) * ratio_y),
+			(1., 1., ratio_x * ratio_y),
+		] {
+			let (x, y) = (left + dx, top + dy);
+			if x < 0. || y < 0. || weight <= 0. {
+				continue;
+			}
+			if let Some(pixel) = image.get_pixel_checked(x as u32, y as u32) {
+				let alpha = f64::from(pixel[3]) * weight;
+				for i in 0..3 {
+					color[i] += f64::from(pixel[i]) * alpha;
+				}
+				color[3] += alpha;
+			}
+		}
+		if color[3] <= 0. {
+			return Rgba([0, 0, 0, 0]);
+		}
+		Rgba([
+			(color[0] / color[3]).round() as u8,
+			(color[1] / color[3]).round() as u8,
+			(color[2] / color[3]).round() as u8,
+			color[3].round() as u8,
+		])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_transform() {
+		assert_eq!(Some((15., 0.)), Transform::parse_shear("15"));
+		assert_eq!(Some((-10., 5.5)), Transform::parse_shear("-10:5.5"));
+		assert_eq!(None, Transform::parse_shear("x"));
+		assert_eq!(
+			Some([(0., 10.), (90., 0.), (100., 80.), (5., 60.)]),
+			Transform::parse_points("0,10:90,0:100,80:5,60")
+		);
+		assert_eq!(None, Transform::parse_points("0,10:90,0:100,80"));
+		let image = RgbaImage::from_fn(4, 3, |x, y| {
+			Rgba([(x * 60) as u8, (y * 100) as u8, 0, 255])
+		});
+		let identity =
+			Transform::perspective((4, 3), [(0., 0.), (4., 0.), (4., 3.), (0., 3.)])
+				.unwrap();
+		assert_eq!(Some(image.clone()), identity.warp(&image));
+		let shear = Transform::shear((45., 0.));
+		assert_eq!((7, 3), shear.get_size((4, 3)));
+		let sheared = shear.warp(&image).unwrap();
+		assert_eq!(Rgba([0, 0, 0, 128]), *sheared.get_pixel(0, 0));
+		assert_eq!(Rgba([30, 100, 0, 255]), *sheared.get_pixel(2, 1));
+		assert_eq!(Rgba([0, 0, 0, 0]), *sheared.get_pixel(6, 0));
+		assert_eq!(Rgba([0, 0, 0, 0]), *sheared.get_pixel(0, 2));
+		let perspective =
+			Transform::perspective((4, 3), [(2., 0.), (6., 0.), (8., 6.), (0., 6.)])
+				.unwrap();
+		assert_eq!((8, 6), perspective.get_size((4, 3)));
+		let warped = perspective.warp(&image).unwrap();
+		assert_eq!(Rgba([0, 0, 0, 0]), *warped.get_pixel(0, 0));
+		assert_eq!(255, warped.get_pixel(4, 3)[3]);
+		assert_eq!(
+			None,
+			Transform::perspective((4, 3), [(0., 0.), (0., 0.), (0., 0.), (0., 0.)])
+		);
+	}
+}
@@ -1,7 +1,10 @@
 use crate::args::matches::ArgMatches;
+use crate::args::parser::ArgParser;
 use crate::file::File;
 use image::codecs::pnm::PnmSubtype;
+use std::ffi::OsStr;
 use std::fmt;
+use std::path::Path;
 use std::str::FromStr;
 
 /* Format of the output file */
@@ -10,9 +13,13 @@ pub enum FileFormat {
 	Any,
 	Gif,
 	Apng,
+	Y4m,
 	Png,
 	Jpg,
 	WebP,
+	Jxl,
+	Heif,
+	Qoi,
 	Bmp,
 	Ico,
 	Tiff,
@@ -20,6 +27,11 @@ pub enum FileFormat {
 	Pnm(String),
 	Ff,
 	Exr,
+	Ansi,
+	Sixel,
+	Pdf,
+	Tar,
+	Zip,
 	Txt,
 }
 
@@ -37,9 +49,13 @@ impl FromStr for FileFormat {
 		match s {
 			"gif" => Ok(Self::Gif),
 			"apng" => Ok(Self::Apng),
+			"y4m" => Ok(Self::Y4m),
 			"png" => Ok(Self::Png),
 			"jpg" => Ok(Self::Jpg),
 			"webp" => Ok(Self::WebP),
+			"jxl" => Ok(Self::Jxl),
+			"heif" | "heic" => Ok(Self::Heif),
+			"qoi" => Ok(Self::Qoi),
 			"bmp" => Ok(Self::Bmp),
 			"ico" => Ok(Self::Ico),
 			"tiff" => Ok(Self::Tiff),
@@ -47,6 +63,11 @@ impl FromStr for FileFormat {
 			"pnm" => Ok(Self::Pnm(String::from("ppm"))),
 			"ff" => Ok(Self::Ff),
 			"exr" => Ok(Self::Exr),
+			"ans" | "ansi" => Ok(Self::Ansi),
+			"six" | "sixel" => Ok(Self::Sixel),
+			"pdf" => Ok(Self::Pdf),
+			"tar" => Ok(Self::Tar),
+			"zip" => Ok(Self::Zip),
 			"txt" => Ok(Self::Txt),
 			_ => Err("Unrecognized file format"),
 		}
@@ -69,6 +90,10 @@ impl FileFormat {
 			"edit"
 		} else if args.is_present("split") {
 			"split"
+		} else if args.is_present("montage") {
+			"montage"
+		} else if args.is_present("diff") {
+			"diff"
 		} else if args.is_present("analyze") {
 			"analyze"
 		} else {
@@ -83,10 +108,16 @@ impl FileFormat {
 					}
 				} else if matches.is_present("gif") {
 					Self::Gif
+				} else if matches.is_present("apng") {
+					Self::Apng
 				} else if matches.is_present("ff") {
 					Self::Ff
 				} else if matches.is_present("exr") {
 					Self::Exr
+				} else if matches.is_present("ansi") {
+					Self::Ansi
+				} else if matches.is_present("sixel") {
+					Self::Sixel
 				} else if matches.is_present("tiff") {
 					Self::Tiff
 				} else if matches.is_present("tga") {
@@ -107,6 +138,12 @@ impl FileFormat {
 					Self::Jpg
 				} else if matches.is_present("webp") {
 					Self::WebP
+				} else if matches.is_present("jxl") {
+					Self::Jxl
+				} else if matches.is_present("heif") {
+					Self::Heif
+				} else if matches.is_present("qoi") {
+					Self::Qoi
 				} else {
 					Self::Png
 				}
@@ -115,8 +152,10 @@ impl FileFormat {
 				if let Some(matches) = args.subcommand_matches("record") {
 					if matches.is_present("apng") {
 						Self::Apng
+					} else if matches.is_present("y4m") {
+						Self::Y4m
 					} else {
-						Self::Gif
+						Self::get_record_format(args).unwrap_or(Self::Gif)
 					}
 				} else {
 					Self::Gif
@@ -125,6 +164,30 @@ impl FileFormat {
 		}
 	}
 
+	/**
+	 * Get the recording format from the extension of the output file.
+	 *
+	 * @param  args
+	 * @return FileFormat (Option)
+	 */
+	fn get_record_format<'a>(args: &'a ArgMatches<'a>) -> Option<Self> {
+		let matches = ArgParser::from_subcommand(args, "save").args?;
+		match Path::new(matches.value_of("file")?)
+			.extension()
+			.and_then(OsStr::to_str)?
+			.to_lowercase()
+			.as_str()
+		{
+			"tif" | "tiff" => Some(Self::Tiff),
+			"ans" | "ansi" => Some(Self::Ansi),
+			"y4m" => Some(Self::Y4m),
+			"pdf" => Some(Self::Pdf),
+			"tar" => Some(Self::Tar),
+			"zip" => Some(Self::Zip),
+			_ => None,
+		}
+	}
+
 	/**
 	 * Get default File from format.
 	 *
@@ -151,7 +214,12 @@ impl FileFormat {
 		String::from(match self {
 			Self::Any => "output",
 			Self::Txt => "report",
-			Self::Gif | Self::Apng => "rec",
+			Self::Gif
+			| Self::Apng
+			| Self::Y4m
+			| Self::Pdf
+			| Self::Tar
+			| Self::Zip => "rec",
 			_ => "cap",
 		})
 	}
@@ -162,7 +230,7 @@ impl FileFormat {
 	 * @return bool
 	 */
 	pub fn is_animation(&self) -> bool {
-		self == &Self::Gif || self == &Self::Apng
+		matches!(self, Self::Gif | Self::Apng | Self::Y4m)
 	}
 
 	/**
@@ -174,6 +242,9 @@ impl FileFormat {
 		match self {
 			Self::Any => String::from("*"),
 			Self::Pnm(v) => v.to_string(),
+			Self::Ansi => String::from("ans"),
+			Self::Sixel => String::from("six"),
+			Self::Heif => String::from("heic"),
 			_ => self.to_string(),
 		}
 		.to_lowercase()
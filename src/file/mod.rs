@@ -89,8 +89,8 @@ impl File {
 		if let Some(config_dir) = dirs::config_dir() {
 			let file_name = concat!(env!("CARGO_PKG_NAME"), ".conf");
 			for config_file in [
-				config_dir.join(&file_name),
-				config_dir.join(env!("CARGO_PKG_NAME")).join(&file_name),
+				config_dir.join(file_name),
+				config_dir.join(env!("CARGO_PKG_NAME")).join(file_name),
 				config_dir.join(env!("CARGO_PKG_NAME")).join("config"),
 			] {
 				if config_file.exists() {
@@ -139,6 +139,18 @@ mod tests {
 			)
 			.to_string()
 		);
+		let args = App::new("test")
+			.subcommand(
+				SubCommand::with_name("record").subcommand(
+					SubCommand::with_name("save")
+						.arg(Arg::with_name("file").takes_value(true)),
+				),
+			)
+			.get_matches_from(vec!["test", "record", "save", "rec.tiff"]);
+		assert_eq!(
+			FileFormat::Tiff,
+			FileFormat::from_args(&ArgMatches::new(&args), None)
+		);
 		for info in ["", "date", "timestamp"] {
 			let args = App::new("test")
 				.arg(Arg::with_name(info).long(&format!("--{info}")))
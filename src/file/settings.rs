@@ -4,6 +4,8 @@ use crate::edit::settings::EditSettings;
 use crate::file::format::FileFormat;
 use crate::file::info::FileInfo;
 use crate::file::File;
+use crate::image::background::Background;
+use crate::image::icc::IccSource;
 use crate::image::settings::PnmSettings;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -12,6 +14,11 @@ use std::str::FromStr;
 #[derive(Debug)]
 pub struct SaveSettings {
 	pub file: File,
+	pub extra: Vec<File>,
+	pub icc: IccSource,
+	pub comment: Option<String>,
+	pub metadata: bool,
+	pub background: Option<Background>,
 }
 
 impl SaveSettings {
@@ -19,10 +26,29 @@ impl SaveSettings {
 	 * Create a new SaveSettings object.
 	 *
 	 * @param  file
+	 * @param  extra
+	 * @param  icc
+	 * @param  comment
+	 * @param  metadata
+	 * @param  background (Option)
 	 * @return SaveSettings
 	 */
-	pub fn new(file: File) -> Self {
-		Self { file }
+	pub fn new(
+		file: File,
+		extra: Vec<File>,
+		icc: IccSource,
+		comment: Option<String>,
+		metadata: bool,
+		background: Option<Background>,
+	) -> Self {
+		Self {
+			file,
+			extra,
+			icc,
+			comment,
+			metadata,
+			background,
+		}
 	}
 
 	/**
@@ -91,13 +117,59 @@ impl SaveSettings {
 						))
 					));
 				}
-				Self::new(File::new(
-					path,
-					file_format,
-					matches.is_present("with-extension"),
-				))
+				let extra = matches
+					.values_of("output")
+					.map(|values| {
+						values
+							.filter_map(|file| {
+								let path = PathBuf::from(
+									shellexpand::full(file)
+										.map(|s| s.to_string())
+										.unwrap_or(file.to_string()),
+								);
+								match FileFormat::from_str(
+									&path
+										.extension()
+										.unwrap_or_default()
+										.to_string_lossy()
+										.to_lowercase(),
+								) {
+									Ok(format) => {
+										Some(File::new(path, format, false))
+									}
+									Err(e) => {
+										warn!("{}: {:?}", e, path);
+										None
+									}
+								}
+							})
+							.collect()
+					})
+					.unwrap_or_default();
+				Self::new(
+					File::new(
+						path,
+						file_format,
+						matches.is_present("with-extension"),
+					),
+					extra,
+					matches
+						.value_of("icc")
+						.map(IccSource::parse)
+						.unwrap_or_default(),
+					matches.value_of("comment").map(String::from),
+					!matches.is_present("no-metadata"),
+					matches.value_of("background").and_then(Background::parse),
+				)
 			}
-			None => Self::new(file_format.into_file()),
+			None => Self::new(
+				file_format.into_file(),
+				Vec::new(),
+				IccSource::default(),
+				None,
+				true,
+				None,
+			),
 		}
 	}
 }
@@ -120,12 +192,31 @@ mod tests {
 									.long("file")
 									.takes_value(true),
 							)
-							.arg(Arg::with_name("date").long("date")),
+							.arg(Arg::with_name("date").long("date"))
+							.arg(
+								Arg::with_name("output")
+									.long("output")
+									.multiple(true)
+									.number_of_values(1)
+									.takes_value(true),
+							),
 					),
 				),
 			)
 			.get_matches_from(vec![
-				"test", "capture", "jpg", "save", "--file", "test.jpg", "--date",
+				"test",
+				"capture",
+				"jpg",
+				"save",
+				"--file",
+				"test.jpg",
+				"--date",
+				"--output",
+				"test.png",
+				"--output",
+				"test.xyz",
+				"--output",
+				"test.WEBP",
 			]);
 		let matches = ArgMatches::new(&args);
 		let save_settings = SaveSettings::from_parser(
@@ -134,5 +225,13 @@ mod tests {
 		);
 		assert!(save_settings.file.path.to_str().unwrap().contains("test_"));
 		assert_eq!(FileFormat::Jpg, save_settings.file.format);
+		assert_eq!(
+			vec![FileFormat::Png, FileFormat::WebP],
+			save_settings
+				.extra
+				.into_iter()
+				.map(|file| file.format)
+				.collect::<Vec<FileFormat>>()
+		);
 	}
 }
@@ -1,9 +1,11 @@
 use crate::anim::settings::AnimSettings;
-use crate::app::AppResult;
+use crate::app::{AppError, AppResult};
 use crate::image::geometry::Geometry;
 use crate::image::Image;
 use crate::util::state::InputState;
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 /* GIF encoder configuration */
 #[derive(Clone, Copy, Debug)]
@@ -40,6 +42,62 @@ impl<'a, Output: Write> EncoderConfig<'a, Output> {
 	}
 }
 
+/* Handle for cancelling the encoding via the cancel keys or another thread */
+#[derive(Clone, Debug, Default)]
+pub struct CancelHandle {
+	cancelled: Arc<AtomicBool>,
+	input_state: Option<&'static InputState>,
+}
+
+impl CancelHandle {
+	/**
+	 * Create a new CancelHandle object.
+	 *
+	 * @param  input_state (Option)
+	 * @return CancelHandle
+	 */
+	pub fn new(input_state: Option<&'static InputState>) -> Self {
+		Self {
+			cancelled: Arc::new(AtomicBool::new(false)),
+			input_state,
+		}
+	}
+
+	/* Cancel the encoding. */
+	pub fn cancel(&self) {
+		self.cancelled.store(true, Ordering::SeqCst);
+	}
+
+	/**
+	 * Check if the encoding is cancelled.
+	 *
+	 * @return bool
+	 */
+	pub fn is_cancelled(&self) -> bool {
+		if self
+			.input_state
+			.is_some_and(|state| state.check_cancel_keys())
+		{
+			self.cancel();
+		}
+		self.cancelled.load(Ordering::SeqCst)
+	}
+
+	/**
+	 * Return an error if the encoding is cancelled.
+	 *
+	 * @return Result
+	 */
+	pub fn check(&self) -> AppResult<()> {
+		if self.is_cancelled() {
+			debug!("\n");
+			warn!("User interrupt detected.");
+			return Err(AppError::FrameError(String::from("Encoding is cancelled")));
+		}
+		Ok(())
+	}
+}
+
 /* Required GIF encoding methods */
 pub trait Encoder<'a, Output: Write> {
 	fn new(config: EncoderConfig<'a, Output>) -> AppResult<Self>
@@ -47,9 +105,22 @@ pub trait Encoder<'a, Output: Write> {
 		Self: Sized;
 	fn save(
 		self,
-		images: Vec<Image>,
-		input_state: Option<&'static InputState>,
-	) -> AppResult<()>;
+		frames: impl Iterator<Item = Image> + Send,
+		cancel: CancelHandle,
+	) -> AppResult<usize>;
+}
+
+/**
+ * Get the number of frames from the size hint of the iterator if it is exact.
+ *
+ * @param  frames
+ * @return usize (Option)
+ */
+pub fn get_frame_count(frames: &impl Iterator<Item = Image>) -> Option<usize> {
+	match frames.size_hint() {
+		(lower, Some(upper)) if lower == upper => Some(lower),
+		_ => None,
+	}
 }
 
 #[cfg(test)]
@@ -58,7 +129,9 @@ mod tests {
 	#[cfg(feature = "ski")]
 	use crate::gif::ski::GifskiEncoder;
 	use crate::gif::GifEncoder;
-	use image::Rgba;
+	use image::codecs::gif::GifDecoder;
+	use image::{AnimationDecoder, Rgb, Rgba};
+	use std::time::Duration;
 	const GIF_HEADER: &[u8] = &[0x47, 0x49, 0x46, 0x38, 0x39, 0x61];
 	fn get_config<Output: Write>(
 		output: Output,
@@ -77,10 +150,221 @@ mod tests {
 		let mut output = Vec::new();
 		let settings = AnimSettings::default();
 		let (config, images) = get_config(&mut output, &settings);
-		GifEncoder::new(config).unwrap().save(images, None).unwrap();
+		assert_eq!(
+			2,
+			GifEncoder::new(config)
+				.unwrap()
+				.save(images.into_iter(), CancelHandle::default())
+				.unwrap()
+		);
 		output.truncate(6);
 		assert_eq!(GIF_HEADER, output);
 		output.clear();
+		let (config, images) = get_config(&mut output, &settings);
+		let cancel = CancelHandle::new(None);
+		cancel.clone().cancel();
+		assert!(cancel.is_cancelled());
+		assert!(GifEncoder::new(config)
+			.unwrap()
+			.save(images.into_iter(), cancel)
+			.is_err());
+		output.clear();
+		let (config, images) = get_config(&mut output, &settings);
+		let images = images
+			.into_iter()
+			.zip([0, 125])
+			.map(|(image, ms)| image.with_timestamp(Duration::from_millis(ms)));
+		GifEncoder::new(config)
+			.unwrap()
+			.save(images, CancelHandle::default())
+			.unwrap();
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		let mut delays = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			delays.push(frame.delay);
+		}
+		assert_eq!(vec![13, 12], delays);
+		let mut stream_output = Vec::new();
+		let (config, images) = get_config(&mut stream_output, &settings);
+		let images = images
+			.into_iter()
+			.zip([0, 125])
+			.map(|(image, ms)| image.with_timestamp(Duration::from_millis(ms)));
+		assert_eq!(
+			2,
+			GifEncoder::new(config)
+				.unwrap()
+				.save_stream(images)
+				.unwrap()
+		);
+		assert_eq!(output, stream_output);
+		output.clear();
+		let geometry = Geometry::new(0, 0, 1, 1);
+		let colors = (0..(rayon::current_num_threads() * 2 + 1) as u8)
+			.map(|i| i.saturating_mul(10))
+			.collect::<Vec<u8>>();
+		let images = colors.iter().map(|c| {
+			Image::new(vec![Rgba::from([*c, *c, *c, 255])], false, geometry)
+		});
+		GifEncoder::new(EncoderConfig::new(10, geometry, &mut output, &settings))
+			.unwrap()
+			.save(images, CancelHandle::default())
+			.unwrap();
+		let mut options = gif::DecodeOptions::new();
+		options.set_color_output(gif::ColorOutput::RGBA);
+		let mut decoder = options.read_info(&output[..]).unwrap();
+		let mut decoded = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			decoded.push(frame.buffer[0]);
+		}
+		assert_eq!(colors, decoded);
+		output.clear();
+		let settings = AnimSettings {
+			global_palette: true,
+			..AnimSettings::default()
+		};
+		let (config, images) = get_config(&mut output, &settings);
+		GifEncoder::new(config)
+			.unwrap()
+			.save(images.into_iter(), CancelHandle::default())
+			.unwrap();
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		assert!(decoder.global_palette().is_some());
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			assert!(frame.palette.is_none());
+		}
+		output.clear();
+		let settings = AnimSettings {
+			lossy: Some(80),
+			..AnimSettings::default()
+		};
+		let (config, images) = get_config(&mut output, &settings);
+		GifEncoder::new(config)
+			.unwrap()
+			.save(images.into_iter(), CancelHandle::default())
+			.unwrap();
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		let mut frames = 0;
+		while decoder.read_next_frame().unwrap().is_some() {
+			frames += 1;
+		}
+		assert_eq!(2, frames);
+		output.clear();
+		let settings = AnimSettings::default();
+		let geometry = Geometry::new(0, 0, 2, 2);
+		let mut data = vec![Rgba::from([255, 0, 0, 255]); 4];
+		let mut images = vec![Image::new(data.clone(), false, geometry)];
+		data[3] = Rgba::from([0, 0, 255, 255]);
+		images.push(Image::new(data, false, geometry));
+		GifEncoder::new(EncoderConfig::new(10, geometry, &mut output, &settings))
+			.unwrap()
+			.save(images.into_iter(), CancelHandle::default())
+			.unwrap();
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		let mut areas = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			areas.push((frame.left, frame.top, frame.width, frame.height));
+			assert_eq!(gif::DisposalMethod::Keep, frame.dispose);
+		}
+		assert_eq!(vec![(0, 0, 2, 2), (1, 1, 1, 1)], areas);
+		output.clear();
+		let geometry = Geometry::new(0, 0, 2, 1);
+		let mut images = vec![
+			Image::new(
+				vec![Rgba::from([255, 0, 0, 255]), Rgba::from([0, 255, 0, 255])],
+				false,
+				geometry,
+			),
+			Image::new(
+				vec![Rgba::from([255, 0, 255, 255]), Rgba::from([0, 0, 255, 255])],
+				false,
+				geometry,
+			),
+		];
+		for image in images.iter_mut() {
+			image.set_transparent(Rgb([255, 0, 255]), 0).unwrap();
+		}
+		let reversed = images.iter().rev().cloned().collect::<Vec<Image>>();
+		GifEncoder::new(EncoderConfig::new(10, geometry, &mut output, &settings))
+			.unwrap()
+			.save(images.into_iter(), CancelHandle::default())
+			.unwrap();
+		let frames = GifDecoder::new(&output[..])
+			.unwrap()
+			.into_frames()
+			.collect_frames()
+			.unwrap();
+		assert_eq!(2, frames.len());
+		assert_eq!(&Rgba([255, 0, 0, 255]), frames[0].buffer().get_pixel(0, 0));
+		assert_eq!(0, frames[1].buffer().get_pixel(0, 0)[3]);
+		assert_eq!(&Rgba([0, 0, 255, 255]), frames[1].buffer().get_pixel(1, 0));
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		let mut disposals = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			disposals.push(frame.dispose);
+		}
+		assert_eq!(
+			vec![gif::DisposalMethod::Background, gif::DisposalMethod::Keep],
+			disposals
+		);
+		output.clear();
+		GifEncoder::new(EncoderConfig::new(10, geometry, &mut output, &settings))
+			.unwrap()
+			.save(reversed.into_iter(), CancelHandle::default())
+			.unwrap();
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		let mut disposals = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			disposals.push(frame.dispose);
+		}
+		assert_eq!(
+			vec![gif::DisposalMethod::Keep, gif::DisposalMethod::Background],
+			disposals
+		);
+		output.clear();
+		let settings = AnimSettings {
+			target_size: Some(1),
+			..AnimSettings::default()
+		};
+		let batch_size = rayon::current_num_threads().max(1);
+		let geometry = Geometry::new(0, 0, 1, 1);
+		let images = (0..batch_size * 3).map(|i| {
+			Image::new(vec![Rgba::from([255, 0, 0, 255])], false, geometry)
+				.with_timestamp(Duration::from_millis(i as u64 * 100))
+		});
+		assert_eq!(
+			batch_size + 2,
+			GifEncoder::new(EncoderConfig::new(
+				10,
+				geometry,
+				&mut output,
+				&settings
+			))
+			.unwrap()
+			.save_stream(images)
+			.unwrap()
+		);
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		let mut delays = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			delays.push(frame.delay);
+		}
+		assert_eq!(batch_size as u16 * 30, delays.iter().sum::<u16>());
+		output.clear();
+		let images = (0..batch_size * 4).map(|_| {
+			Image::new(vec![Rgba::from([255, 0, 0, 255])], false, geometry)
+		});
+		GifEncoder::new(EncoderConfig::new(10, geometry, &mut output, &settings))
+			.unwrap()
+			.save_stream(images)
+			.unwrap();
+		let mut decoder = gif::DecodeOptions::new().read_info(&output[..]).unwrap();
+		let mut delays = Vec::new();
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			delays.push(frame.delay);
+		}
+		assert!(delays.len() < batch_size * 4);
+		assert_eq!(batch_size as u16 * 40, delays.iter().sum::<u16>());
 	}
 	#[cfg(feature = "ski")]
 	#[test]
@@ -88,11 +372,22 @@ mod tests {
 		let mut output = Vec::new();
 		let settings = AnimSettings::default();
 		let (config, images) = get_config(&mut output, &settings);
-		GifskiEncoder::new(config)
-			.unwrap()
-			.save(images, None)
-			.unwrap();
+		assert_eq!(
+			2,
+			GifskiEncoder::new(config)
+				.unwrap()
+				.save(images.into_iter(), CancelHandle::default())
+				.unwrap()
+		);
 		output.truncate(6);
 		assert_eq!(GIF_HEADER, output);
+		output.clear();
+		let (config, images) = get_config(&mut output, &settings);
+		let cancel = CancelHandle::default();
+		cancel.cancel();
+		assert!(GifskiEncoder::new(config)
+			.unwrap()
+			.save(images.into_iter(), cancel)
+			.is_err());
 	}
 }
@@ -0,0 +1,199 @@
+/* Maximum number of codes in the LZW table */
+const MAX_CODES: u16 = 4096;
+
+/* Maximum lossy compression level to use while reducing the output size */
+pub const MAX_LOSSY_LEVEL: u32 = 200;
+
+/* Writer for packing the variable-length codes */
+#[derive(Debug, Default)]
+struct BitWriter {
+	data: Vec<u8>,
+	buffer: u32,
+	bits: u8,
+}
+
+impl BitWriter {
+	/**
+	 * Write a code with the given number of bits.
+	 *
+	 * @param code
+	 * @param size
+	 */
+	fn write(&mut self, code: u16, size: u8) {
+		self.buffer |= u32::from(code) << self.bits;
+		self.bits += size;
+		while self.bits >= 8 {
+			self.data.push(self.buffer as u8);
+			self.buffer >>= 8;
+			self.bits -= 8;
+		}
+	}
+
+	/**
+	 * Flush the remaining bits and return the data.
+	 *
+	 * @return Vector of u8
+	 */
+	fn finish(mut self) -> Vec<u8> {
+		if self.bits > 0 {
+			self.data.push(self.buffer as u8);
+		}
+		self.data
+	}
+}
+
+/* LZW encoder that matches similar colors for smaller output */
+#[derive(Debug)]
+pub struct LossyEncoder {
+	distances: Vec<u32>,
+	transparent: Option<u8>,
+	threshold: u32,
+}
+
+impl LossyEncoder {
+	/**
+	 * Create a new LossyEncoder object.
+	 *
+	 * @param  palette
+	 * @param  transparent (Option)
+	 * @param  lossy
+	 * @return LossyEncoder
+	 */
+	pub fn new(palette: &[u8], transparent: Option<u8>, lossy: u32) -> Self {
+		let colors = palette.chunks_exact(3).collect::<Vec<&[u8]>>();
+		let mut distances = vec![u32::MAX; 256 * 256];
+		for (i, a) in colors.iter().enumerate() {
+			for (j, b) in colors.iter().enumerate() {
+				distances[i * 256 + j] = a
+					.iter()
+					.zip(b.iter())
+					.map(|(a, b)| (i32::from(*a) - i32::from(*b)).pow(2) as u32)
+					.sum();
+			}
+		}
+		Self {
+			distances,
+			transparent,
+			threshold: lossy.saturating_mul(lossy),
+		}
+	}
+
+	/**
+	 * Get the distance between two colors of the palette.
+	 *
+	 * @param  a
+	 * @param  b
+	 * @return u32
+	 */
+	fn get_distance(&self, a: u8, b: u8) -> u32 {
+		if a == b {
+			0
+		} else if self.transparent == Some(a) || self.transparent == Some(b) {
+			u32::MAX
+		} else {
+			self.distances[usize::from(a) * 256 + usize::from(b)]
+		}
+	}
+
+	/**
+	 * Encode the indexed pixels with LZW while extending the strings
+	 * with the closest color within the threshold.
+	 *
+	 * @param  pixels
+	 * @return Vector of u8 (prefixed with the minimum code size)
+	 */
+	pub fn encode(&self, pixels: &[u8]) -> Vec<u8> {
+		let max_index = pixels.iter().copied().max().unwrap_or_default();
+		let mut min_code_size = 2;
+		while (1 << min_code_size) <= u16::from(max_index) {
+			min_code_size += 1;
+		}
+		let clear_code = 1u16 << min_code_size;
+		let mut writer = BitWriter::default();
+		let mut code_size = min_code_size + 1;
+		let mut next_code = clear_code + 2;
+		let mut children: Vec<Vec<(u8, u16)>> = vec![Vec::new(); MAX_CODES.into()];
+		writer.write(clear_code, code_size);
+		let mut pixels = pixels.iter().copied();
+		let mut current = match pixels.next() {
+			Some(pixel) => u16::from(pixel),
+			None => {
+				writer.write(clear_code + 1, code_size);
+				return [vec![min_code_size], writer.finish()].concat();
+			}
+		};
+		for pixel in pixels {
+			let matched = children[usize::from(current)]
+				.iter()
+				.map(|(index, code)| (self.get_distance(*index, pixel), *code))
+				.filter(|(distance, _)| *distance <= self.threshold)
+				.min_by_key(|(distance, _)| *distance);
+			if let Some((_, code)) = matched {
+				current = code;
+				continue;
+			}
+			writer.write(current, code_size);
+			children[usize::from(current)].push((pixel, next_code));
+			if next_code >= (1 << code_size) {
+				code_size += 1;
+			}
+			next_code += 1;
+			if next_code == MAX_CODES {
+				writer.write(clear_code, code_size);
+				children.iter_mut().for_each(|codes| codes.clear());
+				code_size = min_code_size + 1;
+				next_code = clear_code + 2;
+			}
+			current = u16::from(pixel);
+		}
+		writer.write(current, code_size);
+		writer.write(clear_code + 1, code_size);
+		[vec![min_code_size], writer.finish()].concat()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use gif::{DecodeOptions, Encoder, Frame};
+	use pretty_assertions::assert_eq;
+	use std::borrow::Cow;
+	fn decode(palette: &[u8], data: Vec<u8>, width: u16, height: u16) -> Vec<u8> {
+		let mut output = Vec::new();
+		let mut encoder = Encoder::new(&mut output, width, height, palette).unwrap();
+		let frame = Frame {
+			width,
+			height,
+			buffer: Cow::Owned(data),
+			..Frame::default()
+		};
+		encoder.write_lzw_pre_encoded_frame(&frame).unwrap();
+		drop(encoder);
+		let mut options = DecodeOptions::new();
+		options.set_color_output(gif::ColorOutput::Indexed);
+		let mut decoder = options.read_info(&output[..]).unwrap();
+		decoder.read_next_frame().unwrap().unwrap().buffer.to_vec()
+	}
+	#[test]
+	fn test_lossy_encoder() {
+		let palette = [0, 0, 0, 10, 10, 10, 255, 255, 255, 250, 0, 0, 0, 0, 250];
+		let pixels = (0..100 * 100)
+			.map(|i| ((i * 7 + i / 13) % 5) as u8)
+			.collect::<Vec<u8>>();
+		let lossless = LossyEncoder::new(&palette, None, 0).encode(&pixels);
+		assert_eq!(pixels, decode(&palette, lossless.clone(), 100, 100));
+		assert_eq!(3, lossless[0]);
+		let lossy = LossyEncoder::new(&palette, None, 20).encode(&pixels);
+		assert!(lossy.len() < lossless.len());
+		let decoded = decode(&palette, lossy, 100, 100);
+		for (pixel, decoded) in pixels.iter().zip(decoded) {
+			assert!(pixel == &decoded || pixel + decoded == 1);
+		}
+		let transparent = LossyEncoder::new(&palette, Some(1), 20).encode(&pixels);
+		assert_eq!(pixels, decode(&palette, transparent, 100, 100));
+		assert_eq!(
+			vec![2, 0x2C],
+			LossyEncoder::new(&palette, None, 0).encode(&[])
+		);
+	}
+}
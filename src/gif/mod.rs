@@ -1,21 +1,47 @@
 pub mod encoder;
+pub mod lossy;
+pub mod palette;
+pub mod progress;
 #[cfg(feature = "ski")]
 pub mod ski;
+pub mod trim;
 
 use crate::anim::settings::AnimSettings;
-use crate::app::AppResult;
-use crate::gif::encoder::{Encoder, EncoderConfig};
+use crate::app::{AppError, AppResult};
+use crate::gif::encoder::{get_frame_count, CancelHandle, Encoder, EncoderConfig};
+use crate::gif::lossy::{LossyEncoder, MAX_LOSSY_LEVEL};
+use crate::gif::palette::GlobalPalette;
+use crate::gif::progress::{EncoderProgress, ProgressWriter};
+use crate::image::geometry::Geometry;
 use crate::image::Image;
-use crate::util::state::InputState;
-use gif::{Encoder as BaseEncoder, Frame, Repeat};
+use crate::util::progress::ProgressBar;
+use bytesize::ByteSize;
+use gif::{DisposalMethod, Encoder as BaseEncoder, Frame, Repeat};
 use image::ExtendedColorType;
+use rayon::prelude::*;
+use std::borrow::Cow;
 use std::io::{self, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/* Ratios of the maximum size to reach for increasing the lossy level
+ * and the difference percentage threshold for dropping similar frames */
+const BACK_OFF_LEVELS: [(f64, u32, f64); 3] =
+	[(0.5, 40, 0.), (0.75, 80, 1.), (1., 120, 5.)];
 
 /* GIF encoder and settings */
 pub struct GifEncoder<'a, Output: Write> {
 	fps: u32,
-	encoder: BaseEncoder<Output>,
+	geometry: Geometry,
+	output: Option<Output>,
+	encoder: Option<BaseEncoder<ProgressWriter<Output>>>,
 	settings: &'a AnimSettings,
+	lossy: Option<u32>,
+	max_size: Option<u64>,
+	back_off_level: usize,
+	progress: EncoderProgress,
+	progress_file: Option<PathBuf>,
 }
 
 impl<'a, Output: Write> Encoder<'a, Output> for GifEncoder<'a, Output> {
@@ -26,69 +52,406 @@ impl<'a, Output: Write> Encoder<'a, Output> for GifEncoder<'a, Output> {
 	 * @return GifEncoder (Result)
 	 */
 	fn new(config: EncoderConfig<'a, Output>) -> AppResult<Self> {
-		let mut encoder = BaseEncoder::new(
-			config.output,
-			config.geometry.width.try_into().unwrap_or_default(),
-			config.geometry.height.try_into().unwrap_or_default(),
-			&[],
-		)?;
-		encoder.set_repeat(match config.settings.repeat {
-			n if n >= 0 => Repeat::Finite(n.try_into().unwrap_or_default()),
-			_ => Repeat::Infinite,
-		})?;
 		Ok(Self {
 			fps: config.fps,
-			encoder,
+			geometry: config.geometry,
+			output: Some(config.output),
+			encoder: None,
 			settings: config.settings,
+			lossy: config.settings.lossy,
+			max_size: None,
+			back_off_level: 0,
+			progress: EncoderProgress::default(),
+			progress_file: None,
 		})
 	}
 
 	/**
-	 * Encode images as frame and write to the GIF file.
+	 * Encode the frames as they are received and write to the GIF file.
+	 * The frames are only buffered for computing the global palette.
 	 *
-	 * @param  images
-	 * @param  input_state (Option)
-	 * @param  Result
+	 * @param  frames
+	 * @param  cancel
+	 * @return usize (Result)
 	 */
 	fn save(
+		self,
+		frames: impl Iterator<Item = Image> + Send,
+		cancel: CancelHandle,
+	) -> AppResult<usize> {
+		if !self.progress.palette.is_empty() {
+			let palette =
+				Some(GlobalPalette::from_colors(self.progress.palette.clone()));
+			let total = get_frame_count(&frames);
+			self.encode(frames, total, palette, cancel)
+		} else if self.settings.global_palette && self.settings.palette.is_none() {
+			let images = frames.collect::<Vec<Image>>();
+			let palette = self.get_palette(&images)?;
+			let total = Some(images.len());
+			self.encode(images.into_iter(), total, palette, cancel)
+		} else {
+			let palette = self.get_palette(&[])?;
+			let total = get_frame_count(&frames);
+			self.encode(frames, total, palette, cancel)
+		}
+	}
+}
+
+impl<'a, Output: Write> GifEncoder<'a, Output> {
+	/**
+	 * Save the progress to the given file while encoding and
+	 * resume from the previous progress if there is any.
+	 *
+	 * @param  path
+	 * @param  progress (Option)
+	 * @return GifEncoder
+	 */
+	pub fn with_progress(
 		mut self,
-		images: Vec<Image>,
-		input_state: Option<&'static InputState>,
-	) -> AppResult<()> {
-		let speed = 30
-			- self.settings.map_range(
-				self.settings.quality.into(),
-				(1., 100.),
-				(0., 29.),
-			) as i32;
-		for (i, image) in images.iter().enumerate() {
-			let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
-			info!("Saving... ({:.1}%)\r", percentage);
-			debug!(
-				"Encoding... ({:.1}%) [{}/{}]\r",
-				percentage,
-				i + 1,
-				images.len()
+		path: PathBuf,
+		progress: Option<EncoderProgress>,
+	) -> Self {
+		self.progress = progress.unwrap_or_default();
+		self.progress_file = Some(path);
+		self
+	}
+
+	/**
+	 * Encode the frames as they are received and write to the GIF file
+	 * while backing off the quality if the target size is exceeded.
+	 *
+	 * @param  frames
+	 * @return usize (Result)
+	 */
+	pub fn save_stream(
+		mut self,
+		frames: impl Iterator<Item = Image> + Send,
+	) -> AppResult<usize> {
+		self.max_size = self.settings.target_size;
+		self.save(frames, CancelHandle::default())
+	}
+
+	/**
+	 * Get the palette from file or compute it from the frames if enabled.
+	 *
+	 * @param  images
+	 * @return GlobalPalette (Option)
+	 */
+	fn get_palette(&self, images: &[Image]) -> AppResult<Option<GlobalPalette>> {
+		Ok(match &self.settings.palette {
+			Some(path) => Some(GlobalPalette::from_file(path)?),
+			None if self.settings.global_palette && !images.is_empty() => {
+				info!("Computing the global palette...");
+				Some(GlobalPalette::from_frames(images, self.get_speed())?)
+			}
+			None => None,
+		})
+	}
+
+	/**
+	 * Get the speed of the quantization from the quality.
+	 *
+	 * @return i32
+	 */
+	fn get_speed(&self) -> i32 {
+		30 - self.settings.map_range(
+			self.settings.quality.into(),
+			(1., 100.),
+			(0., 29.),
+		) as i32
+	}
+
+	/**
+	 * Increase the lossy level progressively as the written size
+	 * approaches the maximum size.
+	 *
+	 * @param  written
+	 * @return f64 (difference percentage for dropping similar frames)
+	 */
+	fn back_off(&mut self, written: u64) -> f64 {
+		let max_size = match self.max_size {
+			Some(max_size) => max_size,
+			None => return 0.,
+		};
+		while let Some((ratio, lossy, threshold)) =
+			BACK_OFF_LEVELS.get(self.back_off_level)
+		{
+			if (written as f64) < max_size as f64 * ratio {
+				break;
+			}
+			let lossy = (self.settings.lossy.unwrap_or_default() + lossy)
+				.min(MAX_LOSSY_LEVEL);
+			self.lossy = Some(lossy);
+			self.back_off_level += 1;
+			warn!(
+				"Output size reached {} of {}, increasing the lossy level to {}",
+				ByteSize(written),
+				ByteSize(max_size),
+				lossy
 			);
+			if *threshold > 0. {
+				warn!(
+					"Dropping the frames that differ less than {}% from the previous",
+					threshold
+				);
+			}
+		}
+		BACK_OFF_LEVELS[..self.back_off_level]
+			.last()
+			.map_or(0., |(_, _, threshold)| *threshold)
+	}
+
+	/**
+	 * Write the header of the GIF file with the given global palette.
+	 *
+	 * @param  palette
+	 * @return Result
+	 */
+	fn init(&mut self, palette: &[u8]) -> AppResult<()> {
+		if let Some(output) = self.output.take() {
+			let mut encoder = BaseEncoder::new(
+				ProgressWriter::new(output, &self.progress),
+				self.geometry.width.try_into().unwrap_or_default(),
+				self.geometry.height.try_into().unwrap_or_default(),
+				palette,
+			)?;
+			encoder.set_repeat(match self.settings.repeat {
+				n if n >= 0 => Repeat::Finite(n.try_into().unwrap_or_default()),
+				_ => Repeat::Infinite,
+			})?;
+			if self.progress.frames == 0 {
+				self.progress.header = encoder.get_ref().position;
+			}
+			self.encoder = Some(encoder);
+		}
+		Ok(())
+	}
+
+	/**
+	 * Encode the frames in batches while keeping only the next frame
+	 * for calculating the delays and the disposal, and the previous
+	 * frame for cropping the changed area. Frames are kept on the canvas
+	 * for the cropped frames and cleared before the transparent frames
+	 * (including the first frame when the animation loops).
+	 *
+	 * @param  frames
+	 * @param  total (Option)
+	 * @param  palette (Option)
+	 * @param  cancel
+	 * @return usize (Result)
+	 */
+	fn encode(
+		mut self,
+		mut frames: impl Iterator<Item = Image>,
+		total: Option<usize>,
+		palette: Option<GlobalPalette>,
+		cancel: CancelHandle,
+	) -> AppResult<usize> {
+		let speed = self.get_speed();
+		let interval = Duration::from_secs_f64(1. / f64::from(self.fps.max(1)));
+		let colors = palette.as_ref().map(|p| p.get_colors()).unwrap_or_default();
+		self.init(&colors)?;
+		let resume = self.progress.frames;
+		if resume > 0 {
+			info!("Resuming the encoding after {} frames...", resume);
+		}
+		self.progress = EncoderProgress {
+			total: total.unwrap_or_default(),
+			width: self.geometry.width,
+			height: self.geometry.height,
+			palette: colors,
+			..self.progress.clone()
+		};
+		let mut progress_bar = total.map(|total| ProgressBar::new("Saving", total));
+		let mut elapsed = (0., 0);
+		let (mut delay_sum, mut delay_count) = (Duration::ZERO, 0);
+		let batch_size = rayon::current_num_threads().max(1);
+		let mut batch = Vec::with_capacity(batch_size);
+		let mut current = frames.next();
+		let mut previous: Option<Image> = None;
+		let (mut opaque, mut first_opaque) = (None, None);
+		let (mut count, mut dropped, mut written) = (0, 0, 0);
+		while let Some(mut image) = current {
+			let mut next = frames.next();
+			let threshold = self.back_off(written);
+			while let Some(candidate) = &next {
+				if candidate.get_diff_percentage(&image)? >= threshold {
+					break;
+				}
+				let Some(following) = frames.next() else {
+					break;
+				};
+				if let Some(similar) = next.replace(following) {
+					if image.delay.is_some() || image.timestamp.is_none() {
+						image.delay = Some(
+							image.delay.unwrap_or(interval)
+								+ similar.delay.unwrap_or(interval),
+						);
+					}
+				}
+				dropped += 1;
+			}
+			count += 1;
+			match total {
+				Some(total) => {
+					let percentage = (count as f64 / total as f64) * 100.;
+					debug!(
+						"Encoding... ({:.1}%) [{}/{}]\r",
+						percentage, count, total
+					);
+				}
+				None => debug!("Encoded frames: {}\r", count),
+			}
 			io::stdout().flush()?;
-			if let Some(state) = input_state {
-				if state.check_cancel_keys() {
-					info!("\n");
-					warn!("User interrupt detected.");
-					panic!("Failed to write the frames")
+			cancel.check()?;
+			let delay = match (image.delay, &next, image.timestamp) {
+				(Some(delay), _, _) => Some(delay),
+				(None, Some(next), Some(timestamp)) => next.timestamp.map(|next| {
+					let delay = next.saturating_sub(timestamp);
+					delay_sum += delay;
+					delay_count += 1;
+					delay
+				}),
+				(None, None, Some(_)) if delay_count > 0 => {
+					Some(delay_sum / delay_count)
+				}
+				_ => None,
+			};
+			let delay = match delay {
+				Some(delay) => {
+					elapsed.0 += delay.as_secs_f64() * 1e2;
+					let delay =
+						(elapsed.0.round() as u64).saturating_sub(elapsed.1).max(1);
+					elapsed.1 += delay;
+					delay.try_into().unwrap_or(u16::MAX)
+				}
+				None => (1e2 / self.fps as f32) as u16,
+			};
+			let is_opaque = match opaque.take() {
+				Some(is_opaque) => is_opaque,
+				None => image.is_opaque()?,
+			};
+			let area = match &previous {
+				Some(previous) if is_opaque => image
+					.get_diff_area(previous)?
+					.unwrap_or(Geometry::new(0, 0, 1, 1)),
+				_ => {
+					Geometry::new(0, 0, image.geometry.width, image.geometry.height)
 				}
+			};
+			let first_opaque = *first_opaque.get_or_insert(is_opaque);
+			let next_opaque = match &next {
+				Some(next) => next.is_opaque()?,
+				None => first_opaque,
+			};
+			opaque = Some(next_opaque);
+			let dispose = if next_opaque {
+				DisposalMethod::Keep
+			} else {
+				DisposalMethod::Background
+			};
+			if count > resume {
+				batch.push((image.crop(area)?, delay, dispose));
 			}
-			let mut frame = Frame::from_rgba_speed(
-				image.geometry.width.try_into().unwrap_or_default(),
-				image.geometry.height.try_into().unwrap_or_default(),
-				&mut image.get_data(ExtendedColorType::Rgba8),
-				speed,
-			);
-			frame.delay = (1e2 / self.fps as f32) as u16;
-			frame.make_lzw_pre_encoded();
-			self.encoder.write_lzw_pre_encoded_frame(&frame)?;
+			previous = Some(image);
+			if batch.len() >= batch_size || next.is_none() {
+				written += self.write_frames(
+					mem::take(&mut batch),
+					speed,
+					palette.as_ref(),
+				)?;
+				self.save_progress(count)?;
+				if let Some(progress_bar) = progress_bar.as_mut() {
+					progress_bar.set(count);
+				}
+			}
+			current = next;
+		}
+		debug!("\n");
+		if let Some(mut progress_bar) = progress_bar {
+			progress_bar.finish();
+		}
+		if dropped > 0 {
+			warn!("Dropped {} similar frames for reducing the size.", dropped);
+		}
+		Ok(count)
+	}
+
+	/**
+	 * Save the number of encoded frames and the output position.
+	 *
+	 * @param  frames
+	 * @return Result
+	 */
+	fn save_progress(&mut self, frames: usize) -> AppResult<()> {
+		if let (Some(path), Some(encoder)) =
+			(&self.progress_file, self.encoder.as_mut())
+		{
+			encoder.get_mut().flush()?;
+			self.progress.frames = frames;
+			self.progress.offset = encoder.get_ref().position;
+			self.progress.save(path)?;
 		}
-		info!("\n");
 		Ok(())
 	}
+
+	/**
+	 * Quantize the frames in parallel and write them in order.
+	 *
+	 * @param  frames (cropped to the changed area)
+	 * @param  speed
+	 * @param  palette (Option)
+	 * @return u64 (approximate number of written bytes)
+	 */
+	fn write_frames(
+		&mut self,
+		frames: Vec<(Image, u16, DisposalMethod)>,
+		speed: i32,
+		palette: Option<&GlobalPalette>,
+	) -> AppResult<u64> {
+		let encoder = self.encoder.as_mut().ok_or_else(|| {
+			AppError::FrameError(String::from("GIF encoder is not initialized"))
+		})?;
+		let frames = frames
+			.into_par_iter()
+			.map(|(image, delay, dispose)| {
+				let mut frame = match palette {
+					Some(palette) => palette.get_frame(&image)?,
+					None => Frame::from_rgba_speed(
+						image.geometry.width.try_into().unwrap_or_default(),
+						image.geometry.height.try_into().unwrap_or_default(),
+						&mut image.get_data(ExtendedColorType::Rgba8)?,
+						speed,
+					),
+				};
+				frame.delay = delay;
+				frame.left = image.geometry.x.try_into().unwrap_or_default();
+				frame.top = image.geometry.y.try_into().unwrap_or_default();
+				frame.dispose = dispose;
+				match self.lossy {
+					Some(lossy) => {
+						let colors = frame
+							.palette
+							.clone()
+							.or_else(|| palette.map(|p| p.get_colors()))
+							.unwrap_or_default();
+						frame.buffer = Cow::Owned(
+							LossyEncoder::new(&colors, frame.transparent, lossy)
+								.encode(&frame.buffer),
+						);
+					}
+					None => frame.make_lzw_pre_encoded(),
+				}
+				Ok(frame)
+			})
+			.collect::<AppResult<Vec<Frame<'static>>>>()?;
+		let mut written = 0;
+		for frame in frames {
+			encoder.write_lzw_pre_encoded_frame(&frame)?;
+			written += (frame.buffer.len()
+				+ frame.buffer.len() / 255
+				+ frame.palette.as_ref().map_or(0, Vec::len)) as u64;
+		}
+		Ok(written)
+	}
 }
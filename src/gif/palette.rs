@@ -0,0 +1,317 @@
+use crate::app::{AppError, AppResult};
+use crate::image::Image;
+use color_quant::NeuQuant;
+use gif::Frame;
+use image::ExtendedColorType;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/* Maximum number of frames to sample for the palette */
+const MAX_SAMPLE_FRAMES: usize = 16;
+/* Maximum number of pixels to sample from each frame */
+const MAX_SAMPLE_PIXELS: usize = 65536;
+/* Number of colors in the palette */
+const PALETTE_COLORS: usize = 256;
+/* Index of the palette entry that is used for the transparent pixels */
+const TRANSPARENT_INDEX: u8 = 255;
+
+/* Header of the GIMP palette files */
+const GPL_HEADER: &str = "GIMP Palette";
+
+/* Color palette that is shared between the frames */
+pub struct GlobalPalette {
+	colors: Vec<u8>,
+	quantizer: Option<NeuQuant>,
+}
+
+impl GlobalPalette {
+	/**
+	 * Create a new GlobalPalette object from the sampled frames.
+	 *
+	 * @param  images
+	 * @param  speed
+	 * @return GlobalPalette (Result)
+	 */
+	pub fn from_frames(images: &[Image], speed: i32) -> AppResult<Self> {
+		let step = images.len().div_ceil(MAX_SAMPLE_FRAMES).max(1);
+		let mut pixels = Vec::new();
+		for image in images.iter().step_by(step) {
+			let data = Self::get_pixels(image)?;
+			let pixel_step = (data.len() / 4).div_ceil(MAX_SAMPLE_PIXELS).max(1);
+			data.chunks_exact(4)
+				.step_by(pixel_step)
+				.for_each(|pixel| pixels.extend_from_slice(pixel));
+		}
+		debug!("Sampled {} pixels for the global palette", pixels.len() / 4);
+		let quantizer =
+			NeuQuant::new(speed, usize::from(TRANSPARENT_INDEX), &pixels);
+		Ok(Self {
+			colors: Self::pad_colors(quantizer.color_map_rgb()),
+			quantizer: Some(quantizer),
+		})
+	}
+
+	/**
+	 * Create a new GlobalPalette object from the RGB colors.
+	 *
+	 * @param  colors
+	 * @return GlobalPalette
+	 */
+	pub fn from_colors(colors: Vec<u8>) -> Self {
+		Self {
+			colors: Self::pad_colors(colors),
+			quantizer: None,
+		}
+	}
+
+	/**
+	 * Fill the palette up to the full size by repeating the first color
+	 * so that the transparent index always refers to a palette entry.
+	 * Repeated colors are never selected since the first match is used.
+	 *
+	 * @param  colors
+	 * @return Vector of u8
+	 */
+	fn pad_colors(mut colors: Vec<u8>) -> Vec<u8> {
+		let first = colors.get(..3).map(|c| [c[0], c[1], c[2]]);
+		colors.truncate(PALETTE_COLORS * 3);
+		while colors.len() < PALETTE_COLORS * 3 {
+			colors.extend(first.unwrap_or_default());
+		}
+		colors
+	}
+
+	/**
+	 * Create a new GlobalPalette object from a palette file.
+	 *
+	 * @param  path
+	 * @return GlobalPalette (Result)
+	 */
+	pub fn from_file(path: &Path) -> AppResult<Self> {
+		let colors = Self::parse(&fs::read_to_string(path)?).ok_or_else(|| {
+			AppError::FrameError(format!("Invalid palette file: {path:?}"))
+		})?;
+		debug!("Loaded {} colors from {:?}", colors.len() / 3, path);
+		Ok(Self::from_colors(colors))
+	}
+
+	/**
+	 * Parse the colors from a GIMP palette (.gpl) or a hex list (.hex).
+	 *
+	 * @param  palette
+	 * @return Vector of u8 (Option)
+	 */
+	fn parse(palette: &str) -> Option<Vec<u8>> {
+		let gpl = palette.trim_start().starts_with(GPL_HEADER);
+		let mut colors = Vec::new();
+		for line in palette.lines().map(str::trim) {
+			if line.is_empty()
+				|| line.starts_with(';')
+				|| (gpl && !line.starts_with(|c: char| c.is_ascii_digit()))
+			{
+				continue;
+			} else if gpl {
+				let rgb = line
+					.split_whitespace()
+					.take(3)
+					.map(|v| v.parse::<u8>().ok())
+					.collect::<Option<Vec<u8>>>()?;
+				colors.extend(rgb.get(..3)?);
+			} else {
+				let hex = hex::decode(line.trim_start_matches('#')).ok()?;
+				colors.extend(hex.get(..3)?);
+			}
+		}
+		if colors.is_empty() || colors.len() > PALETTE_COLORS * 3 {
+			None
+		} else {
+			Some(colors)
+		}
+	}
+
+	/**
+	 * Get the RGBA data of the image with opaque non-transparent pixels.
+	 *
+	 * @param  image
+	 * @return Vector of u8 (Result)
+	 */
+	fn get_pixels(image: &Image) -> AppResult<Vec<u8>> {
+		let mut data = image.get_data(ExtendedColorType::Rgba8)?;
+		data.chunks_exact_mut(4)
+			.filter(|pixel| pixel[3] != 0)
+			.for_each(|pixel| pixel[3] = 0xFF);
+		Ok(data)
+	}
+
+	/**
+	 * Get the colors of the palette in RGB format.
+	 *
+	 * @return Vector of u8
+	 */
+	pub fn get_colors(&self) -> Vec<u8> {
+		self.colors.clone()
+	}
+
+	/**
+	 * Get the index of the closest color in the palette
+	 * (excluding the entry that is reserved for transparency if needed).
+	 *
+	 * @param  pixel
+	 * @param  transparent
+	 * @return u8
+	 */
+	fn index_of(&self, pixel: &[u8], transparent: bool) -> u8 {
+		match &self.quantizer {
+			Some(quantizer) => quantizer.index_of(pixel) as u8,
+			None => self
+				.colors
+				.chunks_exact(3)
+				.take(if transparent {
+					usize::from(TRANSPARENT_INDEX)
+				} else {
+					PALETTE_COLORS
+				})
+				.enumerate()
+				.min_by_key(|(_, color)| {
+					color
+						.iter()
+						.zip(pixel)
+						.map(|(c, p)| (i32::from(*c) - i32::from(*p)).pow(2))
+						.sum::<i32>()
+				})
+				.map(|(i, _)| i as u8)
+				.unwrap_or_default(),
+		}
+	}
+
+	/**
+	 * Get a frame with the pixels indexed in the palette.
+	 * Transparent pixels are mapped to the last palette entry
+	 * which is not used for the other pixels of the frame.
+	 *
+	 * @param  image
+	 * @return Frame (Result)
+	 */
+	pub fn get_frame(&self, image: &Image) -> AppResult<Frame<'static>> {
+		let data = Self::get_pixels(image)?;
+		let transparent = data
+			.chunks_exact(4)
+			.any(|pixel| pixel[3] == 0)
+			.then_some(TRANSPARENT_INDEX);
+		let mut indices = HashMap::new();
+		let buffer = data
+			.chunks_exact(4)
+			.map(|pixel| {
+				if pixel[3] == 0 {
+					TRANSPARENT_INDEX
+				} else {
+					*indices
+						.entry([pixel[0], pixel[1], pixel[2]])
+						.or_insert_with(|| {
+							self.index_of(pixel, transparent.is_some())
+						})
+				}
+			})
+			.collect::<Vec<u8>>();
+		Ok(Frame::from_indexed_pixels(
+			image.geometry.width.try_into().unwrap_or_default(),
+			image.geometry.height.try_into().unwrap_or_default(),
+			&buffer,
+			transparent,
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image::geometry::Geometry;
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_global_palette() -> AppResult<()> {
+		let geometry = Geometry::new(0, 0, 2, 1);
+		let images = vec![
+			Image::new(
+				vec![Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])],
+				false,
+				geometry,
+			),
+			Image::new(
+				vec![Rgba([0, 0, 255, 255]), Rgba([255, 0, 0, 0])],
+				true,
+				geometry,
+			),
+		];
+		let palette = GlobalPalette::from_frames(&images, 10)?;
+		assert_eq!(PALETTE_COLORS * 3, palette.get_colors().len());
+		let (first, second) = (
+			palette.get_frame(&images[0])?,
+			palette.get_frame(&images[1])?,
+		);
+		assert_eq!(None, first.palette);
+		assert_eq!(None, first.transparent);
+		assert_eq!(first.buffer[1], second.buffer[0]);
+		assert_eq!(Some(TRANSPARENT_INDEX), second.transparent);
+		assert_eq!(TRANSPARENT_INDEX, second.buffer[1]);
+		assert_ne!(TRANSPARENT_INDEX, first.buffer[0]);
+		let image = Image::new(
+			vec![Rgba([255, 0, 0, 255]), Rgba([255, 0, 0, 0])],
+			true,
+			geometry,
+		);
+		let frame = palette.get_frame(&image)?;
+		assert_eq!(Some(TRANSPARENT_INDEX), frame.transparent);
+		assert_eq!(first.buffer[0], frame.buffer[0]);
+		assert_eq!(TRANSPARENT_INDEX, frame.buffer[1]);
+		Ok(())
+	}
+	#[test]
+	fn test_palette_file() -> AppResult<()> {
+		let gpl = "GIMP Palette\nName: Test\nColumns: 2\n#\n\
+			255   0   0\tRed\n  0   0 255\tBlue\n";
+		assert_eq!(Some(vec![255, 0, 0, 0, 0, 255]), GlobalPalette::parse(gpl));
+		let hex = "; Test palette\nff0000\n#0000FF\n\n";
+		assert_eq!(Some(vec![255, 0, 0, 0, 0, 255]), GlobalPalette::parse(hex));
+		assert_eq!(None, GlobalPalette::parse("xyz"));
+		assert_eq!(None, GlobalPalette::parse(""));
+		assert_eq!(
+			None,
+			GlobalPalette::parse(&"000000\n".repeat(PALETTE_COLORS + 1))
+		);
+		let full = (0..PALETTE_COLORS)
+			.map(|i| format!("{i:02x}0000\n"))
+			.collect::<String>();
+		let palette = GlobalPalette::from_colors(
+			GlobalPalette::parse(&full).expect("failed to parse 256 colors"),
+		);
+		assert_eq!(PALETTE_COLORS * 3, palette.get_colors().len());
+		let geometry = Geometry::new(0, 0, 2, 1);
+		let image = Image::new(
+			vec![Rgba([255, 0, 0, 255]), Rgba([0, 0, 0, 255])],
+			false,
+			geometry,
+		);
+		let frame = palette.get_frame(&image)?;
+		assert_eq!(None, frame.transparent);
+		assert_eq!(vec![255, 0], frame.buffer.to_vec());
+		let image = Image::new(
+			vec![Rgba([255, 0, 0, 255]), Rgba([0, 0, 0, 0])],
+			true,
+			geometry,
+		);
+		let frame = palette.get_frame(&image)?;
+		assert_eq!(Some(TRANSPARENT_INDEX), frame.transparent);
+		assert_eq!(vec![254, TRANSPARENT_INDEX], frame.buffer.to_vec());
+		let palette = GlobalPalette::from_colors(GlobalPalette::parse(hex).unwrap());
+		assert_eq!(PALETTE_COLORS * 3, palette.get_colors().len());
+		let image = Image::new(
+			vec![Rgba([200, 10, 30, 255]), Rgba([10, 20, 150, 255])],
+			false,
+			Geometry::new(0, 0, 2, 1),
+		);
+		assert_eq!(vec![0, 1], palette.get_frame(&image)?.buffer.to_vec());
+		Ok(())
+	}
+}
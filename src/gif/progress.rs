@@ -0,0 +1,195 @@
+use crate::app::AppResult;
+use crate::image::geometry::Geometry;
+use serde_json::Value;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/* Extension of the file that keeps the encoder progress */
+const PROGRESS_EXTENSION: &str = "progress";
+
+/* Progress of the GIF encoder for resuming an interrupted encoding */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncoderProgress {
+	pub frames: usize,
+	pub total: usize,
+	pub width: u32,
+	pub height: u32,
+	pub header: u64,
+	pub offset: u64,
+	pub palette: Vec<u8>,
+}
+
+impl EncoderProgress {
+	/**
+	 * Get the path of the progress file for the given output file.
+	 *
+	 * @param  output
+	 * @return PathBuf
+	 */
+	pub fn get_path(output: &Path) -> PathBuf {
+		let mut path = OsString::from(output.as_os_str());
+		path.push(format!(".{PROGRESS_EXTENSION}"));
+		PathBuf::from(path)
+	}
+
+	/**
+	 * Load the progress from a file if it exists.
+	 *
+	 * @param  path
+	 * @return EncoderProgress (Option)
+	 */
+	pub fn from_file(path: &Path) -> Option<Self> {
+		let progress = fs::read_to_string(path).ok()?;
+		let progress = Self::parse(&progress);
+		if progress.is_none() {
+			warn!("Invalid progress file: {:?}", path);
+		}
+		progress
+	}
+
+	/**
+	 * Parse the progress from JSON.
+	 *
+	 * @param  progress
+	 * @return EncoderProgress (Option)
+	 */
+	fn parse(progress: &str) -> Option<Self> {
+		let value = serde_json::from_str::<Value>(progress).ok()?;
+		let get = |key: &str| value.get(key).and_then(Value::as_u64);
+		Some(Self {
+			frames: get("frames")?.try_into().ok()?,
+			total: get("total")?.try_into().ok()?,
+			width: get("width")?.try_into().ok()?,
+			height: get("height")?.try_into().ok()?,
+			header: get("header")?,
+			offset: get("offset")?,
+			palette: hex::decode(value.get("palette")?.as_str()?).ok()?,
+		})
+		.filter(|progress| {
+			progress.frames <= progress.total && progress.header <= progress.offset
+		})
+	}
+
+	/**
+	 * Check if the progress belongs to the frames to encode.
+	 *
+	 * @param  total
+	 * @param  geometry
+	 * @return bool
+	 */
+	pub fn is_compatible(&self, total: usize, geometry: Geometry) -> bool {
+		self.total == total
+			&& self.width == geometry.width
+			&& self.height == geometry.height
+	}
+
+	/**
+	 * Save the progress to a file.
+	 *
+	 * @param  path
+	 * @return Result
+	 */
+	pub fn save(&self, path: &Path) -> AppResult<()> {
+		fs::write(path, self.to_json())?;
+		Ok(())
+	}
+
+	/**
+	 * Get the progress in JSON format.
+	 *
+	 * @return String
+	 */
+	fn to_json(&self) -> String {
+		format!(
+			"{{\"frames\":{},\"total\":{},\"width\":{},\"height\":{},\
+			\"header\":{},\"offset\":{},\"palette\":\"{}\"}}",
+			self.frames,
+			self.total,
+			self.width,
+			self.height,
+			self.header,
+			self.offset,
+			hex::encode(&self.palette)
+		)
+	}
+}
+
+/* Writer that keeps track of the output position and skips the
+ * bytes that are already written before resuming */
+#[derive(Debug)]
+pub struct ProgressWriter<Output: Write> {
+	inner: Output,
+	skip: u64,
+	pub position: u64,
+}
+
+impl<Output: Write> ProgressWriter<Output> {
+	/**
+	 * Create a new ProgressWriter object.
+	 *
+	 * @param  inner
+	 * @param  progress
+	 * @return ProgressWriter
+	 */
+	pub fn new(inner: Output, progress: &EncoderProgress) -> Self {
+		Self {
+			inner,
+			skip: progress.header,
+			position: progress.offset - progress.header,
+		}
+	}
+}
+
+impl<Output: Write> Write for ProgressWriter<Output> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if self.skip > 0 {
+			let skipped = buf.len().min(self.skip.try_into().unwrap_or(usize::MAX));
+			self.skip -= skipped as u64;
+			self.position += skipped as u64;
+			return Ok(skipped);
+		}
+		let written = self.inner.write(buf)?;
+		self.position += written as u64;
+		Ok(written)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_encoder_progress() {
+		let progress = EncoderProgress {
+			frames: 8,
+			total: 10,
+			width: 2,
+			height: 1,
+			header: 3,
+			offset: 6,
+			palette: vec![0, 255, 16],
+		};
+		assert_eq!(
+			Some(progress.clone()),
+			EncoderProgress::parse(&progress.to_json())
+		);
+		assert_eq!(None, EncoderProgress::parse("{\"frames\":1}"));
+		assert!(progress.is_compatible(10, Geometry::new(0, 0, 2, 1)));
+		assert!(!progress.is_compatible(9, Geometry::new(0, 0, 2, 1)));
+		assert_eq!(
+			PathBuf::from("dir/test.gif.progress"),
+			EncoderProgress::get_path(Path::new("dir/test.gif"))
+		);
+		let mut output = Vec::new();
+		let mut writer = ProgressWriter::new(&mut output, &progress);
+		writer.write_all(b"GIFabc").unwrap();
+		assert_eq!(9, writer.position);
+		assert_eq!(b"abc".to_vec(), output);
+	}
+}
@@ -1,9 +1,10 @@
 use crate::app::AppResult;
-use crate::gif::encoder::{Encoder, EncoderConfig};
+use crate::gif::encoder::{get_frame_count, CancelHandle, Encoder, EncoderConfig};
 use crate::image::Image;
-use crate::util::state::InputState;
+use crate::util::progress::ProgressBar;
+use gifski::progress::NoProgress;
 use gifski::{Collector, Repeat, Writer};
-use std::io::{self, Write};
+use std::io::Write;
 use std::thread;
 
 /* GIF encoder and settings */
@@ -22,16 +23,23 @@ impl<'a, Output: Write> Encoder<'a, Output> for GifskiEncoder<Output> {
 	 * @return GifskiEncoder (Result)
 	 */
 	fn new(config: EncoderConfig<'a, Output>) -> AppResult<Self> {
-		let (collector, writer) = gifski::new(gifski::Settings {
-			width: Some(config.geometry.width),
-			height: Some(config.geometry.height),
-			quality: config.settings.quality,
+		let gifski_settings = config.settings.gifski_settings;
+		let (width, height) =
+			gifski_settings.get_size(config.geometry.width, config.geometry.height);
+		let (collector, mut writer) = gifski::new(gifski::Settings {
+			width: Some(width),
+			height: Some(height),
+			quality: gifski_settings.quality.unwrap_or(config.settings.quality),
 			fast: config.settings.gifski.1,
 			repeat: match config.settings.repeat {
 				n if n >= 0 => Repeat::Finite(n.try_into().unwrap_or_default()),
 				_ => Repeat::Infinite,
 			},
 		})?;
+		if let Some(quality) = gifski_settings.motion_quality {
+			#[allow(deprecated)]
+			writer.set_motion_quality(quality);
+		}
 		Ok(Self {
 			fps: config.fps,
 			collector,
@@ -41,47 +49,63 @@ impl<'a, Output: Write> Encoder<'a, Output> for GifskiEncoder<Output> {
 	}
 
 	/**
-	 * Encode images as frame and write to the GIF file.
+	 * Encode the frames as they are received and write to the GIF file.
 	 *
-	 * @param  images
-	 * @param  input_state (Option)
-	 * @param  Result
+	 * @param  frames
+	 * @param  cancel
+	 * @return usize (Result)
 	 */
 	fn save(
 		self,
-		images: Vec<Image>,
-		input_state: Option<&'static InputState>,
-	) -> AppResult<()> {
+		frames: impl Iterator<Item = Image> + Send,
+		cancel: CancelHandle,
+	) -> AppResult<usize> {
 		let fps = self.fps;
-		let collector_thread = thread::spawn(move || {
-			for (i, image) in images.iter().enumerate() {
-				let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
-				info!("Saving... ({:.1}%)\r", percentage);
-				debug!(
-					"Encoding... ({:.1}%) [{}/{}]\r",
-					percentage,
-					i + 1,
-					images.len()
-				);
-				io::stdout().flush().expect("Failed to flush stdout");
-				if let Some(state) = input_state {
-					if state.check_cancel_keys() {
-						info!("\n");
-						warn!("User interrupt detected.");
-						panic!("Failed to write the frames")
-					}
+		let total = get_frame_count(&frames);
+		let mut progress_bar = total.map(|total| ProgressBar::new("Saving", total));
+		let collector = self.collector;
+		thread::scope(|scope| {
+			let collector_cancel = cancel.clone();
+			let collector_thread = scope.spawn(move || -> AppResult<usize> {
+				let (mut elapsed, mut first_timestamp) = (0., None);
+				let mut count = 0;
+				for (i, image) in frames.enumerate() {
+					debug!("Encoded frames: {}\r", i + 1);
+					collector_cancel.check()?;
+					let timestamp = match (image.delay, image.timestamp) {
+						(Some(delay), _) => {
+							let timestamp = elapsed;
+							elapsed += delay.as_secs_f64();
+							timestamp
+						}
+						(None, Some(timestamp)) => timestamp
+							.saturating_sub(
+								*first_timestamp.get_or_insert(timestamp),
+							)
+							.as_secs_f64(),
+						_ => i as f64 / fps as f64,
+					};
+					collector.add_frame_rgba(i, image.get_img_vec()?, timestamp)?;
+					count += 1;
 				}
-				self.collector
-					.add_frame_rgba(i, image.get_img_vec(), i as f64 / fps as f64)
-					.expect("Failed to collect a frame");
+				debug!("\n");
+				Ok(count)
+			});
+			let written = match progress_bar.as_mut() {
+				Some(progress_bar) => self.writer.write(self.output, progress_bar),
+				None => self.writer.write(self.output, &mut NoProgress {}),
+			};
+			if written.is_err() {
+				cancel.cancel();
 			}
-			info!("\n");
-		});
-		self.writer
-			.write(self.output, &mut gifski::progress::NoProgress {})?;
-		collector_thread
-			.join()
-			.expect("Failed to collect the frames");
-		Ok(())
+			if let Some(mut progress_bar) = progress_bar {
+				progress_bar.finish();
+			}
+			let count = collector_thread
+				.join()
+				.expect("Failed to collect the frames")?;
+			written?;
+			Ok(count)
+		})
 	}
 }
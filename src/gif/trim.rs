@@ -0,0 +1,380 @@
+use crate::anim::decoder::AnimDecoder;
+use crate::app::{AppError, AppResult};
+use gif::{
+	ColorOutput, DecodeOptions, DisposalMethod, Encoder as BaseEncoder, Frame,
+	Repeat,
+};
+use std::borrow::Cow;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+/* Quantization speed for the composed frames that have more than 256 colors */
+const COMPOSE_SPEED: i32 = 10;
+
+/* GIF trimmer that copies the frames in a time range without re-quantizing */
+#[derive(Debug)]
+pub struct GifTrimmer {
+	start: u64,
+	end: Option<u64>,
+}
+
+impl GifTrimmer {
+	/**
+	 * Create a new GifTrimmer object.
+	 *
+	 * @param  start
+	 * @param  end (Option)
+	 * @return GifTrimmer
+	 */
+	pub fn new(start: Duration, end: Option<Duration>) -> Self {
+		Self {
+			start: (start.as_millis() / 10) as u64,
+			end: end.map(|end| (end.as_millis() / 10) as u64),
+		}
+	}
+
+	/**
+	 * Copy the frames in the time range to the output.
+	 *
+	 * Frames are copied as is while the output canvas is the same as the
+	 * canvas of the input. Otherwise (e.g. the first frame that is drawn
+	 * on the skipped frames), the changed area is re-quantized from the
+	 * composed canvas until both canvases are in sync again.
+	 *
+	 * @param  input
+	 * @param  output
+	 * @return usize (Result)
+	 */
+	pub fn trim<Input: Read, Output: Write>(
+		&self,
+		mut input: Input,
+		output: Output,
+	) -> AppResult<usize> {
+		let mut data = Vec::new();
+		input.read_to_end(&mut data)?;
+		let mut options = DecodeOptions::new();
+		options.set_color_output(ColorOutput::Indexed);
+		let mut decoder = options.read_info(&data[..])?;
+		let (width, height) = (decoder.width(), decoder.height());
+		let global_palette = decoder.global_palette().unwrap_or_default().to_vec();
+		let mut encoder = BaseEncoder::new(output, width, height, &global_palette)?;
+		match AnimDecoder::parse_repeat(&data) {
+			Some(-1) => encoder.set_repeat(Repeat::Infinite)?,
+			Some(repeat) => encoder
+				.set_repeat(Repeat::Finite(repeat.try_into().unwrap_or(u16::MAX)))?,
+			None => {}
+		}
+		let mut canvas = vec![0; usize::from(width) * usize::from(height) * 4];
+		let mut display = canvas.clone();
+		let (mut elapsed, mut count) = (0, 0);
+		while let Some(frame) = decoder.read_next_frame()? {
+			let (frame_start, frame_end) =
+				(elapsed, elapsed + u64::from(frame.delay));
+			elapsed = frame_end;
+			if self.end.is_some_and(|end| frame_start >= end) {
+				break;
+			}
+			let previous = canvas.clone();
+			Self::draw(&mut canvas, frame, &global_palette, width);
+			let in_range = frame_end > self.start || frame_start >= self.start;
+			let copy = display == previous;
+			let mut output_frame = if !in_range {
+				None
+			} else if copy {
+				Some(Frame {
+					buffer: Cow::Owned(frame.buffer.to_vec()),
+					palette: frame.palette.clone(),
+					interlaced: false,
+					..*frame
+				})
+			} else {
+				Some(Self::compose(&canvas, &mut display, frame, width, height))
+			};
+			Self::dispose(&mut canvas, frame, frame.dispose, &previous, width);
+			if let Some(output_frame) = output_frame.as_mut() {
+				if copy {
+					display = canvas.clone();
+				} else {
+					output_frame.dispose = if display == canvas
+						|| !display.chunks_exact(4).zip(canvas.chunks_exact(4)).any(
+							|(display, canvas)| display[3] != 0 && canvas[3] == 0,
+						) {
+						DisposalMethod::Keep
+					} else {
+						DisposalMethod::Background
+					};
+					let display_previous = display.clone();
+					Self::dispose(
+						&mut display,
+						output_frame,
+						output_frame.dispose,
+						&display_previous,
+						width,
+					);
+				}
+				output_frame.delay = frame_end
+					.min(self.end.unwrap_or(u64::MAX))
+					.saturating_sub(frame_start.max(self.start))
+					.try_into()
+					.unwrap_or(u16::MAX);
+				encoder.write_frame(output_frame)?;
+				count += 1;
+			}
+		}
+		if count == 0 {
+			Err(AppError::FrameError(String::from(
+				"No frames found in the given range",
+			)))
+		} else {
+			Ok(count)
+		}
+	}
+
+	/**
+	 * Create a frame from the area of the canvas that differs from the
+	 * output canvas (including the area of the frame) and draw it on the
+	 * output canvas.
+	 *
+	 * @param  canvas
+	 * @param  display
+	 * @param  frame
+	 * @param  width
+	 * @param  height
+	 * @return Frame
+	 */
+	fn compose(
+		canvas: &[u8],
+		display: &mut [u8],
+		frame: &Frame<'_>,
+		width: u16,
+		height: u16,
+	) -> Frame<'static> {
+		let (mut min, mut max) = (
+			(frame.left, frame.top),
+			(frame.left + frame.width, frame.top + frame.height),
+		);
+		for (i, _) in canvas
+			.chunks_exact(4)
+			.zip(display.chunks_exact(4))
+			.enumerate()
+			.filter(|(_, (canvas, display))| canvas != display)
+		{
+			let (x, y) = (
+				(i % usize::from(width)) as u16,
+				(i / usize::from(width)) as u16,
+			);
+			min = (min.0.min(x), min.1.min(y));
+			max = (max.0.max(x + 1), max.1.max(y + 1));
+		}
+		let (max_x, max_y) = (max.0.min(width), max.1.min(height));
+		let mut pixels = Vec::new();
+		for y in min.1..max_y {
+			for x in min.0..max_x {
+				let i = (usize::from(y) * usize::from(width) + usize::from(x)) * 4;
+				pixels.extend_from_slice(&canvas[i..i + 4]);
+				if canvas[i + 3] != 0 {
+					display[i..i + 4].copy_from_slice(&canvas[i..i + 4]);
+				}
+			}
+		}
+		let mut output_frame = Frame::from_rgba_speed(
+			max_x - min.0,
+			max_y - min.1,
+			&mut pixels,
+			COMPOSE_SPEED,
+		);
+		output_frame.left = min.0;
+		output_frame.top = min.1;
+		output_frame
+	}
+
+	/**
+	 * Apply the disposal method of the frame to the canvas.
+	 *
+	 * @param canvas
+	 * @param frame
+	 * @param dispose
+	 * @param previous
+	 * @param width
+	 */
+	fn dispose(
+		canvas: &mut [u8],
+		frame: &Frame<'_>,
+		dispose: DisposalMethod,
+		previous: &[u8],
+		width: u16,
+	) {
+		match dispose {
+			DisposalMethod::Background => {
+				Self::get_area(frame, width).for_each(|i| {
+					canvas[i * 4..i * 4 + 4].copy_from_slice(&[0; 4]);
+				});
+			}
+			DisposalMethod::Previous => {
+				Self::get_area(frame, width).for_each(|i| {
+					canvas[i * 4..i * 4 + 4]
+						.copy_from_slice(&previous[i * 4..i * 4 + 4]);
+				});
+			}
+			_ => {}
+		}
+	}
+
+	/**
+	 * Get the indices of the canvas pixels that are covered by the frame.
+	 *
+	 * @param  frame
+	 * @param  width
+	 * @return Iterator of usize
+	 */
+	fn get_area<'a>(
+		frame: &'a Frame<'_>,
+		width: u16,
+	) -> impl Iterator<Item = usize> + 'a {
+		(0..frame.height).flat_map(move |y| {
+			(0..frame.width).map(move |x| {
+				usize::from(frame.top + y) * usize::from(width)
+					+ usize::from(frame.left + x)
+			})
+		})
+	}
+
+	/**
+	 * Draw the frame on the RGBA canvas.
+	 *
+	 * @param canvas
+	 * @param frame
+	 * @param global_palette
+	 * @param width
+	 */
+	fn draw(
+		canvas: &mut [u8],
+		frame: &Frame<'_>,
+		global_palette: &[u8],
+		width: u16,
+	) {
+		let palette = frame.palette.as_deref().unwrap_or(global_palette);
+		for (i, index) in Self::get_area(frame, width).zip(frame.buffer.iter()) {
+			if frame.transparent == Some(*index) {
+				continue;
+			}
+			let color = usize::from(*index) * 3;
+			if let (Some(rgb), Some(pixel)) = (
+				palette.get(color..color + 3),
+				canvas.get_mut(i * 4..i * 4 + 4),
+			) {
+				pixel.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 0xFF]);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::codecs::gif::GifDecoder;
+	use image::AnimationDecoder;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_gif_trimmer() -> AppResult<()> {
+		let palette = [255, 0, 0, 0, 0, 255, 0, 255, 0];
+		let mut input = Vec::new();
+		{
+			let mut encoder = BaseEncoder::new(&mut input, 2, 1, &palette)?;
+			for (i, (left, buffer)) in [(0, vec![0, 0]), (1, vec![1]), (0, vec![2])]
+				.into_iter()
+				.enumerate()
+			{
+				encoder.write_frame(&Frame {
+					delay: 50 * (i as u16 + 1),
+					dispose: DisposalMethod::Keep,
+					left,
+					width: buffer.len() as u16,
+					height: 1,
+					buffer: Cow::Owned(buffer),
+					..Frame::default()
+				})?;
+			}
+		}
+		let mut output = Vec::new();
+		let count = GifTrimmer::new(
+			Duration::from_millis(700),
+			Some(Duration::from_millis(2000)),
+		)
+		.trim(&input[..], &mut output)?;
+		assert_eq!(2, count);
+		let mut options = DecodeOptions::new();
+		options.set_color_output(ColorOutput::RGBA);
+		let mut decoder = options.read_info(&output[..])?;
+		let first = decoder.read_next_frame()?.unwrap().clone();
+		assert_eq!(
+			(80, 2, vec![255, 0, 0, 255, 0, 0, 255, 255]),
+			(first.delay, first.width, first.buffer.to_vec())
+		);
+		let second = decoder.read_next_frame()?.unwrap();
+		assert_eq!((50, 1), (second.delay, second.width));
+		assert!(decoder.read_next_frame()?.is_none());
+		assert!(GifTrimmer::new(Duration::from_secs(5), None)
+			.trim(&input[..], &mut Vec::new())
+			.is_err());
+		Ok(())
+	}
+	#[test]
+	fn test_gif_trimmer_disposal() -> AppResult<()> {
+		let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+		let mut input = Vec::new();
+		{
+			let mut encoder = BaseEncoder::new(&mut input, 3, 1, &palette)?;
+			encoder.set_repeat(Repeat::Finite(3))?;
+			for (left, buffer, dispose) in [
+				(0, vec![0, 0, 0], DisposalMethod::Keep),
+				(1, vec![1], DisposalMethod::Background),
+				(2, vec![2], DisposalMethod::Previous),
+				(0, vec![3], DisposalMethod::Keep),
+				(1, vec![1], DisposalMethod::Keep),
+			] {
+				encoder.write_frame(&Frame {
+					delay: 10,
+					dispose,
+					left,
+					width: buffer.len() as u16,
+					height: 1,
+					buffer: Cow::Owned(buffer),
+					..Frame::default()
+				})?;
+			}
+		}
+		let mut output = Vec::new();
+		let count = GifTrimmer::new(Duration::from_millis(100), None)
+			.trim(&input[..], &mut output)?;
+		assert_eq!(4, count);
+		assert_eq!(Some(3), AnimDecoder::parse_repeat(&output));
+		let frames = GifDecoder::new(&output[..])?
+			.into_frames()
+			.collect_frames()?
+			.into_iter()
+			.map(|frame| frame.into_buffer().into_raw())
+			.collect::<Vec<Vec<u8>>>();
+		assert_eq!(
+			vec![
+				vec![255, 0, 0, 255, 0, 255, 0, 255, 255, 0, 0, 255],
+				vec![255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 255, 255],
+				vec![255, 255, 255, 255, 0, 0, 0, 0, 255, 0, 0, 255],
+				vec![255, 255, 255, 255, 0, 255, 0, 255, 255, 0, 0, 255],
+			],
+			frames
+		);
+		let mut output = Vec::new();
+		GifTrimmer::new(Duration::ZERO, None).trim(&input[..], &mut output)?;
+		let mut decoder = DecodeOptions::new().read_info(&output[..])?;
+		decoder.read_next_frame()?;
+		assert_eq!(
+			(1, DisposalMethod::Background),
+			decoder
+				.read_next_frame()?
+				.map(|frame| (frame.width, frame.dispose))
+				.unwrap()
+		);
+		Ok(())
+	}
+}
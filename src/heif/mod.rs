@@ -0,0 +1,132 @@
+use crate::image::settings::HeifSettings;
+use image::error::{EncodingError, ImageFormatHint};
+use image::{ColorType, ImageEncoder, ImageError, ImageResult};
+use libheif_rs::{
+	Channel, ColorSpace, CompressionFormat, EncoderQuality, HeifContext,
+	Image as HeifImage, LibHeif, RgbChroma,
+};
+use std::io::Write;
+
+/* HEIF encoder and settings */
+pub struct HeifEncoder<Output: Write> {
+	output: Output,
+	settings: HeifSettings,
+}
+
+impl<Output: Write> HeifEncoder<Output> {
+	/**
+	 * Create a new HeifEncoder object.
+	 *
+	 * @param  output
+	 * @param  settings
+	 * @return HeifEncoder
+	 */
+	pub fn new(output: Output, settings: HeifSettings) -> Self {
+		Self { output, settings }
+	}
+
+	/**
+	 * Create an encoding error with the given message.
+	 *
+	 * @param  message
+	 * @return ImageError
+	 */
+	fn get_error(message: String) -> ImageError {
+		ImageError::Encoding(EncodingError::new(
+			ImageFormatHint::Name(String::from("HEIF")),
+			message.trim(),
+		))
+	}
+
+	/**
+	 * Create a HEIF image from the given buffer.
+	 *
+	 * @param  buf
+	 * @param  width
+	 * @param  height
+	 * @param  color_type
+	 * @return HeifImage (Result)
+	 */
+	fn get_image(
+		buf: &[u8],
+		width: u32,
+		height: u32,
+		color_type: ColorType,
+	) -> ImageResult<HeifImage> {
+		let chroma = match color_type {
+			ColorType::Rgb8 => RgbChroma::Rgb,
+			ColorType::Rgba8 => RgbChroma::Rgba,
+			_ => {
+				return Err(Self::get_error(format!(
+					"Unsupported color type: {color_type:?}"
+				)))
+			}
+		};
+		let row_size = width as usize * color_type.bytes_per_pixel() as usize;
+		if row_size == 0 || buf.len() != row_size * height as usize {
+			return Err(Self::get_error(String::from("Invalid image size")));
+		}
+		let mut image = HeifImage::new(width, height, ColorSpace::Rgb(chroma))
+			.map_err(|e| Self::get_error(e.to_string()))?;
+		image
+			.create_plane(Channel::Interleaved, width, height, 8)
+			.map_err(|e| Self::get_error(e.to_string()))?;
+		let plane = image.planes_mut().interleaved.ok_or_else(|| {
+			Self::get_error(String::from("Failed to create the image plane"))
+		})?;
+		for (row, data) in buf.chunks_exact(row_size).enumerate() {
+			plane.data[row * plane.stride..row * plane.stride + row_size]
+				.copy_from_slice(data);
+		}
+		Ok(image)
+	}
+}
+
+/* Implementation for encoding HEIF images */
+impl<Output: Write> ImageEncoder for HeifEncoder<Output> {
+	fn write_image(
+		mut self,
+		buf: &[u8],
+		width: u32,
+		height: u32,
+		color_type: ColorType,
+	) -> ImageResult<()> {
+		let image = Self::get_image(buf, width, height, color_type)?;
+		let lib_heif = LibHeif::new();
+		let mut encoder = lib_heif
+			.encoder_for_format(CompressionFormat::Hevc)
+			.map_err(|e| Self::get_error(e.to_string()))?;
+		encoder
+			.set_quality(if self.settings.lossless {
+				EncoderQuality::LossLess
+			} else {
+				EncoderQuality::Lossy(self.settings.quality)
+			})
+			.map_err(|e| Self::get_error(e.to_string()))?;
+		let mut context =
+			HeifContext::new().map_err(|e| Self::get_error(e.to_string()))?;
+		context
+			.encode_image(&image, &mut encoder, None)
+			.map_err(|e| Self::get_error(e.to_string()))?;
+		self.output.write_all(
+			&context
+				.write_to_bytes()
+				.map_err(|e| Self::get_error(e.to_string()))?,
+		)?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn test_heif_encoder() {
+		assert!(HeifEncoder::new(Vec::new(), HeifSettings::default())
+			.write_image(&[255; 4], 2, 2, ColorType::Rgba8)
+			.is_err());
+		assert!(HeifEncoder::new(Vec::new(), HeifSettings::default())
+			.write_image(&[255; 4], 2, 2, ColorType::L8)
+			.is_err());
+	}
+}
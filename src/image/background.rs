@@ -0,0 +1,103 @@
+use crate::edit::text::parse_color;
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgba, RgbaImage};
+use std::path::PathBuf;
+
+/* Solid, gradient or image background */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Background {
+	Solid(Rgba<u8>),
+	Gradient(Rgba<u8>, Rgba<u8>),
+	Image(RgbaImage),
+}
+
+/* Default initialization values for Background */
+impl Default for Background {
+	fn default() -> Self {
+		Self::Gradient(Rgba([106, 130, 251, 255]), Rgba([252, 92, 125, 255]))
+	}
+}
+
+impl Background {
+	/**
+	 * Parse the background from a color (HEX), gradient (HEX:HEX) or image file.
+	 *
+	 * @param  background
+	 * @return Background (Option)
+	 */
+	pub fn parse(background: &str) -> Option<Self> {
+		if let Some(color) = parse_color(background) {
+			return Some(Self::Solid(color));
+		} else if let Some((start, end)) = background.split_once(':') {
+			if let (Some(start), Some(end)) = (parse_color(start), parse_color(end))
+			{
+				return Some(Self::Gradient(start, end));
+			}
+		}
+		let path = PathBuf::from(
+			shellexpand::full(background)
+				.map(|s| s.to_string())
+				.unwrap_or(background.to_string()),
+		);
+		match image::open(&path) {
+			Ok(image) => Some(Self::Image(image.to_rgba8())),
+			Err(e) => {
+				warn!("Invalid background {:?}: {}", background, e);
+				None
+			}
+		}
+	}
+
+	/**
+	 * Create an image that is filled with the background.
+	 *
+	 * @param  size
+	 * @return RgbaImage
+	 */
+	pub fn get_image(&self, size: (u32, u32)) -> RgbaImage {
+		match self {
+			Self::Solid(color) => RgbaImage::from_pixel(size.0, size.1, *color),
+			Self::Gradient(start, end) => {
+				let length = (size.0 + size.1).saturating_sub(2).max(1) as f32;
+				RgbaImage::from_fn(size.0, size.1, |x, y| {
+					let ratio = (x + y) as f32 / length;
+					Rgba(std::array::from_fn(|i| {
+						(f32::from(start[i]) * (1. - ratio)
+							+ f32::from(end[i]) * ratio)
+							.round() as u8
+					}))
+				})
+			}
+			Self::Image(image) => DynamicImage::ImageRgba8(image.clone())
+				.resize_to_fill(size.0, size.1, FilterType::Triangle)
+				.to_rgba8(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_background() {
+		assert_eq!(
+			Some(Background::Solid(Rgba([0, 0, 0, 255]))),
+			Background::parse("#000000")
+		);
+		assert_eq!(None, Background::parse("000000:red"));
+		let gradient = Background::parse("000000:FF0000FF").unwrap();
+		assert_eq!(
+			vec![0, 128, 255],
+			gradient
+				.get_image((3, 1))
+				.pixels()
+				.map(|pixel| pixel[0])
+				.collect::<Vec<u8>>()
+		);
+		let image = Background::Image(RgbaImage::from_fn(2, 1, |x, _| {
+			Rgba([x as u8 * 255, 0, 0, 255])
+		}));
+		assert_eq!((4, 4), image.get_image((4, 4)).dimensions());
+	}
+}
@@ -62,6 +62,25 @@ impl Geometry {
 			.collect()
 	}
 
+	/**
+	 * Parse the area in WxH+X+Y format.
+	 *
+	 * @param  area
+	 * @return Geometry (Option)
+	 */
+	pub fn parse_area(area: &str) -> Option<Self> {
+		let (size, position) = area.split_once('+')?;
+		let (width, height) = size.split_once('x')?;
+		let (x, y) = position.split_once('+')?;
+		Some(Self::new(
+			x.parse().ok()?,
+			y.parse().ok()?,
+			width.parse().ok()?,
+			height.parse().ok()?,
+		))
+		.filter(|area| !area.is_zero() && area.x >= 0 && area.y >= 0)
+	}
+
 	/**
 	 * Check if width and height values are zero.
 	 *
@@ -85,16 +104,34 @@ impl Geometry {
 			.y
 			.checked_add(i32::try_from(padding.top).unwrap_or_default())
 			.unwrap_or_default();
-		self.width = self
-			.width
-			.checked_sub(padding.right + padding.left)
-			.unwrap_or_default();
-		self.height = self
-			.height
-			.checked_sub(padding.top + padding.bottom)
-			.unwrap_or_default();
+		self.width = self.width.saturating_sub(padding.right + padding.left);
+		self.height = self.height.saturating_sub(padding.top + padding.bottom);
 		*self
 	}
+
+	/**
+	 * Get the largest centered area with the given aspect ratio.
+	 *
+	 * @param  aspect
+	 * @return Geometry
+	 */
+	pub fn with_aspect_ratio(&self, aspect: (u32, u32)) -> Self {
+		let (width, height) = (u64::from(self.width), u64::from(self.height));
+		let (aspect_width, aspect_height) =
+			(u64::from(aspect.0.max(1)), u64::from(aspect.1.max(1)));
+		let (new_width, new_height) =
+			if width * aspect_height > height * aspect_width {
+				(height * aspect_width / aspect_height, height)
+			} else {
+				(width, width * aspect_height / aspect_width)
+			};
+		Self::new(
+			self.x + ((width - new_width) / 2) as i32,
+			self.y + ((height - new_height) / 2) as i32,
+			new_width as u32,
+			new_height as u32,
+		)
+	}
 }
 
 #[cfg(test)]
@@ -112,5 +149,19 @@ mod tests {
 		let values = "45x28";
 		let geometry = Geometry::parse(values);
 		assert_eq!(values, geometry.to_string());
+		assert_eq!(
+			Some(Geometry::new(10, 5, 45, 28)),
+			Geometry::parse_area("45x28+10+5")
+		);
+		assert_eq!(None, Geometry::parse_area("45x28"));
+		assert_eq!(None, Geometry::parse_area("0x0+1+1"));
+		assert_eq!(
+			Geometry::new(20, 0, 160, 90),
+			Geometry::new(0, 0, 200, 90).with_aspect_ratio((16, 9))
+		);
+		assert_eq!(
+			Geometry::new(5, 55, 100, 100),
+			Geometry::new(5, 5, 100, 200).with_aspect_ratio((1, 1))
+		);
 	}
 }
@@ -0,0 +1,239 @@
+use crate::app::{AppError, AppResult};
+use crate::file::format::FileFormat;
+use crate::png::{get_chunks, write_chunk, PNG_SIGNATURE};
+use crate::webp::{WebPChunks, ICC_FLAG};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/* Name of the profile in the PNG chunk */
+const PNG_PROFILE_NAME: &[u8] = b"ICC Profile";
+/* Identifier of the JPEG segments that contain the profile */
+const JPEG_ICC_MARKER: &[u8; 12] = b"ICC_PROFILE\0";
+/* Maximum size of the profile data in a JPEG segment */
+const JPEG_SEGMENT_SIZE: usize = 65519;
+
+/* Source of the ICC profile to embed */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum IccSource {
+	#[default]
+	Display,
+	File(PathBuf),
+	None,
+}
+
+impl IccSource {
+	/**
+	 * Parse the source from the given value.
+	 *
+	 * @param  value
+	 * @return IccSource
+	 */
+	pub fn parse(value: &str) -> Self {
+		match value {
+			"display" => Self::Display,
+			"none" => Self::None,
+			_ => Self::File(PathBuf::from(
+				shellexpand::full(value)
+					.map(|s| s.to_string())
+					.unwrap_or(value.to_string()),
+			)),
+		}
+	}
+}
+
+/* ICC color profile */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IccProfile {
+	data: Vec<u8>,
+}
+
+impl IccProfile {
+	/**
+	 * Create a new IccProfile object if the data is valid.
+	 *
+	 * @param  data
+	 * @return IccProfile (Option)
+	 */
+	pub fn new(data: Vec<u8>) -> Option<Self> {
+		(data.len() >= 128 && &data[36..40] == b"acsp").then_some(Self { data })
+	}
+
+	/**
+	 * Read the profile from a file.
+	 *
+	 * @param  path
+	 * @return IccProfile (Result)
+	 */
+	pub fn from_file(path: &Path) -> AppResult<Self> {
+		Self::new(fs::read(path)?).ok_or_else(|| {
+			AppError::FrameError(format!("Invalid ICC profile: {path:?}"))
+		})
+	}
+
+	/**
+	 * Check if the profile can be embedded in the given format.
+	 *
+	 * @param  format
+	 * @return bool
+	 */
+	pub fn is_supported(format: &FileFormat) -> bool {
+		matches!(format, FileFormat::Png | FileFormat::Jpg | FileFormat::WebP)
+	}
+
+	/**
+	 * Embed the profile in the encoded image.
+	 *
+	 * @param  data
+	 * @param  format
+	 * @return Vector of u8 (Result)
+	 */
+	pub fn embed(
+		&self,
+		data: &[u8],
+		format: &FileFormat,
+	) -> Result<Vec<u8>, String> {
+		match format {
+			FileFormat::Png => self.embed_png(data),
+			FileFormat::Jpg => self.embed_jpg(data),
+			FileFormat::WebP => self.embed_webp(data),
+			_ => Err(format!("Unsupported format: {format:?}")),
+		}
+	}
+
+	/**
+	 * Add the profile as a compressed chunk after the PNG header.
+	 *
+	 * @param  data
+	 * @return Vector of u8 (Result)
+	 */
+	fn embed_png(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+		let mut profile = PNG_PROFILE_NAME.to_vec();
+		profile.extend_from_slice(&[0, 0]);
+		profile.extend(miniz_oxide::deflate::compress_to_vec_zlib(&self.data, 6));
+		let mut output = PNG_SIGNATURE.to_vec();
+		for (chunk_type, chunk_data) in get_chunks(data)? {
+			if chunk_type == b"iCCP" || chunk_type == b"sRGB" {
+				continue;
+			}
+			write_chunk(&mut output, chunk_type, chunk_data);
+			if chunk_type == b"IHDR" {
+				write_chunk(&mut output, b"iCCP", &profile);
+			}
+		}
+		Ok(output)
+	}
+
+	/**
+	 * Add the profile as APP2 segments after the JFIF header.
+	 *
+	 * @param  data
+	 * @return Vector of u8 (Result)
+	 */
+	fn embed_jpg(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+		if !data.starts_with(&[0xFF, 0xD8]) {
+			return Err(String::from("Invalid JPEG header"));
+		}
+		let position = match data.get(2..6) {
+			Some([0xFF, 0xE0, high, low]) => {
+				4 + usize::from(u16::from_be_bytes([*high, *low]))
+			}
+			_ => 2,
+		};
+		let segments = self.data.chunks(JPEG_SEGMENT_SIZE).collect::<Vec<&[u8]>>();
+		let count = u8::try_from(segments.len())
+			.map_err(|_| String::from("ICC profile is too large"))?;
+		let mut output = data[..position.min(data.len())].to_vec();
+		for (i, segment) in segments.into_iter().enumerate() {
+			output.extend_from_slice(&[0xFF, 0xE2]);
+			output.extend_from_slice(
+				&((2 + JPEG_ICC_MARKER.len() + 2 + segment.len()) as u16)
+					.to_be_bytes(),
+			);
+			output.extend_from_slice(JPEG_ICC_MARKER);
+			output.extend_from_slice(&[i as u8 + 1, count]);
+			output.extend_from_slice(segment);
+		}
+		output.extend_from_slice(&data[position.min(data.len())..]);
+		Ok(output)
+	}
+
+	/**
+	 * Add the profile as a chunk of the extended WebP format.
+	 *
+	 * @param  data
+	 * @return Vector of u8 (Result)
+	 */
+	fn embed_webp(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+		let mut chunks = WebPChunks::parse(data)?;
+		chunks.set_chunk(b"ICCP", self.data.clone(), ICC_FLAG);
+		Ok(chunks.encode())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::codecs::jpeg::JpegEncoder;
+	use image::codecs::webp::{WebPEncoder, WebPQuality};
+	use image::{ColorType, ImageEncoder};
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_icc_profile() -> AppResult<()> {
+		assert_eq!(IccSource::Display, IccSource::parse("display"));
+		assert_eq!(IccSource::None, IccSource::parse("none"));
+		assert_eq!(
+			IccSource::File(PathBuf::from("test.icc")),
+			IccSource::parse("test.icc")
+		);
+		assert!(IccProfile::new(vec![0; 128]).is_none());
+		let mut data = vec![0; 128];
+		data[36..40].copy_from_slice(b"acsp");
+		let profile = IccProfile::new(data.clone()).unwrap();
+		assert!(IccProfile::is_supported(&FileFormat::WebP));
+		assert!(!IccProfile::is_supported(&FileFormat::Gif));
+		let pixels = vec![255; 4 * 4 * 3];
+		let mut output = Vec::new();
+		image::codecs::png::PngEncoder::new(&mut output).write_image(
+			&pixels,
+			4,
+			4,
+			ColorType::Rgb8,
+		)?;
+		let output = profile.embed(&output, &FileFormat::Png).unwrap();
+		let chunks = get_chunks(&output).unwrap();
+		assert_eq!(b"iCCP", chunks[1].0);
+		assert_eq!(
+			Ok(data.clone()),
+			miniz_oxide::inflate::decompress_to_vec_zlib(
+				&chunks[1].1[PNG_PROFILE_NAME.len() + 2..]
+			)
+			.map_err(|_| ())
+		);
+		assert!(image::load_from_memory(&output).is_ok());
+		let mut output = Vec::new();
+		JpegEncoder::new(&mut output).write_image(&pixels, 4, 4, ColorType::Rgb8)?;
+		let output = profile.embed(&output, &FileFormat::Jpg).unwrap();
+		assert_eq!(&[0xFF, 0xE2], &output[20..22]);
+		assert!(image::load_from_memory(&output).is_ok());
+		for quality in [WebPQuality::lossless(), WebPQuality::lossy(80)] {
+			let mut output = Vec::new();
+			WebPEncoder::new_with_quality(&mut output, quality).write_image(
+				&pixels,
+				4,
+				4,
+				ColorType::Rgb8,
+			)?;
+			let output = profile.embed(&output, &FileFormat::WebP).unwrap();
+			assert_eq!(b"VP8X", &output[12..16]);
+			assert_eq!(&[3, 0, 0, 3, 0, 0], &output[24..30]);
+			assert_eq!(b"ICCP", &output[30..34]);
+			assert_eq!(
+				output.len() - 8,
+				u32::from_le_bytes(output[4..8].try_into().unwrap()) as usize
+			);
+			assert!(image::load_from_memory(&output).is_ok());
+		}
+		assert!(profile.embed(b"test", &FileFormat::Jpg).is_err());
+		Ok(())
+	}
+}
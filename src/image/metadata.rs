@@ -0,0 +1,315 @@
+use crate::file::format::FileFormat;
+use crate::png::{get_chunks, write_chunk, PNG_SIGNATURE};
+use crate::webp::{WebPChunks, EXIF_FLAG, XMP_FLAG};
+use chrono::{DateTime, Local, SecondsFormat};
+use exif::experimental::Writer;
+use exif::{Field, In, Tag, Value};
+use std::io::Cursor;
+
+/* Identifiers of the JPEG segments that contain the metadata */
+const JPEG_EXIF_MARKER: &[u8] = b"Exif\0\0";
+const JPEG_XMP_MARKER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+/* Maximum size of the data in a JPEG segment */
+const JPEG_SEGMENT_SIZE: usize = 65533;
+/* Format of the date/time in EXIF */
+const EXIF_DATE_FORMAT: &str = "%Y:%m:%d %H:%M:%S";
+
+/* Metadata of the captured image */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Metadata {
+	pub software: String,
+	pub timestamp: DateTime<Local>,
+	pub title: Option<String>,
+	pub comment: Option<String>,
+}
+
+impl Metadata {
+	/**
+	 * Create a new Metadata object with the current time.
+	 *
+	 * @param  title
+	 * @param  comment
+	 * @return Metadata
+	 */
+	pub fn new(title: Option<String>, comment: Option<String>) -> Self {
+		Self {
+			software: format!(
+				"{} {}",
+				env!("CARGO_PKG_NAME"),
+				env!("CARGO_PKG_VERSION")
+			),
+			timestamp: Local::now(),
+			title: title.filter(|title| !title.is_empty()),
+			comment: comment.filter(|comment| !comment.is_empty()),
+		}
+	}
+
+	/**
+	 * Check if the metadata can be embedded in the given format.
+	 *
+	 * @param  format
+	 * @return bool
+	 */
+	pub fn is_supported(format: &FileFormat) -> bool {
+		matches!(format, FileFormat::Png | FileFormat::Jpg | FileFormat::WebP)
+	}
+
+	/**
+	 * Embed the metadata in the encoded image.
+	 *
+	 * @param  data
+	 * @param  format
+	 * @return Vector of u8 (Result)
+	 */
+	pub fn embed(
+		&self,
+		data: &[u8],
+		format: &FileFormat,
+	) -> Result<Vec<u8>, String> {
+		match format {
+			FileFormat::Png => self.embed_png(data),
+			FileFormat::Jpg => self.embed_jpg(data),
+			FileFormat::WebP => self.embed_webp(data),
+			_ => Err(format!("Unsupported format: {format:?}")),
+		}
+	}
+
+	/**
+	 * Get the metadata as keyword and text pairs.
+	 *
+	 * @return Vector of entries
+	 */
+	fn get_entries(&self) -> Vec<(&'static str, String)> {
+		let mut entries = vec![
+			("Software", self.software.clone()),
+			("Creation Time", self.timestamp.to_rfc2822()),
+		];
+		if let Some(title) = &self.title {
+			entries.push(("Title", title.to_string()));
+		}
+		if let Some(comment) = &self.comment {
+			entries.push(("Comment", comment.to_string()));
+		}
+		entries
+	}
+
+	/**
+	 * Get the metadata in EXIF (TIFF) format.
+	 *
+	 * @return Vector of u8 (Result)
+	 */
+	fn get_exif(&self) -> Result<Vec<u8>, String> {
+		let date = self.timestamp.format(EXIF_DATE_FORMAT).to_string();
+		let mut values = vec![
+			(Tag::Software, self.software.clone()),
+			(Tag::DateTime, date.clone()),
+			(Tag::DateTimeOriginal, date),
+		];
+		if let Some(title) = &self.title {
+			values.push((Tag::ImageDescription, title.to_string()));
+		}
+		let fields = values
+			.into_iter()
+			.map(|(tag, value)| Field {
+				tag,
+				ifd_num: In::PRIMARY,
+				value: Value::Ascii(vec![value.into_bytes()]),
+			})
+			.collect::<Vec<Field>>();
+		let mut writer = Writer::new();
+		fields.iter().for_each(|field| writer.push_field(field));
+		let mut output = Cursor::new(Vec::new());
+		writer
+			.write(&mut output, false)
+			.map_err(|e| format!("Failed to write EXIF: {e}"))?;
+		Ok(output.into_inner())
+	}
+
+	/**
+	 * Get the metadata as an XMP packet.
+	 *
+	 * @return String
+	 */
+	fn get_xmp(&self) -> String {
+		let text = |name: &str, value: &Option<String>| {
+			value.as_ref().map_or(String::new(), |value| {
+				format!(
+					"<dc:{name}><rdf:Alt><rdf:li xml:lang=\"x-default\">{}\
+					</rdf:li></rdf:Alt></dc:{name}>",
+					escape_xml(value)
+				)
+			})
+		};
+		format!(
+			"<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\
+			<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+			<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+			<rdf:Description rdf:about=\"\" \
+			xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" \
+			xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+			xmp:CreatorTool=\"{}\" xmp:CreateDate=\"{}\">{}{}</rdf:Description>\
+			</rdf:RDF></x:xmpmeta><?xpacket end=\"w\"?>",
+			escape_xml(&self.software),
+			self.timestamp.to_rfc3339_opts(SecondsFormat::Secs, false),
+			text("title", &self.title),
+			text("description", &self.comment)
+		)
+	}
+
+	/**
+	 * Add the metadata as text chunks before the PNG image data.
+	 *
+	 * @param  data
+	 * @return Vector of u8 (Result)
+	 */
+	fn embed_png(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+		let mut output = PNG_SIGNATURE.to_vec();
+		let mut entries = Some(self.get_entries());
+		for (chunk_type, chunk_data) in get_chunks(data)? {
+			if chunk_type == b"IDAT" {
+				for (keyword, text) in entries.take().unwrap_or_default() {
+					let mut chunk = keyword.as_bytes().to_vec();
+					if text.is_ascii() {
+						chunk.push(0);
+						chunk.extend_from_slice(text.as_bytes());
+						write_chunk(&mut output, b"tEXt", &chunk);
+					} else {
+						chunk.extend_from_slice(&[0, 0, 0, 0, 0]);
+						chunk.extend_from_slice(text.as_bytes());
+						write_chunk(&mut output, b"iTXt", &chunk);
+					}
+				}
+			}
+			write_chunk(&mut output, chunk_type, chunk_data);
+		}
+		Ok(output)
+	}
+
+	/**
+	 * Add the metadata as EXIF and XMP segments after the JFIF header.
+	 *
+	 * @param  data
+	 * @return Vector of u8 (Result)
+	 */
+	fn embed_jpg(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+		if !data.starts_with(&[0xFF, 0xD8]) {
+			return Err(String::from("Invalid JPEG header"));
+		}
+		let position = match data.get(2..6) {
+			Some([0xFF, 0xE0, high, low]) => {
+				4 + usize::from(u16::from_be_bytes([*high, *low]))
+			}
+			_ => 2,
+		};
+		let mut output = data[..position.min(data.len())].to_vec();
+		for (marker, segment) in [
+			(JPEG_EXIF_MARKER, self.get_exif()?),
+			(JPEG_XMP_MARKER, self.get_xmp().into_bytes()),
+		] {
+			let size = marker.len() + segment.len();
+			if size > JPEG_SEGMENT_SIZE {
+				return Err(String::from("Metadata is too large"));
+			}
+			output.extend_from_slice(&[0xFF, 0xE1]);
+			output.extend_from_slice(&((2 + size) as u16).to_be_bytes());
+			output.extend_from_slice(marker);
+			output.extend_from_slice(&segment);
+		}
+		output.extend_from_slice(&data[position.min(data.len())..]);
+		Ok(output)
+	}
+
+	/**
+	 * Add the metadata as EXIF and XMP chunks of the extended WebP format.
+	 *
+	 * @param  data
+	 * @return Vector of u8 (Result)
+	 */
+	fn embed_webp(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+		let mut chunks = WebPChunks::parse(data)?;
+		chunks.set_chunk(b"EXIF", self.get_exif()?, EXIF_FLAG);
+		chunks.set_chunk(b"XMP ", self.get_xmp().into_bytes(), XMP_FLAG);
+		Ok(chunks.encode())
+	}
+}
+
+/**
+ * Escape the special characters of XML.
+ *
+ * @param  text
+ * @return String
+ */
+fn escape_xml(text: &str) -> String {
+	text.chars().fold(String::new(), |mut escaped, c| {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			_ => escaped.push(c),
+		}
+		escaped
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::codecs::jpeg::JpegEncoder;
+	use image::codecs::webp::{WebPEncoder, WebPQuality};
+	use image::{ColorType, ImageEncoder, ImageResult};
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_metadata() -> ImageResult<()> {
+		let metadata = Metadata::new(
+			Some(String::from("Terminal – <menyoki>")),
+			Some(String::from("test")),
+		);
+		assert_eq!(None, Metadata::new(Some(String::new()), None).title);
+		assert!(Metadata::is_supported(&FileFormat::Jpg));
+		assert!(!Metadata::is_supported(&FileFormat::Gif));
+		assert!(metadata
+			.get_xmp()
+			.contains("Terminal – &lt;menyoki&gt;</rdf:li>"));
+		let pixels = vec![255; 4 * 4 * 3];
+		let mut output = Vec::new();
+		image::codecs::png::PngEncoder::new(&mut output).write_image(
+			&pixels,
+			4,
+			4,
+			ColorType::Rgb8,
+		)?;
+		let output = metadata.embed(&output, &FileFormat::Png).unwrap();
+		let chunks = get_chunks(&output).unwrap();
+		assert_eq!(
+			vec![b"IHDR", b"tEXt", b"tEXt", b"iTXt", b"tEXt", b"IDAT", b"IEND"],
+			chunks
+				.iter()
+				.map(|(chunk_type, _)| *chunk_type)
+				.collect::<Vec<&[u8]>>()
+		);
+		assert_eq!(b"Comment\0test", chunks[4].1);
+		assert!(image::load_from_memory(&output).is_ok());
+		let mut output = Vec::new();
+		JpegEncoder::new(&mut output).write_image(&pixels, 4, 4, ColorType::Rgb8)?;
+		let output = metadata.embed(&output, &FileFormat::Jpg).unwrap();
+		let exif = exif::Reader::new()
+			.read_from_container(&mut Cursor::new(&output))
+			.unwrap();
+		assert_eq!(
+			Some(metadata.software.clone()),
+			exif.get_field(Tag::Software, In::PRIMARY)
+				.map(|field| field.display_value().to_string().replace('"', ""))
+		);
+		assert!(image::load_from_memory(&output).is_ok());
+		let mut output = Vec::new();
+		WebPEncoder::new_with_quality(&mut output, WebPQuality::lossless())
+			.write_image(&pixels, 4, 4, ColorType::Rgb8)?;
+		let output = metadata.embed(&output, &FileFormat::WebP).unwrap();
+		assert_eq!(b"VP8X", &output[12..16]);
+		assert_eq!(EXIF_FLAG | XMP_FLAG, output[20]);
+		assert!(image::load_from_memory(&output).is_ok());
+		assert!(metadata.embed(b"test", &FileFormat::Png).is_err());
+		Ok(())
+	}
+}
@@ -1,10 +1,23 @@
+pub mod background;
 pub mod geometry;
+pub mod icc;
+pub mod metadata;
 pub mod padding;
 pub mod settings;
+pub mod spool;
+use std::borrow::Cow;
 use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
 
+use crate::app::{AppError, AppResult};
+use crate::edit::text::TextRenderer;
+use crate::image::background::Background;
 use crate::image::geometry::Geometry;
-use image::{ExtendedColorType, Rgba};
+use crate::image::spool::FrameSpool;
+use image::imageops::{self, FilterType};
+use image::{ExtendedColorType, ImageBuffer, Pixel, Rgb, Rgba, RgbaImage};
 #[cfg(feature = "ski")]
 use {
 	imgref::{Img, ImgVec},
@@ -13,22 +26,32 @@ use {
 
 /* Coefficients for transforming sRGB to CIE Y (luminance value) */
 const SRGB_LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+/* Thickness of the circles that are drawn on image */
+const CIRCLE_THICKNESS: f32 = 3.;
 
-/* Image data and geometric properties */
+/* Image data and geometric properties (clones share the pixel buffer) */
 #[derive(Clone)]
 pub struct Image {
-	data: Vec<Rgba<u8>>,
+	data: Arc<Vec<Rgba<u8>>>,
 	alpha_channel: bool,
 	pub geometry: Geometry,
+	pub timestamp: Option<Duration>,
+	pub delay: Option<Duration>,
+	spool: Option<(Arc<FrameSpool>, u64, usize)>,
 }
 
 /* Debug implementation for programmer-facing output */
 impl fmt::Debug for Image {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		f.debug_struct("Image")
-			.field("data_len", &self.data.len())
+			.field(
+				"data_len",
+				&self.spool.as_ref().map_or(self.data.len(), |spool| spool.2),
+			)
 			.field("alpha_channel", &self.alpha_channel)
 			.field("geometry", &self.geometry)
+			.field("timestamp", &self.timestamp)
+			.field("delay", &self.delay)
 			.finish()
 	}
 }
@@ -48,62 +71,483 @@ impl Image {
 		geometry: Geometry,
 	) -> Self {
 		Self {
-			data,
+			data: Arc::new(data),
 			alpha_channel,
 			geometry,
+			timestamp: None,
+			delay: None,
+			spool: None,
+		}
+	}
+
+	/**
+	 * Move the image data to the given spool file.
+	 *
+	 * @param  spool
+	 * @return Image (Result)
+	 */
+	pub fn into_spool(mut self, spool: &Arc<FrameSpool>) -> io::Result<Self> {
+		if self.spool.is_none() {
+			let data = self
+				.data
+				.iter()
+				.flat_map(|rgba| rgba.0)
+				.collect::<Vec<u8>>();
+			self.spool = Some((spool.clone(), spool.write(&data)?, self.data.len()));
+			self.data = Arc::default();
+		}
+		Ok(self)
+	}
+
+	/**
+	 * Get the pixels of the image from memory or the spool file.
+	 *
+	 * @return Cow (Result)
+	 */
+	fn get_pixels(&self) -> AppResult<Cow<'_, [Rgba<u8>]>> {
+		Ok(match &self.spool {
+			Some((spool, offset, len)) => Cow::Owned(
+				spool
+					.read(*offset, len * 4)?
+					.chunks_exact(4)
+					.map(|rgba| Rgba([rgba[0], rgba[1], rgba[2], rgba[3]]))
+					.collect(),
+			),
+			None => Cow::Borrowed(&self.data),
+		})
+	}
+
+	/**
+	 * Get the mutable pixels after loading them into memory.
+	 * (copies the pixel buffer if it is shared with other images)
+	 *
+	 * @return Vector of Rgba (Result)
+	 */
+	fn get_pixels_mut(&mut self) -> AppResult<&mut Vec<Rgba<u8>>> {
+		if self.spool.is_some() {
+			self.data = Arc::new(self.get_pixels()?.into_owned());
+			self.spool = None;
 		}
+		Ok(Arc::make_mut(&mut self.data))
+	}
+
+	/**
+	 * Set the capture timestamp of the image.
+	 *
+	 * @param  timestamp
+	 * @return Image
+	 */
+	pub fn with_timestamp(mut self, timestamp: Duration) -> Self {
+		self.timestamp = Some(timestamp);
+		self
+	}
+
+	/**
+	 * Set the display duration of the image as a frame.
+	 *
+	 * @param  delay
+	 * @return Image
+	 */
+	pub fn with_delay(mut self, delay: Duration) -> Self {
+		self.delay = Some(delay);
+		self
 	}
 
 	/**
 	 * Get image data in the given color type.
 	 *
 	 * @param  color_type
-	 * @return Vector of u8
-	 */
-	pub fn get_data(&self, color_type: ExtendedColorType) -> Vec<u8> {
-		self.data.iter().fold(Vec::<u8>::new(), |mut data, rgba| {
-			let alpha = if self.alpha_channel { rgba[3] } else { 255 };
-			data.extend(&match color_type {
-				ExtendedColorType::L1 | ExtendedColorType::L8 => vec![{
-					let y = (SRGB_LUMA[0] * rgba[0] as f32
-						+ SRGB_LUMA[1] * rgba[1] as f32
-						+ SRGB_LUMA[2] * rgba[2] as f32) as u8;
-					if color_type == ExtendedColorType::L1 {
-						(y >> 7) * 0xFF
-					} else {
-						y
+	 * @return Vector of u8 (Result)
+	 */
+	pub fn get_data(&self, color_type: ExtendedColorType) -> AppResult<Vec<u8>> {
+		Ok(self
+			.get_pixels()?
+			.iter()
+			.fold(Vec::<u8>::new(), |mut data, rgba| {
+				let alpha = if self.alpha_channel { rgba[3] } else { 255 };
+				data.extend(&match color_type {
+					ExtendedColorType::L1 | ExtendedColorType::L8 => vec![{
+						let y = (SRGB_LUMA[0] * rgba[0] as f32
+							+ SRGB_LUMA[1] * rgba[1] as f32
+							+ SRGB_LUMA[2] * rgba[2] as f32) as u8;
+						if color_type == ExtendedColorType::L1 {
+							(y >> 7) * 0xFF
+						} else {
+							y
+						}
+					}],
+					ExtendedColorType::Rgb8 => vec![rgba[0], rgba[1], rgba[2]],
+					ExtendedColorType::Rgba16 => vec![
+						rgba[0], rgba[0], rgba[1], rgba[1], rgba[2], rgba[2], alpha,
+						alpha,
+					],
+					ExtendedColorType::Rgba32F => {
+						let mut data = Vec::new();
+						data.extend_from_slice(
+							&(rgba[0] as f32 / 255.).to_ne_bytes(),
+						);
+						data.extend_from_slice(
+							&(rgba[1] as f32 / 255.).to_ne_bytes(),
+						);
+						data.extend_from_slice(
+							&(rgba[2] as f32 / 255.).to_ne_bytes(),
+						);
+						data.extend_from_slice(&(alpha as f32 / 255.).to_ne_bytes());
+						data
+					}
+					_ => vec![rgba[0], rgba[1], rgba[2], alpha],
+				});
+				data
+			}))
+	}
+
+	/**
+	 * Make the pixels that match the given color within the
+	 * tolerance transparent.
+	 *
+	 * @param  color
+	 * @param  tolerance
+	 * @return Result
+	 */
+	pub fn set_transparent(
+		&mut self,
+		color: Rgb<u8>,
+		tolerance: u8,
+	) -> AppResult<()> {
+		let alpha_channel = self.alpha_channel;
+		for rgba in self.get_pixels_mut()?.iter_mut() {
+			rgba[3] = if rgba.0[0..3]
+				.iter()
+				.zip(color.0)
+				.all(|(value, key)| value.abs_diff(key) <= tolerance)
+			{
+				0
+			} else if alpha_channel {
+				rgba[3]
+			} else {
+				255
+			};
+		}
+		self.alpha_channel = true;
+		Ok(())
+	}
+
+	/**
+	 * Draw a circle outline with the given color and opacity.
+	 *
+	 * @param  center
+	 * @param  radius
+	 * @param  color
+	 * @param  opacity
+	 * @return Result
+	 */
+	pub fn draw_circle(
+		&mut self,
+		center: (i32, i32),
+		radius: u32,
+		color: Rgb<u8>,
+		opacity: f32,
+	) -> AppResult<()> {
+		let (width, height) = (self.geometry.width, self.geometry.height);
+		let radius = radius as f32;
+		let pixels = self.get_pixels_mut()?;
+		for y in 0..height {
+			for x in 0..width {
+				let distance = ((x as f32 - center.0 as f32).powi(2)
+					+ (y as f32 - center.1 as f32).powi(2))
+				.sqrt();
+				if (distance - radius).abs() <= CIRCLE_THICKNESS / 2. {
+					let rgba = &mut pixels[(y * width + x) as usize];
+					for i in 0..3 {
+						rgba[i] = (f32::from(rgba[i]) * (1. - opacity)
+							+ f32::from(color[i]) * opacity) as u8;
 					}
-				}],
-				ExtendedColorType::Rgb8 => vec![rgba[0], rgba[1], rgba[2]],
-				ExtendedColorType::Rgba16 => vec![
-					rgba[0], rgba[0], rgba[1], rgba[1], rgba[2], rgba[2], alpha,
-					alpha,
-				],
-				ExtendedColorType::Rgba32F => {
-					let mut data = Vec::new();
-					data.extend_from_slice(&(rgba[0] as f32 / 255.).to_ne_bytes());
-					data.extend_from_slice(&(rgba[1] as f32 / 255.).to_ne_bytes());
-					data.extend_from_slice(&(rgba[2] as f32 / 255.).to_ne_bytes());
-					data.extend_from_slice(&(alpha as f32 / 255.).to_ne_bytes());
-					data
 				}
-				_ => vec![rgba[0], rgba[1], rgba[2], alpha],
-			});
-			data
+			}
+		}
+		Ok(())
+	}
+
+	/**
+	 * Draw the given text on the image.
+	 *
+	 * @param  renderer
+	 * @param  text
+	 * @return Result
+	 */
+	pub fn draw_text(
+		&mut self,
+		renderer: &TextRenderer,
+		text: &str,
+	) -> AppResult<()> {
+		let (width, height) = (self.geometry.width, self.geometry.height);
+		let pixels = self.get_pixels_mut()?;
+		renderer.render(text, (width, height), |x, y, color| {
+			pixels[(y * width + x) as usize].blend(&color)
+		});
+		Ok(())
+	}
+
+	/**
+	 * Flatten the image onto the given background.
+	 *
+	 * @param  background
+	 * @return Result
+	 */
+	pub fn flatten(&mut self, background: &Background) -> AppResult<()> {
+		if self.alpha_channel {
+			let canvas =
+				background.get_image((self.geometry.width, self.geometry.height));
+			for (pixel, color) in
+				self.get_pixels_mut()?.iter_mut().zip(canvas.pixels())
+			{
+				let mut color = *color;
+				color.blend(pixel);
+				*pixel = color;
+			}
+			self.alpha_channel = !self.is_opaque()?;
+		}
+		Ok(())
+	}
+
+	/**
+	 * Draw the given image over the image at a position.
+	 *
+	 * @param  image
+	 * @param  x
+	 * @param  y
+	 * @return Result
+	 */
+	pub fn overlay(&mut self, image: &Image, x: i32, y: i32) -> AppResult<()> {
+		let (width, height) = (
+			i32::try_from(self.geometry.width).unwrap_or_default(),
+			i32::try_from(self.geometry.height).unwrap_or_default(),
+		);
+		let pixels = self.get_pixels_mut()?;
+		for (i, rgba) in image.get_pixels()?.iter().enumerate() {
+			let i = i32::try_from(i).unwrap_or_default();
+			let image_width =
+				i32::try_from(image.geometry.width.max(1)).unwrap_or_default();
+			let (x, y) = (x + i % image_width, y + i / image_width);
+			if (0..width).contains(&x) && (0..height).contains(&y) {
+				pixels[(y * width + x) as usize] = *rgba;
+			}
+		}
+		Ok(())
+	}
+
+	/**
+	 * Get the percentage of pixels that differ from the given image.
+	 *
+	 * @param  image
+	 * @return f64 (Result)
+	 */
+	pub fn get_diff_percentage(&self, image: &Image) -> AppResult<f64> {
+		if self.geometry.width != image.geometry.width
+			|| self.geometry.height != image.geometry.height
+		{
+			return Ok(100.);
+		}
+		let pixels = self.get_pixels()?;
+		let changed = pixels
+			.iter()
+			.zip(image.get_pixels()?.iter())
+			.filter(|(a, b)| a.0[0..3] != b.0[0..3])
+			.count();
+		Ok((changed as f64 / pixels.len().max(1) as f64) * 100.)
+	}
+
+	/**
+	 * Get the bounding box of the pixels that differ from the given image.
+	 *
+	 * @param  image
+	 * @return Geometry (Option) (Result)
+	 */
+	pub fn get_diff_area(&self, image: &Image) -> AppResult<Option<Geometry>> {
+		let (width, height) = (self.geometry.width, self.geometry.height);
+		if width != image.geometry.width || height != image.geometry.height {
+			return Ok(Some(Geometry::new(0, 0, width, height)));
+		}
+		let alpha_channel = self.alpha_channel || image.alpha_channel;
+		let (mut min, mut max) = ((u32::MAX, u32::MAX), (0, 0));
+		for (i, (a, b)) in self
+			.get_pixels()?
+			.iter()
+			.zip(image.get_pixels()?.iter())
+			.enumerate()
+		{
+			if a.0[0..3] != b.0[0..3] || (alpha_channel && a[3] != b[3]) {
+				let (x, y) = (i as u32 % width.max(1), i as u32 / width.max(1));
+				min = (min.0.min(x), min.1.min(y));
+				max = (max.0.max(x), max.1.max(y));
+			}
+		}
+		Ok(if min.0 > max.0 {
+			None
+		} else {
+			Some(Geometry::new(
+				min.0.try_into().unwrap_or_default(),
+				min.1.try_into().unwrap_or_default(),
+				max.0 - min.0 + 1,
+				max.1 - min.1 + 1,
+			))
+		})
+	}
+
+	/**
+	 * Check if the image has no transparent pixels.
+	 *
+	 * @return bool (Result)
+	 */
+	pub fn is_opaque(&self) -> AppResult<bool> {
+		Ok(!self.alpha_channel
+			|| self.get_pixels()?.iter().all(|pixel| pixel[3] == 0xFF))
+	}
+
+	/**
+	 * Crop the given area of the image.
+	 *
+	 * @param  area
+	 * @return Image (Result)
+	 */
+	pub fn crop(&self, area: Geometry) -> AppResult<Self> {
+		let pixels = self.get_pixels()?;
+		let (x, y) = (
+			u32::try_from(area.x).unwrap_or_default(),
+			u32::try_from(area.y).unwrap_or_default(),
+		);
+		let data = (y..y + area.height)
+			.flat_map(|row| {
+				let start = (row * self.geometry.width + x) as usize;
+				pixels[start..start + area.width as usize].iter().copied()
+			})
+			.collect();
+		Ok(Self {
+			timestamp: self.timestamp,
+			delay: self.delay,
+			..Self::new(data, self.alpha_channel, area)
+		})
+	}
+
+	/**
+	 * Blend the image with another image of the same size.
+	 *
+	 * @param  image
+	 * @param  ratio
+	 * @return Image (Result)
+	 */
+	pub fn blend(&self, image: &Image, ratio: f64) -> AppResult<Self> {
+		let ratio = ratio.clamp(0., 1.);
+		let data = self
+			.get_pixels()?
+			.iter()
+			.zip(image.get_pixels()?.iter())
+			.map(|(a, b)| {
+				Rgba([0, 1, 2, 3].map(|i| {
+					(f64::from(a[i]) * (1. - ratio) + f64::from(b[i]) * ratio)
+						.round() as u8
+				}))
+			})
+			.collect();
+		Ok(Self::new(
+			data,
+			self.alpha_channel || image.alpha_channel,
+			self.geometry,
+		))
+	}
+
+	/**
+	 * Fit the image into the given size by cropping or letterboxing.
+	 *
+	 * @param  width
+	 * @param  height
+	 * @param  letterbox
+	 * @return Image (Result)
+	 */
+	pub fn fit(&self, width: u32, height: u32, letterbox: bool) -> AppResult<Self> {
+		let (src_width, src_height) = (self.geometry.width, self.geometry.height);
+		let (scaled_width, scaled_height) = if letterbox {
+			let scale = f64::min(
+				f64::from(width) / f64::from(src_width.max(1)),
+				f64::from(height) / f64::from(src_height.max(1)),
+			);
+			(
+				((f64::from(src_width) * scale) as u32).clamp(1, width.max(1)),
+				((f64::from(src_height) * scale) as u32).clamp(1, height.max(1)),
+			)
+		} else {
+			(src_width, src_height)
+		};
+		let (x_offset, y_offset) = if letterbox {
+			((width - scaled_width) / 2, (height - scaled_height) / 2)
+		} else {
+			(0, 0)
+		};
+		let pixels = self.get_pixels()?;
+		let mut data = vec![Rgba::from([0, 0, 0, 255]); (width * height) as usize];
+		for y in 0..height {
+			for x in 0..width {
+				let (x_scaled, y_scaled) =
+					(x.wrapping_sub(x_offset), y.wrapping_sub(y_offset));
+				if x_scaled < scaled_width && y_scaled < scaled_height {
+					let (src_x, src_y) = (
+						x_scaled * src_width / scaled_width,
+						y_scaled * src_height / scaled_height,
+					);
+					data[(y * width + x) as usize] =
+						pixels[(src_y * src_width + src_x) as usize];
+				}
+			}
+		}
+		Ok(Self::new(
+			data,
+			self.alpha_channel,
+			Geometry::new(self.geometry.x, self.geometry.y, width, height),
+		))
+	}
+
+	/**
+	 * Scale the image by the given factor.
+	 *
+	 * @param  factor
+	 * @return Image (Result)
+	 */
+	pub fn scale(&self, factor: f64) -> AppResult<Self> {
+		let (width, height) = (
+			((f64::from(self.geometry.width) * factor).round() as u32).max(1),
+			((f64::from(self.geometry.height) * factor).round() as u32).max(1),
+		);
+		let image: RgbaImage = ImageBuffer::from_raw(
+			self.geometry.width,
+			self.geometry.height,
+			self.get_data(ExtendedColorType::Rgba8)?,
+		)
+		.ok_or_else(|| AppError::FrameError(String::from("Invalid image size")))?;
+		Ok(Self {
+			timestamp: self.timestamp,
+			delay: self.delay,
+			..Self::new(
+				imageops::resize(&image, width, height, FilterType::Triangle)
+					.pixels()
+					.copied()
+					.collect(),
+				self.alpha_channel,
+				Geometry::new(self.geometry.x, self.geometry.y, width, height),
+			)
 		})
 	}
 
 	/**
 	 * Get an Img Vector from the image data.
 	 *
-	 * @return ImgVec
+	 * @return ImgVec (Result)
 	 */
 	#[cfg(feature = "ski")]
-	pub fn get_img_vec(&self) -> ImgVec<RGBA8> {
-		Img::new(
-			self.data
-				.iter()
-				.fold(Vec::<RGBA8>::new(), |mut rgba8, rgba| {
+	pub fn get_img_vec(&self) -> AppResult<ImgVec<RGBA8>> {
+		Ok(Img::new(
+			self.get_pixels()?.iter().fold(
+				Vec::<RGBA8>::new(),
+				|mut rgba8, rgba| {
 					let alpha = if self.alpha_channel { rgba[3] } else { 255 };
 					rgba8.extend(vec![RGBA8 {
 						r: rgba[0],
@@ -112,10 +556,11 @@ impl Image {
 						a: alpha,
 					}]);
 					rgba8
-				}),
+				},
+			),
 			self.geometry.width.try_into().unwrap_or_default(),
 			self.geometry.height.try_into().unwrap_or_default(),
-		)
+		))
 	}
 }
 
@@ -124,7 +569,7 @@ mod tests {
 	use super::*;
 	use pretty_assertions::assert_eq;
 	#[test]
-	fn test_image() {
+	fn test_image() -> AppResult<()> {
 		let geometry = Geometry::new(0, 0, 200, 200);
 		let data: [Rgba<u8>; 2] = [
 			Rgba::from([128, 128, 128, 0]),
@@ -134,17 +579,139 @@ mod tests {
 		assert_eq!(
 			format!("{image:?}"),
 			"Image { data_len: 2, alpha_channel: false, \
-			geometry: Geometry { x: 0, y: 0, width: 200, height: 200 } }"
-		);
-		assert_eq!(2, image.get_data(ExtendedColorType::L1).len());
-		assert_eq!(2, image.get_data(ExtendedColorType::L8).len());
-		assert_eq!(6, image.get_data(ExtendedColorType::Rgb8).len());
-		assert_eq!(8, image.get_data(ExtendedColorType::Rgba8).len());
-		assert_eq!(16, image.get_data(ExtendedColorType::Rgba16).len());
-		assert_eq!(255, image.get_data(ExtendedColorType::L1)[0]);
-		assert_eq!(255, image.get_data(ExtendedColorType::L8)[1]);
-		assert_eq!(255, image.get_data(ExtendedColorType::Rgb8)[4]);
-		assert_eq!(255, image.get_data(ExtendedColorType::Rgba8)[5]);
-		assert_eq!(128, image.get_data(ExtendedColorType::Rgba16)[5]);
+			geometry: Geometry { x: 0, y: 0, width: 200, height: 200 }, \
+			timestamp: None, delay: None }"
+		);
+		assert_eq!(2, image.get_data(ExtendedColorType::L1)?.len());
+		assert_eq!(2, image.get_data(ExtendedColorType::L8)?.len());
+		assert_eq!(6, image.get_data(ExtendedColorType::Rgb8)?.len());
+		assert_eq!(8, image.get_data(ExtendedColorType::Rgba8)?.len());
+		assert_eq!(16, image.get_data(ExtendedColorType::Rgba16)?.len());
+		assert_eq!(255, image.get_data(ExtendedColorType::L1)?[0]);
+		assert_eq!(255, image.get_data(ExtendedColorType::L8)?[1]);
+		assert_eq!(255, image.get_data(ExtendedColorType::Rgb8)?[4]);
+		assert_eq!(255, image.get_data(ExtendedColorType::Rgba8)?[5]);
+		assert_eq!(128, image.get_data(ExtendedColorType::Rgba16)?[5]);
+		let mut image = image;
+		image.set_transparent(Rgb([255, 255, 255]), 0)?;
+		assert_eq!(
+			vec![128, 128, 128, 255, 255, 255, 255, 0],
+			image.get_data(ExtendedColorType::Rgba8)?
+		);
+		image.set_transparent(Rgb([120, 130, 125]), 10)?;
+		assert_eq!(0, image.get_data(ExtendedColorType::Rgba8)?[3]);
+		let image = Image::new(
+			vec![Rgba::from([255, 0, 0, 255]); 4],
+			false,
+			Geometry::new(0, 0, 2, 2),
+		);
+		let fitted = image.fit(3, 1, false)?;
+		assert_eq!(Geometry::new(0, 0, 3, 1), fitted.geometry);
+		assert_eq!(
+			vec![255, 0, 0, 255, 0, 0, 0, 0, 0],
+			fitted.get_data(ExtendedColorType::Rgb8)?
+		);
+		let fitted = image.fit(3, 1, true)?;
+		assert_eq!(
+			vec![0, 0, 0, 255, 0, 0, 0, 0, 0],
+			fitted.get_data(ExtendedColorType::Rgb8)?
+		);
+		assert_eq!(
+			16 * 3,
+			image
+				.fit(4, 4, true)?
+				.get_data(ExtendedColorType::Rgb8)?
+				.len()
+		);
+		let mut image = image.fit(5, 5, false)?;
+		image.overlay(
+			&Image::new(
+				vec![Rgba::from([0, 255, 0, 255]); 4],
+				false,
+				Geometry::new(0, 0, 2, 2),
+			),
+			4,
+			-1,
+		)?;
+		assert_eq!(
+			vec![0, 255, 0],
+			image.get_data(ExtendedColorType::Rgb8)?[12..15]
+		);
+		let previous = image.clone();
+		assert!(Arc::ptr_eq(&image.data, &previous.data));
+		assert_eq!(0., image.get_diff_percentage(&previous)?);
+		assert_eq!(None, image.get_diff_area(&previous)?);
+		image.draw_circle((4, 4), 1, Rgb([0, 0, 255]), 1.)?;
+		assert!(!Arc::ptr_eq(&image.data, &previous.data));
+		assert_eq!(32., image.get_diff_percentage(&previous)?);
+		let area = image.get_diff_area(&previous)?.unwrap();
+		assert_eq!(
+			(5, 5),
+			(area.x + area.width as i32, area.y + area.height as i32)
+		);
+		let cropped = image.crop(area)?;
+		assert_eq!(area, cropped.geometry);
+		assert!(cropped.is_opaque()?);
+		assert_eq!(
+			image
+				.get_data(ExtendedColorType::Rgb8)?
+				.iter()
+				.zip(previous.get_data(ExtendedColorType::Rgb8)?)
+				.map(|(a, b)| (u16::from(*a) + u16::from(b)).div_ceil(2) as u8)
+				.collect::<Vec<u8>>(),
+			image
+				.blend(&previous, 0.5)?
+				.get_data(ExtendedColorType::Rgb8)?
+		);
+		assert_eq!(
+			image.get_data(ExtendedColorType::Rgb8)?[72..75],
+			cropped.get_data(ExtendedColorType::Rgb8)?
+				[(area.width * area.height * 3 - 3) as usize..]
+		);
+		assert_eq!(100., image.get_diff_percentage(&image.fit(1, 1, false)?)?);
+		let data = image.get_data(ExtendedColorType::Rgb8)?;
+		assert_eq!(vec![255, 0, 0], data[0..3]);
+		assert_eq!(vec![0, 0, 255], data[66..69]);
+		let scaled = image.with_timestamp(Duration::from_millis(5)).scale(0.5)?;
+		assert_eq!(Geometry::new(0, 0, 3, 3), scaled.geometry);
+		assert_eq!(Some(Duration::from_millis(5)), scaled.timestamp);
+		assert_eq!(
+			vec![255, 0, 0],
+			scaled.get_data(ExtendedColorType::Rgb8)?[0..3]
+		);
+		let mut image = Image::new(
+			vec![Rgba([255, 0, 0, 0]), Rgba([255, 0, 0, 255])],
+			true,
+			Geometry::new(0, 0, 2, 1),
+		);
+		image.flatten(&Background::Solid(Rgba([0, 0, 255, 255])))?;
+		assert!(image.is_opaque()?);
+		assert_eq!(
+			vec![0, 0, 255, 255, 0, 0],
+			image.get_data(ExtendedColorType::Rgb8)?
+		);
+		Ok(())
+	}
+	#[test]
+	fn test_spooled_image() -> AppResult<()> {
+		let spool = Arc::new(FrameSpool::new()?);
+		let image = Image::new(
+			vec![Rgba([1, 2, 3, 255]); 2],
+			false,
+			Geometry::new(0, 0, 2, 1),
+		)
+		.into_spool(&spool)?;
+		assert_eq!(
+			vec![1, 2, 3, 1, 2, 3],
+			image.get_data(ExtendedColorType::Rgb8)?
+		);
+		let mut image = Image {
+			spool: Some((spool, 100, 2)),
+			..image
+		};
+		assert!(image.get_data(ExtendedColorType::Rgb8).is_err());
+		assert!(image.crop(Geometry::new(0, 0, 1, 1)).is_err());
+		assert!(image.set_transparent(Rgb([1, 2, 3]), 0).is_err());
+		Ok(())
 	}
 }
@@ -1,5 +1,6 @@
 use device_query::Keycode;
 use std::fmt;
+use std::ops::AddAssign;
 
 /* Padding properties */
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -34,6 +35,16 @@ impl fmt::Display for Padding {
 	}
 }
 
+/* Implementation for extending the padding values */
+impl AddAssign for Padding {
+	fn add_assign(&mut self, other: Self) {
+		self.top += other.top;
+		self.right += other.right;
+		self.bottom += other.bottom;
+		self.left += other.left;
+	}
+}
+
 impl Padding {
 	/**
 	 * Create a new Padding object.
@@ -113,5 +124,8 @@ mod tests {
 		assert_eq!(30, padding.right);
 		assert_eq!(20, padding.bottom);
 		assert_eq!(40, padding.left);
+		let mut padding = Padding::new(1, 2, 3, 4);
+		padding += Padding::new(4, 3, 2, 1);
+		assert_eq!(Padding::new(5, 5, 5, 5), padding);
 	}
 }
@@ -3,12 +3,25 @@ use crate::args::parser::ArgParser;
 use image::codecs::png::{CompressionType, FilterType};
 use image::codecs::pnm::{PnmSubtype, SampleEncoding};
 use image::codecs::webp::WebPQuality;
+use image::Rgb;
 
-/* PNG compression and filter settings */
+/* Color types of the PNG output */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PngColorType {
+	Rgba,
+	Rgb,
+	Gray,
+	GrayAlpha,
+	Indexed,
+}
+
+/* PNG compression, filter, color type and bit depth settings */
 #[derive(Clone, Copy, Debug)]
 pub struct PngSettings {
 	pub compression: CompressionType,
 	pub filter: FilterType,
+	pub color_type: PngColorType,
+	pub depth: u8,
 }
 
 /* Default initialization values for PngSettings */
@@ -17,6 +30,8 @@ impl Default for PngSettings {
 		Self {
 			compression: CompressionType::Fast,
 			filter: FilterType::Sub,
+			color_type: PngColorType::Rgba,
+			depth: 8,
 		}
 	}
 }
@@ -27,15 +42,33 @@ impl PngSettings {
 	 *
 	 * @param  compression
 	 * @param  filter
+	 * @param  color_type
+	 * @param  depth
 	 * @return PngSettings
 	 */
-	pub fn new(compression: CompressionType, filter: FilterType) -> Self {
+	pub fn new(
+		compression: CompressionType,
+		filter: FilterType,
+		color_type: PngColorType,
+		depth: u8,
+	) -> Self {
 		Self {
 			compression,
 			filter,
+			color_type,
+			depth,
 		}
 	}
 
+	/**
+	 * Check if the output is 8-bit RGBA.
+	 *
+	 * @return bool
+	 */
+	pub fn is_rgba8(&self) -> bool {
+		self.color_type == PngColorType::Rgba && self.depth == 8
+	}
+
 	/**
 	 * Create a new PngSettings object from arguments.
 	 *
@@ -69,6 +102,17 @@ impl PngSettings {
 					Some("paeth") => FilterType::Paeth,
 					_ => FilterType::Sub,
 				},
+				match matches.value_of("color-type") {
+					Some("rgb") => PngColorType::Rgb,
+					Some("gray") => PngColorType::Gray,
+					Some("gray-alpha") => PngColorType::GrayAlpha,
+					Some("indexed") => PngColorType::Indexed,
+					_ => PngColorType::Rgba,
+				},
+				match matches.value_of("depth") {
+					Some("16") => 16,
+					_ => 8,
+				},
 			),
 			None => Self::default(),
 		}
@@ -123,10 +167,12 @@ impl JpgSettings {
 	}
 }
 
-/* WebP quality setting */
+/* WebP quality and transparency settings */
 #[derive(Clone, Copy, Debug)]
 pub struct WebPSettings {
 	quality: Option<u8>,
+	pub transparent: Option<Rgb<u8>>,
+	pub tolerance: u8,
 }
 
 /* Default initialization values for WebPSettings */
@@ -134,6 +180,8 @@ impl Default for WebPSettings {
 	fn default() -> Self {
 		Self {
 			quality: Some(WebPQuality::DEFAULT),
+			transparent: None,
+			tolerance: 0,
 		}
 	}
 }
@@ -146,7 +194,10 @@ impl WebPSettings {
 	 * @return WebPSettings
 	 */
 	pub fn new(quality: Option<u8>) -> Self {
-		Self { quality }
+		Self {
+			quality,
+			..Self::default()
+		}
 	}
 
 	/**
@@ -179,18 +230,267 @@ impl WebPSettings {
 	 */
 	fn from_parser(parser: ArgParser<'_>) -> Self {
 		match &parser.args {
-			Some(args) => {
-				if args.is_present("lossless") {
+			Some(args) => Self {
+				transparent: args
+					.value_of("transparent")
+					.and_then(|color| {
+						hex::decode(color.trim_start_matches('#')).ok()
+					})
+					.filter(|rgb| rgb.len() == 3)
+					.map(|rgb| Rgb([rgb[0], rgb[1], rgb[2]])),
+				tolerance: parser.parse("tolerance", 0),
+				..if args.is_present("lossless") {
 					Self::new(None)
 				} else {
 					Self::new(Some(parser.parse("quality", WebPQuality::DEFAULT)))
 				}
+			},
+			None => Self::default(),
+		}
+	}
+}
+
+/* JPEG XL encoding settings */
+#[derive(Clone, Copy, Debug)]
+pub struct JxlSettings {
+	pub effort: u8,
+}
+
+/* Default initialization values for JxlSettings */
+impl Default for JxlSettings {
+	fn default() -> Self {
+		Self { effort: 4 }
+	}
+}
+
+impl JxlSettings {
+	/**
+	 * Create a new JxlSettings object.
+	 *
+	 * @param  effort
+	 * @return JxlSettings
+	 */
+	pub fn new(effort: u8) -> Self {
+		Self { effort }
+	}
+
+	/**
+	 * Create a new JxlSettings object from arguments.
+	 *
+	 * @param  matches
+	 * @return JxlSettings
+	 */
+	pub fn from_args(matches: &ArgMatches<'_>) -> Self {
+		Self::from_parser(ArgParser::from_subcommand(matches, "jxl"))
+	}
+
+	/**
+	 * Create a JxlSettings object from an argument parser.
+	 *
+	 * @param  parser
+	 * @return JxlSettings
+	 */
+	fn from_parser(parser: ArgParser<'_>) -> Self {
+		match parser.args {
+			Some(_) => {
+				Self::new(parser.parse("effort", Self::default().effort).min(127))
 			}
 			None => Self::default(),
 		}
 	}
 }
 
+/* HEIF quality settings */
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "heif"), allow(dead_code))]
+pub struct HeifSettings {
+	pub quality: u8,
+	pub lossless: bool,
+}
+
+/* Default initialization values for HeifSettings */
+impl Default for HeifSettings {
+	fn default() -> Self {
+		Self {
+			quality: 80,
+			lossless: false,
+		}
+	}
+}
+
+impl HeifSettings {
+	/**
+	 * Create a new HeifSettings object.
+	 *
+	 * @param  quality
+	 * @param  lossless
+	 * @return HeifSettings
+	 */
+	pub fn new(quality: u8, lossless: bool) -> Self {
+		Self { quality, lossless }
+	}
+
+	/**
+	 * Create a new HeifSettings object from arguments.
+	 *
+	 * @param  matches
+	 * @return HeifSettings
+	 */
+	pub fn from_args(matches: &ArgMatches<'_>) -> Self {
+		Self::from_parser(ArgParser::from_subcommand(matches, "heif"))
+	}
+
+	/**
+	 * Create a HeifSettings object from an argument parser.
+	 *
+	 * @param  parser
+	 * @return HeifSettings
+	 */
+	fn from_parser(parser: ArgParser<'_>) -> Self {
+		match &parser.args {
+			Some(args) => Self::new(
+				parser.parse("quality", Self::default().quality).min(100),
+				args.is_present("lossless"),
+			),
+			None => Self::default(),
+		}
+	}
+}
+
+/* ICO image sizes setting */
+#[derive(Clone, Debug)]
+pub struct IcoSettings {
+	pub sizes: Vec<u32>,
+}
+
+/* Default initialization values for IcoSettings */
+impl Default for IcoSettings {
+	fn default() -> Self {
+		Self {
+			sizes: vec![16, 32, 48, 64],
+		}
+	}
+}
+
+impl IcoSettings {
+	/**
+	 * Create a new IcoSettings object.
+	 *
+	 * @param  sizes
+	 * @return IcoSettings
+	 */
+	pub fn new(sizes: Vec<u32>) -> Self {
+		Self { sizes }
+	}
+
+	/**
+	 * Create a new IcoSettings object from arguments.
+	 *
+	 * @param  matches
+	 * @return IcoSettings
+	 */
+	pub fn from_args(matches: &ArgMatches<'_>) -> Self {
+		Self::from_parser(ArgParser::from_subcommand(matches, "ico"))
+	}
+
+	/**
+	 * Create a IcoSettings object from an argument parser.
+	 *
+	 * @param  parser
+	 * @return IcoSettings
+	 */
+	fn from_parser(parser: ArgParser<'_>) -> Self {
+		match parser
+			.args
+			.as_ref()
+			.and_then(|matches| matches.value_of("sizes"))
+		{
+			Some(sizes) => match Self::parse_sizes(sizes) {
+				Some(sizes) => Self::new(sizes),
+				None => {
+					warn!("Invalid icon sizes: {:?}", sizes);
+					Self::default()
+				}
+			},
+			None => Self::default(),
+		}
+	}
+
+	/**
+	 * Parse the comma-separated icon sizes (1-256).
+	 *
+	 * @param  sizes
+	 * @return Vector of u32 (Option)
+	 */
+	pub fn parse_sizes(sizes: &str) -> Option<Vec<u32>> {
+		sizes
+			.split(',')
+			.map(|size| {
+				size.trim()
+					.parse::<u32>()
+					.ok()
+					.filter(|size| (1..=256).contains(size))
+			})
+			.collect()
+	}
+}
+
+/* ANSI/ASCII art settings */
+#[derive(Clone, Copy, Debug)]
+pub struct AnsiSettings {
+	pub width: u32,
+	pub ascii: bool,
+}
+
+/* Default initialization values for AnsiSettings */
+impl Default for AnsiSettings {
+	fn default() -> Self {
+		Self {
+			width: 80,
+			ascii: false,
+		}
+	}
+}
+
+impl AnsiSettings {
+	/**
+	 * Create a new AnsiSettings object.
+	 *
+	 * @param  width
+	 * @param  ascii
+	 * @return AnsiSettings
+	 */
+	pub fn new(width: u32, ascii: bool) -> Self {
+		Self { width, ascii }
+	}
+
+	/**
+	 * Create a new AnsiSettings object from arguments.
+	 *
+	 * @param  matches
+	 * @return AnsiSettings
+	 */
+	pub fn from_args(matches: &ArgMatches<'_>) -> Self {
+		Self::from_parser(ArgParser::from_subcommand(matches, "ansi"))
+	}
+
+	/**
+	 * Create a AnsiSettings object from an argument parser.
+	 *
+	 * @param  parser
+	 * @return AnsiSettings
+	 */
+	fn from_parser(parser: ArgParser<'_>) -> Self {
+		match &parser.args {
+			Some(matches) => Self::new(
+				parser.parse("width", Self::default().width),
+				matches.is_present("ascii"),
+			),
+			None => Self::default(),
+		}
+	}
+}
+
 /* PNM subtype settings */
 #[derive(Clone, Copy, Debug)]
 pub struct PnmSettings {
@@ -260,11 +560,11 @@ mod tests {
 	#[test]
 	fn test_png_settings() {
 		for value in &[
-			("default", "none"),
-			("best", "up"),
-			("default", "avg"),
-			("best", "paeth"),
-			("", ""),
+			("default", "none", "gray", "16"),
+			("best", "up", "indexed", "16"),
+			("default", "avg", "rgb", "16"),
+			("best", "paeth", "gray-alpha", "16"),
+			("", "", "", ""),
 		] {
 			let args = App::new("test")
 				.arg(
@@ -273,12 +573,22 @@ mod tests {
 						.takes_value(true),
 				)
 				.arg(Arg::with_name("filter").long("filter").takes_value(true))
+				.arg(
+					Arg::with_name("color-type")
+						.long("color-type")
+						.takes_value(true),
+				)
+				.arg(Arg::with_name("depth").long("depth").takes_value(true))
 				.get_matches_from(vec![
 					"test",
 					"--compression",
 					value.0,
 					"--filter",
 					value.1,
+					"--color-type",
+					value.2,
+					"--depth",
+					value.3,
 				]);
 			let png_settings = PngSettings::from_parser(ArgParser::from_args(&args));
 			if value.0.is_empty() && value.1.is_empty() {
@@ -287,12 +597,18 @@ mod tests {
 					png_settings.compression
 				);
 				assert_eq!(PngSettings::default().filter, png_settings.filter);
+				assert!(png_settings.is_rgba8());
 			} else {
 				assert_ne!(
 					PngSettings::default().compression,
 					png_settings.compression
 				);
 				assert_ne!(PngSettings::default().filter, png_settings.filter);
+				assert_ne!(
+					PngSettings::default().color_type,
+					png_settings.color_type
+				);
+				assert_eq!(16, png_settings.depth);
 			}
 		}
 	}
@@ -322,11 +638,82 @@ mod tests {
 		);
 		let args = App::new("test")
 			.arg(Arg::with_name("lossless").long("lossless"))
-			.get_matches_from(vec!["test", "--lossless"]);
+			.arg(
+				Arg::with_name("transparent")
+					.long("transparent")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("tolerance")
+					.long("tolerance")
+					.takes_value(true),
+			)
+			.get_matches_from(vec![
+				"test",
+				"--lossless",
+				"--transparent",
+				"ff00ff",
+				"--tolerance",
+				"30",
+			]);
+		let webp_settings = WebPSettings::from_parser(ArgParser::from_args(&args));
+		assert_eq!(None, webp_settings.quality);
+		assert_eq!(Some(Rgb([255, 0, 255])), webp_settings.transparent);
+		assert_eq!(30, webp_settings.tolerance);
+	}
+	#[test]
+	fn test_jxl_settings() {
+		let args = App::new("test")
+			.arg(Arg::with_name("effort").long("effort").takes_value(true))
+			.get_matches_from(vec!["test", "--effort", "200"]);
 		assert_eq!(
-			None,
-			WebPSettings::from_parser(ArgParser::from_args(&args)).quality
+			127,
+			JxlSettings::from_parser(ArgParser::from_args(&args)).effort
+		);
+		assert_eq!(4, JxlSettings::from_parser(ArgParser::new(None)).effort);
+	}
+	#[test]
+	fn test_heif_settings() {
+		let args = App::new("test")
+			.arg(Arg::with_name("quality").long("quality").takes_value(true))
+			.arg(Arg::with_name("lossless").long("lossless"))
+			.get_matches_from(vec!["test", "--quality", "120", "--lossless"]);
+		let heif_settings = HeifSettings::from_parser(ArgParser::from_args(&args));
+		assert_eq!(100, heif_settings.quality);
+		assert!(heif_settings.lossless);
+		let heif_settings = HeifSettings::from_parser(ArgParser::new(None));
+		assert_eq!(80, heif_settings.quality);
+		assert!(!heif_settings.lossless);
+	}
+	#[test]
+	fn test_ico_settings() {
+		let args = App::new("test")
+			.arg(Arg::with_name("sizes").long("sizes").takes_value(true))
+			.get_matches_from(vec!["test", "--sizes", "16, 32,256"]);
+		assert_eq!(
+			vec![16, 32, 256],
+			IcoSettings::from_parser(ArgParser::from_args(&args)).sizes
 		);
+		assert_eq!(
+			vec![16, 32, 48, 64],
+			IcoSettings::from_parser(ArgParser::new(None)).sizes
+		);
+		assert_eq!(None, IcoSettings::parse_sizes("16,32,x"));
+		assert_eq!(None, IcoSettings::parse_sizes("16,512"));
+		assert_eq!(None, IcoSettings::parse_sizes("0"));
+	}
+	#[test]
+	fn test_ansi_settings() {
+		let args = App::new("test")
+			.arg(Arg::with_name("width").long("width").takes_value(true))
+			.arg(Arg::with_name("ascii").long("ascii"))
+			.get_matches_from(vec!["test", "--width", "40", "--ascii"]);
+		let ansi_settings = AnsiSettings::from_parser(ArgParser::from_args(&args));
+		assert_eq!(40, ansi_settings.width);
+		assert!(ansi_settings.ascii);
+		let ansi_settings = AnsiSettings::from_parser(ArgParser::new(None));
+		assert_eq!(80, ansi_settings.width);
+		assert!(!ansi_settings.ascii);
 	}
 	#[test]
 	fn test_pnm_settings() {
@@ -0,0 +1,100 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* Temporary file for keeping the frames on disk */
+#[derive(Debug)]
+pub struct FrameSpool {
+	path: PathBuf,
+	file: Mutex<(File, u64)>,
+}
+
+impl FrameSpool {
+	/**
+	 * Create a new FrameSpool object in the temporary directory.
+	 *
+	 * @return FrameSpool (Result)
+	 */
+	pub fn new() -> io::Result<Self> {
+		let path = env::temp_dir().join(format!(
+			"menyoki-{}-{}.spool",
+			process::id(),
+			SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.unwrap_or_default()
+				.as_nanos()
+		));
+		let file = OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create_new(true)
+			.open(&path)?;
+		debug!("Spooling frames to {:?}", path);
+		Ok(Self {
+			path,
+			file: Mutex::new((file, 0)),
+		})
+	}
+
+	/**
+	 * Append the data to the spool file.
+	 *
+	 * @param  data
+	 * @return u64 (Result)
+	 */
+	pub fn write(&self, data: &[u8]) -> io::Result<u64> {
+		let mut file = self.file.lock().expect("Failed to lock the spool file");
+		let offset = file.1;
+		file.0.seek(SeekFrom::Start(offset))?;
+		file.0.write_all(data)?;
+		file.1 += data.len() as u64;
+		Ok(offset)
+	}
+
+	/**
+	 * Read the data from the spool file.
+	 *
+	 * @param  offset
+	 * @param  len
+	 * @return Vector of u8 (Result)
+	 */
+	pub fn read(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+		let mut file = self.file.lock().expect("Failed to lock the spool file");
+		let mut data = vec![0; len];
+		file.0.seek(SeekFrom::Start(offset))?;
+		file.0.read_exact(&mut data)?;
+		Ok(data)
+	}
+}
+
+/* Remove the spool file when it is no longer used */
+impl Drop for FrameSpool {
+	fn drop(&mut self) {
+		if let Err(e) = fs::remove_file(&self.path) {
+			warn!("Failed to remove the spool file: {}", e);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_frame_spool() -> io::Result<()> {
+		let spool = FrameSpool::new()?;
+		let path = spool.path.clone();
+		assert_eq!(0, spool.write(&[1, 2, 3])?);
+		assert_eq!(3, spool.write(&[4, 5])?);
+		assert_eq!(vec![4, 5], spool.read(3, 2)?);
+		assert_eq!(vec![2, 3, 4], spool.read(1, 3)?);
+		assert!(spool.read(4, 2).is_err());
+		drop(spool);
+		assert!(!path.exists());
+		Ok(())
+	}
+}
@@ -0,0 +1,92 @@
+use crate::image::settings::JxlSettings;
+use image::error::{EncodingError, ImageFormatHint};
+use image::{ColorType, ImageEncoder, ImageError, ImageResult};
+use std::io::Write;
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::EncoderOptions;
+use zune_jpegxl::JxlSimpleEncoder;
+
+/* JPEG XL encoder and settings */
+pub struct JxlEncoder<Output: Write> {
+	output: Output,
+	settings: JxlSettings,
+}
+
+impl<Output: Write> JxlEncoder<Output> {
+	/**
+	 * Create a new JxlEncoder object.
+	 *
+	 * @param  output
+	 * @param  settings
+	 * @return JxlEncoder
+	 */
+	pub fn new(output: Output, settings: JxlSettings) -> Self {
+		Self { output, settings }
+	}
+
+	/**
+	 * Create an encoding error with the given message.
+	 *
+	 * @param  message
+	 * @return ImageError
+	 */
+	fn get_error(message: String) -> ImageError {
+		ImageError::Encoding(EncodingError::new(
+			ImageFormatHint::Name(String::from("JPEG XL")),
+			message.trim(),
+		))
+	}
+}
+
+/* Implementation for encoding JPEG XL images */
+impl<Output: Write> ImageEncoder for JxlEncoder<Output> {
+	fn write_image(
+		mut self,
+		buf: &[u8],
+		width: u32,
+		height: u32,
+		color_type: ColorType,
+	) -> ImageResult<()> {
+		let (colorspace, depth) = match color_type {
+			ColorType::L8 => (ColorSpace::Luma, BitDepth::Eight),
+			ColorType::La8 => (ColorSpace::LumaA, BitDepth::Eight),
+			ColorType::Rgb8 => (ColorSpace::RGB, BitDepth::Eight),
+			ColorType::Rgba8 => (ColorSpace::RGBA, BitDepth::Eight),
+			ColorType::L16 => (ColorSpace::Luma, BitDepth::Sixteen),
+			ColorType::La16 => (ColorSpace::LumaA, BitDepth::Sixteen),
+			ColorType::Rgb16 => (ColorSpace::RGB, BitDepth::Sixteen),
+			ColorType::Rgba16 => (ColorSpace::RGBA, BitDepth::Sixteen),
+			_ => {
+				return Err(Self::get_error(format!(
+					"Unsupported color type: {color_type:?}"
+				)))
+			}
+		};
+		let options =
+			EncoderOptions::new(width as usize, height as usize, colorspace, depth)
+				.set_effort(self.settings.effort);
+		let data = JxlSimpleEncoder::new(buf, options)
+			.encode()
+			.map_err(|e| Self::get_error(format!("{e:?}")))?;
+		self.output.write_all(&data)?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_jxl_encoder() {
+		let mut output = Vec::new();
+		JxlEncoder::new(&mut output, JxlSettings::default())
+			.write_image(&[255; 16], 2, 2, ColorType::Rgba8)
+			.unwrap();
+		assert_eq!([0xff, 0x0a], output[..2]);
+		assert!(JxlEncoder::new(Vec::new(), JxlSettings::default())
+			.write_image(&[255; 4], 1, 1, ColorType::Rgba8)
+			.is_err());
+	}
+}
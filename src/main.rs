@@ -7,22 +7,36 @@ extern crate log;
 
 mod analyze;
 mod anim;
+mod ansi;
 mod apng;
 mod app;
+mod archive;
 mod args;
+mod diff;
 mod edit;
 mod file;
 mod gif;
+#[cfg(feature = "heif")]
+mod heif;
 mod image;
+mod jxl;
+mod montage;
+mod pdf;
+mod png;
+mod qoi;
 mod record;
 mod settings;
+mod sixel;
+mod tiff;
 mod util;
 mod view;
+mod webp;
 mod window;
 #[cfg(not(all(unix, not(target_os = "macos"))))]
 mod ws;
 #[cfg(all(unix, not(target_os = "macos")))]
 mod x11;
+mod y4m;
 use self::app::App;
 use self::args::matches::ArgMatches;
 use self::args::Args;
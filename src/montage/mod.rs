@@ -0,0 +1,191 @@
+pub mod settings;
+pub mod stitch;
+
+use crate::image::geometry::Geometry;
+use crate::image::Image;
+use crate::montage::settings::MontageSettings;
+use crate::montage::stitch::Overlap;
+use image::{imageops, Rgba, RgbaImage};
+
+/* Combiner of the images into a grid */
+#[derive(Debug)]
+pub struct ImageMontage<'a> {
+	settings: &'a MontageSettings,
+}
+
+impl<'a> ImageMontage<'a> {
+	/**
+	 * Create a new ImageMontage object.
+	 *
+	 * @param  settings
+	 * @return ImageMontage
+	 */
+	pub fn new(settings: &'a MontageSettings) -> Self {
+		Self { settings }
+	}
+
+	/**
+	 * Tile the images into the cells of the grid which have the size of the
+	 * largest image, and center them inside their cells.
+	 *
+	 * @param  images
+	 * @return Image
+	 */
+	pub fn combine(&self, images: &[RgbaImage]) -> Image {
+		let (columns, rows) = self.settings.get_grid(images.len() as u32);
+		let cell = images.iter().fold((0, 0), |size, image| {
+			(size.0.max(image.width()), size.1.max(image.height()))
+		});
+		let gap = self.settings.gap;
+		let (width, height) = (
+			cell.0 * columns + gap * (columns - 1),
+			cell.1 * rows + gap * (rows - 1),
+		);
+		info!(
+			"Combining {} images into a {}x{} grid... ({}x{})",
+			images.len(),
+			columns,
+			rows,
+			width,
+			height
+		);
+		let mut canvas = match &self.settings.background {
+			Some(background) => background.get_image((width, height)),
+			None => RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 0])),
+		};
+		for (i, image) in images.iter().take((columns * rows) as usize).enumerate() {
+			let (column, row) = (i as u32 % columns, i as u32 / columns);
+			imageops::overlay(
+				&mut canvas,
+				image,
+				i64::from(column * (cell.0 + gap) + (cell.0 - image.width()) / 2),
+				i64::from(row * (cell.1 + gap) + (cell.1 - image.height()) / 2),
+			);
+		}
+		Image::new(
+			canvas.pixels().copied().collect(),
+			true,
+			Geometry::new(0, 0, width, height),
+		)
+	}
+
+	/**
+	 * Stitch the captures of a scrolling window vertically by appending
+	 * the rows that are scrolled into view in each capture.
+	 *
+	 * @param  images
+	 * @return Image
+	 */
+	pub fn stitch(&self, images: &[RgbaImage]) -> Image {
+		let mut rows = Vec::<(usize, u32)>::new();
+		for (i, image) in images.iter().enumerate() {
+			let height = image.height() as usize;
+			match i
+				.checked_sub(1)
+				.and_then(|previous| Overlap::detect(&images[previous], image))
+			{
+				Some(overlap) if overlap.scroll == 0 => {
+					debug!("Skipping the capture #{} without scroll", i + 1);
+				}
+				Some(overlap) => {
+					debug!("Capture #{}: {:?}", i + 1, overlap);
+					rows.truncate(rows.len().saturating_sub(overlap.footer));
+					rows.extend(
+						(height - overlap.footer - overlap.scroll..height)
+							.map(|y| (i, y as u32)),
+					);
+				}
+				None => {
+					if i != 0 {
+						warn!("No overlap found for the capture #{}", i + 1);
+					}
+					rows.extend((0..image.height()).map(|y| (i, y)));
+				}
+			}
+		}
+		let width = images.iter().map(|image| image.width()).max().unwrap_or(0);
+		let height = rows.len() as u32;
+		info!(
+			"Stitching {} images vertically... ({}x{})",
+			images.len(),
+			width,
+			height
+		);
+		let mut canvas = match &self.settings.background {
+			Some(background) => background.get_image((width, height)),
+			None => RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 0])),
+		};
+		for (row, (i, y)) in rows.into_iter().enumerate() {
+			let image = &images[i];
+			let offset = (width - image.width()) / 2;
+			for x in 0..image.width() {
+				canvas.put_pixel(x + offset, row as u32, *image.get_pixel(x, y));
+			}
+		}
+		Image::new(
+			canvas.pixels().copied().collect(),
+			true,
+			Geometry::new(0, 0, width, height),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::AppResult;
+	use image::ExtendedColorType;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_montage() -> AppResult<()> {
+		let settings = MontageSettings::new(Vec::new(), (2, 0), 1, None, false);
+		let image = ImageMontage::new(&settings).combine(&[
+			RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255])),
+			RgbaImage::from_pixel(2, 1, Rgba([0, 255, 0, 255])),
+			RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255])),
+		]);
+		assert_eq!(Geometry::new(0, 0, 5, 5), image.geometry);
+		let data = image.get_data(ExtendedColorType::Rgba8)?;
+		let get_pixel = |x: usize, y: usize| {
+			let i = (y * 5 + x) * 4;
+			[data[i], data[i + 1], data[i + 2], data[i + 3]]
+		};
+		assert_eq!([255, 0, 0, 255], get_pixel(1, 1));
+		assert_eq!([0, 0, 0, 0], get_pixel(2, 0));
+		assert_eq!([0, 255, 0, 255], get_pixel(3, 0));
+		assert_eq!([0, 0, 0, 0], get_pixel(3, 1));
+		assert_eq!([0, 0, 255, 255], get_pixel(0, 3));
+		assert_eq!([0, 0, 0, 0], get_pixel(1, 4));
+		assert_eq!([0, 0, 0, 0], get_pixel(4, 4));
+		Ok(())
+	}
+	#[test]
+	fn test_stitch() -> AppResult<()> {
+		let get_color = |y: u32| match y {
+			0..=4 => Rgba([255, 0, 0, 255]),
+			y if y >= 1000 => Rgba([0, 0, 255, 255]),
+			y => Rgba([(y * 7 % 256) as u8, (y / 37) as u8, 0, 255]),
+		};
+		let images = [0, 20, 45, 45, 80]
+			.map(|scroll| {
+				RgbaImage::from_fn(8, 60, |_, y| match y {
+					0..=4 => get_color(y),
+					56..=59 => get_color(1000),
+					y => get_color(y + scroll),
+				})
+			})
+			.to_vec();
+		let settings = MontageSettings::new(Vec::new(), (0, 0), 0, None, true);
+		let image = ImageMontage::new(&settings).stitch(&images);
+		assert_eq!(Geometry::new(0, 0, 8, 140), image.geometry);
+		let data = image.get_data(ExtendedColorType::Rgba8)?;
+		for y in 0..140 {
+			let i = (y * 8 + 7) as usize * 4;
+			assert_eq!(
+				get_color(if y < 136 { y } else { 1000 }).0,
+				[data[i], data[i + 1], data[i + 2], data[i + 3]]
+			);
+		}
+		Ok(())
+	}
+}
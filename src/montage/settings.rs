@@ -0,0 +1,161 @@
+use crate::args::matches::ArgMatches;
+use crate::args::parser::ArgParser;
+use crate::image::background::Background;
+use crate::image::geometry::Geometry;
+use std::path::PathBuf;
+
+/* Image montage settings */
+#[derive(Debug, Default)]
+pub struct MontageSettings {
+	pub files: Vec<PathBuf>,
+	pub grid: (u32, u32),
+	pub gap: u32,
+	pub background: Option<Background>,
+	pub stitch: bool,
+}
+
+impl MontageSettings {
+	/**
+	 * Create a new MontageSettings object.
+	 *
+	 * @param  files
+	 * @param  grid
+	 * @param  gap
+	 * @param  background (Option)
+	 * @param  stitch
+	 * @return MontageSettings
+	 */
+	pub fn new(
+		files: Vec<PathBuf>,
+		grid: (u32, u32),
+		gap: u32,
+		background: Option<Background>,
+		stitch: bool,
+	) -> Self {
+		Self {
+			files,
+			grid,
+			gap,
+			background,
+			stitch,
+		}
+	}
+
+	/**
+	 * Create a new MontageSettings object from arguments.
+	 *
+	 * @param  matches
+	 * @return MontageSettings
+	 */
+	pub fn from_args(matches: &ArgMatches<'_>) -> Self {
+		Self::from_parser(ArgParser::from_subcommand(matches, "montage"))
+	}
+
+	/**
+	 * Create a MontageSettings object from an argument parser.
+	 *
+	 * @param  parser
+	 * @return MontageSettings
+	 */
+	fn from_parser(parser: ArgParser<'_>) -> Self {
+		match parser.args {
+			Some(ref matches) => {
+				let grid =
+					Geometry::parse(matches.value_of("grid").unwrap_or_default());
+				Self::new(
+					matches
+						.values_of("files")
+						.map(|values| {
+							values
+								.map(|file| {
+									PathBuf::from(
+										shellexpand::full(file)
+											.map(|s| s.to_string())
+											.unwrap_or(file.to_string()),
+									)
+								})
+								.collect()
+						})
+						.unwrap_or_default(),
+					(grid.width, grid.height),
+					parser.parse("gap", Self::default().gap),
+					matches.value_of("background").and_then(Background::parse),
+					matches.is_present("stitch"),
+				)
+			}
+			None => Self::default(),
+		}
+	}
+
+	/**
+	 * Get the number of columns and rows for the given number of images.
+	 *
+	 * @param  count
+	 * @return Tuple of u32
+	 */
+	pub fn get_grid(&self, count: u32) -> (u32, u32) {
+		let columns = match self.grid {
+			(0, 0) => (f64::from(count).sqrt().ceil() as u32).max(1),
+			(0, rows) => count.div_ceil(rows).max(1),
+			(columns, _) => columns,
+		};
+		(columns, self.grid.1.max(count.div_ceil(columns)).max(1))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::{App, Arg};
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_montage_settings() {
+		let args = App::new("test")
+			.arg(Arg::with_name("files").multiple(true).required(true))
+			.arg(Arg::with_name("grid").long("grid").takes_value(true))
+			.arg(Arg::with_name("gap").long("gap").takes_value(true))
+			.arg(
+				Arg::with_name("background")
+					.long("background")
+					.takes_value(true),
+			)
+			.arg(Arg::with_name("stitch").long("stitch"))
+			.get_matches_from(vec![
+				"test",
+				"1.png",
+				"2.png",
+				"--grid",
+				"2x0",
+				"--gap",
+				"10",
+				"--background",
+				"FFFFFF",
+				"--stitch",
+			]);
+		let settings = MontageSettings::from_parser(ArgParser::from_args(&args));
+		assert_eq!(
+			vec![PathBuf::from("1.png"), PathBuf::from("2.png")],
+			settings.files
+		);
+		assert_eq!((2, 0), settings.grid);
+		assert_eq!(10, settings.gap);
+		assert_eq!(
+			Some(Background::Solid(Rgba([255, 255, 255, 255]))),
+			settings.background
+		);
+		assert!(settings.stitch);
+		assert_eq!((2, 3), settings.get_grid(5));
+		let settings = MontageSettings::default();
+		assert_eq!((3, 2), settings.get_grid(5));
+		assert_eq!((1, 1), settings.get_grid(0));
+		assert_eq!(
+			(2, 3),
+			MontageSettings::new(Vec::new(), (0, 3), 0, None, false).get_grid(4)
+		);
+		assert_eq!(
+			(2, 4),
+			MontageSettings::new(Vec::new(), (2, 1), 0, None, false).get_grid(7)
+		);
+	}
+}
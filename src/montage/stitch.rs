@@ -0,0 +1,125 @@
+use image::RgbaImage;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/* Minimum number of rows that should overlap between the captures */
+const MIN_OVERLAP: usize = 8;
+/* Minimum ratio of the overlapping rows that should match */
+const MIN_MATCH_RATIO: f64 = 0.9;
+
+/* Overlap of the consecutive captures of a scrolling window */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overlap {
+	pub header: usize,
+	pub footer: usize,
+	pub scroll: usize,
+}
+
+impl Overlap {
+	/**
+	 * Detect the overlap of the captures by finding the fixed rows at
+	 * the top and bottom (e.g. toolbars) and the scrolled distance of
+	 * the content between them.
+	 *
+	 * Rows are compared by their hashes since the captures of the same
+	 * content are expected to be identical, and a small ratio of the
+	 * rows is allowed to differ for the changing parts (e.g. cursor).
+	 *
+	 * @param  previous
+	 * @param  next
+	 * @return Overlap (Option)
+	 */
+	pub fn detect(previous: &RgbaImage, next: &RgbaImage) -> Option<Self> {
+		if previous.dimensions() != next.dimensions() {
+			return None;
+		}
+		let (previous, next) = (get_row_hashes(previous), get_row_hashes(next));
+		let height = previous.len();
+		let header = previous
+			.iter()
+			.zip(&next)
+			.take_while(|(previous, next)| previous == next)
+			.count();
+		if header == height {
+			return Some(Self {
+				header,
+				footer: 0,
+				scroll: 0,
+			});
+		}
+		let footer = previous
+			.iter()
+			.rev()
+			.zip(next.iter().rev())
+			.take_while(|(previous, next)| previous == next)
+			.count();
+		let region = height - header - footer;
+		(1..=region.saturating_sub(MIN_OVERLAP))
+			.map(|scroll| {
+				let matches = (header..height - footer - scroll)
+					.filter(|y| next[*y] == previous[y + scroll])
+					.count();
+				(scroll, matches as f64 / (region - scroll) as f64)
+			})
+			.filter(|(_, ratio)| *ratio >= MIN_MATCH_RATIO)
+			.max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+			.map(|(scroll, _)| Self {
+				header,
+				footer,
+				scroll,
+			})
+	}
+}
+
+/**
+ * Get the hashes of the image rows.
+ *
+ * @param  image
+ * @return Vector of u64
+ */
+fn get_row_hashes(image: &RgbaImage) -> Vec<u64> {
+	image
+		.as_raw()
+		.chunks(image.width().max(1) as usize * 4)
+		.map(|row| {
+			let mut hasher = DefaultHasher::new();
+			row.hash(&mut hasher);
+			hasher.finish()
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_overlap() {
+		let get_capture = |scroll: u32| {
+			RgbaImage::from_fn(10, 40, |x, y| match y {
+				0..=2 => Rgba([255, 0, 0, 255]),
+				38..=39 => Rgba([0, 0, 255, 255]),
+				_ => Rgba([((y + scroll) * 7 % 256) as u8, x as u8, 0, 255]),
+			})
+		};
+		assert_eq!(
+			Some(Overlap {
+				header: 3,
+				footer: 2,
+				scroll: 12
+			}),
+			Overlap::detect(&get_capture(0), &get_capture(12))
+		);
+		assert_eq!(
+			Some(0),
+			Overlap::detect(&get_capture(5), &get_capture(5))
+				.map(|overlap| overlap.scroll)
+		);
+		assert_eq!(None, Overlap::detect(&get_capture(0), &get_capture(34)));
+		assert_eq!(
+			None,
+			Overlap::detect(&get_capture(0), &RgbaImage::new(10, 20))
+		);
+	}
+}
@@ -0,0 +1,197 @@
+use crate::app::AppResult;
+use crate::gif::encoder::CancelHandle;
+use crate::image::Image;
+use image::codecs::jpeg::JpegEncoder;
+use image::{ColorType, ExtendedColorType};
+use std::io::{self, Write};
+
+/* PDF encoder for writing images as pages */
+pub struct PdfEncoder<Output: Write> {
+	output: Output,
+	quality: u8,
+	offsets: Vec<usize>,
+	position: usize,
+}
+
+impl<Output: Write> PdfEncoder<Output> {
+	/**
+	 * Create a new PdfEncoder object.
+	 *
+	 * @param  output
+	 * @param  quality
+	 * @return PdfEncoder
+	 */
+	pub fn new(output: Output, quality: u8) -> Self {
+		Self {
+			output,
+			quality,
+			offsets: Vec::new(),
+			position: 0,
+		}
+	}
+
+	/**
+	 * Write raw bytes to the output and keep track of the position.
+	 *
+	 * @param  data
+	 * @return Result
+	 */
+	fn write(&mut self, data: &[u8]) -> AppResult<()> {
+		self.output.write_all(data)?;
+		self.position += data.len();
+		Ok(())
+	}
+
+	/**
+	 * Write an indirect object with an optional stream.
+	 *
+	 * @param  id
+	 * @param  dict
+	 * @param  stream (Option)
+	 * @return Result
+	 */
+	fn write_object(
+		&mut self,
+		id: usize,
+		dict: &str,
+		stream: Option<&[u8]>,
+	) -> AppResult<()> {
+		if self.offsets.len() < id {
+			self.offsets.resize(id, 0);
+		}
+		self.offsets[id - 1] = self.position;
+		self.write(format!("{id} 0 obj\n{dict}\n").as_bytes())?;
+		if let Some(stream) = stream {
+			self.write(b"stream\n")?;
+			self.write(stream)?;
+			self.write(b"\nendstream\n")?;
+		}
+		self.write(b"endobj\n")
+	}
+
+	/**
+	 * Encode images as pages and write to the PDF file.
+	 *
+	 * @param  images
+	 * @param  cancel
+	 * @return Result
+	 */
+	pub fn save(
+		mut self,
+		images: Vec<Image>,
+		cancel: CancelHandle,
+	) -> AppResult<()> {
+		self.write(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n")?;
+		let mut pages = Vec::new();
+		for (i, image) in images.iter().enumerate() {
+			let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
+			info!("Saving... ({:.1}%)\r", percentage);
+			debug!(
+				"Encoding... ({:.1}%) [{}/{}]\r",
+				percentage,
+				i + 1,
+				images.len()
+			);
+			io::stdout().flush()?;
+			cancel.check()?;
+			let (width, height) = (image.geometry.width, image.geometry.height);
+			let mut jpeg = Vec::new();
+			JpegEncoder::new_with_quality(&mut jpeg, self.quality).encode(
+				&image.get_data(ExtendedColorType::Rgb8)?,
+				width,
+				height,
+				ColorType::Rgb8,
+			)?;
+			let id = 3 + i * 3;
+			self.write_object(
+				id + 2,
+				&format!(
+					"<< /Type /XObject /Subtype /Image /Width {width} \
+					/Height {height} /ColorSpace /DeviceRGB /BitsPerComponent 8 \
+					/Filter /DCTDecode /Length {} >>",
+					jpeg.len()
+				),
+				Some(&jpeg),
+			)?;
+			let content = format!("q {width} 0 0 {height} 0 0 cm /Im0 Do Q");
+			self.write_object(
+				id + 1,
+				&format!("<< /Length {} >>", content.len()),
+				Some(content.as_bytes()),
+			)?;
+			self.write_object(
+				id,
+				&format!(
+					"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] \
+					/Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+					id + 2,
+					id + 1
+				),
+				None,
+			)?;
+			pages.push(format!("{id} 0 R"));
+		}
+		self.write_object(
+			2,
+			&format!(
+				"<< /Type /Pages /Kids [{}] /Count {} >>",
+				pages.join(" "),
+				pages.len()
+			),
+			None,
+		)?;
+		self.write_object(1, "<< /Type /Catalog /Pages 2 0 R >>", None)?;
+		let xref = self.position;
+		let mut trailer =
+			format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+		for offset in &self.offsets {
+			trailer.push_str(&format!("{offset:010} 00000 n \n"));
+		}
+		trailer.push_str(&format!(
+			"trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+			self.offsets.len() + 1
+		));
+		self.write(trailer.as_bytes())?;
+		self.output.flush()?;
+		info!("\n");
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image::geometry::Geometry;
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_pdf_encoder() -> AppResult<()> {
+		let geometry = Geometry::new(0, 0, 1, 2);
+		let data = vec![Rgba::from([128, 128, 128, 0]), Rgba::from([16, 16, 16, 0])];
+		let images = vec![
+			Image::new(data.clone(), false, geometry),
+			Image::new(data.into_iter().rev().collect(), false, geometry),
+		];
+		let cancel = CancelHandle::new(None);
+		cancel.cancel();
+		assert!(PdfEncoder::new(Vec::new(), 90)
+			.save(images.clone(), cancel)
+			.is_err());
+		let mut output = Vec::new();
+		PdfEncoder::new(&mut output, 90).save(images, CancelHandle::new(None))?;
+		let find = |pattern: &[u8]| {
+			output
+				.windows(pattern.len())
+				.rposition(|window| window == pattern)
+				.unwrap_or_default()
+		};
+		let (xref, catalog) = (find(b"\nxref\n") + 1, find(b"1 0 obj"));
+		let pdf = String::from_utf8_lossy(&output);
+		assert!(pdf.starts_with("%PDF-1.4"));
+		assert!(pdf.contains("/Kids [3 0 R 6 0 R] /Count 2"));
+		assert_eq!(2, pdf.matches("/DCTDecode").count());
+		assert!(pdf.ends_with(&format!("startxref\n{xref}\n%%EOF\n")));
+		assert!(pdf.contains(&format!("{catalog:010} 00000 n")));
+		Ok(())
+	}
+}
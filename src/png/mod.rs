@@ -0,0 +1,348 @@
+use crate::image::settings::{PngColorType, PngSettings};
+use color_quant::NeuQuant;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder as BaseEncoder};
+use image::error::{EncodingError, ImageFormatHint};
+use image::{
+	ColorType, DynamicImage, ImageEncoder, ImageError, ImageFormat, ImageResult,
+	RgbaImage,
+};
+use rayon::prelude::*;
+use std::error::Error;
+use std::io::Write;
+use std::num::NonZeroU8;
+
+/* Signature at the start of a PNG file */
+pub const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/* Size of the raw image data for using fewer zopfli iterations */
+const LARGE_DATA_SIZE: usize = 1024 * 1024;
+
+/* Maximum number of colors in the palette of indexed images */
+const PALETTE_COLORS: usize = 256;
+
+/* Type and data of a PNG chunk */
+pub type Chunk<'a> = (&'a [u8], &'a [u8]);
+
+/* PNG encoder that uses zopfli for the best compression */
+pub struct PngEncoder<Output: Write> {
+	output: Output,
+	settings: PngSettings,
+}
+
+impl<Output: Write> PngEncoder<Output> {
+	/**
+	 * Create a new PngEncoder object.
+	 *
+	 * @param  output
+	 * @param  settings
+	 * @return PngEncoder
+	 */
+	pub fn new(output: Output, settings: PngSettings) -> Self {
+		Self { output, settings }
+	}
+
+	/**
+	 * Convert the RGBA image to the color type and bit depth.
+	 *
+	 * @param  image
+	 * @return DynamicImage
+	 */
+	fn convert(&self, image: RgbaImage) -> DynamicImage {
+		let image = DynamicImage::ImageRgba8(image);
+		match (self.settings.color_type, self.settings.depth) {
+			(PngColorType::Rgb, 16) => DynamicImage::ImageRgb16(image.into_rgb16()),
+			(PngColorType::Rgb, _) => DynamicImage::ImageRgb8(image.into_rgb8()),
+			(PngColorType::Gray, 16) => {
+				DynamicImage::ImageLuma16(image.into_luma16())
+			}
+			(PngColorType::Gray, _) => DynamicImage::ImageLuma8(image.into_luma8()),
+			(PngColorType::GrayAlpha, 16) => {
+				DynamicImage::ImageLumaA16(image.into_luma_alpha16())
+			}
+			(PngColorType::GrayAlpha, _) => {
+				DynamicImage::ImageLumaA8(image.into_luma_alpha8())
+			}
+			(_, 16) => DynamicImage::ImageRgba16(image.into_rgba16()),
+			_ => image,
+		}
+	}
+
+	/**
+	 * Quantize the RGBA data and encode it as an indexed image.
+	 *
+	 * @param  output
+	 * @param  buf
+	 * @param  width
+	 * @param  height
+	 * @return Result
+	 */
+	fn encode_indexed<W: Write>(
+		&self,
+		output: W,
+		buf: &[u8],
+		width: u32,
+		height: u32,
+	) -> ImageResult<()> {
+		let quantizer = NeuQuant::new(10, PALETTE_COLORS, buf);
+		let colors = quantizer.color_map_rgba();
+		let indices = buf
+			.par_chunks_exact(4)
+			.map(|pixel| quantizer.index_of(pixel) as u8)
+			.collect::<Vec<u8>>();
+		let mut encoder = png::Encoder::new(output, width, height);
+		encoder.set_color(png::ColorType::Indexed);
+		encoder.set_depth(png::BitDepth::Eight);
+		encoder.set_palette(
+			colors
+				.chunks_exact(4)
+				.flat_map(|color| color[..3].to_vec())
+				.collect::<Vec<u8>>(),
+		);
+		if buf.par_chunks_exact(4).any(|pixel| pixel[3] != u8::MAX) {
+			encoder.set_trns(
+				colors
+					.chunks_exact(4)
+					.map(|color| color[3])
+					.collect::<Vec<u8>>(),
+			);
+		}
+		encoder.set_compression(match self.settings.compression {
+			CompressionType::Default => png::Compression::Default,
+			CompressionType::Best => png::Compression::Best,
+			_ => png::Compression::Fast,
+		});
+		encoder.set_filter(match self.settings.filter {
+			FilterType::NoFilter => png::FilterType::NoFilter,
+			FilterType::Up => png::FilterType::Up,
+			FilterType::Avg => png::FilterType::Avg,
+			FilterType::Paeth => png::FilterType::Paeth,
+			_ => png::FilterType::Sub,
+		});
+		encoder
+			.write_header()
+			.and_then(|mut writer| writer.write_image_data(&indices))
+			.map_err(get_error)
+	}
+
+	/**
+	 * Encode the image with the color type and bit depth.
+	 *
+	 * @param  output
+	 * @param  buf
+	 * @param  width
+	 * @param  height
+	 * @param  color_type
+	 * @return Result
+	 */
+	fn encode<W: Write>(
+		&self,
+		output: W,
+		buf: &[u8],
+		width: u32,
+		height: u32,
+		color_type: ColorType,
+	) -> ImageResult<()> {
+		let encoder = |output| {
+			BaseEncoder::new_with_quality(
+				output,
+				self.settings.compression,
+				self.settings.filter,
+			)
+		};
+		if color_type != ColorType::Rgba8 || self.settings.is_rgba8() {
+			encoder(output).write_image(buf, width, height, color_type)
+		} else if self.settings.color_type == PngColorType::Indexed {
+			self.encode_indexed(output, buf, width, height)
+		} else {
+			let image = self.convert(
+				RgbaImage::from_raw(width, height, buf.to_vec())
+					.ok_or_else(|| get_error("Invalid image size"))?,
+			);
+			encoder(output).write_image(
+				image.as_bytes(),
+				width,
+				height,
+				image.color(),
+			)
+		}
+	}
+}
+
+/* Implementation for encoding PNG images */
+impl<Output: Write> ImageEncoder for PngEncoder<Output> {
+	fn write_image(
+		mut self,
+		buf: &[u8],
+		width: u32,
+		height: u32,
+		color_type: ColorType,
+	) -> ImageResult<()> {
+		let mut data = Vec::new();
+		self.encode(&mut data, buf, width, height, color_type)?;
+		if self.settings.compression == CompressionType::Best {
+			debug!("Compressing the image data with zopfli...");
+			data = recompress(&data).map_err(get_error)?;
+		}
+		self.output.write_all(&data)?;
+		Ok(())
+	}
+}
+
+/**
+ * Get the encoding error for PNG.
+ *
+ * @param  error
+ * @return ImageError
+ */
+fn get_error(error: impl Into<Box<dyn Error + Send + Sync>>) -> ImageError {
+	ImageError::Encoding(EncodingError::new(
+		ImageFormatHint::Exact(ImageFormat::Png),
+		error,
+	))
+}
+
+/**
+ * Split the PNG data into chunks of type and data.
+ *
+ * @param  png
+ * @return Vector of chunks (Result)
+ */
+pub fn get_chunks(png: &[u8]) -> Result<Vec<Chunk<'_>>, String> {
+	let mut data = png
+		.strip_prefix(PNG_SIGNATURE)
+		.ok_or_else(|| String::from("Invalid PNG signature"))?;
+	let mut chunks = Vec::new();
+	while !data.is_empty() {
+		let length = data
+			.get(..4)
+			.and_then(|length| length.try_into().ok())
+			.map(|length| u32::from_be_bytes(length) as usize)
+			.filter(|length| data.len() >= length + 12)
+			.ok_or_else(|| String::from("Invalid PNG chunk"))?;
+		chunks.push((&data[4..8], &data[8..8 + length]));
+		data = &data[length + 12..];
+	}
+	Ok(chunks)
+}
+
+/**
+ * Write a PNG chunk with its length and checksum.
+ *
+ * @param  output
+ * @param  chunk_type
+ * @param  data
+ */
+pub fn write_chunk(output: &mut Vec<u8>, chunk_type: &[u8], data: &[u8]) {
+	let mut hasher = crc32fast::Hasher::new();
+	hasher.update(chunk_type);
+	hasher.update(data);
+	output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+	output.extend_from_slice(chunk_type);
+	output.extend_from_slice(data);
+	output.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+/**
+ * Recompress the image data of the PNG with zopfli and
+ * replace the data chunks with a single one if it is smaller.
+ *
+ * @param  png
+ * @return Vector of u8 (Result)
+ */
+fn recompress(png: &[u8]) -> Result<Vec<u8>, String> {
+	let chunks = get_chunks(png)?;
+	let image_data = chunks
+		.iter()
+		.filter(|(chunk_type, _)| *chunk_type == b"IDAT")
+		.flat_map(|(_, data)| data.iter().copied())
+		.collect::<Vec<u8>>();
+	let raw_data = miniz_oxide::inflate::decompress_to_vec_zlib(&image_data)
+		.map_err(|e| format!("Failed to decompress the image data: {e:?}"))?;
+	let mut options = zopfli::Options::default();
+	if raw_data.len() > LARGE_DATA_SIZE {
+		options.iteration_count =
+			NonZeroU8::new(5).unwrap_or(options.iteration_count);
+	}
+	let mut compressed = Vec::new();
+	zopfli::compress(
+		&options,
+		&zopfli::Format::Zlib,
+		&raw_data[..],
+		&mut compressed,
+	)
+	.map_err(|e| e.to_string())?;
+	debug!(
+		"Image data: {} -> {} bytes",
+		image_data.len(),
+		compressed.len()
+	);
+	if compressed.len() >= image_data.len() {
+		return Ok(png.to_vec());
+	}
+	let mut output = PNG_SIGNATURE.to_vec();
+	let mut image_data = Some(compressed);
+	for (chunk_type, data) in chunks {
+		if chunk_type != b"IDAT" {
+			write_chunk(&mut output, chunk_type, data);
+		} else if let Some(image_data) = image_data.take() {
+			write_chunk(&mut output, chunk_type, &image_data);
+		}
+	}
+	Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::codecs::png::PngDecoder;
+	use image::ImageDecoder;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_png_encoder() -> ImageResult<()> {
+		let data = (0..64 * 64 * 4)
+			.map(|i| ((i / 4) % 64 * 4) as u8)
+			.collect::<Vec<u8>>();
+		let mut outputs = Vec::new();
+		for compression in [CompressionType::Fast, CompressionType::Best] {
+			let mut output = Vec::new();
+			PngEncoder::new(
+				&mut output,
+				PngSettings::new(
+					compression,
+					FilterType::Sub,
+					PngColorType::Rgba,
+					8,
+				),
+			)
+			.write_image(&data, 64, 64, ColorType::Rgba8)?;
+			let decoder = PngDecoder::new(&output[..])?;
+			let mut decoded = vec![0; decoder.total_bytes() as usize];
+			decoder.read_image(&mut decoded)?;
+			assert_eq!(data, decoded);
+			outputs.push(output);
+		}
+		assert!(outputs[1].len() < outputs[0].len());
+		assert_eq!(Ok(3), get_chunks(&outputs[1]).map(|chunks| chunks.len()));
+		assert!(get_chunks(b"PNG").is_err());
+		for (color_type, depth, decoded_type) in [
+			(PngColorType::Gray, 16, ColorType::L16),
+			(PngColorType::GrayAlpha, 8, ColorType::La8),
+			(PngColorType::Rgb, 16, ColorType::Rgb16),
+			(PngColorType::Indexed, 16, ColorType::Rgba8),
+		] {
+			let mut output = Vec::new();
+			PngEncoder::new(
+				&mut output,
+				PngSettings::new(
+					CompressionType::Fast,
+					FilterType::Sub,
+					color_type,
+					depth,
+				),
+			)
+			.write_image(&data, 64, 64, ColorType::Rgba8)?;
+			assert_eq!(decoded_type, PngDecoder::new(&output[..])?.color_type());
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,116 @@
+use image::error::{DecodingError, EncodingError, ImageFormatHint};
+use image::{ColorType, ImageEncoder, ImageError, ImageResult, RgbaImage};
+use qoi::{Channels, Decoder};
+use std::io::Write;
+
+/* Magic bytes at the start of a QOI file */
+const QOI_MAGIC: &[u8; 4] = b"qoif";
+
+/* QOI encoder */
+pub struct QoiEncoder<Output: Write> {
+	output: Output,
+}
+
+impl<Output: Write> QoiEncoder<Output> {
+	/**
+	 * Create a new QoiEncoder object.
+	 *
+	 * @param  output
+	 * @return QoiEncoder
+	 */
+	pub fn new(output: Output) -> Self {
+		Self { output }
+	}
+}
+
+/* Implementation for encoding QOI images */
+impl<Output: Write> ImageEncoder for QoiEncoder<Output> {
+	fn write_image(
+		mut self,
+		buf: &[u8],
+		width: u32,
+		height: u32,
+		color_type: ColorType,
+	) -> ImageResult<()> {
+		if !matches!(color_type, ColorType::Rgb8 | ColorType::Rgba8) {
+			return Err(ImageError::Encoding(EncodingError::new(
+				ImageFormatHint::Name(String::from("QOI")),
+				format!("Unsupported color type: {color_type:?}"),
+			)));
+		}
+		let data = qoi::encode_to_vec(buf, width, height).map_err(|e| {
+			ImageError::Encoding(EncodingError::new(
+				ImageFormatHint::Name(String::from("QOI")),
+				e,
+			))
+		})?;
+		self.output.write_all(&data)?;
+		Ok(())
+	}
+}
+
+/* QOI decoder */
+#[derive(Debug)]
+pub struct QoiDecoder<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> QoiDecoder<'a> {
+	/**
+	 * Create a new QoiDecoder object.
+	 *
+	 * @param  data
+	 * @return QoiDecoder (Option)
+	 */
+	pub fn new(data: &'a [u8]) -> Option<Self> {
+		data.starts_with(QOI_MAGIC).then_some(Self { data })
+	}
+
+	/**
+	 * Decode the data into an RGBA image.
+	 *
+	 * @return RgbaImage (Result)
+	 */
+	pub fn decode(self) -> ImageResult<RgbaImage> {
+		let get_error = |e: qoi::Error| {
+			ImageError::Decoding(DecodingError::new(
+				ImageFormatHint::Name(String::from("QOI")),
+				e,
+			))
+		};
+		let mut decoder = Decoder::new(self.data)
+			.map_err(get_error)?
+			.with_channels(Channels::Rgba);
+		let header = *decoder.header();
+		let data = decoder.decode_to_vec().map_err(get_error)?;
+		let size = data.len();
+		RgbaImage::from_raw(header.width, header.height, data).ok_or_else(|| {
+			get_error(qoi::Error::InvalidImageLength {
+				size,
+				width: header.width,
+				height: header.height,
+			})
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_qoi() {
+		let mut output = Vec::new();
+		let data = [10, 20, 30, 255, 40, 50, 60, 128];
+		QoiEncoder::new(&mut output)
+			.write_image(&data, 2, 1, ColorType::Rgba8)
+			.unwrap();
+		let image = QoiDecoder::new(&output).unwrap().decode().unwrap();
+		assert_eq!((2, 1), image.dimensions());
+		assert_eq!(data.to_vec(), image.into_raw());
+		assert!(QoiDecoder::new(&[0, 1, 2, 3]).is_none());
+		assert!(QoiEncoder::new(Vec::new())
+			.write_image(&[0; 2], 2, 1, ColorType::L8)
+			.is_err());
+	}
+}
@@ -5,6 +5,7 @@ use std::time::{Duration, Instant};
 #[derive(Clone, Copy, Debug)]
 pub struct FpsClock {
 	pub fps: u32,
+	pub interval: Duration,
 	last_tick_time: Instant,
 }
 
@@ -18,21 +19,67 @@ impl FpsClock {
 	pub fn new(fps: u32) -> Self {
 		Self {
 			fps,
+			interval: Duration::from_secs_f64(1. / f64::from(fps.max(1))),
 			last_tick_time: Instant::now(),
 		}
 	}
 
+	/**
+	 * Set a custom interval between ticks.
+	 *
+	 * @param  interval
+	 * @return FpsClock
+	 */
+	pub fn with_interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+
+	/* Reset the time of the last tick. */
+	pub fn reset(&mut self) {
+		self.last_tick_time = Instant::now();
+	}
+
+	/**
+	 * Get the number of ticks that are missed from the given time difference.
+	 *
+	 * @param  diff
+	 * @return usize
+	 */
+	pub fn get_missed_ticks(&self, diff: f32) -> usize {
+		if diff < 0. {
+			(-diff / self.interval.as_nanos() as f32) as usize
+		} else {
+			0
+		}
+	}
+
+	/**
+	 * Get the playback delay of a frame which is extended
+	 * by the time difference when the capture falls behind.
+	 *
+	 * @param  diff
+	 * @return Duration
+	 */
+	pub fn get_delay(&self, diff: f32) -> Duration {
+		let delay = Duration::from_secs_f64(1. / f64::from(self.fps.max(1)));
+		if diff < 0. {
+			delay.mul_f64(1. + f64::from(-diff) / self.interval.as_nanos() as f64)
+		} else {
+			delay
+		}
+	}
+
 	/**
 	 * Sleep the thread to run at the correct FPS.
 	 *
 	 * @return f32
 	 */
 	pub fn tick(&mut self) -> f32 {
-		let t = self.last_tick_time.elapsed();
-		let total_nanos = t.as_secs() * 1e9 as u64 + t.subsec_nanos() as u64;
-		let diff = ((1. / self.fps as f32) * 1e9) - (total_nanos as f32);
-		if diff > 0. {
-			thread::sleep(Duration::new(0, diff as u32))
+		let elapsed = self.last_tick_time.elapsed();
+		let diff = self.interval.as_nanos() as f32 - elapsed.as_nanos() as f32;
+		if let Some(duration) = self.interval.checked_sub(elapsed) {
+			thread::sleep(duration)
 		}
 		self.last_tick_time = Instant::now();
 		diff
@@ -53,5 +100,18 @@ mod tests {
 			thread::sleep(Duration::from_nanos(i));
 			assert!(fps > fps_clock.tick());
 		}
+		let mut fps_clock =
+			FpsClock::new(10).with_interval(Duration::from_millis(5));
+		assert_eq!(10, fps_clock.fps);
+		fps_clock.tick();
+		let start = Instant::now();
+		fps_clock.tick();
+		assert!(start.elapsed() >= Duration::from_millis(4));
+		assert_eq!(0, fps_clock.get_missed_ticks(1e6));
+		assert_eq!(2, fps_clock.get_missed_ticks(-1.2e7));
+		assert_eq!(Duration::from_millis(100), fps_clock.get_delay(1e6));
+		assert_eq!(Duration::from_millis(300), fps_clock.get_delay(-1e7));
+		fps_clock.reset();
+		assert!(fps_clock.tick() > 0.);
 	}
 }
@@ -0,0 +1,118 @@
+use crate::app::AppResult;
+use crate::image::Image;
+use image::Rgb;
+
+/* Duration of the fade-out effect in seconds */
+const FADE_DURATION: f32 = 0.5;
+
+/* Settings of the mouse click highlighting */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HighlightSettings {
+	pub color: Rgb<u8>,
+	pub radius: u32,
+}
+
+/* Default initialization values for HighlightSettings */
+impl Default for HighlightSettings {
+	fn default() -> Self {
+		Self {
+			color: Rgb([255, 255, 0]),
+			radius: 20,
+		}
+	}
+}
+
+impl HighlightSettings {
+	/**
+	 * Create a new HighlightSettings object.
+	 *
+	 * @param  color
+	 * @param  radius
+	 * @return HighlightSettings
+	 */
+	pub fn new(color: Rgb<u8>, radius: u32) -> Self {
+		Self { color, radius }
+	}
+}
+
+/* Highlighter for drawing fading circles on mouse clicks */
+#[derive(Debug)]
+pub struct ClickHighlighter {
+	settings: HighlightSettings,
+	fade_frames: usize,
+	clicks: Vec<((i32, i32), usize)>,
+}
+
+impl ClickHighlighter {
+	/**
+	 * Create a new ClickHighlighter object.
+	 *
+	 * @param  settings
+	 * @param  fps
+	 * @return ClickHighlighter
+	 */
+	pub fn new(settings: HighlightSettings, fps: u32) -> Self {
+		Self {
+			settings,
+			fade_frames: ((fps as f32 * FADE_DURATION) as usize).max(1),
+			clicks: Vec::new(),
+		}
+	}
+
+	/**
+	 * Draw the active clicks on the image.
+	 *
+	 * @param  image
+	 * @param  pointer (Option)
+	 * @return Result
+	 */
+	pub fn apply(
+		&mut self,
+		image: &mut Image,
+		pointer: Option<((i32, i32), bool)>,
+	) -> AppResult<()> {
+		if let Some((position, true)) = pointer {
+			self.clicks.retain(|(click, _)| *click != position);
+			self.clicks.push((position, self.fade_frames));
+		}
+		for (position, frames) in self.clicks.iter_mut() {
+			image.draw_circle(
+				*position,
+				self.settings.radius,
+				self.settings.color,
+				*frames as f32 / self.fade_frames as f32,
+			)?;
+			*frames -= 1;
+		}
+		self.clicks.retain(|(_, frames)| *frames > 0);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image::geometry::Geometry;
+	use image::{ExtendedColorType, Rgba};
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_click_highlighter() -> AppResult<()> {
+		let mut highlighter =
+			ClickHighlighter::new(HighlightSettings::new(Rgb([0, 0, 200]), 1), 4);
+		let geometry = Geometry::new(0, 0, 3, 3);
+		let get_image =
+			|| Image::new(vec![Rgba([0, 0, 0, 255]); 9], false, geometry);
+		let mut image = get_image();
+		highlighter.apply(&mut image, Some(((1, 1), false)))?;
+		assert_eq!(vec![0; 27], image.get_data(ExtendedColorType::Rgb8)?);
+		highlighter.apply(&mut image, Some(((1, 1), true)))?;
+		assert_eq!(200, image.get_data(ExtendedColorType::Rgb8)?[5]);
+		let mut image = get_image();
+		highlighter.apply(&mut image, None)?;
+		assert_eq!(100, image.get_data(ExtendedColorType::Rgb8)?[5]);
+		let mut image = get_image();
+		highlighter.apply(&mut image, None)?;
+		assert_eq!(vec![0; 27], image.get_data(ExtendedColorType::Rgb8)?);
+		Ok(())
+	}
+}
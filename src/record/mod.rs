@@ -1,16 +1,29 @@
 pub mod fps;
+pub mod highlight;
 pub mod settings;
+pub mod signal;
+pub mod stamp;
+pub mod stats;
 
 use crate::app::{AppError, AppResult};
+use crate::image::spool::FrameSpool;
 use crate::image::Image;
 use crate::record::fps::FpsClock;
+use crate::record::highlight::ClickHighlighter;
 use crate::record::settings::RecordSettings;
+use crate::record::signal::StopSignals;
+use crate::record::stamp::FrameStamper;
+use crate::record::stats::RecordStats;
 use crate::util::state::InputState;
 use crate::window::Capture;
 use std::io::{self, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc};
 use std::thread;
+use std::time::{Duration, Instant};
+
+/* Maximum number of frames to buffer while streaming to the encoder */
+pub const STREAM_BUFFER_SIZE: usize = 4;
 
 /* Asynchronous recording result */
 #[derive(Debug)]
@@ -34,14 +47,11 @@ impl<T> RecordResult<T> {
 	/**
 	 * Stop the thread and retrieve values.
 	 *
-	 * @return Option
+	 * @return Result
 	 */
-	pub fn get(self) -> Option<thread::Result<T>> {
-		if self.sender.send(()).is_ok() {
-			Some(self.thread.join())
-		} else {
-			None
-		}
+	pub fn get(self) -> thread::Result<T> {
+		let _ = self.sender.send(());
+		self.thread.join()
 	}
 }
 
@@ -52,6 +62,14 @@ pub struct Recorder<Window> {
 	channel: (mpsc::Sender<()>, mpsc::Receiver<()>),
 	gifski: bool,
 	settings: RecordSettings,
+	highlighter: Option<ClickHighlighter>,
+	stamper: Option<FrameStamper>,
+	dropped: usize,
+	spool: Option<Arc<FrameSpool>>,
+	size: Option<(u32, u32)>,
+	stats: RecordStats,
+	stream: Option<mpsc::SyncSender<Image>>,
+	streamed: usize,
 }
 
 impl<Window> Recorder<Window>
@@ -73,13 +91,119 @@ where
 		gifski: bool,
 		settings: RecordSettings,
 	) -> Self {
+		let clock = FpsClock::new(fps);
 		Self {
 			window,
-			clock: FpsClock::new(fps),
+			clock: match settings.time.timelapse {
+				Some(interval) => {
+					clock.with_interval(Duration::from_secs_f64(interval))
+				}
+				None => clock,
+			},
 			channel: mpsc::channel(),
 			gifski,
 			settings,
+			highlighter: settings
+				.flag
+				.highlight
+				.map(|highlight| ClickHighlighter::new(highlight, fps)),
+			stamper: settings.flag.stamp.and_then(|stamp| {
+				FrameStamper::new(stamp)
+					.map_err(|e| warn!("Failed to load the timestamp font: {}", e))
+					.ok()
+			}),
+			dropped: 0,
+			spool: if settings.flag.disk_buffer {
+				FrameSpool::new()
+					.map_err(|e| warn!("Failed to create the disk buffer: {}", e))
+					.ok()
+					.map(Arc::new)
+			} else {
+				None
+			},
+			size: None,
+			stats: RecordStats::default(),
+			stream: None,
+			streamed: 0,
+		}
+	}
+
+	/**
+	 * Send the recorded frames to the given channel instead of keeping them.
+	 *
+	 * @param  sender
+	 * @return Recorder
+	 */
+	pub fn with_stream(mut self, sender: mpsc::SyncSender<Image>) -> Self {
+		self.stream = Some(sender);
+		self
+	}
+
+	/**
+	 * Get the total number of recorded frames.
+	 *
+	 * @param  frames
+	 * @return usize
+	 */
+	fn get_frame_count(&self, frames: &[Image]) -> usize {
+		self.streamed + frames.len()
+	}
+
+	/**
+	 * Send the frames to the stream while keeping the last frames.
+	 *
+	 * @param  frames
+	 * @param  keep
+	 * @return Result
+	 */
+	fn flush_frames(
+		&mut self,
+		frames: &mut Vec<Image>,
+		keep: usize,
+	) -> AppResult<()> {
+		if let Some(stream) = &self.stream {
+			for frame in frames.drain(..frames.len().saturating_sub(keep)) {
+				stream.send(frame).map_err(|_| {
+					AppError::FrameError(String::from("Failed to stream the frame"))
+				})?;
+				self.streamed += 1;
+			}
 		}
+		Ok(())
+	}
+
+	/**
+	 * Discard the recorded frames.
+	 *
+	 * @param frames
+	 */
+	fn discard_frames(&mut self, frames: &mut Vec<Image>) {
+		frames.clear();
+		self.streamed = 0;
+	}
+
+	/**
+	 * Finish the recording and send the remaining frames to the stream.
+	 *
+	 * @param  frames
+	 * @param  replay_frames (Option)
+	 * @return Result
+	 */
+	fn finish(
+		&mut self,
+		frames: &mut Vec<Image>,
+		replay_frames: Option<usize>,
+	) -> AppResult<()> {
+		self.extend_delay(frames, 0.);
+		let count = self.get_frame_count(frames);
+		self.report_dropped_frames(count);
+		Self::trim_frames(frames, replay_frames, true);
+		self.finish_stats(self.get_frame_count(frames));
+		if self.stats.frames != 0 {
+			self.flush_frames(frames, 0)?;
+		}
+		self.stream = None;
+		Ok(())
 	}
 
 	/**
@@ -88,7 +212,33 @@ where
 	 * @return usize
 	 */
 	fn get_max_frames(&self) -> usize {
-		if let Some(duration) = self.settings.time.duration {
+		let max_frames = self.get_duration_frames();
+		match self.settings.time.max_frames {
+			Some(frames) if frames < max_frames => {
+				info!("Recording at most {} frames.", frames);
+				frames
+			}
+			_ => max_frames,
+		}
+	}
+
+	/**
+	 * Get the number of frames to record for the given duration.
+	 *
+	 * @return usize
+	 */
+	fn get_duration_frames(&self) -> usize {
+		if let Some(interval) = self.settings.time.timelapse {
+			info!(
+				"Recording a timelapse with 1 frame per {} seconds...",
+				interval
+			);
+			self.settings
+				.time
+				.duration
+				.map(|duration| (duration / interval) as usize)
+				.unwrap_or(usize::MAX)
+		} else if let Some(duration) = self.settings.time.duration {
 			info!(
 				"Recording {} FPS for {} seconds...",
 				self.clock.fps, duration
@@ -105,6 +255,253 @@ where
 		}
 	}
 
+	/**
+	 * Get the number of frames to keep in the replay buffer.
+	 *
+	 * @return usize (Option)
+	 */
+	fn get_replay_frames(&self) -> Option<usize> {
+		self.settings.time.replay.map(|duration| {
+			info!("Keeping the last {} seconds of the recording.", duration);
+			((duration / self.clock.interval.as_secs_f64()) as usize).max(1)
+		})
+	}
+
+	/**
+	 * Drop the oldest frames that do not fit in the replay buffer.
+	 *
+	 * @param frames
+	 * @param capacity (Option)
+	 * @param flush
+	 */
+	fn trim_frames(frames: &mut Vec<Image>, capacity: Option<usize>, flush: bool) {
+		if let Some(capacity) = capacity {
+			if flush || frames.len() >= capacity.saturating_mul(2) {
+				frames.drain(..frames.len().saturating_sub(capacity));
+			}
+		}
+	}
+
+	/**
+	 * Get the image of the window with the click highlights.
+	 *
+	 * @param  timestamp
+	 * @return Image (Option) (Result)
+	 */
+	fn get_frame(&mut self, timestamp: Duration) -> AppResult<Option<Image>> {
+		let capture_start = Instant::now();
+		let mut image = match self.window.get_image() {
+			Some(image) => image,
+			None => return Ok(None),
+		};
+		self.stats.add_latency(capture_start.elapsed());
+		self.stats.duration = timestamp;
+		if let Some(highlighter) = self.highlighter.as_mut() {
+			highlighter.apply(&mut image, self.window.get_pointer())?;
+		}
+		if let Some((width, height)) = self.size {
+			if (image.geometry.width, image.geometry.height) != (width, height) {
+				image = image.fit(width, height, self.settings.flag.letterbox)?;
+			}
+		}
+		if let Some(factor) = self.settings.flag.scale {
+			image = image.scale(factor)?;
+		}
+		if let Some(stamper) = &self.stamper {
+			stamper.apply(&mut image, timestamp)?;
+		}
+		if self.settings.time.timelapse.is_none() {
+			image = image.with_timestamp(timestamp);
+		}
+		Ok(Some(image))
+	}
+
+	/**
+	 * Select another window and continue recording it.
+	 *
+	 * @param input_state
+	 */
+	fn reselect_window(&mut self, input_state: &InputState) {
+		info!("Select the window to continue recording.");
+		let size = self
+			.window
+			.get_image()
+			.map(|image| (image.geometry.width, image.geometry.height));
+		match self.window.reselect(input_state) {
+			Some(window) => {
+				self.size = self.size.or(size);
+				self.window = window;
+				info!("Recording resumed.");
+			}
+			None => warn!("Continuing to record the previous window."),
+		}
+		while input_state.check_action() || input_state.check_cancel_keys() {
+			thread::sleep(Duration::from_millis(10));
+		}
+	}
+
+	/**
+	 * Add the frame unless it is too similar to the last frame.
+	 * The delay of the frame is accumulated on the next ticks
+	 * if the delays are adjusted.
+	 *
+	 * @param  frames
+	 * @param  frame
+	 * @return Result
+	 */
+	fn push_frame(
+		&self,
+		frames: &mut Vec<Image>,
+		mut frame: Image,
+	) -> AppResult<()> {
+		if self.settings.flag.best_effort {
+			frame.delay = Some(Duration::ZERO);
+		}
+		match (self.settings.flag.motion, frames.last()) {
+			(Some(threshold), Some(last))
+				if frame.get_diff_percentage(last)? < threshold => {}
+			_ => frames.push(match &self.spool {
+				Some(spool) => frame.into_spool(spool)?,
+				None => frame,
+			}),
+		}
+		Ok(())
+	}
+
+	/**
+	 * Extend the delay of the last frame if the delays are adjusted.
+	 *
+	 * @param frames
+	 * @param diff
+	 */
+	fn extend_delay(&self, frames: &mut [Image], diff: f32) {
+		if let (true, Some(frame)) =
+			(self.settings.flag.best_effort, frames.last_mut())
+		{
+			frame.delay =
+				Some(frame.delay.unwrap_or_default() + self.clock.get_delay(diff));
+		}
+	}
+
+	/**
+	 * Count the dropped frames and duplicate the last frame
+	 * or adjust its delay if necessary.
+	 *
+	 * @param diff
+	 * @param frames
+	 * @param max_frames
+	 */
+	fn handle_dropped_frames(
+		&mut self,
+		diff: f32,
+		frames: &mut Vec<Image>,
+		max_frames: usize,
+	) {
+		self.extend_delay(frames, diff);
+		let missed = self.clock.get_missed_ticks(diff);
+		if let (true, Some(frame)) = (missed > 0, frames.last().cloned()) {
+			self.dropped += missed;
+			if self.settings.flag.drop_frames {
+				let count = self.get_frame_count(frames);
+				for i in 0..missed.min(max_frames.saturating_sub(count + 1)) {
+					let mut frame = frame.clone();
+					frame.timestamp = frame
+						.timestamp
+						.map(|t| t + self.clock.interval * (i as u32 + 1));
+					frames.push(frame);
+				}
+			}
+		}
+	}
+
+	/**
+	 * Show the statistics of the dropped frames.
+	 *
+	 * @param frames
+	 */
+	fn report_dropped_frames(&self, frames: usize) {
+		if self.dropped != 0 {
+			let total = if self.settings.flag.drop_frames {
+				frames
+			} else {
+				frames + self.dropped
+			};
+			warn!(
+				"Dropped {} of {} frames ({:.1}%){}.",
+				self.dropped,
+				total,
+				(self.dropped as f64 / total.max(1) as f64) * 100.,
+				if self.settings.flag.drop_frames {
+					", duplicated the previous frames to hold timing"
+				} else if self.settings.flag.best_effort {
+					", adjusted the frame delays to hold timing"
+				} else {
+					""
+				}
+			);
+		}
+	}
+
+	/**
+	 * Finish and report the statistics of the recording.
+	 *
+	 * @param frames
+	 */
+	fn finish_stats(&mut self, frames: usize) {
+		self.stats.frames = frames;
+		self.stats.dropped = self.dropped;
+		self.stats.report();
+	}
+
+	/**
+	 * Get the statistics of the recording.
+	 *
+	 * @return RecordStats
+	 */
+	pub fn get_stats(&self) -> RecordStats {
+		self.stats.clone()
+	}
+
+	/**
+	 * Check if the recording duration has elapsed in wall-clock time.
+	 *
+	 * @param  start
+	 * @return bool
+	 */
+	fn is_timed_out(&self, start: Instant) -> bool {
+		self.settings.time.duration.is_some_and(|duration| {
+			start.elapsed() >= Duration::from_secs_f64(duration)
+		})
+	}
+
+	/**
+	 * Check if a stop signal is received and discard the frames if requested.
+	 *
+	 * @param  signals
+	 * @param  frames
+	 * @return bool
+	 */
+	fn check_signals(
+		&mut self,
+		signals: &StopSignals,
+		frames: &mut Vec<Image>,
+	) -> bool {
+		match signals.check() {
+			Some(true) => {
+				debug!("\n");
+				info!("Stop signal received.");
+				true
+			}
+			Some(false) => {
+				self.discard_frames(frames);
+				debug!("\n");
+				warn!("Discard signal received.");
+				true
+			}
+			None => false,
+		}
+	}
+
 	/**
 	 * Record frames synchronously with blocking the current thread.
 	 *
@@ -121,27 +518,69 @@ where
 		ctrlc::set_handler(move || {
 			rec_state.store(false, Ordering::SeqCst);
 		})?;
+		let signals = StopSignals::register()?;
+		self.settings.time.wait_for_start();
 		self.window.show_countdown();
 		let max_frames = self.get_max_frames();
-		while recording.load(Ordering::SeqCst) && frames.len() < max_frames {
+		let replay_frames = self.get_replay_frames();
+		let start = Instant::now();
+		let mut paused = false;
+		let (mut pause_start, mut pause_duration) = (start, Duration::ZERO);
+		self.clock.reset();
+		while recording.load(Ordering::SeqCst)
+			&& self.get_frame_count(&frames) < max_frames
+			&& !self.is_timed_out(start)
+		{
+			if self.check_signals(&signals, &mut frames) {
+				break;
+			}
 			if let Some(state) = input_state {
 				if state.check_cancel_keys() {
-					frames.clear();
+					self.discard_frames(&mut frames);
 					debug!("\n");
 					warn!("User interrupt detected.");
 					break;
 				} else if state.check_action() {
 					break;
+				} else if state.check_reselect() {
+					let reselect_start = Instant::now();
+					self.reselect_window(state);
+					if !paused {
+						pause_duration += reselect_start.elapsed();
+					}
+					self.clock.reset();
+					continue;
+				} else if state.check_pause() != paused {
+					paused = !paused;
+					if paused {
+						pause_start = Instant::now();
+					} else {
+						pause_duration += pause_start.elapsed();
+					}
+					info!(
+						"Recording {}.",
+						if paused { "paused" } else { "resumed" }
+					);
 				}
 			}
-			self.clock.tick();
-			frames.push(self.window.get_image().ok_or_else(|| {
+			if paused {
+				self.clock.tick();
+				continue;
+			}
+			let diff = self.clock.tick();
+			self.handle_dropped_frames(diff, &mut frames, max_frames);
+			let timestamp = start.elapsed().saturating_sub(pause_duration);
+			let frame = self.get_frame(timestamp)?.ok_or_else(|| {
 				AppError::FrameError(String::from("Failed to get image"))
-			})?);
-			debug!("Frames: {}\r", frames.len());
+			})?;
+			self.push_frame(&mut frames, frame)?;
+			Self::trim_frames(&mut frames, replay_frames, false);
+			self.flush_frames(&mut frames, 1)?;
+			debug!("Frames: {}\r", self.get_frame_count(&frames));
 			io::stdout().flush()?;
 		}
 		debug!("\n");
+		self.finish(&mut frames, replay_frames)?;
 		Ok(frames)
 	}
 
@@ -150,27 +589,45 @@ where
 	 *
 	 * @return RecordResult
 	 */
-	pub fn record_async(mut self) -> RecordResult<Vec<Image>> {
+	pub fn record_async(
+		mut self,
+	) -> RecordResult<AppResult<(Vec<Image>, RecordStats)>> {
 		let mut frames = Vec::new();
 		RecordResult::new(
 			self.channel.0.clone(),
 			thread::spawn(move || {
+				let signals = StopSignals::register()?;
+				self.settings.time.wait_for_start();
 				self.window.show_countdown();
 				let max_frames = self.get_max_frames();
+				let replay_frames = self.get_replay_frames();
+				let start = Instant::now();
+				self.clock.reset();
 				while self.channel.1.try_recv().is_err() {
-					self.clock.tick();
-					if frames.len() < max_frames {
-						frames.push(
-							self.window
-								.get_image()
-								.expect("Failed to get the image"),
-						);
-						debug!("Frames: {}\r", frames.len());
-						io::stdout().flush().expect("Failed to flush stdout");
+					if self.check_signals(&signals, &mut frames) {
+						break;
+					}
+					let diff = self.clock.tick();
+					if self.get_frame_count(&frames) < max_frames
+						&& !self.is_timed_out(start)
+					{
+						self.handle_dropped_frames(diff, &mut frames, max_frames);
+						let frame =
+							self.get_frame(start.elapsed())?.ok_or_else(|| {
+								AppError::FrameError(String::from(
+									"Failed to get image",
+								))
+							})?;
+						self.push_frame(&mut frames, frame)?;
+						Self::trim_frames(&mut frames, replay_frames, false);
+						self.flush_frames(&mut frames, 1)?;
+						debug!("Frames: {}\r", self.get_frame_count(&frames));
+						io::stdout().flush()?;
 					}
 				}
 				debug!("\n");
-				frames
+				self.finish(&mut frames, replay_frames)?;
+				Ok((frames, self.stats))
 			}),
 		)
 	}
@@ -181,19 +638,116 @@ mod tests {
 	use super::*;
 	use crate::record::settings::RecordSettings;
 	use crate::window::test::TestWindow;
-	use pretty_assertions::assert_ne;
+	use image::ExtendedColorType;
+	use pretty_assertions::{assert_eq, assert_ne};
 	use std::thread;
-	use std::time::Duration;
 	#[test]
-	fn test_record() {
+	fn test_record() -> AppResult<()> {
 		let window = TestWindow::default();
 		let recorder = Recorder::new(window, 10, false, RecordSettings::default());
 		let record = recorder.record_async();
 		thread::sleep(Duration::from_millis(200));
-		assert!(!record.get().unwrap().unwrap().is_empty());
+		let (frames, stats) = record.get().unwrap()?;
+		assert!(!frames.is_empty());
+		assert_eq!(frames.len(), stats.frames);
+		assert!(stats.get_fps() > 0.);
+		let (sender, receiver) = mpsc::sync_channel(100);
+		let record = Recorder::new(window, 10, false, RecordSettings::default())
+			.with_stream(sender)
+			.record_async();
+		thread::sleep(Duration::from_millis(200));
+		let (frames, stats) = record.get().unwrap()?;
+		assert!(frames.is_empty());
+		assert_ne!(0, stats.frames);
+		assert_eq!(stats.frames, receiver.iter().count());
+		let (sender, receiver) = mpsc::sync_channel(100);
+		drop(receiver);
+		let record = Recorder::new(window, 50, false, RecordSettings::default())
+			.with_stream(sender)
+			.record_async();
+		thread::sleep(Duration::from_millis(200));
+		assert!(record.get().unwrap().is_err());
+		#[cfg(unix)]
+		{
+			let record = Recorder::new(window, 10, false, RecordSettings::default())
+				.record_async();
+			thread::sleep(Duration::from_millis(200));
+			signal_hook::low_level::raise(signal_hook::consts::SIGUSR2)?;
+			thread::sleep(Duration::from_millis(100));
+			let (frames, stats) = record.get().unwrap()?;
+			assert!(frames.is_empty());
+			assert_eq!(0, stats.frames);
+		}
 		let mut recorder =
 			Recorder::new(window, 10, false, RecordSettings::default());
 		recorder.settings.time.duration = Some(0.2);
 		assert_ne!(0, recorder.record_sync(None).unwrap().len());
+		assert!(!recorder.is_timed_out(Instant::now()));
+		assert!(recorder.is_timed_out(Instant::now() - Duration::from_secs(1)));
+		recorder.settings.time.duration = None;
+		recorder.settings.time.max_frames = Some(3);
+		assert_eq!(3, recorder.get_max_frames());
+		recorder.settings.time.duration = Some(60.);
+		recorder.settings.time.max_frames = None;
+		recorder.settings.time.timelapse = Some(5.);
+		assert_eq!(12, recorder.get_max_frames());
+		let mut frames = vec![window.get_image().unwrap()];
+		recorder.handle_dropped_frames(-5e7, &mut frames, 12);
+		assert_eq!((1, 0), (frames.len(), recorder.dropped));
+		recorder.handle_dropped_frames(-2.5e8, &mut frames, 12);
+		assert_eq!((1, 2), (frames.len(), recorder.dropped));
+		recorder.settings.flag.drop_frames = true;
+		recorder.handle_dropped_frames(-1e10, &mut frames, 12);
+		assert_eq!((11, 102), (frames.len(), recorder.dropped));
+		recorder.settings.flag.motion = Some(1.);
+		recorder.push_frame(&mut frames, window.get_image().unwrap())?;
+		assert_eq!(11, frames.len());
+		let mut frame = window.get_image().unwrap();
+		frame.draw_circle((0, 0), 0, image::Rgb([0, 0, 0]), 1.)?;
+		recorder.push_frame(&mut frames, frame)?;
+		assert_eq!(12, frames.len());
+		recorder.settings.flag.motion = None;
+		recorder.settings.flag.drop_frames = false;
+		recorder.settings.flag.best_effort = true;
+		let mut delayed = Vec::new();
+		recorder.push_frame(&mut delayed, window.get_image().unwrap())?;
+		assert_eq!(Some(Duration::ZERO), delayed[0].delay);
+		recorder.handle_dropped_frames(-5e7, &mut delayed, 12);
+		recorder.handle_dropped_frames(0., &mut delayed, 12);
+		assert_eq!(Some(Duration::from_millis(250)), delayed[0].delay);
+		recorder.settings.flag.best_effort = false;
+		recorder.spool = Some(Arc::new(FrameSpool::new()?));
+		recorder.push_frame(&mut frames, window.get_image().unwrap())?;
+		recorder.size = Some((2, 3));
+		recorder.settings.time.timelapse = None;
+		recorder.settings.flag.scale = Some(2.);
+		let frame = recorder.get_frame(Duration::ZERO)?.unwrap();
+		assert_eq!((4, 6), (frame.geometry.width, frame.geometry.height));
+		recorder.settings.flag.scale = None;
+		recorder.settings.time.replay = Some(0.5);
+		let replay_frames = recorder.get_replay_frames();
+		assert_eq!(Some(5), replay_frames);
+		let mut buffer = vec![window.get_image().unwrap(); 9];
+		Recorder::<TestWindow>::trim_frames(&mut buffer, replay_frames, false);
+		assert_eq!(9, buffer.len());
+		buffer.push(window.get_image().unwrap());
+		Recorder::<TestWindow>::trim_frames(&mut buffer, replay_frames, false);
+		assert_eq!(5, buffer.len());
+		Recorder::<TestWindow>::trim_frames(&mut buffer, None, true);
+		assert_eq!(5, buffer.len());
+		buffer.push(window.get_image().unwrap());
+		Recorder::<TestWindow>::trim_frames(&mut buffer, replay_frames, true);
+		assert_eq!(5, buffer.len());
+		let frame = recorder.get_frame(Duration::from_millis(10))?.unwrap();
+		assert_eq!((2, 3), (frame.geometry.width, frame.geometry.height));
+		assert_eq!(Some(Duration::from_millis(10)), frame.timestamp);
+		assert_eq!(
+			window
+				.get_image()
+				.unwrap()
+				.get_data(ExtendedColorType::Rgba8)?,
+			frames[12].get_data(ExtendedColorType::Rgba8)?
+		);
+		Ok(())
 	}
 }
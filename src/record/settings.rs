@@ -1,16 +1,28 @@
 use crate::args::matches::ArgMatches;
 use crate::args::parser::ArgParser;
+use crate::edit::settings::OverlayPosition;
 use crate::image::geometry::Geometry;
 use crate::image::padding::Padding;
+use crate::record::highlight::HighlightSettings;
+use crate::record::stamp::{StampMode, StampSettings, DEFAULT_STAMP_FORMAT};
 use crate::util::command::Command;
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use image::Rgb;
+use std::thread;
+use std::time::Duration;
 
 /* Time related recording settings */
 #[derive(Clone, Copy, Debug)]
 pub struct RecordTime {
 	pub duration: Option<f64>,
+	pub max_frames: Option<usize>,
+	pub timelapse: Option<f64>,
+	pub replay: Option<f64>,
+	pub segment: Option<RecordSegment>,
 	pub countdown: u64,
 	pub timeout: u64,
 	pub interval: u64,
+	pub start: Option<DateTime<Local>>,
 }
 
 /* Default initialization values for RecordTime */
@@ -18,9 +30,14 @@ impl Default for RecordTime {
 	fn default() -> Self {
 		Self {
 			duration: None,
+			max_frames: None,
+			timelapse: None,
+			replay: None,
+			segment: None,
 			countdown: 3,
 			timeout: 300,
 			interval: 10,
+			start: None,
 		}
 	}
 }
@@ -30,6 +47,7 @@ impl RecordTime {
 	 * Create a new RecordTime object.
 	 *
 	 * @param  duration (Option)
+	 * @param  max_frames (Option)
 	 * @param  countdown
 	 * @param  timeout
 	 * @param  interval
@@ -37,15 +55,21 @@ impl RecordTime {
 	 */
 	pub fn new(
 		duration: Option<f64>,
+		max_frames: Option<usize>,
 		countdown: u64,
 		timeout: u64,
 		interval: u64,
 	) -> Self {
 		Self {
 			duration,
+			max_frames,
 			countdown,
 			timeout,
 			interval,
+			timelapse: None,
+			replay: None,
+			segment: None,
+			start: None,
 		}
 	}
 
@@ -56,16 +80,112 @@ impl RecordTime {
 	 * @return RecordTime
 	 */
 	fn from_parser(parser: &ArgParser<'_>) -> Self {
+		let matches = parser.args.as_ref().expect("Invalid arguments");
+		Self {
+			start: Self::get_start(
+				matches.value_of("at"),
+				matches.value_of("after"),
+			),
+			timelapse: match parser.parse("timelapse", 0.0) {
+				interval if interval > 0.0 => Some(interval),
+				_ => Self::default().timelapse,
+			},
+			replay: match parser.parse("replay", 0.0) {
+				duration if duration > 0.0 => Some(duration),
+				_ => Self::default().replay,
+			},
+			segment: matches.value_of("segment").and_then(RecordSegment::parse),
+			..Self::from_values(parser)
+		}
+	}
+
+	/**
+	 * Create a RecordTime object from the parsed values.
+	 *
+	 * @param  parser
+	 * @return RecordTime
+	 */
+	fn from_values(parser: &ArgParser<'_>) -> Self {
 		RecordTime::new(
 			match parser.parse("duration", 0.0) {
 				duration if duration > 0.0 => Some(duration),
 				_ => Self::default().duration,
 			},
+			match parser.parse("max-frames", 0) {
+				max_frames if max_frames > 0 => Some(max_frames),
+				_ => Self::default().max_frames,
+			},
 			parser.parse("countdown", Self::default().countdown),
 			parser.parse("timeout", Self::default().timeout),
 			parser.parse("interval", Self::default().interval),
 		)
 	}
+
+	/**
+	 * Parse a delay value such as "90", "1.5m" or "2h".
+	 *
+	 * @param  delay
+	 * @return Duration (Option)
+	 */
+	pub fn parse_delay(delay: &str) -> Option<Duration> {
+		let (value, unit) = match delay.trim().char_indices().last() {
+			Some((i, 'h')) => (&delay[..i], 3600.),
+			Some((i, 'm')) => (&delay[..i], 60.),
+			Some((i, 's')) => (&delay[..i], 1.),
+			_ => (delay, 1.),
+		};
+		value
+			.trim()
+			.parse::<f64>()
+			.ok()
+			.filter(|v| v.is_finite() && *v >= 0.)
+			.map(|v| Duration::from_secs_f64(v * unit))
+	}
+
+	/**
+	 * Parse a time of day in the "HH:MM[:SS]" format.
+	 *
+	 * @param  time
+	 * @return NaiveTime (Option)
+	 */
+	pub fn parse_time(time: &str) -> Option<NaiveTime> {
+		NaiveTime::parse_from_str(time, "%H:%M:%S")
+			.or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+			.ok()
+	}
+
+	/**
+	 * Get the scheduled start time from the given values.
+	 *
+	 * @param  at (Option)
+	 * @param  after (Option)
+	 * @return DateTime (Option)
+	 */
+	fn get_start(at: Option<&str>, after: Option<&str>) -> Option<DateTime<Local>> {
+		let now = Local::now();
+		if let Some(delay) = after.and_then(Self::parse_delay) {
+			chrono::Duration::from_std(delay)
+				.ok()
+				.map(|delay| now + delay)
+		} else {
+			let time = at.and_then(Self::parse_time)?;
+			let mut date = now.date_naive();
+			if time <= now.time() {
+				date = date.succ_opt()?;
+			}
+			Local.from_local_datetime(&date.and_time(time)).earliest()
+		}
+	}
+
+	/* Wait until the scheduled start time. */
+	pub fn wait_for_start(&self) {
+		if let Some(start) = self.start {
+			if let Ok(delay) = (start - Local::now()).to_std() {
+				info!("Waiting until {}...", start.format("%H:%M:%S"));
+				thread::sleep(delay);
+			}
+		}
+	}
 }
 
 /* Flag values of recording */
@@ -74,10 +194,28 @@ pub struct RecordFlag {
 	pub alpha: bool,
 	pub action_keys: Option<&'static str>,
 	pub cancel_keys: Option<&'static str>,
+	pub pause_keys: Option<&'static str>,
+	pub reselect_keys: Option<&'static str>,
 	pub font: Option<&'static str>,
-	pub monitor: Option<usize>,
+	pub stats_file: Option<&'static str>,
+	pub monitor: Option<RecordMonitor>,
 	pub select: bool,
 	pub mouse: bool,
+	pub region: bool,
+	pub freeze: bool,
+	pub decorations: Option<bool>,
+	pub follow: bool,
+	pub letterbox: bool,
+	pub highlight: Option<HighlightSettings>,
+	pub stamp: Option<StampSettings>,
+	pub drop_frames: bool,
+	pub best_effort: bool,
+	pub windows: Option<&'static [u64]>,
+	pub window_filter: Option<WindowFilter>,
+	pub side_by_side: bool,
+	pub motion: Option<f64>,
+	pub disk_buffer: bool,
+	pub scale: Option<f64>,
 }
 
 /* Default initialization values for RecordFlag */
@@ -87,10 +225,28 @@ impl Default for RecordFlag {
 			alpha: false,
 			action_keys: Some(""),
 			cancel_keys: Some(""),
+			pause_keys: Some(""),
+			reselect_keys: Some(""),
 			font: None,
+			stats_file: None,
 			monitor: None,
 			select: true,
 			mouse: false,
+			region: false,
+			freeze: false,
+			decorations: None,
+			follow: false,
+			letterbox: false,
+			highlight: None,
+			stamp: None,
+			drop_frames: false,
+			best_effort: false,
+			windows: None,
+			window_filter: None,
+			side_by_side: false,
+			motion: None,
+			disk_buffer: false,
+			scale: None,
 		}
 	}
 }
@@ -113,7 +269,7 @@ impl RecordFlag {
 		action_keys: Option<&'static str>,
 		cancel_keys: Option<&'static str>,
 		font: &str,
-		monitor: Option<usize>,
+		monitor: Option<RecordMonitor>,
 		select: bool,
 		mouse: bool,
 	) -> Self {
@@ -121,6 +277,9 @@ impl RecordFlag {
 			alpha,
 			action_keys,
 			cancel_keys,
+			pause_keys: Some(""),
+			reselect_keys: Some(""),
+			stats_file: None,
 			font: if font.is_empty() {
 				None
 			} else {
@@ -129,6 +288,131 @@ impl RecordFlag {
 			monitor,
 			select,
 			mouse,
+			region: false,
+			freeze: false,
+			decorations: None,
+			follow: false,
+			letterbox: false,
+			highlight: None,
+			stamp: None,
+			drop_frames: false,
+			best_effort: false,
+			windows: None,
+			window_filter: None,
+			side_by_side: false,
+			motion: None,
+			disk_buffer: false,
+			scale: None,
+		}
+	}
+}
+
+/* Properties for finding the window to record without selection */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowFilter {
+	pub name: Option<&'static str>,
+	pub class: Option<&'static str>,
+	pub pid: Option<u32>,
+}
+
+impl WindowFilter {
+	/**
+	 * Create a WindowFilter object from parsed arguments.
+	 *
+	 * @param  matches
+	 * @return WindowFilter (Option)
+	 */
+	fn from_args(matches: &ArgMatches<'_>) -> Option<Self> {
+		let leak = |value: &str| &*Box::leak(value.to_string().into_boxed_str());
+		let filter = Self {
+			name: matches.value_of("window-name").map(leak),
+			class: matches.value_of("class").map(leak),
+			pid: matches.value_of("pid").and_then(|pid| pid.parse().ok()),
+		};
+		(filter != Self::default()).then_some(filter)
+	}
+
+	/**
+	 * Check if the given window properties match the filter.
+	 *
+	 * @param  name
+	 * @param  class
+	 * @param  pid (Option)
+	 * @return bool
+	 */
+	pub fn matches(&self, name: &str, class: &[String], pid: Option<u32>) -> bool {
+		self.name.is_none_or(|filter| {
+			name.to_lowercase().contains(&filter.to_lowercase())
+		}) && self.class.is_none_or(|filter| {
+			class.iter().any(|class| class.eq_ignore_ascii_case(filter))
+		}) && self.pid.is_none_or(|filter| pid == Some(filter))
+	}
+}
+
+/* Monitor to record, given by its number or output name */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordMonitor {
+	Index(usize),
+	Name(&'static str),
+}
+
+impl RecordMonitor {
+	/**
+	 * Parse RecordMonitor from a string.
+	 *
+	 * @param  monitor
+	 * @return RecordMonitor (Option)
+	 */
+	pub fn parse(monitor: &str) -> Option<Self> {
+		let monitor = monitor.trim();
+		if monitor.is_empty() {
+			None
+		} else if let Ok(index) = monitor.parse() {
+			Some(Self::Index(index))
+		} else {
+			Some(Self::Name(Box::leak(monitor.to_string().into_boxed_str())))
+		}
+	}
+}
+
+/* Length of the output files for splitting the recording */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordSegment {
+	Duration(Duration),
+	Frames(usize),
+}
+
+impl RecordSegment {
+	/**
+	 * Parse RecordSegment from a string such as "30", "1.5m" or "300f".
+	 *
+	 * @param  segment
+	 * @return RecordSegment (Option)
+	 */
+	pub fn parse(segment: &str) -> Option<Self> {
+		let segment = segment.trim();
+		match segment.strip_suffix('f') {
+			Some(frames) => frames.trim().parse().ok().map(Self::Frames),
+			None => RecordTime::parse_delay(segment).map(Self::Duration),
+		}
+		.filter(|segment| match segment {
+			Self::Duration(duration) => !duration.is_zero(),
+			Self::Frames(frames) => *frames > 0,
+		})
+	}
+
+	/**
+	 * Get the number of frames in a segment.
+	 *
+	 * @param  fps
+	 * @return usize
+	 */
+	pub fn get_frames(&self, fps: u32) -> usize {
+		match self {
+			Self::Duration(duration) => {
+				(duration.as_secs_f64() * f64::from(fps)).round().max(1.) as usize
+			}
+			Self::Frames(frames) => *frames,
 		}
 	}
 }
@@ -162,7 +446,10 @@ impl RecordWindow {
 			};
 		if matches.is_present("focus") && !matches.is_present("monitor") {
 			Self::Focus(size, matches.is_present("parent"))
-		} else if matches.is_present("root") || matches.is_present("monitor") {
+		} else if matches.is_present("root")
+			|| matches.is_present("monitor")
+			|| matches.is_present("pos")
+		{
 			Self::Root(size)
 		} else {
 			Self::Focus(Some(size.unwrap_or_default()), matches.is_present("parent"))
@@ -271,41 +558,150 @@ impl RecordSettings {
 				},
 				Self::parse_padding(matches),
 				RecordTime::from_parser(&parser),
-				RecordFlag::new(
-					matches.is_present("with-alpha"),
-					if matches.is_present("no-keys") {
-						None
+				RecordFlag {
+					region: matches.is_present("region"),
+					freeze: matches.is_present("freeze"),
+					decorations: if matches.is_present("with-decorations") {
+						Some(true)
+					} else if matches.is_present("no-decorations") {
+						Some(false)
 					} else {
-						Some(Box::leak(
+						None
+					},
+					follow: matches.is_present("follow"),
+					letterbox: matches.is_present("letterbox"),
+					drop_frames: matches.is_present("drop-frames"),
+					best_effort: matches.is_present("best-effort"),
+					windows: matches
+						.value_of("windows")
+						.and_then(Self::parse_window_ids)
+						.map(|ids| &*Box::leak(ids.into_boxed_slice())),
+					side_by_side: matches.is_present("side-by-side"),
+					window_filter: WindowFilter::from_args(matches),
+					disk_buffer: matches.value_of("buffer") == Some("disk"),
+					stats_file: matches
+						.value_of("stats")
+						.map(|path| &*Box::leak(path.to_string().into_boxed_str())),
+					scale: match parser.parse("scale", 1.0) {
+						factor if factor > 0.0 && factor != 1.0 => Some(factor),
+						_ => None,
+					},
+					motion: match parser.parse("motion", 0.0) {
+						threshold if threshold > 0.0 => Some(threshold),
+						_ => None,
+					},
+					highlight: if matches.is_present("highlight") {
+						Some(HighlightSettings::new(
 							matches
-								.value_of("action-keys")
-								.unwrap_or_default()
-								.to_string()
-								.into_boxed_str(),
+								.value_of("highlight-color")
+								.and_then(|color| {
+									hex::decode(color.trim_start_matches('#')).ok()
+								})
+								.filter(|rgb| rgb.len() == 3)
+								.map(|rgb| Rgb([rgb[0], rgb[1], rgb[2]]))
+								.unwrap_or(HighlightSettings::default().color),
+							parser.parse(
+								"highlight-radius",
+								HighlightSettings::default().radius,
+							),
 						))
+					} else {
+						None
 					},
-					Some(Box::leak(
+					stamp: matches.value_of("stamp").map(|mode| {
+						StampSettings::new(
+							StampMode::parse(mode),
+							matches
+								.value_of("stamp-format")
+								.unwrap_or(DEFAULT_STAMP_FORMAT),
+							OverlayPosition::parse(
+								matches
+									.value_of("stamp-position")
+									.unwrap_or_default(),
+							),
+						)
+					}),
+					pause_keys: Some(Box::leak(
 						matches
-							.value_of("cancel-keys")
+							.value_of("pause-keys")
 							.unwrap_or_default()
 							.to_string()
 							.into_boxed_str(),
 					)),
-					matches.value_of("font").unwrap_or_default(),
-					matches.value_of("monitor").and_then(|v| v.parse().ok()),
-					if matches.value_of("size").unwrap_or_default().contains('+') {
-						matches.is_present("select")
-					} else {
-						true
-					},
-					matches.is_present("mouse"),
-				),
+					reselect_keys: Some(Box::leak(
+						matches
+							.value_of("reselect-keys")
+							.unwrap_or_default()
+							.to_string()
+							.into_boxed_str(),
+					)),
+					..RecordFlag::new(
+						matches.is_present("with-alpha"),
+						if matches.is_present("no-keys") {
+							None
+						} else {
+							Some(Box::leak(
+								matches
+									.value_of("action-keys")
+									.unwrap_or_default()
+									.to_string()
+									.into_boxed_str(),
+							))
+						},
+						Some(Box::leak(
+							matches
+								.value_of("cancel-keys")
+								.unwrap_or_default()
+								.to_string()
+								.into_boxed_str(),
+						)),
+						matches.value_of("font").unwrap_or_default(),
+						matches.value_of("monitor").and_then(RecordMonitor::parse),
+						if matches.value_of("size").unwrap_or_default().contains('+')
+							|| matches.is_present("pos")
+						{
+							matches.is_present("select")
+						} else {
+							true
+						},
+						matches.is_present("mouse"),
+					)
+				},
 				RecordWindow::from_args(matches),
 			),
 			None => RecordSettings::default(),
 		}
 	}
 
+	/**
+	 * Parse the comma-separated window IDs in decimal or hexadecimal.
+	 *
+	 * @param  ids
+	 * @return Vector of u64 (Option)
+	 */
+	pub fn parse_window_ids(ids: &str) -> Option<Vec<u64>> {
+		ids.split(',')
+			.map(|id| {
+				let id = id.trim();
+				match id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")) {
+					Some(hex) => u64::from_str_radix(hex, 16).ok(),
+					None => id.parse().ok(),
+				}
+			})
+			.collect()
+	}
+
+	/**
+	 * Parse the area position in X:Y format.
+	 *
+	 * @param  pos
+	 * @return Tuple (Option)
+	 */
+	pub fn parse_position(pos: &str) -> Option<(u32, u32)> {
+		let (x, y) = pos.split_once(':')?;
+		Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+	}
+
 	/**
 	 * Parse the padding value from arguments.
 	 *
@@ -339,6 +735,11 @@ impl RecordSettings {
 				.parse()
 				.unwrap_or_default();
 		};
+		if let Some((x, y)) = matches.value_of("pos").and_then(Self::parse_position)
+		{
+			padding.left = x;
+			padding.top = y;
+		}
 		padding
 	}
 
@@ -370,6 +771,16 @@ mod tests {
 					.long("cancel-keys")
 					.takes_value(true),
 			)
+			.arg(
+				Arg::with_name("pause-keys")
+					.long("pause-keys")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("reselect-keys")
+					.long("reselect-keys")
+					.takes_value(true),
+			)
 			.arg(Arg::with_name("border").long("border").takes_value(true))
 			.arg(Arg::with_name("padding").long("padding").takes_value(true))
 			.arg(Arg::with_name("size").long("size").takes_value(true))
@@ -399,6 +810,10 @@ mod tests {
 				"LControl-Q,S",
 				"--cancel-keys",
 				"X",
+				"--pause-keys",
+				"LAlt-Space",
+				"--reselect-keys",
+				"LAlt-Tab",
 				"--border",
 				"10",
 				"--padding",
@@ -431,5 +846,78 @@ mod tests {
 		assert!(record_settings.flag.alpha);
 		assert_eq!("LControl-Q,S", record_settings.flag.action_keys.unwrap());
 		assert_eq!("X", record_settings.flag.cancel_keys.unwrap());
+		assert_eq!("LAlt-Space", record_settings.flag.pause_keys.unwrap());
+		assert_eq!("LAlt-Tab", record_settings.flag.reselect_keys.unwrap());
+		assert_eq!(None, record_settings.time.start);
+		assert_eq!(
+			Some(vec![0x3a0_0007, 42]),
+			RecordSettings::parse_window_ids("0x3a00007, 42")
+		);
+		assert_eq!(None, RecordSettings::parse_window_ids("0x3a00007,x"));
+		assert_eq!(Some((100, 20)), RecordSettings::parse_position("100:20"));
+		assert_eq!(None, RecordSettings::parse_position("100x20"));
+		assert_eq!(None, RecordSettings::parse_position("-1:20"));
+		assert_eq!(Some(RecordMonitor::Index(2)), RecordMonitor::parse("2"));
+		assert_eq!(
+			Some(RecordMonitor::Name("DP-1")),
+			RecordMonitor::parse(" DP-1")
+		);
+		assert_eq!(None, RecordMonitor::parse(""));
+		assert_eq!(
+			Some(RecordSegment::Duration(Duration::from_secs(90))),
+			RecordSegment::parse("1.5m")
+		);
+		assert_eq!(
+			Some(RecordSegment::Frames(300)),
+			RecordSegment::parse("300f")
+		);
+		assert_eq!(None, RecordSegment::parse("0"));
+		assert_eq!(None, RecordSegment::parse("f"));
+		assert_eq!(40, RecordSegment::parse("2").unwrap().get_frames(20));
+		assert_eq!(1, RecordSegment::parse("0.01s").unwrap().get_frames(20));
+		assert_eq!(5, RecordSegment::Frames(5).get_frames(20));
+		let filter = WindowFilter {
+			name: Some("menyoki"),
+			class: Some("alacritty"),
+			pid: None,
+		};
+		let class = vec![String::from("Alacritty")];
+		assert!(filter.matches("Menyoki - README.md", &class, Some(42)));
+		assert!(!filter.matches("Menyoki - README.md", &[], Some(42)));
+		assert!(!filter.matches("README.md", &class, None));
+		assert!(!WindowFilter {
+			pid: Some(42),
+			..WindowFilter::default()
+		}
+		.matches("", &[], None));
+	}
+	#[test]
+	fn test_record_time() {
+		assert_eq!(Some(Duration::from_secs(90)), RecordTime::parse_delay("90"));
+		assert_eq!(
+			Some(Duration::from_secs(90)),
+			RecordTime::parse_delay("1.5m")
+		);
+		assert_eq!(
+			Some(Duration::from_secs(7200)),
+			RecordTime::parse_delay("2h")
+		);
+		assert_eq!(None, RecordTime::parse_delay("-1s"));
+		assert_eq!(None, RecordTime::parse_delay("x"));
+		assert_eq!(
+			NaiveTime::from_hms_opt(9, 30, 0),
+			RecordTime::parse_time("09:30")
+		);
+		assert_eq!(
+			NaiveTime::from_hms_opt(23, 59, 5),
+			RecordTime::parse_time("23:59:05")
+		);
+		assert_eq!(None, RecordTime::parse_time("25:00"));
+		let start = RecordTime::get_start(None, Some("1h")).unwrap();
+		assert!(start > Local::now() + chrono::Duration::minutes(59));
+		let start = RecordTime::get_start(Some("00:00"), None).unwrap();
+		assert!(start > Local::now());
+		assert!(start <= Local::now() + chrono::Duration::days(1));
+		assert_eq!(None, RecordTime::get_start(None, None));
 	}
 }
@@ -0,0 +1,74 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/* Unix signals for stopping the recording (SIGUSR1: save, SIGUSR2: discard) */
+#[derive(Debug, Default)]
+pub struct StopSignals {
+	save: Arc<AtomicBool>,
+	discard: Arc<AtomicBool>,
+	#[cfg(unix)]
+	ids: Vec<signal_hook::SigId>,
+}
+
+impl StopSignals {
+	/**
+	 * Register the signal handlers.
+	 *
+	 * @return StopSignals (Result)
+	 */
+	pub fn register() -> io::Result<Self> {
+		#[allow(unused_mut)]
+		let mut signals = Self::default();
+		#[cfg(unix)]
+		{
+			use signal_hook::consts::{SIGUSR1, SIGUSR2};
+			signals.ids = vec![
+				signal_hook::flag::register(SIGUSR1, signals.save.clone())?,
+				signal_hook::flag::register(SIGUSR2, signals.discard.clone())?,
+			];
+		}
+		Ok(signals)
+	}
+
+	/**
+	 * Check if a stop signal is received.
+	 *
+	 * @return bool (Option)
+	 */
+	pub fn check(&self) -> Option<bool> {
+		if self.discard.load(Ordering::SeqCst) {
+			Some(false)
+		} else if self.save.load(Ordering::SeqCst) {
+			Some(true)
+		} else {
+			None
+		}
+	}
+}
+
+/* Unregister the signal handlers when the recording ends */
+impl Drop for StopSignals {
+	fn drop(&mut self) {
+		#[cfg(unix)]
+		self.ids.drain(..).for_each(|id| {
+			signal_hook::low_level::unregister(id);
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_stop_signals() -> io::Result<()> {
+		let signals = StopSignals::register()?;
+		assert_eq!(None, signals.check());
+		signals.save.store(true, Ordering::SeqCst);
+		assert_eq!(Some(true), signals.check());
+		signals.discard.store(true, Ordering::SeqCst);
+		assert_eq!(Some(false), signals.check());
+		Ok(())
+	}
+}
@@ -0,0 +1,187 @@
+use crate::app::AppResult;
+use crate::edit::settings::{OverlayPosition, TextSettings};
+use crate::edit::text::TextRenderer;
+use crate::image::Image;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Local, NaiveTime};
+use image::Rgba;
+use std::time::Duration;
+
+/* Default format of the timestamps */
+pub const DEFAULT_STAMP_FORMAT: &str = "%H:%M:%S%.3f";
+/* Font size of the timestamps */
+const STAMP_FONT_SIZE: f32 = 18.;
+/* Distance of the timestamps to the frame edges */
+const STAMP_MARGIN: u32 = 10;
+/* Number of seconds in a day for wrapping the elapsed time */
+const SECONDS_PER_DAY: u64 = 86400;
+
+/* Source of the time that is stamped on the frames */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StampMode {
+	#[default]
+	Elapsed,
+	Clock,
+}
+
+impl StampMode {
+	/**
+	 * Parse the stamp mode from the given value.
+	 *
+	 * @param  mode
+	 * @return StampMode
+	 */
+	pub fn parse(mode: &str) -> Self {
+		match mode {
+			"clock" => Self::Clock,
+			_ => Self::Elapsed,
+		}
+	}
+}
+
+/* Settings of the frame timestamps */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StampSettings {
+	pub mode: StampMode,
+	pub format: &'static str,
+	pub position: OverlayPosition,
+}
+
+/* Default initialization values for StampSettings */
+impl Default for StampSettings {
+	fn default() -> Self {
+		Self {
+			mode: StampMode::default(),
+			format: DEFAULT_STAMP_FORMAT,
+			position: OverlayPosition::TopLeft,
+		}
+	}
+}
+
+impl StampSettings {
+	/**
+	 * Create a new StampSettings object.
+	 *
+	 * @param  mode
+	 * @param  format
+	 * @param  position
+	 * @return StampSettings
+	 */
+	pub fn new(mode: StampMode, format: &str, position: OverlayPosition) -> Self {
+		Self {
+			mode,
+			format: if StrftimeItems::new(format).any(|item| item == Item::Error) {
+				warn!("Invalid timestamp format: {:?}", format);
+				DEFAULT_STAMP_FORMAT
+			} else {
+				Box::leak(format.to_string().into_boxed_str())
+			},
+			position,
+		}
+	}
+}
+
+/* Stamper for drawing the time on the recorded frames */
+#[derive(Debug)]
+pub struct FrameStamper {
+	settings: StampSettings,
+	renderer: TextRenderer,
+}
+
+impl FrameStamper {
+	/**
+	 * Create a new FrameStamper object by loading the default font.
+	 *
+	 * @param  settings
+	 * @return FrameStamper (Result)
+	 */
+	pub fn new(settings: StampSettings) -> AppResult<Self> {
+		Ok(Self {
+			settings,
+			renderer: TextRenderer::new(TextSettings::new(
+				String::new(),
+				None,
+				STAMP_FONT_SIZE,
+				Rgba([255, 255, 255, 255]),
+				settings.position,
+				STAMP_MARGIN,
+			))?,
+		})
+	}
+
+	/**
+	 * Get the text of the timestamp.
+	 *
+	 * @param  elapsed
+	 * @return String
+	 */
+	fn get_text(&self, elapsed: Duration) -> String {
+		match self.settings.mode {
+			StampMode::Elapsed => NaiveTime::from_num_seconds_from_midnight_opt(
+				(elapsed.as_secs() % SECONDS_PER_DAY) as u32,
+				elapsed.subsec_nanos(),
+			)
+			.map(|time| time.format(self.settings.format).to_string())
+			.unwrap_or_default(),
+			StampMode::Clock => {
+				Local::now().format(self.settings.format).to_string()
+			}
+		}
+	}
+
+	/**
+	 * Draw the timestamp on the frame.
+	 *
+	 * @param  image
+	 * @param  elapsed
+	 * @return Result
+	 */
+	pub fn apply(&self, image: &mut Image, elapsed: Duration) -> AppResult<()> {
+		image.draw_text(&self.renderer, &self.get_text(elapsed))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image::geometry::Geometry;
+	use image::ExtendedColorType;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_stamp_settings() {
+		let settings = StampSettings::new(
+			StampMode::parse("elapsed"),
+			"%M:%S",
+			OverlayPosition::TopLeft,
+		);
+		assert_eq!("%M:%S", settings.format);
+		assert_eq!(StampMode::Clock, StampMode::parse("clock"));
+		assert_eq!(
+			DEFAULT_STAMP_FORMAT,
+			StampSettings::new(StampMode::Clock, "%Q", OverlayPosition::Top).format
+		);
+		let Ok(stamper) = FrameStamper::new(settings) else {
+			return;
+		};
+		assert_eq!("01:05", stamper.get_text(Duration::from_millis(65_250)));
+		let mut image = Image::new(
+			vec![Rgba([0, 0, 0, 255]); 100 * 40],
+			false,
+			Geometry::new(0, 0, 100, 40),
+		);
+		stamper.apply(&mut image, Duration::from_secs(1)).unwrap();
+		assert!(image
+			.get_data(ExtendedColorType::Rgb8)
+			.unwrap()
+			.iter()
+			.any(|v| *v > 128));
+		let stamper = FrameStamper {
+			settings: StampSettings::default(),
+			..stamper
+		};
+		assert_eq!(
+			"00:01:05.250",
+			stamper.get_text(Duration::from_millis(65_250))
+		);
+	}
+}
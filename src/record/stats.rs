@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+/* Percentiles of the capture latency to report */
+const LATENCY_PERCENTILES: [f64; 3] = [50., 90., 99.];
+
+/* Statistics of a recording */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecordStats {
+	pub frames: usize,
+	pub dropped: usize,
+	pub duration: Duration,
+	latencies: Vec<Duration>,
+}
+
+impl RecordStats {
+	/**
+	 * Add the time that is spent for capturing a frame.
+	 *
+	 * @param latency
+	 */
+	pub fn add_latency(&mut self, latency: Duration) {
+		self.latencies.push(latency);
+	}
+
+	/**
+	 * Get the average number of captured frames per second.
+	 *
+	 * @return f64
+	 */
+	pub fn get_fps(&self) -> f64 {
+		if self.duration.is_zero() {
+			0.
+		} else {
+			self.latencies.len() as f64 / self.duration.as_secs_f64()
+		}
+	}
+
+	/**
+	 * Get the capture latency at the given percentile.
+	 *
+	 * @param  percentile
+	 * @return Duration
+	 */
+	pub fn get_latency(&self, percentile: f64) -> Duration {
+		let mut latencies = self.latencies.clone();
+		latencies.sort_unstable();
+		let rank = ((percentile / 100. * latencies.len() as f64).ceil() as usize)
+			.clamp(1, latencies.len().max(1));
+		latencies.get(rank - 1).copied().unwrap_or_default()
+	}
+
+	/* Print the statistics. */
+	pub fn report(&self) {
+		info!(
+			"Recorded {} frames in {:.2}s ({:.2} FPS, {} dropped).",
+			self.frames,
+			self.duration.as_secs_f64(),
+			self.get_fps(),
+			self.dropped
+		);
+		info!(
+			"Capture latency: {}",
+			LATENCY_PERCENTILES
+				.iter()
+				.map(|p| format!(
+					"p{}={:.2}ms",
+					p,
+					self.get_latency(*p).as_secs_f64() * 1e3
+				))
+				.collect::<Vec<String>>()
+				.join(", ")
+		);
+	}
+
+	/**
+	 * Get the statistics in JSON format.
+	 *
+	 * @param  file_size (Option)
+	 * @return String
+	 */
+	pub fn to_json(&self, file_size: Option<u64>) -> String {
+		format!(
+			"{{\"frames\":{},\"dropped_frames\":{},\"duration\":{:.3},\
+			\"fps\":{:.2},\"latency_ms\":{{{}}},\"file_size\":{}}}",
+			self.frames,
+			self.dropped,
+			self.duration.as_secs_f64(),
+			self.get_fps(),
+			LATENCY_PERCENTILES
+				.iter()
+				.map(|p| format!(
+					"\"p{}\":{:.3}",
+					p,
+					self.get_latency(*p).as_secs_f64() * 1e3
+				))
+				.collect::<Vec<String>>()
+				.join(","),
+			file_size.map_or(String::from("null"), |size| size.to_string())
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_record_stats() {
+		let mut stats = RecordStats::default();
+		assert_eq!(0., stats.get_fps());
+		assert_eq!(Duration::ZERO, stats.get_latency(50.));
+		for ms in (1..=10).rev() {
+			stats.add_latency(Duration::from_millis(ms));
+		}
+		stats.frames = 9;
+		stats.dropped = 1;
+		stats.duration = Duration::from_secs(2);
+		assert_eq!(5., stats.get_fps());
+		assert_eq!(Duration::from_millis(5), stats.get_latency(50.));
+		assert_eq!(Duration::from_millis(9), stats.get_latency(90.));
+		assert_eq!(Duration::from_millis(10), stats.get_latency(99.));
+		assert_eq!(
+			"{\"frames\":9,\"dropped_frames\":1,\"duration\":2.000,\"fps\":5.00,\
+			\"latency_ms\":{\"p50\":5.000,\"p90\":9.000,\"p99\":10.000},\
+			\"file_size\":42}",
+			stats.to_json(Some(42))
+		);
+	}
+}
@@ -1,16 +1,25 @@
 use crate::analyze::settings::AnalyzeSettings;
-use crate::anim::settings::{AnimSettings, SplitSettings};
+use crate::anim::decoder::AnimDecoder;
+use crate::anim::settings::{AnimSettings, SplitSettings, TrimSettings};
 use crate::args::matches::ArgMatches;
+use crate::diff::settings::DiffSettings;
 use crate::edit::settings::EditSettings;
 use crate::file::format::FileFormat;
 use crate::file::settings::SaveSettings;
 use crate::image::geometry::Geometry;
-use crate::image::settings::{JpgSettings, PngSettings, PnmSettings, WebPSettings};
+use crate::image::metadata::Metadata;
+use crate::image::settings::{
+	AnsiSettings, HeifSettings, IcoSettings, JpgSettings, JxlSettings, PngSettings,
+	PnmSettings, WebPSettings,
+};
+use crate::montage::settings::MontageSettings;
 use crate::record::settings::{RecordSettings, RecordWindow};
+use crate::record::stats::RecordStats;
 use crate::util::keys::{ActionKeys, KeyType};
 use crate::util::state::InputState;
 use crate::view::settings::ViewSettings;
 use colored::Color;
+use std::sync::OnceLock;
 
 /* General application settings */
 #[derive(Debug)]
@@ -19,16 +28,25 @@ pub struct AppSettings<'a> {
 	pub record: RecordSettings,
 	pub anim: AnimSettings,
 	pub split: SplitSettings,
+	pub trim: TrimSettings,
 	pub png: PngSettings,
 	pub jpg: JpgSettings,
 	pub webp: WebPSettings,
+	pub jxl: JxlSettings,
+	pub heif: HeifSettings,
+	pub ico: IcoSettings,
+	pub ansi: AnsiSettings,
 	pub pnm: PnmSettings,
 	pub edit: EditSettings,
+	pub montage: MontageSettings,
+	pub diff: DiffSettings,
 	pub analyze: AnalyzeSettings,
 	pub view: ViewSettings,
 	pub save: SaveSettings,
 	pub input_state: Option<&'static InputState>,
 	pub window_required: bool,
+	pub stats: OnceLock<RecordStats>,
+	pub metadata: OnceLock<Metadata>,
 }
 
 impl<'a> AppSettings<'a> {
@@ -51,9 +69,16 @@ impl<'a> AppSettings<'a> {
 			record,
 			anim: AnimSettings::from_args(args, &save.file.format),
 			split: SplitSettings::from_args(args),
+			trim: TrimSettings::from_args(args),
 			png: PngSettings::from_args(args),
 			jpg: JpgSettings::from_args(args),
 			webp: WebPSettings::from_args(args),
+			jxl: JxlSettings::from_args(args),
+			heif: HeifSettings::from_args(args),
+			ico: IcoSettings::from_args(args),
+			ansi: AnsiSettings::from_args(args),
+			montage: MontageSettings::from_args(args),
+			diff: DiffSettings::from_args(args),
 			analyze: AnalyzeSettings::from_args(args, Self::get_color(args)),
 			view: ViewSettings::from_args(args),
 			pnm,
@@ -61,6 +86,8 @@ impl<'a> AppSettings<'a> {
 			save,
 			input_state,
 			window_required,
+			stats: OnceLock::new(),
+			metadata: OnceLock::new(),
 		}
 	}
 
@@ -118,6 +145,16 @@ impl<'a> AppSettings<'a> {
 					} else {
 						ActionKeys::default(KeyType::CancelKeys)
 					},
+					if let Some(keys) = record.flag.pause_keys {
+						ActionKeys::parse(keys, KeyType::PauseKeys)
+					} else {
+						ActionKeys::default(KeyType::PauseKeys)
+					},
+					if let Some(keys) = record.flag.reselect_keys {
+						ActionKeys::parse(keys, KeyType::ReselectKeys)
+					} else {
+						ActionKeys::default(KeyType::ReselectKeys)
+					},
 					record.flag.mouse,
 				)
 				.into_boxed_state(),
@@ -157,6 +194,22 @@ impl<'a> AppSettings<'a> {
 					input_state.cancel_keys.to_string()
 				);
 			}
+			if self.record.flag.pause_keys
+				!= Some(&ActionKeys::default(KeyType::PauseKeys).to_string())
+			{
+				info!(
+					"Using custom pause keys: {}",
+					input_state.pause_keys.to_string()
+				);
+			}
+			if self.record.flag.reselect_keys
+				!= Some(&ActionKeys::default(KeyType::ReselectKeys).to_string())
+			{
+				info!(
+					"Using custom reselect keys: {}",
+					input_state.reselect_keys.to_string()
+				);
+			}
 		}
 		if !self.record.flag.select {
 			self.record.border = None;
@@ -164,6 +217,12 @@ impl<'a> AppSettings<'a> {
 		if self.save.file.format == FileFormat::Ico {
 			self.set_icon_size()
 		}
+		if self.args.is_present("edit") && !self.anim.repeat_override {
+			if let Ok(Some(repeat)) = AnimDecoder::get_repeat(&self.edit.path) {
+				debug!("Using the repeat count of the input: {}", repeat);
+				self.anim.repeat = repeat;
+			}
+		}
 	}
 
 	/* Set the area size to 256x256 for encoding ICO. */
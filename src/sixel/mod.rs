@@ -0,0 +1,140 @@
+use color_quant::NeuQuant;
+use image::error::{EncodingError, ImageFormatHint};
+use image::{ColorType, ImageEncoder, ImageError, ImageResult};
+use std::io::Write;
+
+/* Maximum number of color registers to use */
+const MAX_COLORS: usize = 256;
+
+/* Sixel encoder */
+pub struct SixelEncoder<Output: Write> {
+	output: Output,
+}
+
+impl<Output: Write> SixelEncoder<Output> {
+	/**
+	 * Create a new SixelEncoder object.
+	 *
+	 * @param  output
+	 * @return SixelEncoder
+	 */
+	pub fn new(output: Output) -> Self {
+		Self { output }
+	}
+
+	/**
+	 * Append a run of sixel characters to the data.
+	 *
+	 * @param data
+	 * @param sixel
+	 * @param count
+	 */
+	fn push_run(data: &mut String, sixel: u8, count: usize) {
+		let sixel = char::from(63 + sixel);
+		if count > 3 {
+			data.push_str(&format!("!{count}{sixel}"));
+		} else {
+			(0..count).for_each(|_| data.push(sixel));
+		}
+	}
+
+	/**
+	 * Encode RGBA pixels as sixel data.
+	 *
+	 * @param  rgba
+	 * @param  width
+	 * @param  height
+	 * @return String
+	 */
+	fn get_sixel(rgba: &[u8], width: usize, height: usize) -> String {
+		let quantizer = NeuQuant::new(10, MAX_COLORS, rgba);
+		let palette = quantizer.color_map_rgb();
+		let indices = rgba
+			.chunks_exact(4)
+			.map(|pixel| (pixel[3] >= 128).then(|| quantizer.index_of(pixel)))
+			.collect::<Vec<Option<usize>>>();
+		let mut data = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+		for (i, rgb) in palette.chunks_exact(3).enumerate() {
+			data.push_str(&format!(
+				"#{};2;{};{};{}",
+				i,
+				rgb[0] as u32 * 100 / 255,
+				rgb[1] as u32 * 100 / 255,
+				rgb[2] as u32 * 100 / 255
+			));
+		}
+		for band in (0..height).step_by(6) {
+			let mut sixels = vec![None::<Vec<u8>>; palette.len() / 3];
+			for y in band..(band + 6).min(height) {
+				for x in 0..width {
+					if let Some(index) = indices[y * width + x] {
+						sixels[index].get_or_insert_with(|| vec![0; width])[x] |=
+							1 << (y - band);
+					}
+				}
+			}
+			for (index, row) in sixels.iter().enumerate() {
+				if let Some(row) = row {
+					data.push_str(&format!("#{index}"));
+					let (mut sixel, mut count) = (row[0], 0);
+					for value in row {
+						if *value == sixel {
+							count += 1;
+						} else {
+							Self::push_run(&mut data, sixel, count);
+							(sixel, count) = (*value, 1);
+						}
+					}
+					Self::push_run(&mut data, sixel, count);
+					data.push('$');
+				}
+			}
+			data.push('-');
+		}
+		data.push_str("\x1b\\");
+		data
+	}
+}
+
+/* Implementation for encoding sixel images */
+impl<Output: Write> ImageEncoder for SixelEncoder<Output> {
+	fn write_image(
+		mut self,
+		buf: &[u8],
+		width: u32,
+		height: u32,
+		color_type: ColorType,
+	) -> ImageResult<()> {
+		if color_type != ColorType::Rgba8 {
+			return Err(ImageError::Encoding(EncodingError::new(
+				ImageFormatHint::Name(String::from("Sixel")),
+				format!("Unsupported color type: {color_type:?}"),
+			)));
+		}
+		let data = Self::get_sixel(buf, width as usize, height as usize);
+		self.output.write_all(data.as_bytes())?;
+		self.output.flush()?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_sixel_encoder() {
+		let mut output = Vec::new();
+		SixelEncoder::new(&mut output)
+			.write_image(&[255; 4 * 8], 8, 1, ColorType::Rgba8)
+			.unwrap();
+		let output = String::from_utf8(output).unwrap();
+		assert!(output.starts_with("\x1bP0;1;0q\"1;1;8;1#0;2;"));
+		assert!(output.ends_with("!8@$-\x1b\\"));
+		let mut output = Vec::new();
+		SixelEncoder::new(&mut output)
+			.write_image(&[0; 4 * 2], 1, 2, ColorType::Rgba8)
+			.unwrap();
+		assert_eq!(0, String::from_utf8(output).unwrap().matches('$').count());
+	}
+}
@@ -0,0 +1,96 @@
+use crate::app::AppResult;
+use crate::gif::encoder::CancelHandle;
+use crate::image::Image;
+use image::ExtendedColorType;
+use std::io::{self, Seek, Write};
+use tiff::encoder::{colortype, TiffEncoder};
+
+/* Multi-page TIFF encoder */
+pub struct MultiTiffEncoder<Output: Write + Seek> {
+	encoder: TiffEncoder<Output>,
+}
+
+impl<Output: Write + Seek> MultiTiffEncoder<Output> {
+	/**
+	 * Create a new MultiTiffEncoder object.
+	 *
+	 * @param  output
+	 * @return MultiTiffEncoder (Result)
+	 */
+	pub fn new(output: Output) -> AppResult<Self> {
+		Ok(Self {
+			encoder: TiffEncoder::new(output)?,
+		})
+	}
+
+	/**
+	 * Encode images as pages and write to the TIFF file.
+	 *
+	 * @param  images
+	 * @param  cancel
+	 * @return Result
+	 */
+	pub fn save(
+		mut self,
+		images: Vec<Image>,
+		cancel: CancelHandle,
+	) -> AppResult<()> {
+		for (i, image) in images.iter().enumerate() {
+			let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
+			info!("Saving... ({:.1}%)\r", percentage);
+			debug!(
+				"Encoding... ({:.1}%) [{}/{}]\r",
+				percentage,
+				i + 1,
+				images.len()
+			);
+			io::stdout().flush()?;
+			cancel.check()?;
+			self.encoder.write_image::<colortype::RGBA8>(
+				image.geometry.width,
+				image.geometry.height,
+				&image.get_data(ExtendedColorType::Rgba8)?,
+			)?;
+		}
+		info!("\n");
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image::geometry::Geometry;
+	use image::codecs::tiff::TiffDecoder;
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	use std::io::Cursor;
+	use tiff::decoder::Decoder;
+	#[test]
+	fn test_multi_tiff_encoder() -> AppResult<()> {
+		let geometry = Geometry::new(0, 0, 1, 2);
+		let data = vec![Rgba::from([128, 128, 128, 0]), Rgba::from([16, 16, 16, 0])];
+		let images = vec![
+			Image::new(data.clone(), false, geometry),
+			Image::new(data.into_iter().rev().collect(), false, geometry),
+		];
+		let cancel = CancelHandle::new(None);
+		cancel.cancel();
+		assert!(MultiTiffEncoder::new(Cursor::new(Vec::new()))?
+			.save(images.clone(), cancel)
+			.is_err());
+		let mut output = Cursor::new(Vec::new());
+		MultiTiffEncoder::new(&mut output)?.save(images, CancelHandle::new(None))?;
+		output.set_position(0);
+		TiffDecoder::new(&mut output)?;
+		output.set_position(0);
+		let mut decoder = Decoder::new(output)?;
+		let mut pages = 1;
+		while decoder.more_images() {
+			decoder.next_image()?;
+			pages += 1;
+		}
+		assert_eq!(2, pages);
+		Ok(())
+	}
+}
@@ -4,20 +4,33 @@ use std::str::FromStr;
 
 /* Types of key bindings. */
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum KeyType {
 	ActionKeys,
 	CancelKeys,
+	PauseKeys,
+	ReselectKeys,
 }
 
+/* Prefix of the mouse buttons in key bindings */
+const MOUSE_PREFIX: &str = "Mouse";
+
 /* Operational keys and combinations */
 #[derive(Debug)]
 pub struct ActionKeys {
 	key_groups: Vec<Vec<Keycode>>,
+	button_groups: Vec<Vec<usize>>,
 }
 
 /* Alias for cancel keys */
 pub type CancelKeys = ActionKeys;
 
+/* Alias for pause keys */
+pub type PauseKeys = ActionKeys;
+
+/* Alias for reselect keys */
+pub type ReselectKeys = ActionKeys;
+
 /* Display implementation for user-facing output */
 impl fmt::Display for ActionKeys {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -26,9 +39,15 @@ impl fmt::Display for ActionKeys {
 			"{}",
 			self.key_groups
 				.iter()
-				.map(|keys| keys
+				.zip(&self.button_groups)
+				.map(|(keys, buttons)| keys
 					.iter()
 					.map(|key| format!("{key:?}"))
+					.chain(
+						buttons
+							.iter()
+							.map(|button| format!("{MOUSE_PREFIX}{button}"))
+					)
 					.collect::<Vec<String>>()
 					.join("-"))
 				.collect::<Vec<String>>()
@@ -45,7 +64,10 @@ impl ActionKeys {
 	 * @return ActionKeys
 	 */
 	pub fn new(key_groups: Vec<Vec<Keycode>>) -> Self {
-		Self { key_groups }
+		Self {
+			button_groups: vec![Vec::new(); key_groups.len()],
+			key_groups,
+		}
 	}
 
 	/**
@@ -55,20 +77,17 @@ impl ActionKeys {
 	 * @return ActionKeys
 	 */
 	pub fn default(key_type: KeyType) -> Self {
-		match key_type {
-			KeyType::ActionKeys => Self {
-				key_groups: vec![
-					vec![Keycode::LAlt, Keycode::S],
-					vec![Keycode::LAlt, Keycode::Enter],
-				],
-			},
-			KeyType::CancelKeys => Self {
-				key_groups: vec![
-					vec![Keycode::LControl, Keycode::D],
-					vec![Keycode::Escape],
-				],
-			},
-		}
+		Self::new(match key_type {
+			KeyType::ActionKeys => vec![
+				vec![Keycode::LAlt, Keycode::S],
+				vec![Keycode::LAlt, Keycode::Enter],
+			],
+			KeyType::CancelKeys => {
+				vec![vec![Keycode::LControl, Keycode::D], vec![Keycode::Escape]]
+			}
+			KeyType::PauseKeys => vec![vec![Keycode::LAlt, Keycode::P]],
+			KeyType::ReselectKeys => vec![vec![Keycode::LAlt, Keycode::W]],
+		})
 	}
 
 	/**
@@ -80,7 +99,7 @@ impl ActionKeys {
 	pub fn get_primary(&self) -> Vec<&Keycode> {
 		self.key_groups
 			.iter()
-			.filter_map(|keys| keys.get(0))
+			.filter_map(|keys| keys.first())
 			.collect()
 	}
 
@@ -92,34 +111,78 @@ impl ActionKeys {
 	 * @return ActionKeys
 	 */
 	pub fn parse(keys: &str, key_type: KeyType) -> Self {
-		let keys = keys
-			.split(',')
-			.filter_map(|keys| {
-				let group = keys
-					.split('-')
-					.filter_map(|v| Keycode::from_str(v).ok())
-					.collect::<Vec<Keycode>>();
-				(!group.is_empty()).then_some(group)
-			})
-			.collect::<Vec<Vec<Keycode>>>();
-		if keys.is_empty() {
+		let (mut key_groups, mut button_groups) = (Vec::new(), Vec::new());
+		for keys in keys.split(',') {
+			let (mut group, mut buttons) = (Vec::new(), Vec::new());
+			for key in keys.split('-') {
+				if let Some(button) = Self::parse_button(key) {
+					buttons.push(button);
+				} else if let Ok(key) = Keycode::from_str(key) {
+					group.push(key);
+				} else {
+					warn!("Ignoring invalid key: {:?}", key);
+				}
+			}
+			if !group.is_empty() || !buttons.is_empty() {
+				key_groups.push(group);
+				button_groups.push(buttons);
+			}
+		}
+		if key_groups.is_empty() {
 			Self::default(key_type)
 		} else {
-			Self::new(keys)
+			Self {
+				key_groups,
+				button_groups,
+			}
+		}
+	}
+
+	/**
+	 * Parse the mouse button number from a key (e.g. Mouse1).
+	 *
+	 * @param  key
+	 * @return usize (Option)
+	 */
+	fn parse_button(key: &str) -> Option<usize> {
+		key.strip_prefix(MOUSE_PREFIX)?
+			.parse()
+			.ok()
+			.filter(|button| *button > 0)
+	}
+
+	/**
+	 * Validate the given key bindings.
+	 *
+	 * @param  keys
+	 * @return Result
+	 */
+	pub fn validate(keys: String) -> Result<(), String> {
+		for key in keys.split(',').flat_map(|keys| keys.split('-')) {
+			if Keycode::from_str(key).is_err() && Self::parse_button(key).is_none() {
+				return Err(format!("Invalid key: {key:?}"));
+			}
 		}
+		Ok(())
 	}
 
 	/**
-	 * Check if the given Vector contains action keys.
+	 * Check if the given keys and mouse buttons contain action keys.
 	 *
 	 * @param  keys
+	 * @param  buttons
 	 * @return bool
 	 */
-	pub fn check(&self, keys: Vec<Keycode>) -> bool {
-		for target_keys in &self.key_groups {
+	pub fn check(&self, keys: Vec<Keycode>, buttons: &[bool]) -> bool {
+		for (target_keys, target_buttons) in
+			self.key_groups.iter().zip(&self.button_groups)
+		{
 			if target_keys.len() == keys.len()
 				&& target_keys.len()
 					== target_keys.iter().filter(|k| keys.contains(k)).count()
+				&& target_buttons
+					.iter()
+					.all(|button| buttons.get(*button).copied().unwrap_or(false))
 			{
 				return true;
 			}
@@ -144,25 +207,38 @@ mod tests {
 			],
 			keys.key_groups
 		);
-		assert!(!keys.check(vec![Keycode::RAlt, Keycode::X]));
-		assert!(!keys.check(vec![Keycode::LControl, Keycode::X]));
-		assert!(!keys.check(vec![Keycode::LControl]));
-		assert!(!keys.check(vec![Keycode::W]));
-		assert!(keys.check(vec![Keycode::LControl, Keycode::Q]));
-		assert!(keys.check(vec![Keycode::LControl, Keycode::W]));
+		assert!(!keys.check(vec![Keycode::RAlt, Keycode::X], &[]));
+		assert!(!keys.check(vec![Keycode::LControl, Keycode::X], &[]));
+		assert!(!keys.check(vec![Keycode::LControl], &[]));
+		assert!(!keys.check(vec![Keycode::W], &[]));
+		assert!(keys.check(vec![Keycode::LControl, Keycode::Q], &[]));
+		assert!(keys.check(vec![Keycode::LControl, Keycode::W], &[]));
 		assert!(!ActionKeys::parse("S", KeyType::ActionKeys)
-			.check(vec![Keycode::S, Keycode::Slash]));
-		assert!(
-			ActionKeys::parse("X,Y", KeyType::ActionKeys).check(vec![Keycode::X])
-		);
+			.check(vec![Keycode::S, Keycode::Slash], &[]));
+		assert!(ActionKeys::parse("X,Y", KeyType::ActionKeys)
+			.check(vec![Keycode::X], &[]));
 		assert!(ActionKeys::parse("LControl-J,A,B,C", KeyType::ActionKeys)
-			.check(vec![Keycode::LControl, Keycode::J]));
+			.check(vec![Keycode::LControl, Keycode::J], &[]));
 		assert!(ActionKeys::parse("LControl-A,X,Y", KeyType::ActionKeys)
-			.check(vec![Keycode::Y]));
+			.check(vec![Keycode::Y], &[]));
 		assert_eq!(
 			ActionKeys::default(KeyType::CancelKeys).key_groups,
 			ActionKeys::parse("LCxntrxl-WW", KeyType::CancelKeys).key_groups
 		);
+		assert_eq!(
+			"LAlt-P",
+			ActionKeys::parse("", KeyType::PauseKeys).to_string()
+		);
+		assert_eq!(
+			"LAlt-W",
+			ActionKeys::parse("", KeyType::ReselectKeys).to_string()
+		);
+		assert!(ActionKeys::validate(String::from("LAlt-S,Escape")).is_ok());
+		assert!(ActionKeys::validate(String::from("LAlt-S,")).is_err());
+		assert_eq!(
+			Err(String::from("Invalid key: \"Foo\"")),
+			ActionKeys::validate(String::from("LControl-Foo"))
+		);
 		assert_eq!(
 			vec![vec![Keycode::X]],
 			ActionKeys::parse("test,X,...", KeyType::ActionKeys).key_groups
@@ -175,5 +251,15 @@ mod tests {
 			vec![&Keycode::A, &Keycode::C],
 			ActionKeys::parse("A-B,C-D,...", KeyType::ActionKeys).get_primary()
 		);
+		let keys =
+			ActionKeys::parse("LAlt-S,Mouse3,LControl-Mouse1", KeyType::ActionKeys);
+		assert_eq!("LAlt-S,Mouse3,LControl-Mouse1", keys.to_string());
+		assert_eq!(vec![&Keycode::LAlt, &Keycode::LControl], keys.get_primary());
+		assert!(!keys.check(vec![], &[]));
+		assert!(keys.check(vec![], &[false, false, false, true]));
+		assert!(!keys.check(vec![], &[false, true]));
+		assert!(keys.check(vec![Keycode::LControl], &[false, true]));
+		assert!(ActionKeys::validate(String::from("Mouse2,LAlt-Mouse1")).is_ok());
+		assert!(ActionKeys::validate(String::from("Mouse0")).is_err());
 	}
 }
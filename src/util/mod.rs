@@ -1,4 +1,5 @@
 pub mod command;
 pub mod keys;
 pub mod logger;
+pub mod progress;
 pub mod state;
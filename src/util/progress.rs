@@ -0,0 +1,157 @@
+use log::LevelFilter;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/* Number of characters in the progress bar */
+const BAR_WIDTH: usize = 30;
+
+/* Minimum interval between the updates of the progress bar */
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/* Progress bar that is rendered on stderr */
+#[derive(Debug)]
+pub struct ProgressBar {
+	label: &'static str,
+	total: usize,
+	done: usize,
+	start: Instant,
+	updated: Option<Instant>,
+	enabled: bool,
+}
+
+impl ProgressBar {
+	/**
+	 * Create a new ProgressBar object.
+	 *
+	 * @param  label
+	 * @param  total
+	 * @return ProgressBar
+	 */
+	pub fn new(label: &'static str, total: usize) -> Self {
+		Self {
+			label,
+			total,
+			done: 0,
+			start: Instant::now(),
+			updated: None,
+			enabled: log::max_level() == LevelFilter::Info
+				&& io::stderr().is_terminal(),
+		}
+	}
+
+	/* Increase the number of done items by one. */
+	pub fn inc(&mut self) {
+		self.set(self.done + 1);
+	}
+
+	/**
+	 * Set the number of done items and update the bar.
+	 *
+	 * @param  done
+	 */
+	pub fn set(&mut self, done: usize) {
+		self.done = done.min(self.total);
+		let now = Instant::now();
+		if self.enabled
+			&& (self.done == self.total
+				|| self.updated.is_none_or(|updated| {
+					now.duration_since(updated) >= UPDATE_INTERVAL
+				})) {
+			self.updated = Some(now);
+			eprint!("\r{}", self.format(now.duration_since(self.start)));
+			io::stderr().flush().unwrap_or_default();
+		}
+	}
+
+	/* Finish the line of the progress bar. */
+	pub fn finish(&mut self) {
+		if self.enabled && self.updated.take().is_some() {
+			eprintln!();
+		}
+	}
+
+	/**
+	 * Get the estimated remaining time from the elapsed time.
+	 *
+	 * @param  elapsed
+	 * @return Duration (Option)
+	 */
+	fn get_eta(&self, elapsed: Duration) -> Option<Duration> {
+		(self.done > 0).then(|| {
+			elapsed.mul_f64((self.total - self.done) as f64 / self.done as f64)
+		})
+	}
+
+	/**
+	 * Format the progress bar with the elapsed time.
+	 *
+	 * @param  elapsed
+	 * @return String
+	 */
+	fn format(&self, elapsed: Duration) -> String {
+		let ratio = if self.total == 0 {
+			1.
+		} else {
+			self.done as f64 / self.total as f64
+		};
+		let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+		let eta = self.get_eta(elapsed).map_or(String::from("--:--"), |eta| {
+			let seconds = eta.as_secs();
+			format!("{:02}:{:02}", seconds / 60, seconds % 60)
+		});
+		format!(
+			"{} [{}{}] {}/{} ({:.1}%) ETA {}",
+			self.label,
+			"#".repeat(filled),
+			"-".repeat(BAR_WIDTH - filled),
+			self.done,
+			self.total,
+			ratio * 100.,
+			eta
+		)
+	}
+}
+
+/* Progress reporter implementation for the gifski writer */
+#[cfg(feature = "ski")]
+impl gifski::progress::ProgressReporter for ProgressBar {
+	fn increase(&mut self) -> bool {
+		self.inc();
+		true
+	}
+
+	fn done(&mut self, _msg: &str) {
+		self.finish();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_progress_bar() {
+		let mut progress = ProgressBar::new("Saving", 4);
+		assert_eq!(None, progress.get_eta(Duration::from_secs(1)));
+		assert_eq!(
+			format!("Saving [{}] 0/4 (0.0%) ETA --:--", "-".repeat(BAR_WIDTH)),
+			progress.format(Duration::ZERO)
+		);
+		progress.inc();
+		assert_eq!(
+			Some(Duration::from_secs(6)),
+			progress.get_eta(Duration::from_secs(2))
+		);
+		assert_eq!(
+			format!(
+				"Saving [{}{}] 1/4 (25.0%) ETA 01:21",
+				"#".repeat(8),
+				"-".repeat(BAR_WIDTH - 8)
+			),
+			progress.format(Duration::from_secs(27))
+		);
+		(0..5).for_each(|_| progress.inc());
+		assert_eq!(4, progress.done);
+		progress.finish();
+	}
+}
@@ -1,13 +1,19 @@
-use crate::util::keys::{ActionKeys, CancelKeys, KeyType};
+use crate::util::keys::{ActionKeys, CancelKeys, KeyType, PauseKeys, ReselectKeys};
 use device_query::{DeviceQuery, DeviceState};
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /* State of the mouse and keyboard inputs */
 pub struct InputState {
 	pub state: DeviceState,
 	pub action_keys: ActionKeys,
 	pub cancel_keys: CancelKeys,
+	pub pause_keys: PauseKeys,
+	pub reselect_keys: ReselectKeys,
 	check_mouse: bool,
+	paused: AtomicBool,
+	pause_pressed: AtomicBool,
+	reselect_pressed: AtomicBool,
 }
 
 /* Default initialization values for InputState */
@@ -16,6 +22,8 @@ impl Default for InputState {
 		Self::new(
 			ActionKeys::default(KeyType::ActionKeys),
 			CancelKeys::default(KeyType::CancelKeys),
+			PauseKeys::default(KeyType::PauseKeys),
+			ReselectKeys::default(KeyType::ReselectKeys),
 			false,
 		)
 	}
@@ -40,19 +48,28 @@ impl InputState {
 	 *
 	 * @param  action_keys
 	 * @param  cancel_keys
+	 * @param  pause_keys
+	 * @param  reselect_keys
 	 * @param  check_mouse
 	 * @return InputState
 	 */
 	pub fn new(
 		action_keys: ActionKeys,
 		cancel_keys: CancelKeys,
+		pause_keys: PauseKeys,
+		reselect_keys: ReselectKeys,
 		check_mouse: bool,
 	) -> Self {
 		Self {
 			state: DeviceState::new(),
 			action_keys,
 			cancel_keys,
+			pause_keys,
+			reselect_keys,
 			check_mouse,
+			paused: AtomicBool::new(false),
+			pause_pressed: AtomicBool::new(false),
+			reselect_pressed: AtomicBool::new(false),
 		}
 	}
 
@@ -65,13 +82,26 @@ impl InputState {
 		Box::new(self)
 	}
 
+	/**
+	 * Check if the given keys are pressed along with the mouse buttons.
+	 *
+	 * @param  keys
+	 * @return bool
+	 */
+	fn check_keys(&self, keys: &ActionKeys) -> bool {
+		keys.check(
+			self.state.get_keys(),
+			&self.state.get_mouse().button_pressed,
+		)
+	}
+
 	/**
 	 * Check for action keys and mouse state to see if there is any action.
 	 *
 	 * @return bool
 	 */
 	pub fn check_action(&self) -> bool {
-		let keys_pressed = self.action_keys.check(self.state.get_keys());
+		let keys_pressed = self.check_keys(&self.action_keys);
 		if self.check_mouse {
 			keys_pressed || self.state.get_mouse().button_pressed[1]
 		} else {
@@ -85,19 +115,97 @@ impl InputState {
 	 * @return bool
 	 */
 	pub fn check_cancel_keys(&self) -> bool {
-		self.cancel_keys.check(self.state.get_keys())
+		self.check_keys(&self.cancel_keys)
+	}
+
+	/**
+	 * Toggle the pause state when the pause keys are pressed.
+	 *
+	 * @return bool
+	 */
+	pub fn check_pause(&self) -> bool {
+		self.update_pause(self.check_keys(&self.pause_keys))
+	}
+
+	/**
+	 * Check if the reselect keys are just pressed.
+	 *
+	 * @return bool
+	 */
+	pub fn check_reselect(&self) -> bool {
+		self.update_reselect(self.check_keys(&self.reselect_keys))
+	}
+
+	/**
+	 * Update the reselect state with the current state of reselect keys.
+	 *
+	 * @param  pressed
+	 * @return bool
+	 */
+	fn update_reselect(&self, pressed: bool) -> bool {
+		is_just_pressed(&self.reselect_pressed, pressed)
+	}
+
+	/**
+	 * Update the pause state with the current state of pause keys.
+	 *
+	 * @param  pressed
+	 * @return bool
+	 */
+	fn update_pause(&self, pressed: bool) -> bool {
+		if is_just_pressed(&self.pause_pressed, pressed) {
+			self.paused.fetch_xor(true, Ordering::SeqCst);
+		}
+		self.paused.load(Ordering::SeqCst)
+	}
+}
+
+/**
+ * Latch the pressed state of keys and check if they are just pressed.
+ *
+ * @param  latch
+ * @param  pressed
+ * @return bool
+ */
+fn is_just_pressed(latch: &AtomicBool, pressed: bool) -> bool {
+	if pressed {
+		!latch.swap(true, Ordering::SeqCst)
+	} else {
+		latch.store(false, Ordering::SeqCst);
+		false
 	}
 }
 
 #[cfg(test)]
-#[cfg(feature = "test-ws")]
 mod tests {
 	use super::*;
 	#[test]
+	fn test_key_latch() {
+		let latch = AtomicBool::new(false);
+		assert!(is_just_pressed(&latch, true));
+		assert!(!is_just_pressed(&latch, true));
+		assert!(!is_just_pressed(&latch, false));
+		assert!(is_just_pressed(&latch, true));
+		assert!(!is_just_pressed(&latch, true));
+		assert!(!is_just_pressed(&latch, false));
+		assert!(!is_just_pressed(&latch, false));
+		assert!(is_just_pressed(&latch, true));
+	}
+	#[test]
+	#[cfg(feature = "test-ws")]
 	fn test_input_state() {
 		let input_state = InputState::default().into_boxed_state();
 		assert!(!input_state.check_action());
 		assert!(!input_state.check_cancel_keys());
+		assert!(!input_state.check_pause());
+		assert!(input_state.update_pause(true));
+		assert!(input_state.update_pause(true));
+		assert!(input_state.update_pause(false));
+		assert!(!input_state.update_pause(true));
+		assert!(!input_state.check_reselect());
+		assert!(input_state.update_reselect(true));
+		assert!(!input_state.update_reselect(true));
+		assert!(!input_state.update_reselect(false));
 		assert!(format!("{:?}", input_state).len() > 0);
 	}
 }
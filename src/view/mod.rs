@@ -1,14 +1,17 @@
 pub mod settings;
 
 use crate::app::AppResult;
+use crate::sixel::SixelEncoder;
 use crate::view::settings::ViewSettings;
-use image::DynamicImage;
+use image::{ColorType, DynamicImage, GenericImageView, ImageEncoder};
+use std::io;
 use viuer::Config;
 
 /* Viewer for image files */
 pub struct ImageViewer {
 	image: DynamicImage,
 	config: Config,
+	sixel: bool,
 }
 
 impl ImageViewer {
@@ -28,6 +31,7 @@ impl ImageViewer {
 				absolute_offset: false,
 				..Config::default()
 			},
+			sixel: settings.sixel,
 		}
 	}
 
@@ -37,7 +41,28 @@ impl ImageViewer {
 	 * @return Result
 	 */
 	pub fn view(&self) -> AppResult<(u32, u32)> {
-		Ok(viuer::print(&self.image, &self.config)?)
+		if self.sixel {
+			self.view_sixel()
+		} else {
+			Ok(viuer::print(&self.image, &self.config)?)
+		}
+	}
+
+	/**
+	 * View the image by printing sixel data to the terminal.
+	 *
+	 * @return Result
+	 */
+	fn view_sixel(&self) -> AppResult<(u32, u32)> {
+		let (width, height) = self.image.dimensions();
+		SixelEncoder::new(io::stdout().lock()).write_image(
+			self.image.to_rgba8().as_raw(),
+			width,
+			height,
+			ColorType::Rgba8,
+		)?;
+		println!();
+		Ok((width, height))
 	}
 }
 
@@ -62,5 +87,7 @@ mod tests {
 		} else {
 			viewer.view().unwrap();
 		}
+		viewer.sixel = true;
+		assert_eq!((1, 2), viewer.view().unwrap());
 	}
 }
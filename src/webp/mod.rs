@@ -0,0 +1,138 @@
+/* Flags of the extended WebP header */
+pub const ICC_FLAG: u8 = 0x20;
+pub const ALPHA_FLAG: u8 = 0x10;
+pub const EXIF_FLAG: u8 = 0x08;
+pub const XMP_FLAG: u8 = 0x04;
+
+/* Chunks of a WebP file in the extended format */
+#[derive(Debug)]
+pub struct WebPChunks<'a> {
+	chunks: Vec<(&'a [u8], Vec<u8>)>,
+}
+
+impl<'a> WebPChunks<'a> {
+	/**
+	 * Parse the chunks of the WebP data and add the extended header if needed.
+	 *
+	 * @param  data
+	 * @return WebPChunks (Result)
+	 */
+	pub fn parse(data: &'a [u8]) -> Result<Self, String> {
+		if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+			return Err(String::from("Invalid WebP header"));
+		}
+		let mut chunks = Vec::<(&[u8], Vec<u8>)>::new();
+		let mut rest = &data[12..];
+		while rest.len() >= 8 {
+			let size =
+				u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+			let chunk = rest
+				.get(8..8 + size)
+				.ok_or_else(|| String::from("Invalid WebP chunk"))?;
+			chunks.push((&rest[..4], chunk.to_vec()));
+			rest = rest.get(8 + size + size % 2..).unwrap_or_default();
+		}
+		match chunks.first() {
+			Some((chunk_type, _)) if *chunk_type == b"VP8X" => {}
+			Some((chunk_type, chunk)) => {
+				let (width, height, alpha) = Self::get_info(chunk_type, chunk)
+					.ok_or_else(|| String::from("Invalid WebP bitstream"))?;
+				let mut header = vec![if alpha { ALPHA_FLAG } else { 0 }, 0, 0, 0];
+				header.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+				header.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+				chunks.insert(0, (b"VP8X", header));
+			}
+			None => return Err(String::from("Empty WebP file")),
+		}
+		Ok(Self { chunks })
+	}
+
+	/**
+	 * Set the chunk at its place in the file and the header flag for it.
+	 *
+	 * @param  chunk_type
+	 * @param  data
+	 * @param  flag
+	 */
+	pub fn set_chunk(&mut self, chunk_type: &'a [u8], data: Vec<u8>, flag: u8) {
+		self.chunks.retain(|(t, _)| *t != chunk_type);
+		if let Some((_, header)) = self.chunks.first_mut() {
+			header[0] |= flag;
+		}
+		let order = Self::get_order(chunk_type);
+		let index = self
+			.chunks
+			.iter()
+			.position(|(t, _)| Self::get_order(t) > order)
+			.unwrap_or(self.chunks.len());
+		self.chunks.insert(index, (chunk_type, data));
+	}
+
+	/**
+	 * Encode the chunks as a WebP file.
+	 *
+	 * @return Vector of u8
+	 */
+	pub fn encode(&self) -> Vec<u8> {
+		let mut output = b"RIFF\0\0\0\0WEBP".to_vec();
+		for (chunk_type, chunk) in &self.chunks {
+			output.extend_from_slice(chunk_type);
+			output.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+			output.extend_from_slice(chunk);
+			if chunk.len() % 2 == 1 {
+				output.push(0);
+			}
+		}
+		let size = (output.len() - 8) as u32;
+		output[4..8].copy_from_slice(&size.to_le_bytes());
+		output
+	}
+
+	/**
+	 * Get the order of the chunk type in the extended format.
+	 *
+	 * @param  chunk_type
+	 * @return u8
+	 */
+	fn get_order(chunk_type: &[u8]) -> u8 {
+		match chunk_type {
+			b"VP8X" => 0,
+			b"ICCP" => 1,
+			b"ANIM" => 2,
+			b"EXIF" => 4,
+			b"XMP " => 5,
+			_ => 3,
+		}
+	}
+
+	/**
+	 * Get the size and alpha usage from the simple WebP bitstream.
+	 *
+	 * @param  chunk_type
+	 * @param  chunk
+	 * @return Tuple of width, height and alpha (Option)
+	 */
+	fn get_info(chunk_type: &[u8], chunk: &[u8]) -> Option<(u32, u32, bool)> {
+		match chunk_type {
+			b"VP8L" if chunk.first() == Some(&0x2F) => {
+				let bits = u32::from_le_bytes(chunk.get(1..5)?.try_into().ok()?);
+				Some((
+					(bits & 0x3FFF) + 1,
+					((bits >> 14) & 0x3FFF) + 1,
+					(bits >> 28) & 1 == 1,
+				))
+			}
+			b"VP8 " if chunk.get(3..6) == Some(&[0x9D, 0x01, 0x2A]) => {
+				let size = |i: usize| {
+					Some(
+						u32::from(u16::from_le_bytes(
+							chunk.get(i..i + 2)?.try_into().ok()?,
+						)) & 0x3FFF,
+					)
+				};
+				Some((size(6)?, size(8)?, false))
+			}
+			_ => None,
+		}
+	}
+}
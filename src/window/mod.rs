@@ -3,6 +3,7 @@ pub mod test;
 
 use crate::image::Image;
 use crate::settings::AppSettings;
+use crate::util::state::InputState;
 use std::fmt::Debug;
 
 /* Window system functions for accessing a window */
@@ -16,6 +17,12 @@ pub trait Access<'a, Window: Capture + Send + Sync + Copy + Debug + 'static> {
 /* Window methods for capturing an image */
 pub trait Capture {
 	fn get_image(&self) -> Option<Image>;
+	fn get_pointer(&self) -> Option<((i32, i32), bool)>;
+	fn get_icc_profile(&self) -> Option<Vec<u8>>;
+	fn get_title(&self) -> Option<String>;
+	fn reselect(&self, input_state: &InputState) -> Option<Self>
+	where
+		Self: Sized;
 	fn show_countdown(&self);
 	fn release(&self);
 }
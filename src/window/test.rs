@@ -1,5 +1,6 @@
 use crate::image::geometry::Geometry;
 use crate::image::Image;
+use crate::util::state::InputState;
 use crate::window::Capture;
 use image::Rgba;
 
@@ -43,6 +44,43 @@ impl Capture for TestWindow {
 		))
 	}
 
+	/**
+	 * Get the pointer state of the test window.
+	 *
+	 * @return Tuple (Option)
+	 */
+	fn get_pointer(&self) -> Option<((i32, i32), bool)> {
+		None
+	}
+
+	/**
+	 * Get the ICC profile of the test window.
+	 *
+	 * @return Vector of u8 (Option)
+	 */
+	fn get_icc_profile(&self) -> Option<Vec<u8>> {
+		None
+	}
+
+	/**
+	 * Get the title of the test window.
+	 *
+	 * @return String (Option)
+	 */
+	fn get_title(&self) -> Option<String> {
+		None
+	}
+
+	/**
+	 * Select the same test window again.
+	 *
+	 * @param  input_state
+	 * @return TestWindow (Option)
+	 */
+	fn reselect(&self, _input_state: &InputState) -> Option<Self> {
+		Some(*self)
+	}
+
 	/* Do not show countdown for testing window. */
 	fn show_countdown(&self) {}
 
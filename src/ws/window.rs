@@ -1,4 +1,5 @@
 use crate::image::Image;
+use crate::util::state::InputState;
 use crate::window::Capture;
 
 /* Window implementation */
@@ -28,6 +29,43 @@ impl Capture for Window {
 		unimplemented!()
 	}
 
+	/**
+	 * Get the pointer position and button state.
+	 *
+	 * @return Tuple (Option)
+	 */
+	fn get_pointer(&self) -> Option<((i32, i32), bool)> {
+		unimplemented!()
+	}
+
+	/**
+	 * Get the ICC profile of the display.
+	 *
+	 * @return Vector of u8 (Option)
+	 */
+	fn get_icc_profile(&self) -> Option<Vec<u8>> {
+		None
+	}
+
+	/**
+	 * Get the title of the window.
+	 *
+	 * @return String (Option)
+	 */
+	fn get_title(&self) -> Option<String> {
+		None
+	}
+
+	/**
+	 * Select another window to record.
+	 *
+	 * @param  input_state
+	 * @return Window (Option)
+	 */
+	fn reselect(&self, _input_state: &InputState) -> Option<Self> {
+		unimplemented!()
+	}
+
 	/* Show countdown on the window. */
 	fn show_countdown(&self) {
 		unimplemented!()
@@ -1,14 +1,16 @@
 use crate::image::geometry::Geometry;
+use crate::image::padding::Padding;
 use crate::record::fps::FpsClock;
-use crate::record::settings::{RecordSettings, RecordWindow};
+use crate::record::settings::{RecordMonitor, RecordSettings, RecordWindow};
 use crate::util::state::InputState;
 use crate::x11::window::Window;
 use device_query::{DeviceQuery, Keycode};
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::io::{self, Write};
 use std::mem::MaybeUninit;
-use std::os::raw::{c_int, c_ulong};
+use std::os::raw::{c_int, c_long, c_uchar, c_uint, c_ulong};
 use std::ptr;
+use std::slice;
 use std::thread;
 use std::time::{Duration, Instant};
 use x11::xlib;
@@ -88,6 +90,146 @@ impl Display {
 		}
 	}
 
+	/**
+	 * Get the window for compositing the given windows.
+	 *
+	 * @return Window (Option)
+	 */
+	pub fn get_composite_window(&self) -> Option<Window> {
+		let mut window = Window::new(*self.settings.flag.windows?.first()?, *self);
+		let (_, canvas) = window.get_composite_layout()?;
+		window.geometry = Geometry::new(0, 0, canvas.width, canvas.height);
+		window.area = window.geometry;
+		debug!("Composite area: {:?}", window.area);
+		if window.area.width == 0 || window.area.height == 0 {
+			warn!("The composite area is empty.");
+			None
+		} else {
+			Some(window)
+		}
+	}
+
+	/**
+	 * Find the window that matches the filter in the client list.
+	 *
+	 * @return Window (Option)
+	 */
+	pub fn find_window(&self) -> Option<Window> {
+		let filter = self.settings.flag.window_filter?;
+		let root = self.get_root_window();
+		let xid = unsafe {
+			self.get_property::<c_ulong>(root.xid, "_NET_CLIENT_LIST")
+				.into_iter()
+				.find(|xid| {
+					let mut name =
+						self.get_property::<c_uchar>(*xid, "_NET_WM_NAME");
+					if name.is_empty() {
+						name = self.get_property::<c_uchar>(*xid, "WM_NAME");
+					}
+					filter.matches(
+						&String::from_utf8_lossy(&name),
+						&self.get_class(*xid),
+						self.get_property::<c_ulong>(*xid, "_NET_WM_PID")
+							.first()
+							.and_then(|pid| u32::try_from(*pid).ok()),
+					)
+				})
+		};
+		match xid {
+			Some(xid) => {
+				debug!("Found window: {}", xid);
+				Some(Window::new(xid, *self))
+			}
+			None => {
+				warn!("No window found for {:?}", filter);
+				None
+			}
+		}
+	}
+
+	/**
+	 * Get the items of a window property.
+	 *
+	 * @param  xid
+	 * @param  property
+	 * @return Vector of T
+	 */
+	unsafe fn get_property<T: Copy>(&self, xid: c_ulong, property: &str) -> Vec<T> {
+		let property = CString::new(property).expect("Failed to create CString");
+		let atom = xlib::XInternAtom(self.inner, property.as_ptr(), xlib::True);
+		let (mut actual_type, mut format, mut items, mut bytes_after) = (0, 0, 0, 0);
+		let mut data = ptr::null_mut();
+		if atom == 0
+			|| xlib::XGetWindowProperty(
+				self.inner,
+				xid,
+				atom,
+				0,
+				c_long::MAX / 4,
+				xlib::False,
+				xlib::AnyPropertyType as c_ulong,
+				&mut actual_type,
+				&mut format,
+				&mut items,
+				&mut bytes_after,
+				&mut data,
+			) != xlib::Success as c_int
+			|| data.is_null()
+		{
+			return Vec::new();
+		}
+		let values = slice::from_raw_parts(
+			data as *const T,
+			items.try_into().unwrap_or_default(),
+		)
+		.to_vec();
+		xlib::XFree(data as *mut _);
+		values
+	}
+
+	/**
+	 * Get the ICC profile of the default screen via a new connection
+	 * since the display of the window might be already closed.
+	 *
+	 * @return Vector of u8 (Option)
+	 */
+	pub fn get_icc_profile() -> Option<Vec<u8>> {
+		let display = Self::open(None)?;
+		unsafe {
+			let profile = display.get_property::<c_uchar>(
+				xlib::XDefaultRootWindow(display.inner),
+				"_ICC_PROFILE",
+			);
+			xlib::XCloseDisplay(display.inner);
+			Some(profile).filter(|profile| !profile.is_empty())
+		}
+	}
+
+	/**
+	 * Get the instance and class names of a window.
+	 *
+	 * @param  xid
+	 * @return Vector of String
+	 */
+	unsafe fn get_class(&self, xid: c_ulong) -> Vec<String> {
+		let mut hint = xlib::XClassHint {
+			res_name: ptr::null_mut(),
+			res_class: ptr::null_mut(),
+		};
+		if xlib::XGetClassHint(self.inner, xid, &mut hint) == 0 {
+			return Vec::new();
+		}
+		[hint.res_name, hint.res_class]
+			.into_iter()
+			.filter(|value| !value.is_null())
+			.map(|value| {
+				let class = CStr::from_ptr(value).to_string_lossy().into_owned();
+				xlib::XFree(value as *mut _);
+				class
+			})
+			.collect()
+	}
+
 	/**
 	 * Get the focused window.
 	 *
@@ -137,17 +279,92 @@ impl Display {
 	 */
 	fn get_window(&self) -> (Window, Geometry) {
 		match self.settings.window {
-			RecordWindow::Focus(geometry, parent) => (
-				self.get_focused_window(parent)
-					.expect("Failed to get the window"),
-				geometry.unwrap_or_default(),
-			),
+			RecordWindow::Focus(geometry, parent) => {
+				let window = self
+					.get_focused_window(parent)
+					.expect("Failed to get the window");
+				(
+					if self.settings.flag.decorations.is_some() {
+						self.get_toplevel_window(window)
+					} else {
+						window
+					},
+					geometry.unwrap_or_default(),
+				)
+			}
 			RecordWindow::Root(geometry) => {
 				(self.get_root_window(), geometry.unwrap_or_default())
 			}
 		}
 	}
 
+	/**
+	 * Get the top-level window that contains the decorations.
+	 *
+	 * @param  window
+	 * @return Window
+	 */
+	fn get_toplevel_window(&self, mut window: Window) -> Window {
+		let root = self.get_root_window();
+		while let Some(parent) = unsafe { window.get_parent() } {
+			if parent.xid == root.xid || parent.xid == 0 {
+				break;
+			}
+			window = parent;
+		}
+		window
+	}
+
+	/**
+	 * Get the child windows of a window.
+	 *
+	 * @param  xid
+	 * @return Vector of c_ulong
+	 */
+	unsafe fn get_children(&self, xid: c_ulong) -> Vec<c_ulong> {
+		let (mut root, mut parent) = (0, 0);
+		let (mut children, mut nchildren) = (ptr::null_mut(), 0);
+		if xlib::XQueryTree(
+			self.inner,
+			xid,
+			&mut root,
+			&mut parent,
+			&mut children,
+			&mut nchildren,
+		) == 0 || children.is_null()
+		{
+			return Vec::new();
+		}
+		let values = slice::from_raw_parts(children, nchildren as usize).to_vec();
+		xlib::XFree(children as *mut _);
+		values
+	}
+
+	/**
+	 * Get the frame extents (_NET_FRAME_EXTENTS) of the client inside a frame.
+	 *
+	 * @param  xid
+	 * @return Padding (Option)
+	 */
+	fn get_frame_extents(&self, xid: c_ulong) -> Option<Padding> {
+		unsafe { self.get_children(xid) }
+			.into_iter()
+			.find_map(|child| {
+				match unsafe {
+					self.get_property::<c_ulong>(child, "_NET_FRAME_EXTENTS")
+				}[..]
+				{
+					[left, right, top, bottom] => Some(
+						[top, right, bottom, left]
+							.into_iter()
+							.map(|v| u32::try_from(v).unwrap_or_default())
+							.collect(),
+					),
+					_ => self.get_frame_extents(child),
+				}
+			})
+	}
+
 	/**
 	 * Get a window from monitor specified via settings.
 	 *
@@ -156,12 +373,8 @@ impl Display {
 	fn get_window_from_monitor(&mut self) -> (Window, Geometry) {
 		let (window, mut size) = self.get_window();
 		if let RecordWindow::Root(_) = self.settings.window {
-			if let Some(monitor) = self.settings.flag.monitor {
-				let crtc = window.get_crtc_info();
-				let geometry = crtc
-					.get(monitor.checked_sub(1).unwrap_or_default())
-					.expect("Invalid monitor number");
-				size = *geometry;
+			if let Some(geometry) = self.get_monitor_geometry(&window) {
+				size = geometry;
 				self.settings.padding.left =
 					geometry.x.try_into().unwrap_or_default();
 				self.settings.padding.top =
@@ -171,6 +384,50 @@ impl Display {
 		(window, size)
 	}
 
+	/**
+	 * Get the geometry of the monitor specified via settings.
+	 *
+	 * @param  window
+	 * @return Geometry (Option)
+	 */
+	fn get_monitor_geometry(&self, window: &Window) -> Option<Geometry> {
+		let monitor = self.settings.flag.monitor?;
+		let crtc = window.get_crtc_info();
+		debug!("Monitors: {:?}", crtc);
+		let geometry = match monitor {
+			RecordMonitor::Index(index) => crtc.get(index.saturating_sub(1)),
+			RecordMonitor::Name(name) => crtc
+				.iter()
+				.find(|(output, _)| output.eq_ignore_ascii_case(name)),
+		}
+		.map(|(_, geometry)| *geometry);
+		if geometry.is_none() {
+			warn!("Monitor not found: {:?}", monitor);
+		}
+		geometry
+	}
+
+	/**
+	 * Get the root window with the area of the monitor.
+	 *
+	 * @return Window (Option)
+	 */
+	pub fn get_monitor_window(&mut self) -> Option<Window> {
+		let root = self.get_root_window();
+		let monitor = self.get_monitor_geometry(&root)?;
+		let (x, y) = (
+			u32::try_from(monitor.x).unwrap_or_default(),
+			u32::try_from(monitor.y).unwrap_or_default(),
+		);
+		self.settings.padding = Padding::new(
+			y,
+			root.geometry.width.saturating_sub(x + monitor.width),
+			root.geometry.height.saturating_sub(y + monitor.height),
+			x,
+		);
+		Some(Window::new(root.xid, *self))
+	}
+
 	/**
 	 * Get the corresponding key symbol from keycode.
 	 *
@@ -204,12 +461,7 @@ impl Display {
 		let mut xid = None;
 		let window_padding = self.settings.padding;
 		let mut change_factor = AREA_CHANGE_FACTOR;
-		let font = self.font.unwrap_or_else(|| unsafe {
-			xlib::XLoadQueryFont(
-				self.inner,
-				CString::new(DEFAULT_FONT).unwrap_or_default().as_ptr(),
-			)
-		});
+		let font = self.get_font();
 		let start_time = Instant::now();
 		while !input_state.check_action() {
 			thread::sleep(Duration::from_millis(self.settings.time.interval));
@@ -240,6 +492,12 @@ impl Display {
 				}
 				self.settings.padding = window_padding;
 				self.update_padding(size, window.geometry);
+				if self.settings.flag.decorations == Some(false) {
+					if let Some(extents) = self.get_frame_extents(window.xid) {
+						debug!("Frame extents: {}", extents);
+						self.settings.padding += extents;
+					}
+				}
 				window.clear_area();
 				input_state
 					.action_keys
@@ -270,6 +528,200 @@ impl Display {
 		}
 	}
 
+	/**
+	 * Select a region of the root window, freezing the screen if enabled.
+	 *
+	 * @param  input_state
+	 * @return Window (Option)
+	 */
+	pub fn select_region(&mut self, input_state: &InputState) -> Option<Window> {
+		let frozen = if self.settings.flag.freeze {
+			self.freeze_screen()
+		} else {
+			None
+		};
+		let window = self.drag_region(input_state);
+		if let Some(frozen) = frozen {
+			self.unfreeze_screen(frozen);
+		}
+		window
+	}
+
+	/**
+	 * Cover the screen with a frozen copy of itself.
+	 *
+	 * @return Tuple (Option)
+	 */
+	fn freeze_screen(&self) -> Option<(c_ulong, xlib::Pixmap)> {
+		let root = self.get_root_window();
+		unsafe {
+			let screen = xlib::XDefaultScreen(self.inner);
+			let pixmap = xlib::XCreatePixmap(
+				self.inner,
+				root.xid,
+				root.geometry.width,
+				root.geometry.height,
+				xlib::XDefaultDepth(self.inner, screen)
+					.try_into()
+					.unwrap_or_default(),
+			);
+			if pixmap == 0 {
+				warn!("Failed to freeze the screen.");
+				return None;
+			}
+			let gc = xlib::XCreateGC(self.inner, root.xid, 0, ptr::null_mut());
+			xlib::XSetSubwindowMode(self.inner, gc, xlib::IncludeInferiors);
+			xlib::XCopyArea(
+				self.inner,
+				root.xid,
+				pixmap,
+				gc,
+				0,
+				0,
+				root.geometry.width,
+				root.geometry.height,
+				0,
+				0,
+			);
+			xlib::XFreeGC(self.inner, gc);
+			let mut attributes =
+				MaybeUninit::<xlib::XSetWindowAttributes>::zeroed().assume_init();
+			attributes.override_redirect = xlib::True;
+			attributes.background_pixmap = pixmap;
+			let xid = xlib::XCreateWindow(
+				self.inner,
+				root.xid,
+				0,
+				0,
+				root.geometry.width,
+				root.geometry.height,
+				0,
+				xlib::CopyFromParent,
+				xlib::InputOutput as c_uint,
+				ptr::null_mut(),
+				xlib::CWOverrideRedirect | xlib::CWBackPixmap,
+				&mut attributes,
+			);
+			xlib::XMapRaised(self.inner, xid);
+			xlib::XSync(self.inner, xlib::False);
+			debug!("Froze the screen: {:?}", xid);
+			Some((xid, pixmap))
+		}
+	}
+
+	/**
+	 * Remove the frozen copy of the screen.
+	 *
+	 * @param frozen
+	 */
+	fn unfreeze_screen(&self, (xid, pixmap): (c_ulong, xlib::Pixmap)) {
+		unsafe {
+			xlib::XDestroyWindow(self.inner, xid);
+			xlib::XFreePixmap(self.inner, pixmap);
+			xlib::XSync(self.inner, xlib::False);
+		}
+		debug!("Unfroze the screen.");
+	}
+
+	/**
+	 * Select a region of the root window by dragging the mouse.
+	 *
+	 * @param  input_state
+	 * @return Window (Option)
+	 */
+	fn drag_region(&mut self, input_state: &InputState) -> Option<Window> {
+		let root = self.get_root_window();
+		let mut window = root;
+		let mut start = None;
+		let font = self.get_font();
+		let start_time = Instant::now();
+		info!("Drag the mouse to select a region.");
+		loop {
+			thread::sleep(Duration::from_millis(self.settings.time.interval));
+			let mouse = input_state.state.get_mouse();
+			let pressed = mouse.button_pressed.get(1).copied().unwrap_or(false);
+			if input_state.check_cancel_keys() {
+				warn!("User interrupt detected.");
+				window.clear_area();
+				return None;
+			} else if start.is_none()
+				&& start_time.elapsed().as_secs() > self.settings.time.timeout
+			{
+				warn!("The operation timed out.");
+				return None;
+			}
+			match start {
+				None if pressed => start = Some(mouse.coords),
+				Some((x, y)) => {
+					self.settings.padding = Self::get_region_padding(
+						root.geometry,
+						(x, y),
+						mouse.coords,
+					);
+					window.clear_area();
+					window = Window::new(root.xid, *self);
+					if !pressed {
+						break;
+					}
+					window.include_inferiors();
+					window.draw_borders();
+					window.show_text_centered(Some(window.area.to_string()), font);
+					info!(" Selected area -> [{}]{:<10}\r#", window.area, " ");
+					io::stdout().flush().expect("Failed to flush stdout");
+				}
+				_ => {}
+			}
+		}
+		info!("\n");
+		debug!("Selected region: {:?}", window.area);
+		if window.area.width == 0 || window.area.height == 0 {
+			warn!("The selected region is empty.");
+			None
+		} else {
+			Some(window)
+		}
+	}
+
+	/**
+	 * Get the padding of a region between two points.
+	 *
+	 * @param  geometry
+	 * @param  start
+	 * @param  end
+	 * @return Padding
+	 */
+	fn get_region_padding(
+		geometry: Geometry,
+		start: (i32, i32),
+		end: (i32, i32),
+	) -> Padding {
+		let clamp =
+			|value: i32, max: u32| u32::try_from(value).unwrap_or_default().min(max);
+		let (x1, x2) = (
+			clamp(start.0.min(end.0), geometry.width),
+			clamp(start.0.max(end.0), geometry.width),
+		);
+		let (y1, y2) = (
+			clamp(start.1.min(end.1), geometry.height),
+			clamp(start.1.max(end.1), geometry.height),
+		);
+		Padding::new(y1, geometry.width - x2, geometry.height - y2, x1)
+	}
+
+	/**
+	 * Get the font to use for drawing text.
+	 *
+	 * @return XFontStruct
+	 */
+	fn get_font(&self) -> *mut xlib::XFontStruct {
+		self.font.unwrap_or_else(|| unsafe {
+			xlib::XLoadQueryFont(
+				self.inner,
+				CString::new(DEFAULT_FONT).unwrap_or_default().as_ptr(),
+			)
+		})
+	}
+
 	/**
 	 * Update padding to set the given width and height.
 	 *
@@ -280,12 +732,10 @@ impl Display {
 		if !size.is_zero() {
 			self.settings.padding.right = window_geometry
 				.width
-				.checked_sub(size.width + self.settings.padding.left)
-				.unwrap_or_default();
+				.saturating_sub(size.width + self.settings.padding.left);
 			self.settings.padding.bottom = window_geometry
 				.height
-				.checked_sub(size.height + self.settings.padding.top)
-				.unwrap_or_default();
+				.saturating_sub(size.height + self.settings.padding.top);
 		}
 	}
 
@@ -297,6 +747,7 @@ impl Display {
 	 * @param  change
 	 * @return bool
 	 */
+	#[allow(clippy::collapsible_match)]
 	fn update_area(
 		&mut self,
 		window: Window,
@@ -377,12 +828,20 @@ mod tests {
 	#[test]
 	fn test_x11_display() {
 		let mut settings = RecordSettings::default();
-		settings.time = RecordTime::new(Some(0.0), 0, 0, 10);
+		settings.time = RecordTime::new(Some(0.0), None, 0, 0, 10);
 		settings.flag.font = Some(DEFAULT_FONT);
 		let mut display = Display::open(Some(settings)).unwrap();
 		display
 			.set_focused_window(display.get_root_window().xid, xlib::RevertToParent);
 		display.update_padding(Geometry::new(0, 0, 10, 10), Geometry::default());
+		assert_eq!(
+			Padding::new(20, 50, 30, 10),
+			Display::get_region_padding(
+				Geometry::new(0, 0, 100, 100),
+				(50, 70),
+				(10, 20)
+			)
+		);
 		assert_eq!(
 			display.get_root_window().xid,
 			display.get_focused_window(false).unwrap().xid
@@ -40,11 +40,28 @@ impl<'a> Access<'a, Window> for WindowSystem<'a> {
 	 */
 	fn get_window(&mut self) -> Option<Window> {
 		debug!("Record window: {:?}", self.settings.record.window);
+		if self.settings.record.flag.windows.is_some() {
+			return self.display.get_composite_window();
+		} else if self.settings.record.flag.window_filter.is_some() {
+			return self.display.find_window();
+		} else if self.settings.record.flag.region {
+			return self.display.select_region(
+				self.settings
+					.input_state
+					.expect("Failed to get the input state"),
+			);
+		}
 		match self.settings.record.window {
 			RecordWindow::Focus(None, parent) => {
 				self.display.get_focused_window(parent)
 			}
-			RecordWindow::Root(None) => Some(self.display.get_root_window()),
+			RecordWindow::Root(None) => {
+				if self.settings.record.flag.monitor.is_some() {
+					self.display.get_monitor_window()
+				} else {
+					Some(self.display.get_root_window())
+				}
+			}
 			_ => {
 				if self.settings.record.command.is_some() {
 					self.display.get_focused_window(
@@ -80,7 +97,7 @@ unsafe extern "C" fn handle_x11_errors(
 		"{}[Opcode: {}, Serial: {}]",
 		if xlib::XGetErrorText(
 			display,
-			(*error).error_code.try_into().unwrap_or_default(),
+			(*error).error_code.into(),
 			error_text.as_mut_ptr() as *mut c_char,
 			error_text.capacity().try_into().unwrap_or_default(),
 		) == 0
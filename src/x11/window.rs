@@ -1,6 +1,8 @@
 use crate::image::geometry::Geometry;
 use crate::image::Image;
 use crate::record::fps::FpsClock;
+use crate::record::settings::RecordWindow;
+use crate::util::state::InputState;
 use crate::window::Capture;
 use crate::x11::display::Display;
 use image::Rgba;
@@ -20,6 +22,9 @@ const TEXT_CORNER_OFFSET: i32 = 20;
 /* Padding value to apply to window borders */
 const BORDER_PADDING: u32 = 1;
 
+/* Windows to composite with their offsets and the canvas geometry */
+pub type CompositeLayout = (Vec<(Window, i32, i32)>, Geometry);
+
 /* X11 window id, geometric properties and its display */
 #[derive(Clone, Copy, Debug)]
 pub struct Window {
@@ -166,11 +171,11 @@ impl Window {
 	}
 
 	/**
-	 * Get CRTC (VDC) information of the window.
+	 * Get CRTC (VDC) information of the window with output names.
 	 *
-	 * @param Vector of Geometry
+	 * @return Vector of Tuple (String, Geometry)
 	 */
-	pub fn get_crtc_info(&self) -> Vec<Geometry> {
+	pub fn get_crtc_info(&self) -> Vec<(String, Geometry)> {
 		let mut crtc_info = Vec::new();
 		unsafe {
 			let resources =
@@ -183,11 +188,30 @@ impl Window {
 			.map(|v| xrandr::XRRGetCrtcInfo(self.display.inner, resources, *v))
 			{
 				if (*crtc).noutput > 0 {
-					crtc_info.push(Geometry::new(
-						(*crtc).x,
-						(*crtc).y,
-						(*crtc).width,
-						(*crtc).height,
+					let output = xrandr::XRRGetOutputInfo(
+						self.display.inner,
+						resources,
+						*(*crtc).outputs,
+					);
+					let name = if output.is_null() {
+						String::new()
+					} else {
+						let name = String::from_utf8_lossy(slice::from_raw_parts(
+							(*output).name as *const u8,
+							(*output).nameLen.try_into().unwrap_or_default(),
+						))
+						.into_owned();
+						xrandr::XRRFreeOutputInfo(output);
+						name
+					};
+					crtc_info.push((
+						name,
+						Geometry::new(
+							(*crtc).x,
+							(*crtc).y,
+							(*crtc).width,
+							(*crtc).height,
+						),
 					));
 				}
 				xrandr::XRRFreeCrtcInfo(crtc);
@@ -222,6 +246,17 @@ impl Window {
 		}
 	}
 
+	/* Draw over the child windows as well. */
+	pub fn include_inferiors(&self) {
+		unsafe {
+			xlib::XSetSubwindowMode(
+				self.display.inner,
+				self.gc,
+				xlib::IncludeInferiors,
+			);
+		}
+	}
+
 	/* Draw a rectangle inside the window. */
 	pub fn draw_borders(&self) {
 		if self.display.settings.border.is_some() {
@@ -365,36 +400,21 @@ impl Window {
 		trace!("Grabbed the key {} of {:?}", key, self.xid);
 	}
 
-	/* Ungrab the keys in the window.*/
-	pub fn ungrab_keys(&self) {
-		unsafe {
-			xlib::XUngrabKey(
-				self.display.inner,
-				xlib::AnyKey,
-				xlib::AnyModifier,
-				self.xid,
-			);
-		}
-		trace!("Ungrabbed the keys of {:?}", self.xid);
-	}
-}
-
-/* Capture implementation for X11 Window */
-impl Capture for Window {
 	/**
-	 * Get the image of the window.
+	 * Get the image of the given area of window.
 	 *
+	 * @param  area
 	 * @return Image (Option)
 	 */
-	fn get_image(&self) -> Option<Image> {
+	fn get_area_image(&self, area: Geometry) -> Option<Image> {
 		unsafe {
 			let window_image = xlib::XGetImage(
 				self.display.inner,
 				self.xid,
-				self.area.x,
-				self.area.y,
-				self.area.width,
-				self.area.height,
+				area.x,
+				area.y,
+				area.width,
+				area.height,
 				xlib::XAllPlanes(),
 				xlib::ZPixmap,
 			);
@@ -411,7 +431,7 @@ impl Capture for Window {
 						.map(|bgra| Rgba::from([bgra[2], bgra[1], bgra[0], bgra[3]]))
 						.collect(),
 					self.display.settings.flag.alpha,
-					self.area,
+					area,
 				))
 			} else {
 				None
@@ -419,6 +439,233 @@ impl Capture for Window {
 		}
 	}
 
+	/**
+	 * Get the position of the window relative to the root window.
+	 *
+	 * @return Tuple
+	 */
+	fn get_position(&self) -> (i32, i32) {
+		let (mut x, mut y, mut child) = (0, 0, 0);
+		unsafe {
+			xlib::XTranslateCoordinates(
+				self.display.inner,
+				self.xid,
+				xlib::XDefaultRootWindow(self.display.inner),
+				0,
+				0,
+				&mut x,
+				&mut y,
+				&mut child,
+			);
+		}
+		(x, y)
+	}
+
+	/**
+	 * Get the windows to composite with their offsets and the canvas.
+	 *
+	 * @return Tuple (Option)
+	 */
+	pub fn get_composite_layout(&self) -> Option<CompositeLayout> {
+		let windows = self
+			.display
+			.settings
+			.flag
+			.windows?
+			.iter()
+			.map(|xid| Self::new(*xid, self.display))
+			.collect::<Vec<Self>>();
+		let mut layout = Vec::new();
+		if self.display.settings.flag.side_by_side {
+			let mut x = 0;
+			for window in windows {
+				layout.push((window, x, 0));
+				x += i32::try_from(window.geometry.width).unwrap_or_default();
+			}
+		} else {
+			for window in windows {
+				let (x, y) = window.get_position();
+				layout.push((window, x, y));
+			}
+		}
+		let (min_x, min_y) = (
+			layout.iter().map(|(_, x, _)| *x).min()?,
+			layout.iter().map(|(_, _, y)| *y).min()?,
+		);
+		let (max_x, max_y) = (
+			layout
+				.iter()
+				.map(|(window, x, _)| {
+					x + i32::try_from(window.geometry.width).unwrap_or_default()
+				})
+				.max()?,
+			layout
+				.iter()
+				.map(|(window, _, y)| {
+					y + i32::try_from(window.geometry.height).unwrap_or_default()
+				})
+				.max()?,
+		);
+		for (_, x, y) in layout.iter_mut() {
+			*x -= min_x;
+			*y -= min_y;
+		}
+		Some((
+			layout,
+			Geometry::new(
+				min_x,
+				min_y,
+				u32::try_from(max_x - min_x).unwrap_or_default(),
+				u32::try_from(max_y - min_y).unwrap_or_default(),
+			),
+		))
+	}
+
+	/**
+	 * Get the composited image of the windows.
+	 *
+	 * @return Image (Option)
+	 */
+	fn get_composite_image(&self) -> Option<Image> {
+		let (layout, canvas) = self.get_composite_layout()?;
+		let mut image = Image::new(
+			vec![
+				Rgba::from([0, 0, 0, 255]);
+				(canvas.width * canvas.height) as usize
+			],
+			self.display.settings.flag.alpha,
+			Geometry::new(0, 0, canvas.width, canvas.height),
+		);
+		for (window, x, y) in layout {
+			image
+				.overlay(&window.get_area_image(window.geometry)?, x, y)
+				.ok()?;
+		}
+		Some(image)
+	}
+
+	/* Ungrab the keys in the window.*/
+	pub fn ungrab_keys(&self) {
+		unsafe {
+			xlib::XUngrabKey(
+				self.display.inner,
+				xlib::AnyKey,
+				xlib::AnyModifier,
+				self.xid,
+			);
+		}
+		trace!("Ungrabbed the keys of {:?}", self.xid);
+	}
+}
+
+/* Capture implementation for X11 Window */
+impl Capture for Window {
+	/**
+	 * Get the image of the window.
+	 *
+	 * @return Image (Option)
+	 */
+	fn get_image(&self) -> Option<Image> {
+		let image = if self.display.settings.flag.windows.is_some() {
+			self.get_composite_image()?
+		} else if self.display.settings.flag.follow {
+			self.get_area_image(
+				unsafe { self.get_geometry() }
+					.with_padding(self.display.settings.padding),
+			)?
+		} else {
+			self.get_area_image(self.area)?
+		};
+		if (image.geometry.width, image.geometry.height)
+			!= (self.area.width, self.area.height)
+		{
+			image
+				.fit(
+					self.area.width,
+					self.area.height,
+					self.display.settings.flag.letterbox,
+				)
+				.ok()
+		} else {
+			Some(image)
+		}
+	}
+
+	/**
+	 * Get the pointer position in the area and button state.
+	 *
+	 * @return Tuple (Option)
+	 */
+	fn get_pointer(&self) -> Option<((i32, i32), bool)> {
+		let (mut root, mut child) = (0, 0);
+		let (mut root_x, mut root_y, mut x, mut y, mut mask) = (0, 0, 0, 0, 0);
+		if unsafe {
+			xlib::XQueryPointer(
+				self.display.inner,
+				self.xid,
+				&mut root,
+				&mut child,
+				&mut root_x,
+				&mut root_y,
+				&mut x,
+				&mut y,
+				&mut mask,
+			)
+		} == xlib::True
+		{
+			let position = if self.display.settings.flag.windows.is_some() {
+				if self.display.settings.flag.side_by_side {
+					return None;
+				}
+				let (_, canvas) = self.get_composite_layout()?;
+				(root_x - canvas.x, root_y - canvas.y)
+			} else {
+				(x - self.area.x, y - self.area.y)
+			};
+			Some((
+				position,
+				mask & (xlib::Button1Mask | xlib::Button2Mask | xlib::Button3Mask)
+					!= 0,
+			))
+		} else {
+			None
+		}
+	}
+
+	/**
+	 * Get the ICC profile of the display.
+	 *
+	 * @return Vector of u8 (Option)
+	 */
+	fn get_icc_profile(&self) -> Option<Vec<u8>> {
+		Display::get_icc_profile()
+	}
+
+	/**
+	 * Get the title of the window.
+	 *
+	 * @return String (Option)
+	 */
+	fn get_title(&self) -> Option<String> {
+		self.get_name()
+	}
+
+	/**
+	 * Select another window from the display to record.
+	 *
+	 * @param  input_state
+	 * @return Window (Option)
+	 */
+	fn reselect(&self, input_state: &InputState) -> Option<Self> {
+		let mut display = self.display;
+		if let RecordWindow::Root(geometry) = display.settings.window {
+			display.settings.window = RecordWindow::Focus(geometry, false);
+		}
+		display.settings.flag.windows = None;
+		self.clear_area();
+		display.select_window(input_state)
+	}
+
 	/* Show a countdown on the corner of window. */
 	fn show_countdown(&self) {
 		if self.display.settings.time.countdown != 0 {
@@ -474,7 +721,7 @@ mod tests {
 	#[test]
 	fn test_x11_window() {
 		let mut settings = RecordSettings::default();
-		settings.time = RecordTime::new(Some(0.0), 1, 0, 10);
+		settings.time = RecordTime::new(Some(0.0), None, 1, 0, 10);
 		let display = Display::open(Some(settings)).unwrap();
 		let window = display.get_root_window();
 		unsafe {
@@ -489,7 +736,7 @@ mod tests {
 		window.draw_borders();
 		window.show_countdown();
 		window.clear_area();
-		assert_eq!(Geometry::new(0, 0, 1920, 1080), window.get_crtc_info()[0]);
+		assert_eq!(Geometry::new(0, 0, 1920, 1080), window.get_crtc_info()[0].1);
 		assert_eq!(0, unsafe { window.get_parent() }.unwrap().xid);
 		assert_eq!(
 			"\n Window title  -> \"root-window\"\n Window size   -> [1920x1080]",
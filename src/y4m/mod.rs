@@ -0,0 +1,127 @@
+use crate::app::AppResult;
+use crate::gif::encoder::CancelHandle;
+use crate::image::geometry::Geometry;
+use crate::image::Image;
+use image::ExtendedColorType;
+use std::io::{self, Write};
+
+/* YUV4MPEG2 (y4m) encoder for uncompressed video */
+pub struct Y4mEncoder<Output: Write> {
+	fps: u32,
+	geometry: Geometry,
+	output: Output,
+}
+
+impl<Output: Write> Y4mEncoder<Output> {
+	/**
+	 * Create a new Y4mEncoder object.
+	 *
+	 * @param  fps
+	 * @param  geometry
+	 * @param  output
+	 * @return Y4mEncoder
+	 */
+	pub fn new(fps: u32, geometry: Geometry, output: Output) -> Self {
+		Self {
+			fps,
+			geometry,
+			output,
+		}
+	}
+
+	/**
+	 * Convert RGBA data to planar YCbCr (BT.601, 4:4:4).
+	 *
+	 * @param  data
+	 * @return Vector of u8
+	 */
+	fn get_planes(data: &[u8]) -> Vec<u8> {
+		let pixels = data.len() / 4;
+		let mut planes = vec![0; pixels * 3];
+		for (i, rgba) in data.chunks_exact(4).enumerate() {
+			let (r, g, b) = (
+				rgba[0] as f32 / 255.,
+				rgba[1] as f32 / 255.,
+				rgba[2] as f32 / 255.,
+			);
+			planes[i] = (16. + 65.481 * r + 128.553 * g + 24.966 * b).round() as u8;
+			planes[pixels + i] =
+				(128. - 37.797 * r - 74.203 * g + 112. * b).round() as u8;
+			planes[pixels * 2 + i] =
+				(128. + 112. * r - 93.786 * g - 18.214 * b).round() as u8;
+		}
+		planes
+	}
+
+	/**
+	 * Encode images as frames and write to the y4m stream.
+	 *
+	 * @param  images
+	 * @param  cancel
+	 * @return Result
+	 */
+	pub fn save(
+		mut self,
+		images: Vec<Image>,
+		cancel: CancelHandle,
+	) -> AppResult<()> {
+		writeln!(
+			self.output,
+			"YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+			self.geometry.width,
+			self.geometry.height,
+			self.fps.max(1)
+		)?;
+		for (i, image) in images.iter().enumerate() {
+			let percentage = ((i + 1) as f64 / images.len() as f64) * 100.;
+			info!("Saving... ({:.1}%)\r", percentage);
+			debug!(
+				"Encoding... ({:.1}%) [{}/{}]\r",
+				percentage,
+				i + 1,
+				images.len()
+			);
+			io::stdout().flush()?;
+			cancel.check()?;
+			self.output.write_all(b"FRAME\n")?;
+			self.output.write_all(&Self::get_planes(
+				&image.get_data(ExtendedColorType::Rgba8)?,
+			))?;
+		}
+		info!("\n");
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::Rgba;
+	use pretty_assertions::assert_eq;
+	#[test]
+	fn test_y4m_encoder() -> AppResult<()> {
+		let geometry = Geometry::new(0, 0, 1, 2);
+		let data =
+			vec![Rgba::from([0, 0, 0, 255]), Rgba::from([255, 255, 255, 255])];
+		let images = vec![
+			Image::new(data.clone(), false, geometry),
+			Image::new(data.into_iter().rev().collect(), false, geometry),
+		];
+		let cancel = CancelHandle::new(None);
+		cancel.cancel();
+		assert!(Y4mEncoder::new(25, geometry, Vec::new())
+			.save(images.clone(), cancel)
+			.is_err());
+		let mut output = Vec::new();
+		Y4mEncoder::new(25, geometry, &mut output)
+			.save(images, CancelHandle::new(None))?;
+		let header = b"YUV4MPEG2 W1 H2 F25:1 Ip A1:1 C444\n";
+		assert_eq!(header, &output[..header.len()]);
+		assert_eq!(
+			b"FRAME\n\x10\xeb\x80\x80\x80\x80",
+			&output[header.len()..header.len() + 12]
+		);
+		assert_eq!(header.len() + 2 * (6 + 6), output.len());
+		Ok(())
+	}
+}